@@ -1,6 +1,10 @@
 use crate::backend::*;
 use bytes::BufMut;
-use std::{cell::Cell, sync::Arc};
+use std::{
+    cell::Cell,
+    ops::{Bound, RangeBounds},
+    sync::Arc,
+};
 
 #[cfg(feature = "metrics")]
 use metrics::register_counter;
@@ -17,6 +21,8 @@ where
     pub extra_data: S::ExtraData,
     pub state_type: S,
     pub registered: bool,
+    /// Version of the value type, recorded in the backend when the handle is registered
+    pub version: u32,
 }
 
 impl<S, IK, N> Debug for Handle<S, IK, N>
@@ -69,6 +75,7 @@ impl<S: StateType<ExtraData = ()>> Handle<S, (), ()> {
             extra_data: (),
             state_type: Default::default(),
             registered: false,
+            version: 1,
         }
     }
 }
@@ -83,6 +90,11 @@ impl<K: Key, V: Value> Handle<MapState<K, V>> {
         Handle::no_extra(id.into())
     }
 }
+impl<K: OrderedKey, V: Value> Handle<SortedMapState<K, V>> {
+    pub fn sorted_map(id: impl Into<String>) -> Self {
+        Handle::no_extra(id.into())
+    }
+}
 impl<T: Value> Handle<VecState<T>> {
     pub fn vec(id: impl Into<String>) -> Self {
         Handle::no_extra(id.into())
@@ -97,6 +109,7 @@ impl<T: Value, F: Reducer<T>> Handle<ReducerState<T, F>> {
             extra_data: reducer,
             state_type: ReducerState::default(),
             registered: false,
+            version: 1,
         }
     }
 }
@@ -109,6 +122,7 @@ impl<A: Aggregator> Handle<AggregatorState<A>> {
             extra_data: aggregator,
             state_type: AggregatorState::default(),
             registered: false,
+            version: 1,
         }
     }
 }
@@ -122,6 +136,7 @@ impl<S: StateType, IK: Metakey, N: Metakey> Handle<S, IK, N> {
             extra_data: self.extra_data,
             state_type: self.state_type,
             registered: self.registered,
+            version: self.version,
         }
     }
     pub fn with_namespace<NN: Metakey>(self, namespace: NN) -> Handle<S, IK, NN> {
@@ -132,9 +147,15 @@ impl<S: StateType, IK: Metakey, N: Metakey> Handle<S, IK, N> {
             extra_data: self.extra_data,
             state_type: self.state_type,
             registered: self.registered,
+            version: self.version,
         }
     }
 
+    pub fn with_version(mut self, version: u32) -> Self {
+        self.version = version;
+        self
+    }
+
     pub fn set_item_key(&self, item_key: IK) {
         self.item_key.set(item_key);
     }
@@ -263,6 +284,33 @@ impl<K: Key, V: Value, IK: Metakey, N: Metakey> Handle<MapState<K, V>, IK, N> {
         backend.register_map_handle(self)
     }
 }
+impl<K: OrderedKey, V: Value, IK: Metakey, N: Metakey> Handle<SortedMapState<K, V>, IK, N> {
+    pub fn register<B: Backend>(&mut self, backend: &mut B) {
+        backend.register_sorted_map_handle(self)
+    }
+
+    #[inline(always)]
+    pub fn serialize_metakeys_and_ordered_key(&self, key: &K) -> Result<Vec<u8>> {
+        use crate::backend::serialization::ordered;
+        let mut dest = self.serialize_metakeys()?;
+        ordered::serialize_into(&mut dest, key);
+        Ok(dest)
+    }
+
+    /// Serialized bounds of the stored keys of the records in `range`
+    pub fn serialize_range(
+        &self,
+        range: impl RangeBounds<K>,
+    ) -> Result<(Bound<Vec<u8>>, Bound<Vec<u8>>)> {
+        use crate::backend::serialization::ordered;
+        let prefix = self.serialize_metakeys()?;
+        Ok(ordered::serialize_range(
+            &prefix,
+            range.start_bound(),
+            range.end_bound(),
+        ))
+    }
+}
 impl<T: Value, IK: Metakey, N: Metakey> Handle<VecState<T>, IK, N> {
     pub fn register<B: Backend>(&mut self, backend: &mut B) {
         backend.register_vec_handle(self)
@@ -399,6 +447,48 @@ impl<B: Backend, K: Key, V: Value, IK: Metakey, N: Metakey> ActiveHandle<B, MapS
     }
 }
 
+impl<B: Backend, K: OrderedKey, V: Value, IK: Metakey, N: Metakey>
+    ActiveHandle<B, SortedMapState<K, V>, IK, N>
+{
+    #[inline]
+    pub fn clear(&self) -> Result<()> {
+        self.backend.sorted_map_clear(&self.inner)
+    }
+    #[inline]
+    pub fn get(&self, key: &K) -> Result<Option<V>> {
+        self.backend.sorted_map_get(&self.inner, key)
+    }
+    #[inline]
+    pub fn fast_insert(&self, key: K, value: V) -> Result<()> {
+        self.backend.sorted_map_fast_insert(&self.inner, key, value)
+    }
+    #[inline]
+    pub fn remove(&self, key: &K) -> Result<Option<V>> {
+        self.backend.sorted_map_remove(&self.inner, key)
+    }
+    #[inline]
+    pub fn range(&self, range: impl RangeBounds<K>) -> Result<BoxedIteratorOfResult<(K, V)>> {
+        self.backend.sorted_map_range(&self.inner, range)
+    }
+    #[inline]
+    pub fn first(&self) -> Result<Option<(K, V)>> {
+        self.backend.sorted_map_first(&self.inner)
+    }
+    #[inline]
+    pub fn last(&self) -> Result<Option<(K, V)>> {
+        self.backend.sorted_map_last(&self.inner)
+    }
+    #[inline]
+    pub fn pop_first(&self) -> Result<Option<(K, V)>> {
+        self.backend.sorted_map_pop_first(&self.inner)
+    }
+    #[inline]
+    #[allow(clippy::len_without_is_empty)]
+    pub fn len(&self) -> Result<usize> {
+        self.backend.sorted_map_len(&self.inner)
+    }
+}
+
 impl<B: Backend, T: Value, IK: Metakey, N: Metakey> ActiveHandle<B, VecState<T>, IK, N> {
     #[inline]
     pub fn clear(&self) -> Result<()> {
@@ -0,0 +1,73 @@
+use crate::{
+    data::{Metakey, Value},
+    error::*,
+    in_memory::InMemory,
+    serialization::protobuf,
+    Aggregator, AggregatorOps, AggregatorState, Handle,
+};
+
+#[cfg(feature = "metrics")]
+use crate::metrics_utils::*;
+
+/// Same marker as the disk-backed backends prefix their accumulators with
+const ACCUMULATOR_MARKER: u8 = 0xAC;
+
+impl AggregatorOps for InMemory {
+    fn aggregator_clear<A: Aggregator, IK: Metakey, N: Metakey>(
+        &self,
+        handle: &Handle<AggregatorState<A>, IK, N>,
+    ) -> Result<()> {
+        let key = handle.serialize_metakeys()?;
+        self.remove(&handle.id, &key);
+        Ok(())
+    }
+
+    fn aggregator_get<A: Aggregator, IK: Metakey, N: Metakey>(
+        &self,
+        handle: &Handle<AggregatorState<A>, IK, N>,
+    ) -> Result<<A as Aggregator>::Result> {
+        let key = handle.serialize_metakeys()?;
+
+        if let Some(serialized) = self.get(&handle.id, &key) {
+            #[cfg(feature = "metrics")]
+            record_bytes_read(handle.name(), serialized.len() as u64, self.name.as_str());
+            let current_accumulator = deserialize_accumulator(&serialized)?;
+            Ok(handle
+                .extra_data
+                .accumulator_into_result(current_accumulator))
+        } else {
+            Ok(handle
+                .extra_data
+                .accumulator_into_result(handle.extra_data.create_accumulator()))
+        }
+    }
+
+    fn aggregator_aggregate<A: Aggregator, IK: Metakey, N: Metakey>(
+        &self,
+        handle: &Handle<AggregatorState<A>, IK, N>,
+        value: <A as Aggregator>::Input,
+    ) -> Result<()> {
+        let key = handle.serialize_metakeys()?;
+        self.update(&handle.id, key, |existent| {
+            let mut accumulator = match existent {
+                Some(bytes) => deserialize_accumulator(bytes)?,
+                None => handle.extra_data.create_accumulator(),
+            };
+            handle.extra_data.add(&mut accumulator, value);
+
+            let mut serialized = vec![ACCUMULATOR_MARKER];
+            protobuf::serialize_into(&mut serialized, &accumulator)?;
+            #[cfg(feature = "metrics")]
+            record_bytes_written(handle.name(), serialized.len() as u64, self.name.as_str());
+            Ok(serialized)
+        })
+    }
+}
+
+fn deserialize_accumulator<T: Value>(serialized: &[u8]) -> Result<T> {
+    ensure!(
+        serialized.first() == Some(&ACCUMULATOR_MARKER),
+        InMemoryWrongType
+    );
+    protobuf::deserialize(&serialized[1..])
+}
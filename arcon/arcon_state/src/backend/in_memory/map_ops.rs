@@ -0,0 +1,204 @@
+use crate::{
+    data::{Key, Metakey, Value},
+    error::*,
+    handles::BoxedIteratorOfResult,
+    in_memory::InMemory,
+    serialization::{fixed_bytes, protobuf},
+    Handle, MapOps, MapState,
+};
+
+#[cfg(feature = "metrics")]
+use crate::metrics_utils::*;
+
+impl MapOps for InMemory {
+    fn map_clear<K: Key, V: Value, IK: Metakey, N: Metakey>(
+        &self,
+        handle: &Handle<MapState<K, V>, IK, N>,
+    ) -> Result<()> {
+        let prefix = handle.serialize_metakeys()?;
+        self.remove_prefix(&handle.id, &prefix);
+        Ok(())
+    }
+
+    fn map_get<K: Key, V: Value, IK: Metakey, N: Metakey>(
+        &self,
+        handle: &Handle<MapState<K, V>, IK, N>,
+        key: &K,
+    ) -> Result<Option<V>> {
+        let key = handle.serialize_metakeys_and_key(key)?;
+        if let Some(serialized) = self.get(&handle.id, &key) {
+            #[cfg(feature = "metrics")]
+            record_bytes_read(handle.name(), serialized.len() as u64, self.name.as_str());
+            let value = protobuf::deserialize(&serialized)?;
+            Ok(Some(value))
+        } else {
+            Ok(None)
+        }
+    }
+
+    fn map_fast_insert<K: Key, V: Value, IK: Metakey, N: Metakey>(
+        &self,
+        handle: &Handle<MapState<K, V>, IK, N>,
+        key: K,
+        value: V,
+    ) -> Result<()> {
+        self.map_fast_insert_by_ref(handle, &key, &value)
+    }
+
+    fn map_fast_insert_by_ref<K: Key, V: Value, IK: Metakey, N: Metakey>(
+        &self,
+        handle: &Handle<MapState<K, V>, IK, N>,
+        key: &K,
+        value: &V,
+    ) -> Result<()> {
+        let key = handle.serialize_metakeys_and_key(key)?;
+        let serialized = protobuf::serialize(value)?;
+        #[cfg(feature = "metrics")]
+        record_bytes_written(handle.name(), serialized.len() as u64, self.name.as_str());
+        self.put(&handle.id, key, serialized);
+
+        Ok(())
+    }
+
+    fn map_insert<K: Key, V: Value, IK: Metakey, N: Metakey>(
+        &self,
+        handle: &Handle<MapState<K, V>, IK, N>,
+        key: K,
+        value: V,
+    ) -> Result<Option<V>> {
+        let key = handle.serialize_metakeys_and_key(&key)?;
+        let serialized = protobuf::serialize(&value)?;
+        #[cfg(feature = "metrics")]
+        record_bytes_written(handle.name(), serialized.len() as u64, self.name.as_str());
+        let old = match self.put(&handle.id, key, serialized) {
+            Some(bytes) => Some(protobuf::deserialize(&bytes)?),
+            None => None,
+        };
+
+        Ok(old)
+    }
+
+    fn map_insert_all<K: Key, V: Value, IK: Metakey, N: Metakey>(
+        &self,
+        handle: &Handle<MapState<K, V>, IK, N>,
+        key_value_pairs: impl IntoIterator<Item = (K, V)>,
+    ) -> Result<()> {
+        for (key, value) in key_value_pairs {
+            self.map_fast_insert_by_ref(handle, &key, &value)?;
+        }
+        Ok(())
+    }
+
+    fn map_insert_all_by_ref<'a, K: Key, V: Value, IK: Metakey, N: Metakey>(
+        &self,
+        handle: &Handle<MapState<K, V>, IK, N>,
+        key_value_pairs: impl IntoIterator<Item = (&'a K, &'a V)>,
+    ) -> Result<()> {
+        for (key, value) in key_value_pairs {
+            self.map_fast_insert_by_ref(handle, key, value)?;
+        }
+        Ok(())
+    }
+
+    fn map_remove<K: Key, V: Value, IK: Metakey, N: Metakey>(
+        &self,
+        handle: &Handle<MapState<K, V>, IK, N>,
+        key: &K,
+    ) -> Result<Option<V>> {
+        let key = handle.serialize_metakeys_and_key(key)?;
+        let old = match self.remove(&handle.id, &key) {
+            Some(bytes) => Some(protobuf::deserialize(&bytes)?),
+            None => None,
+        };
+
+        Ok(old)
+    }
+
+    fn map_fast_remove<K: Key, V: Value, IK: Metakey, N: Metakey>(
+        &self,
+        handle: &Handle<MapState<K, V>, IK, N>,
+        key: &K,
+    ) -> Result<()> {
+        let key = handle.serialize_metakeys_and_key(key)?;
+        self.remove(&handle.id, &key);
+
+        Ok(())
+    }
+
+    fn map_contains<K: Key, V: Value, IK: Metakey, N: Metakey>(
+        &self,
+        handle: &Handle<MapState<K, V>, IK, N>,
+        key: &K,
+    ) -> Result<bool> {
+        let key = handle.serialize_metakeys_and_key(key)?;
+        Ok(self.contains(&handle.id, &key))
+    }
+
+    fn map_iter<K: Key, V: Value, IK: Metakey, N: Metakey>(
+        &self,
+        handle: &Handle<MapState<K, V>, IK, N>,
+    ) -> Result<BoxedIteratorOfResult<'_, (K, V)>> {
+        let prefix = handle.serialize_metakeys()?;
+        let entries = self.scan_prefix(&handle.id, &prefix);
+
+        let iter = entries.into_iter().map(|(db_key, serialized_value)| {
+            let key: K = deserialize_user_key::<K, IK, N>(&db_key)?;
+            let value: V = protobuf::deserialize(&serialized_value)?;
+
+            Ok((key, value))
+        });
+
+        Ok(Box::new(iter))
+    }
+
+    fn map_keys<K: Key, V: Value, IK: Metakey, N: Metakey>(
+        &self,
+        handle: &Handle<MapState<K, V>, IK, N>,
+    ) -> Result<BoxedIteratorOfResult<'_, K>> {
+        let prefix = handle.serialize_metakeys()?;
+        let entries = self.scan_prefix(&handle.id, &prefix);
+
+        let iter = entries
+            .into_iter()
+            .map(|(db_key, _)| deserialize_user_key::<K, IK, N>(&db_key));
+
+        Ok(Box::new(iter))
+    }
+
+    fn map_values<K: Key, V: Value, IK: Metakey, N: Metakey>(
+        &self,
+        handle: &Handle<MapState<K, V>, IK, N>,
+    ) -> Result<BoxedIteratorOfResult<'_, V>> {
+        let prefix = handle.serialize_metakeys()?;
+        let entries = self.scan_prefix(&handle.id, &prefix);
+
+        let iter = entries
+            .into_iter()
+            .map(|(_, serialized_value)| protobuf::deserialize(&serialized_value));
+
+        Ok(Box::new(iter))
+    }
+
+    fn map_len<K: Key, V: Value, IK: Metakey, N: Metakey>(
+        &self,
+        handle: &Handle<MapState<K, V>, IK, N>,
+    ) -> Result<usize> {
+        let prefix = handle.serialize_metakeys()?;
+        Ok(self.scan_prefix(&handle.id, &prefix).len())
+    }
+
+    fn map_is_empty<K: Key, V: Value, IK: Metakey, N: Metakey>(
+        &self,
+        handle: &Handle<MapState<K, V>, IK, N>,
+    ) -> Result<bool> {
+        Ok(self.map_len(handle)? == 0)
+    }
+}
+
+/// Skips the item key and namespace that prefix every stored key and decodes the user key
+fn deserialize_user_key<K: Key, IK: Metakey, N: Metakey>(db_key: &[u8]) -> Result<K> {
+    let mut key_cursor = db_key;
+    let _item_key: IK = fixed_bytes::deserialize_from(&mut key_cursor)?;
+    let _namespace: N = fixed_bytes::deserialize_from(&mut key_cursor)?;
+    protobuf::deserialize_from(&mut key_cursor)
+}
@@ -0,0 +1,424 @@
+use crate::{
+    data::{Key, Metakey, OrderedKey, Value},
+    error::*,
+    Aggregator, AggregatorState, AsyncCheckpoint, Backend, Handle, MapState, Reducer, ReducerState,
+    SortedMapState, ValueState, VecState,
+};
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs,
+    io::{self, BufReader, BufWriter, Read, Write},
+    ops::Bound,
+    path::Path,
+    sync::{Arc, RwLock, RwLockReadGuard, RwLockWriteGuard},
+};
+
+/// Name of the file in a checkpoint directory that keeps the state of an [InMemory] backend
+const CHECKPOINT_FILE: &str = "IN_MEMORY_STATE";
+
+/// Serialized keys and their serialized values, ordered by key
+type Table = BTreeMap<Vec<u8>, Vec<u8>>;
+
+/// A state backend that keeps all state in ordered maps
+///
+/// Keys and values are stored serialized in the same format as the disk-backed
+/// backends use, one table per handle. Checkpoints write all tables into a single file.
+///
+/// Tables are shared copy-on-write with the checkpoints that are being written, so
+/// taking a checkpoint is cheap. A table that is modified before the checkpoint is
+/// complete is copied though, which at worst doubles the memory used by the state.
+#[derive(Debug)]
+pub struct InMemory {
+    tables: RwLock<HashMap<String, Arc<Table>>>,
+    restored: bool,
+    name: String,
+}
+
+impl InMemory {
+    fn read(&self) -> RwLockReadGuard<'_, HashMap<String, Arc<Table>>> {
+        self.tables.read().expect("InMemory state lock poisoned")
+    }
+
+    fn write(&self) -> RwLockWriteGuard<'_, HashMap<String, Arc<Table>>> {
+        self.tables.write().expect("InMemory state lock poisoned")
+    }
+
+    /// Runs `f` on `table`, which is copied first if a checkpoint still shares it
+    fn modify<T>(&self, table: &str, f: impl FnOnce(&mut Table) -> T) -> T {
+        let mut tables = self.write();
+        let table = tables.entry(table.to_string()).or_default();
+        f(Arc::make_mut(table))
+    }
+
+    fn get(&self, table: &str, key: &[u8]) -> Option<Vec<u8>> {
+        self.read().get(table).and_then(|t| t.get(key).cloned())
+    }
+
+    fn put(&self, table: &str, key: Vec<u8>, value: Vec<u8>) -> Option<Vec<u8>> {
+        self.modify(table, |t| t.insert(key, value))
+    }
+
+    fn remove(&self, table: &str, key: &[u8]) -> Option<Vec<u8>> {
+        if !self.contains(table, key) {
+            return None;
+        }
+        self.modify(table, |t| t.remove(key))
+    }
+
+    fn remove_prefix(&self, table: &str, prefix: &[u8]) {
+        let keys: Vec<Vec<u8>> = self
+            .scan_prefix(table, prefix)
+            .into_iter()
+            .map(|(key, _)| key)
+            .collect();
+        if !keys.is_empty() {
+            self.modify(table, |t| {
+                for key in keys.iter() {
+                    t.remove(key);
+                }
+            });
+        }
+    }
+
+    fn contains(&self, table: &str, key: &[u8]) -> bool {
+        self.read()
+            .get(table)
+            .map(|t| t.contains_key(key))
+            .unwrap_or(false)
+    }
+
+    /// Returns copies of all entries of `table` whose keys start with `prefix`, in key order
+    fn scan_prefix(&self, table: &str, prefix: &[u8]) -> Vec<(Vec<u8>, Vec<u8>)> {
+        match self.read().get(table) {
+            Some(table) => table
+                .range::<[u8], _>((Bound::Included(prefix), Bound::Unbounded))
+                .take_while(|(key, _)| key.starts_with(prefix))
+                .map(|(key, value)| (key.clone(), value.clone()))
+                .collect(),
+            None => Vec::new(),
+        }
+    }
+
+    /// Returns copies of all entries of `table` whose keys are within `bounds`, in key order
+    fn scan_range(
+        &self,
+        table: &str,
+        bounds: &(Bound<Vec<u8>>, Bound<Vec<u8>>),
+    ) -> Vec<(Vec<u8>, Vec<u8>)> {
+        match self.read().get(table) {
+            Some(table) if !is_empty_range(bounds) => table
+                .range(bounds.clone())
+                .map(|(key, value)| (key.clone(), value.clone()))
+                .collect(),
+            _ => Vec::new(),
+        }
+    }
+
+    /// Returns a copy of the entry of `table` with the smallest key within `bounds`
+    fn first_in_range(
+        &self,
+        table: &str,
+        bounds: &(Bound<Vec<u8>>, Bound<Vec<u8>>),
+    ) -> Option<(Vec<u8>, Vec<u8>)> {
+        match self.read().get(table) {
+            Some(table) if !is_empty_range(bounds) => table
+                .range(bounds.clone())
+                .next()
+                .map(|(key, value)| (key.clone(), value.clone())),
+            _ => None,
+        }
+    }
+
+    /// Returns a copy of the entry of `table` with the greatest key within `bounds`
+    fn last_in_range(
+        &self,
+        table: &str,
+        bounds: &(Bound<Vec<u8>>, Bound<Vec<u8>>),
+    ) -> Option<(Vec<u8>, Vec<u8>)> {
+        match self.read().get(table) {
+            Some(table) if !is_empty_range(bounds) => table
+                .range(bounds.clone())
+                .next_back()
+                .map(|(key, value)| (key.clone(), value.clone())),
+            _ => None,
+        }
+    }
+
+    /// Returns the amount of keys of `table` within `bounds`
+    fn count_range(&self, table: &str, bounds: &(Bound<Vec<u8>>, Bound<Vec<u8>>)) -> usize {
+        match self.read().get(table) {
+            Some(table) if !is_empty_range(bounds) => table.range(bounds.clone()).count(),
+            _ => 0,
+        }
+    }
+
+    /// Replaces the value of `key` with the result of `f` applied to the current one
+    ///
+    /// Plays the role of the merge operators of the disk-backed backends.
+    fn update(
+        &self,
+        table: &str,
+        key: Vec<u8>,
+        f: impl FnOnce(Option<&[u8]>) -> Result<Vec<u8>>,
+    ) -> Result<()> {
+        self.modify(table, |table| {
+            let value = f(table.get(&key).map(Vec::as_slice))?;
+            table.insert(key, value);
+            Ok(())
+        })
+    }
+}
+
+impl Backend for InMemory {
+    fn name(&self) -> &str {
+        self.name.as_str()
+    }
+
+    fn create(_live_path: &Path, name: String) -> Result<Self>
+    where
+        Self: Sized,
+    {
+        Ok(InMemory {
+            tables: RwLock::new(HashMap::new()),
+            restored: false,
+            name,
+        })
+    }
+
+    fn restore(_live_path: &Path, checkpoint_path: &Path, name: String) -> Result<Self>
+    where
+        Self: Sized,
+    {
+        let tables = read_tables(&checkpoint_path.join(CHECKPOINT_FILE))?;
+        Ok(InMemory {
+            tables: RwLock::new(tables),
+            restored: true,
+            name,
+        })
+    }
+
+    fn was_restored(&self) -> bool {
+        self.restored
+    }
+
+    fn checkpoint(&self, checkpoint_path: &Path) -> Result<()> {
+        self.async_checkpoint(checkpoint_path)?.complete()
+    }
+
+    fn async_checkpoint(&self, checkpoint_path: &Path) -> Result<AsyncCheckpoint> {
+        // the tables are shared with the backend, which copies the ones it modifies afterwards
+        let tables = self.read().clone();
+        let checkpoint_path = checkpoint_path.to_path_buf();
+        Ok(AsyncCheckpoint::new(move || {
+            write_tables(&checkpoint_path, &tables)
+        }))
+    }
+
+    fn handle_ids(&self) -> Result<Vec<String>> {
+        let mut ids: Vec<String> = self.read().keys().cloned().collect();
+        ids.sort();
+        Ok(ids)
+    }
+
+    fn scan_raw(&self, handle_id: &str, f: &mut dyn FnMut(&[u8], &[u8])) -> Result<()> {
+        if let Some(table) = self.read().get(handle_id) {
+            for (key, value) in table.iter() {
+                f(key, value);
+            }
+        }
+        Ok(())
+    }
+
+    fn rewrite_values<E: From<ArconStateError>>(
+        &self,
+        handle_id: &str,
+        mut f: impl FnMut(&[u8]) -> std::result::Result<Vec<u8>, E>,
+    ) -> std::result::Result<(), E> {
+        let mut tables = self.write();
+        if let Some(table) = tables.get_mut(handle_id) {
+            // nothing is replaced unless every value could be rewritten
+            let mut rewritten = Vec::with_capacity(table.len());
+            for (key, value) in table.iter() {
+                rewritten.push((key.clone(), f(value)?));
+            }
+            Arc::make_mut(table).extend(rewritten);
+        }
+        Ok(())
+    }
+
+    fn register_value_handle<T: Value, IK: Metakey, N: Metakey>(
+        &self,
+        handle: &mut Handle<ValueState<T>, IK, N>,
+    ) {
+        handle.registered = true;
+        self.record_schema_version(&handle.id, handle.version);
+    }
+
+    fn register_map_handle<K: Key, V: Value, IK: Metakey, N: Metakey>(
+        &self,
+        handle: &mut Handle<MapState<K, V>, IK, N>,
+    ) {
+        handle.registered = true;
+        self.record_schema_version(&handle.id, handle.version);
+    }
+
+    fn register_sorted_map_handle<K: OrderedKey, V: Value, IK: Metakey, N: Metakey>(
+        &self,
+        handle: &mut Handle<SortedMapState<K, V>, IK, N>,
+    ) {
+        handle.registered = true;
+        self.record_schema_version(&handle.id, handle.version);
+    }
+
+    fn register_vec_handle<T: Value, IK: Metakey, N: Metakey>(
+        &self,
+        handle: &mut Handle<VecState<T>, IK, N>,
+    ) {
+        handle.registered = true;
+        self.record_schema_version(&handle.id, handle.version);
+    }
+
+    fn register_reducer_handle<T: Value, F: Reducer<T>, IK: Metakey, N: Metakey>(
+        &self,
+        handle: &mut Handle<ReducerState<T, F>, IK, N>,
+    ) {
+        handle.registered = true;
+        self.record_schema_version(&handle.id, handle.version);
+    }
+
+    fn register_aggregator_handle<A: Aggregator, IK: Metakey, N: Metakey>(
+        &self,
+        handle: &mut Handle<AggregatorState<A>, IK, N>,
+    ) {
+        handle.registered = true;
+        self.record_schema_version(&handle.id, handle.version);
+    }
+}
+
+/// Returns true if no key lies within `bounds`, which [BTreeMap::range] may panic on
+fn is_empty_range(bounds: &(Bound<Vec<u8>>, Bound<Vec<u8>>)) -> bool {
+    match bounds {
+        (Bound::Included(start), Bound::Included(end)) => start > end,
+        (Bound::Included(start), Bound::Excluded(end))
+        | (Bound::Excluded(start), Bound::Included(end))
+        | (Bound::Excluded(start), Bound::Excluded(end)) => start >= end,
+        _ => false,
+    }
+}
+
+mod aggregator_ops;
+mod map_ops;
+mod reducer_ops;
+mod sorted_map_ops;
+mod value_ops;
+mod vec_ops;
+
+fn write_tables(checkpoint_path: &Path, tables: &HashMap<String, Arc<Table>>) -> Result<()> {
+    fs::create_dir_all(checkpoint_path)?;
+    let out = fs::File::create(checkpoint_path.join(CHECKPOINT_FILE))?;
+    let mut writer = BufWriter::new(out);
+
+    #[inline]
+    fn write_len_and_bytes(mut w: impl Write, bytes: &[u8]) -> io::Result<()> {
+        w.write_all(&(bytes.len() as u64).to_le_bytes())?;
+        w.write_all(bytes)
+    }
+
+    writer.write_all(&(tables.len() as u64).to_le_bytes())?;
+    for (name, table) in tables {
+        write_len_and_bytes(&mut writer, name.as_bytes())?;
+        writer.write_all(&(table.len() as u64).to_le_bytes())?;
+        for (key, value) in table.iter() {
+            write_len_and_bytes(&mut writer, key)?;
+            write_len_and_bytes(&mut writer, value)?;
+        }
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+fn read_tables(path: &Path) -> Result<HashMap<String, Arc<Table>>> {
+    let mut reader = BufReader::new(fs::File::open(path)?);
+
+    #[inline]
+    fn read_len(mut r: impl Read) -> io::Result<usize> {
+        let mut bytes = [0u8; 8];
+        r.read_exact(&mut bytes)?;
+        Ok(u64::from_le_bytes(bytes) as usize)
+    }
+
+    #[inline]
+    fn read_bytes(mut r: impl Read) -> io::Result<Vec<u8>> {
+        let mut bytes = vec![0u8; read_len(&mut r)?];
+        r.read_exact(&mut bytes)?;
+        Ok(bytes)
+    }
+
+    let table_count = read_len(&mut reader)?;
+    let mut tables = HashMap::with_capacity(table_count);
+    for _ in 0..table_count {
+        let name = String::from_utf8(read_bytes(&mut reader)?)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        let entry_count = read_len(&mut reader)?;
+        let mut table = Table::new();
+        for _ in 0..entry_count {
+            let key = read_bytes(&mut reader)?;
+            let value = read_bytes(&mut reader)?;
+            table.insert(key, value);
+        }
+        tables.insert(name, Arc::new(table));
+    }
+    Ok(tables)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::SCHEMA_HANDLE_ID;
+    use tempfile::TempDir;
+
+    fn in_memory() -> Arc<InMemory> {
+        Arc::new(InMemory::create(Path::new(""), "testDB".to_string()).unwrap())
+    }
+
+    #[test]
+    fn test_in_memory_checkpoints() {
+        let backend = in_memory();
+        let mut value = Handle::value("value");
+        backend.register_value_handle(&mut value);
+        let value = value.activate(backend.clone());
+        value.fast_set(1u32).unwrap();
+
+        let mut vec = Handle::vec("vec");
+        backend.register_vec_handle(&mut vec);
+        let vec = vec.activate(backend.clone());
+        vec.append(1u32).unwrap();
+        vec.append(2u32).unwrap();
+
+        let chkp_dir = TempDir::new().unwrap();
+        let checkpoint = backend.async_checkpoint(chkp_dir.path()).unwrap();
+        // changes after the synchronous phase are not part of the checkpoint
+        value.fast_set(2u32).unwrap();
+        checkpoint.complete().unwrap();
+
+        let restored = Arc::new(
+            InMemory::restore(Path::new(""), chkp_dir.path(), "testDB".to_string()).unwrap(),
+        );
+        assert!(!backend.was_restored());
+        assert!(restored.was_restored());
+        assert_eq!(
+            restored.handle_ids().unwrap(),
+            vec![SCHEMA_HANDLE_ID, "value", "vec"]
+        );
+
+        let mut value = Handle::<ValueState<u32>>::value("value");
+        restored.register_value_handle(&mut value);
+        assert_eq!(value.activate(restored.clone()).get().unwrap(), Some(1));
+
+        let mut vec = Handle::<VecState<u32>>::vec("vec");
+        restored.register_vec_handle(&mut vec);
+        assert_eq!(vec.activate(restored).get().unwrap(), vec![1, 2]);
+    }
+
+    common_state_tests!(in_memory());
+}
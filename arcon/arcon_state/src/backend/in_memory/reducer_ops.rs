@@ -0,0 +1,57 @@
+use crate::{
+    data::{Metakey, Value},
+    error::*,
+    in_memory::InMemory,
+    serialization::protobuf,
+    Handle, Reducer, ReducerOps, ReducerState,
+};
+
+#[cfg(feature = "metrics")]
+use crate::metrics_utils::*;
+
+impl ReducerOps for InMemory {
+    fn reducer_clear<T: Value, F: Reducer<T>, IK: Metakey, N: Metakey>(
+        &self,
+        handle: &Handle<ReducerState<T, F>, IK, N>,
+    ) -> Result<()> {
+        let key = handle.serialize_metakeys()?;
+        self.remove(&handle.id, &key);
+        Ok(())
+    }
+
+    fn reducer_get<T: Value, F: Reducer<T>, IK: Metakey, N: Metakey>(
+        &self,
+        handle: &Handle<ReducerState<T, F>, IK, N>,
+    ) -> Result<Option<T>> {
+        let key = handle.serialize_metakeys()?;
+        if let Some(storage) = self.get(&handle.id, &key) {
+            #[cfg(feature = "metrics")]
+            record_bytes_read(handle.name(), storage.len() as u64, self.name.as_str());
+            let value = protobuf::deserialize(&storage)?;
+            Ok(Some(value))
+        } else {
+            Ok(None)
+        }
+    }
+
+    fn reducer_reduce<T: Value, F: Reducer<T>, IK: Metakey, N: Metakey>(
+        &self,
+        handle: &Handle<ReducerState<T, F>, IK, N>,
+        value: T,
+    ) -> Result<()> {
+        let key = handle.serialize_metakeys()?;
+        self.update(&handle.id, key, |existent| {
+            let reduced = match existent {
+                Some(bytes) => {
+                    let old: T = protobuf::deserialize(bytes)?;
+                    (handle.extra_data)(&old, &value)
+                }
+                None => value,
+            };
+            let serialized = protobuf::serialize(&reduced)?;
+            #[cfg(feature = "metrics")]
+            record_bytes_written(handle.name(), serialized.len() as u64, self.name.as_str());
+            Ok(serialized)
+        })
+    }
+}
@@ -0,0 +1,139 @@
+use crate::{
+    data::{Metakey, OrderedKey, Value},
+    error::*,
+    handles::BoxedIteratorOfResult,
+    in_memory::InMemory,
+    serialization::{ordered, protobuf},
+    Handle, SortedMapOps, SortedMapState,
+};
+
+#[cfg(feature = "metrics")]
+use crate::metrics_utils::*;
+
+use std::ops::RangeBounds;
+
+impl SortedMapOps for InMemory {
+    fn sorted_map_clear<K: OrderedKey, V: Value, IK: Metakey, N: Metakey>(
+        &self,
+        handle: &Handle<SortedMapState<K, V>, IK, N>,
+    ) -> Result<()> {
+        let prefix = handle.serialize_metakeys()?;
+        self.remove_prefix(&handle.id, &prefix);
+        Ok(())
+    }
+
+    fn sorted_map_get<K: OrderedKey, V: Value, IK: Metakey, N: Metakey>(
+        &self,
+        handle: &Handle<SortedMapState<K, V>, IK, N>,
+        key: &K,
+    ) -> Result<Option<V>> {
+        let key = handle.serialize_metakeys_and_ordered_key(key)?;
+        if let Some(serialized) = self.get(&handle.id, &key) {
+            #[cfg(feature = "metrics")]
+            record_bytes_read(handle.name(), serialized.len() as u64, self.name.as_str());
+            let value = protobuf::deserialize(&serialized)?;
+            Ok(Some(value))
+        } else {
+            Ok(None)
+        }
+    }
+
+    fn sorted_map_fast_insert<K: OrderedKey, V: Value, IK: Metakey, N: Metakey>(
+        &self,
+        handle: &Handle<SortedMapState<K, V>, IK, N>,
+        key: K,
+        value: V,
+    ) -> Result<()> {
+        let key = handle.serialize_metakeys_and_ordered_key(&key)?;
+        let serialized = protobuf::serialize(&value)?;
+        #[cfg(feature = "metrics")]
+        record_bytes_written(handle.name(), serialized.len() as u64, self.name.as_str());
+        self.put(&handle.id, key, serialized);
+
+        Ok(())
+    }
+
+    fn sorted_map_remove<K: OrderedKey, V: Value, IK: Metakey, N: Metakey>(
+        &self,
+        handle: &Handle<SortedMapState<K, V>, IK, N>,
+        key: &K,
+    ) -> Result<Option<V>> {
+        let key = handle.serialize_metakeys_and_ordered_key(key)?;
+        match self.remove(&handle.id, &key) {
+            Some(bytes) => Ok(Some(protobuf::deserialize(&bytes)?)),
+            None => Ok(None),
+        }
+    }
+
+    fn sorted_map_range<K: OrderedKey, V: Value, IK: Metakey, N: Metakey>(
+        &self,
+        handle: &Handle<SortedMapState<K, V>, IK, N>,
+        range: impl RangeBounds<K>,
+    ) -> Result<BoxedIteratorOfResult<'_, (K, V)>> {
+        let bounds = handle.serialize_range(range)?;
+        let metakey_size = handle.metakey_size();
+
+        let iter = self.scan_range(&handle.id, &bounds).into_iter().map(
+            move |(db_key, serialized_value)| {
+                let key: K = ordered::deserialize(&db_key[metakey_size..])?;
+                let value: V = protobuf::deserialize(&serialized_value)?;
+
+                Ok((key, value))
+            },
+        );
+
+        Ok(Box::new(iter))
+    }
+
+    fn sorted_map_last<K: OrderedKey, V: Value, IK: Metakey, N: Metakey>(
+        &self,
+        handle: &Handle<SortedMapState<K, V>, IK, N>,
+    ) -> Result<Option<(K, V)>> {
+        let bounds = handle.serialize_range(..)?;
+        let last = self.last_in_range(&handle.id, &bounds);
+        deserialize_entry(handle, last)
+    }
+
+    fn sorted_map_first<K: OrderedKey, V: Value, IK: Metakey, N: Metakey>(
+        &self,
+        handle: &Handle<SortedMapState<K, V>, IK, N>,
+    ) -> Result<Option<(K, V)>> {
+        let bounds = handle.serialize_range(..)?;
+        let first = self.first_in_range(&handle.id, &bounds);
+        deserialize_entry(handle, first)
+    }
+
+    fn sorted_map_pop_first<K: OrderedKey, V: Value, IK: Metakey, N: Metakey>(
+        &self,
+        handle: &Handle<SortedMapState<K, V>, IK, N>,
+    ) -> Result<Option<(K, V)>> {
+        let bounds = handle.serialize_range(..)?;
+        let first = self.first_in_range(&handle.id, &bounds);
+        if let Some((db_key, _)) = &first {
+            self.remove(&handle.id, db_key);
+        }
+        deserialize_entry(handle, first)
+    }
+
+    fn sorted_map_len<K: OrderedKey, V: Value, IK: Metakey, N: Metakey>(
+        &self,
+        handle: &Handle<SortedMapState<K, V>, IK, N>,
+    ) -> Result<usize> {
+        let bounds = handle.serialize_range(..)?;
+        Ok(self.count_range(&handle.id, &bounds))
+    }
+}
+
+fn deserialize_entry<K: OrderedKey, V: Value, IK: Metakey, N: Metakey>(
+    handle: &Handle<SortedMapState<K, V>, IK, N>,
+    entry: Option<(Vec<u8>, Vec<u8>)>,
+) -> Result<Option<(K, V)>> {
+    match entry {
+        Some((db_key, serialized_value)) => {
+            let key: K = ordered::deserialize(&db_key[handle.metakey_size()..])?;
+            let value: V = protobuf::deserialize(&serialized_value)?;
+            Ok(Some((key, value)))
+        }
+        None => Ok(None),
+    }
+}
@@ -0,0 +1,147 @@
+use crate::{
+    data::{Metakey, Value},
+    error::*,
+    handles::BoxedIteratorOfResult,
+    in_memory::InMemory,
+    serialization::{fixed_bytes, fixed_bytes::FixedBytes, protobuf},
+    Handle, VecOps, VecState,
+};
+
+#[cfg(feature = "metrics")]
+use crate::metrics_utils::*;
+
+impl VecOps for InMemory {
+    fn vec_clear<T: Value, IK: Metakey, N: Metakey>(
+        &self,
+        handle: &Handle<VecState<T>, IK, N>,
+    ) -> Result<()> {
+        let key = handle.serialize_metakeys()?;
+        self.remove(&handle.id, &key);
+        Ok(())
+    }
+
+    fn vec_append<T: Value, IK: Metakey, N: Metakey>(
+        &self,
+        handle: &Handle<VecState<T>, IK, N>,
+        value: T,
+    ) -> Result<()> {
+        self.vec_add_all(handle, std::iter::once(value))
+    }
+
+    fn vec_get<T: Value, IK: Metakey, N: Metakey>(
+        &self,
+        handle: &Handle<VecState<T>, IK, N>,
+    ) -> Result<Vec<T>> {
+        let key = handle.serialize_metakeys()?;
+        if let Some(serialized) = self.get(&handle.id, &key) {
+            #[cfg(feature = "metrics")]
+            record_bytes_read(handle.name(), serialized.len() as u64, self.name.as_str());
+            // reader is updated to point at the yet unconsumed part of the serialized data
+            let mut reader = &serialized[..];
+            let len: usize = fixed_bytes::deserialize_from(&mut reader)?;
+            let mut res = Vec::with_capacity(len);
+            while !reader.is_empty() {
+                let val = protobuf::deserialize_from(&mut reader)?;
+                res.push(val);
+            }
+            // sanity check
+            assert_eq!(res.len(), len);
+
+            Ok(res)
+        } else {
+            Ok(vec![])
+        }
+    }
+
+    fn vec_iter<T: Value, IK: Metakey, N: Metakey>(
+        &self,
+        handle: &Handle<VecState<T>, IK, N>,
+    ) -> Result<BoxedIteratorOfResult<'_, T>> {
+        // the values are copied out of the lock anyway, so there is nothing to gain from lazy decoding
+        let values = self.vec_get(handle)?;
+        Ok(Box::new(values.into_iter().map(Ok)))
+    }
+
+    fn vec_set<T: Value, IK: Metakey, N: Metakey>(
+        &self,
+        handle: &Handle<VecState<T>, IK, N>,
+        value: Vec<T>,
+    ) -> Result<()> {
+        let key = handle.serialize_metakeys()?;
+        let raw_serialized_len: usize = value
+            .iter()
+            .flat_map(|x| protobuf::size_hint(x).into_iter())
+            .sum();
+        let cap = <usize as FixedBytes>::SIZE + raw_serialized_len;
+
+        let mut storage = Vec::with_capacity(cap);
+        fixed_bytes::serialize_into(&mut storage, &value.len())?;
+        for elem in value {
+            protobuf::serialize_into(&mut storage, &elem)?;
+        }
+        #[cfg(feature = "metrics")]
+        record_bytes_written(handle.name(), storage.len() as u64, self.name.as_str());
+        self.put(&handle.id, key, storage);
+
+        Ok(())
+    }
+
+    fn vec_add_all<T: Value, IK: Metakey, N: Metakey>(
+        &self,
+        handle: &Handle<VecState<T>, IK, N>,
+        values: impl IntoIterator<Item = T>,
+    ) -> Result<()> {
+        let key = handle.serialize_metakeys()?;
+
+        let mut serialized = Vec::new();
+        let mut len = 0usize;
+        for elem in values {
+            len += 1;
+            protobuf::serialize_into(&mut serialized, &elem)?;
+        }
+        #[cfg(feature = "metrics")]
+        record_bytes_written(handle.name(), serialized.len() as u64, self.name.as_str());
+
+        self.update(&handle.id, key, |existent| {
+            let mut storage = match existent {
+                Some(bytes) => bytes.to_vec(),
+                None => {
+                    let mut storage =
+                        Vec::with_capacity(<usize as FixedBytes>::SIZE + serialized.len());
+                    fixed_bytes::serialize_into(&mut storage, &0usize)?;
+                    storage
+                }
+            };
+            let old_len: usize = fixed_bytes::deserialize_from(&mut storage.as_slice())?;
+            // BufMut impl for mutable slices overwrites from the beginning instead of extending
+            fixed_bytes::serialize_into(&mut storage.as_mut_slice(), &(old_len + len))?;
+            storage.extend_from_slice(&serialized);
+            Ok(storage)
+        })
+    }
+
+    fn vec_len<T: Value, IK: Metakey, N: Metakey>(
+        &self,
+        handle: &Handle<VecState<T>, IK, N>,
+    ) -> Result<usize> {
+        let key = handle.serialize_metakeys()?;
+        if let Some(storage) = self.get(&handle.id, &key) {
+            if storage.is_empty() {
+                return Ok(0);
+            }
+            #[cfg(feature = "metrics")]
+            record_bytes_read(handle.name(), storage.len() as u64, self.name.as_str());
+            let len = fixed_bytes::deserialize_from(&mut storage.as_slice())?;
+            Ok(len)
+        } else {
+            Ok(0)
+        }
+    }
+
+    fn vec_is_empty<T: Value, IK: Metakey, N: Metakey>(
+        &self,
+        handle: &Handle<VecState<T>, IK, N>,
+    ) -> Result<bool> {
+        Ok(self.vec_len(handle)? == 0)
+    }
+}
@@ -83,6 +83,10 @@ macro_rules! with_backend_type {
                     $body
                 }
             }
+            InMemory => {
+                type $type_ident = $crate::backend::in_memory::InMemory;
+                $body
+            }
         }
     }};
 }
@@ -10,32 +10,53 @@ pub mod test_common;
 
 pub use crate::{
     handles::Handle,
-    ops::{AggregatorOps, MapOps, ReducerOps, ValueOps, VecOps},
+    ops::{AggregatorOps, MapOps, ReducerOps, SortedMapOps, ValueOps, VecOps},
 };
 
 use crate::{
-    data::{Key, Metakey, Value},
+    data::{Key, Metakey, OrderedKey, Value},
     error::*,
+    storage::CheckpointStorage,
 };
 use std::{
     any,
-    collections::{BTreeSet, HashMap},
+    collections::{BTreeMap, BTreeSet, HashMap},
     fmt,
     fmt::{Debug, Formatter},
     fs,
     marker::PhantomData,
     path::{Path, PathBuf},
+    sync::Arc,
 };
 
+/// Directory next to the checkpoints of a backend that holds files shared between them
+///
+/// Backends that checkpoint incrementally, such as RocksDB, only store the files that changed
+/// since their previous checkpoint in a checkpoint directory and reference the rest in here.
+pub const SHARED_CHECKPOINT_DIR: &str = "shared";
+
+/// Suffix of the directories that backends assemble checkpoints in before completing them
+///
+/// Checkpoints that never completed may leave these behind, they are removed when restoring.
+pub const STAGING_SUFFIX: &str = ".staging";
+
+/// Id of the map handle in which a backend records the versions of the value types of handles
+pub const SCHEMA_HANDLE_ID: &str = "__arcon_schema";
+
 #[derive(Debug, Default)]
 pub struct Config {
     pub live_state_base_path: PathBuf,
     pub checkpoints_base_path: PathBuf,
     pub backend_ids: Vec<String>,
+    /// Storage that keeps a copy of `checkpoints_base_path`
+    ///
+    /// If set, checkpoints are looked up in the storage and fetched into
+    /// `checkpoints_base_path` before they are restored.
+    pub storage: Option<Arc<dyn CheckpointStorage>>,
 }
 
 pub trait Backend:
-    ValueOps + MapOps + VecOps + ReducerOps + AggregatorOps + Send + Sync + 'static
+    ValueOps + MapOps + SortedMapOps + VecOps + ReducerOps + AggregatorOps + Send + Sync + 'static
 {
     fn restore_or_create(config: &Config, id: String) -> Result<Self>
     where
@@ -54,24 +75,53 @@ pub trait Backend:
             .map(|id| (id.as_str(), BTreeSet::new()))
             .collect();
 
-        for directory in fs::read_dir(&config.checkpoints_base_path)? {
-            let directory = directory?;
+        // names of the entries of the checkpoint directory and whether they are directories
+        let entries: Vec<(String, bool)> = match &config.storage {
+            Some(storage) => {
+                let mut entries = BTreeMap::new();
+                for key in storage.list("")? {
+                    match key.split_once('/') {
+                        Some((dir, _)) => entries.insert(dir.to_string(), true),
+                        None => entries.insert(key, false),
+                    };
+                }
+                entries.into_iter().collect()
+            }
+            None => {
+                let mut entries = Vec::new();
+                for directory in fs::read_dir(&config.checkpoints_base_path)? {
+                    let directory = directory?;
+                    let dir_name = directory.file_name();
+                    let dir_name = dir_name.to_str().context(InvalidPath {
+                        path: directory.path(),
+                    })?;
+                    let is_dir = directory.metadata().map(|m| m.is_dir()).unwrap_or(false);
+                    entries.push((dir_name.to_string(), is_dir));
+                }
+                entries
+            }
+        };
 
-            let invalid_path = || InvalidPath {
-                path: directory.path(),
-            };
+        for (dir_name, is_dir) in &entries {
+            let path = config.checkpoints_base_path.join(dir_name);
+            let invalid_path = || InvalidPath { path: path.clone() };
 
-            let dir_name = directory.file_name();
-            let dir_name = dir_name.to_str().with_context(invalid_path)?;
+            if dir_name == SHARED_CHECKPOINT_DIR {
+                continue;
+            }
+
+            if dir_name.ends_with(STAGING_SUFFIX) {
+                if path.exists() {
+                    fs::remove_dir_all(&path)?;
+                }
+                continue;
+            }
 
             const CHECKPOINT_PREFIX: &str = "checkpoint_";
 
             ensure!(
-                dir_name.starts_with(CHECKPOINT_PREFIX)
-                    && directory.metadata().map(|m| m.is_dir()).unwrap_or(false),
-                InvalidPath {
-                    path: directory.path()
-                }
+                dir_name.starts_with(CHECKPOINT_PREFIX) && *is_dir,
+                InvalidPath { path: path.clone() }
             );
 
             let dir_name = &dir_name[CHECKPOINT_PREFIX.len()..];
@@ -86,9 +136,7 @@ pub trait Backend:
 
             ensure!(
                 dir_name_parts.next().is_none(),
-                InvalidPath {
-                    path: directory.path(),
-                }
+                InvalidPath { path: path.clone() }
             );
 
             let checkpoints_for_id = checkpoints.get_mut(id).with_context(|| UnknownNode {
@@ -114,12 +162,16 @@ pub trait Backend:
 
         match last_complete_checkpoint {
             Some(epoch) => {
+                let checkpoint_name = format!("checkpoint_{id}_{epoch}", id = id, epoch = epoch);
                 let mut latest_checkpoint_path = config.checkpoints_base_path.clone();
-                latest_checkpoint_path.push(format!(
-                    "checkpoint_{id}_{epoch}",
-                    id = id,
-                    epoch = epoch
-                ));
+                latest_checkpoint_path.push(&checkpoint_name);
+
+                // fetch the checkpoint and the files it may share with others
+                if let Some(storage) = &config.storage {
+                    storage.get_dir(&checkpoint_name, &latest_checkpoint_path)?;
+                    let shared_path = config.checkpoints_base_path.join(SHARED_CHECKPOINT_DIR);
+                    storage.get_dir(SHARED_CHECKPOINT_DIR, &shared_path)?;
+                }
 
                 Self::restore(&state_path, &latest_checkpoint_path, id)
             }
@@ -140,6 +192,77 @@ pub trait Backend:
 
     fn checkpoint(&self, checkpoint_path: &Path) -> Result<()>;
 
+    /// Takes a checkpoint in two phases
+    ///
+    /// The call itself captures the state of the backend, which may be modified again as
+    /// soon as it returns. Writing the captured state to `checkpoint_path` is left to the
+    /// returned [AsyncCheckpoint], which may be completed on another thread. The default
+    /// implementation writes the whole checkpoint synchronously.
+    fn async_checkpoint(&self, checkpoint_path: &Path) -> Result<AsyncCheckpoint> {
+        self.checkpoint(checkpoint_path)?;
+        Ok(AsyncCheckpoint::completed())
+    }
+
+    /// Returns the version of the value type that the values of `handle_id` were written with
+    fn schema_version(&self, handle_id: &str) -> Result<Option<u32>>
+    where
+        Self: Sized,
+    {
+        let mut handle = Handle::<MapState<String, u32>>::map(SCHEMA_HANDLE_ID);
+        self.register_map_handle(&mut handle);
+        self.map_get(&handle, &handle_id.to_string())
+    }
+
+    /// Records the version of the value type that the values of `handle_id` are written with
+    fn set_schema_version(&self, handle_id: &str, version: u32) -> Result<()>
+    where
+        Self: Sized,
+    {
+        let mut handle = Handle::<MapState<String, u32>>::map(SCHEMA_HANDLE_ID);
+        self.register_map_handle(&mut handle);
+        self.map_fast_insert(&handle, handle_id.to_string(), version)
+    }
+
+    /// Records `version` for `handle_id` unless a version is already recorded
+    ///
+    /// Called when a handle is registered, so that values written before a schema
+    /// is declared for the handle are migrated from the version they were written with.
+    fn record_schema_version(&self, handle_id: &str, version: u32)
+    where
+        Self: Sized,
+    {
+        if handle_id == SCHEMA_HANDLE_ID {
+            return;
+        }
+        let recorded = self
+            .schema_version(handle_id)
+            .and_then(|stored| match stored {
+                Some(_) => Ok(()),
+                None => self.set_schema_version(handle_id, version),
+            });
+        if let Err(err) = recorded {
+            eprintln!(
+                "Failed to record the schema version of {}: {:?}",
+                handle_id, err
+            );
+        }
+    }
+
+    /// Returns the ids of all handles that have storage allocated in the backend
+    fn handle_ids(&self) -> Result<Vec<String>>;
+
+    /// Calls `f` with the serialized key and value of every entry stored for `handle_id`
+    fn scan_raw(&self, handle_id: &str, f: &mut dyn FnMut(&[u8], &[u8])) -> Result<()>;
+
+    /// Replaces every value stored for `handle_id` with the result of `f`
+    ///
+    /// Values are passed in the serialized form the state operations store them in.
+    fn rewrite_values<E: From<ArconStateError>>(
+        &self,
+        handle_id: &str,
+        f: impl FnMut(&[u8]) -> std::result::Result<Vec<u8>, E>,
+    ) -> std::result::Result<(), E>;
+
     // region handle registration
     fn register_value_handle<T: Value, IK: Metakey, N: Metakey>(
         &self,
@@ -149,6 +272,10 @@ pub trait Backend:
         &self,
         handle: &mut Handle<MapState<K, V>, IK, N>,
     );
+    fn register_sorted_map_handle<K: OrderedKey, V: Value, IK: Metakey, N: Metakey>(
+        &self,
+        handle: &mut Handle<SortedMapState<K, V>, IK, N>,
+    );
     fn register_vec_handle<T: Value, IK: Metakey, N: Metakey>(
         &self,
         handle: &mut Handle<VecState<T>, IK, N>,
@@ -164,6 +291,32 @@ pub trait Backend:
     // endregion
 }
 
+/// The asynchronous phase of a checkpoint, see [Backend::async_checkpoint]
+#[must_use = "the checkpoint is only written once it has been completed"]
+pub struct AsyncCheckpoint(Box<dyn FnOnce() -> Result<()> + Send>);
+
+impl AsyncCheckpoint {
+    pub fn new(write: impl FnOnce() -> Result<()> + Send + 'static) -> Self {
+        AsyncCheckpoint(Box::new(write))
+    }
+
+    /// A checkpoint that has already been written
+    pub fn completed() -> Self {
+        Self::new(|| Ok(()))
+    }
+
+    /// Writes the checkpoint, blocking until it is done
+    pub fn complete(self) -> Result<()> {
+        (self.0)()
+    }
+}
+
+impl Debug for AsyncCheckpoint {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str("AsyncCheckpoint")
+    }
+}
+
 pub trait StateType: Default {
     type ExtraData;
 }
@@ -190,6 +343,18 @@ impl<K: Key, V: Value> Default for MapState<K, V> {
     }
 }
 
+/// Map state that keeps its records ordered by key
+#[derive(Debug)]
+pub struct SortedMapState<K: OrderedKey, V: Value>(PhantomData<(K, V)>);
+impl<K: OrderedKey, V: Value> StateType for SortedMapState<K, V> {
+    type ExtraData = ();
+}
+impl<K: OrderedKey, V: Value> Default for SortedMapState<K, V> {
+    fn default() -> Self {
+        SortedMapState(Default::default())
+    }
+}
+
 #[derive(Debug)]
 pub struct VecState<T: Value>(PhantomData<T>);
 impl<T: Value> StateType for VecState<T> {
@@ -241,16 +406,35 @@ impl<A: Aggregator> Default for AggregatorState<A> {
     }
 }
 
+/// Returns the files in the [SHARED_CHECKPOINT_DIR] that the checkpoint at `checkpoint_path` references
+///
+/// Only incremental checkpoints reference shared files.
+#[cfg(feature = "rocks")]
+pub fn shared_checkpoint_files(checkpoint_path: &Path) -> Result<Vec<PathBuf>> {
+    rocks::shared_files(checkpoint_path)
+}
+
+/// Returns the files in the [SHARED_CHECKPOINT_DIR] that the checkpoint at `checkpoint_path` references
+///
+/// Only incremental checkpoints, which are taken by RocksDB, reference shared files.
+#[cfg(not(feature = "rocks"))]
+pub fn shared_checkpoint_files(_checkpoint_path: &Path) -> Result<Vec<PathBuf>> {
+    Ok(Vec::new())
+}
+
 #[cfg(feature = "rocks")]
 pub mod rocks;
 #[cfg(feature = "rocks")]
 pub use self::rocks::Rocks;
 
+pub mod in_memory;
 #[cfg(feature = "metrics")]
 pub mod metrics_utils;
 #[cfg(feature = "sled")]
 pub mod sled;
 
+pub use self::in_memory::InMemory;
+
 #[cfg(feature = "sled")]
 pub use self::sled::Sled;
 
@@ -260,6 +444,7 @@ pub enum BackendType {
     Rocks,
     #[cfg(feature = "sled")]
     Sled,
+    InMemory,
 }
 
 impl fmt::Display for BackendType {
@@ -276,6 +461,7 @@ impl BackendType {
             Rocks,
             #[cfg(feature = "sled")]
             Sled,
+            InMemory,
         ]
     };
 
@@ -285,6 +471,7 @@ impl BackendType {
             "Rocks",
             #[cfg(feature = "sled")]
             "Sled",
+            "InMemory",
         ]
     };
 }
@@ -300,6 +487,7 @@ impl FromStr for BackendType {
             x if x.eq_ignore_ascii_case("Rocks") => Ok(Rocks),
             #[cfg(feature = "sled")]
             x if x.eq_ignore_ascii_case("Sled") => Ok(Sled),
+            x if x.eq_ignore_ascii_case("InMemory") => Ok(InMemory),
             _ => Err(format!(
                 "valid values: {}",
                 BackendType::VARIANTS
@@ -1,9 +1,11 @@
 use crate::{
-    data::{Key, Metakey, Value},
+    data::{Key, Metakey, OrderedKey, Value},
     error::*,
     handles::BoxedIteratorOfResult,
-    Aggregator, AggregatorState, Handle, MapState, Reducer, ReducerState, ValueState, VecState,
+    Aggregator, AggregatorState, Handle, MapState, Reducer, ReducerState, SortedMapState,
+    ValueState, VecState,
 };
+use std::ops::RangeBounds;
 
 pub trait ValueOps {
     fn value_clear<T: Value, IK: Metakey, N: Metakey>(
@@ -124,6 +126,72 @@ pub trait MapOps {
     ) -> Result<bool>;
 }
 
+pub trait SortedMapOps {
+    fn sorted_map_clear<K: OrderedKey, V: Value, IK: Metakey, N: Metakey>(
+        &self,
+        handle: &Handle<SortedMapState<K, V>, IK, N>,
+    ) -> Result<()>;
+
+    fn sorted_map_get<K: OrderedKey, V: Value, IK: Metakey, N: Metakey>(
+        &self,
+        handle: &Handle<SortedMapState<K, V>, IK, N>,
+        key: &K,
+    ) -> Result<Option<V>>;
+
+    fn sorted_map_fast_insert<K: OrderedKey, V: Value, IK: Metakey, N: Metakey>(
+        &self,
+        handle: &Handle<SortedMapState<K, V>, IK, N>,
+        key: K,
+        value: V,
+    ) -> Result<()>;
+
+    fn sorted_map_remove<K: OrderedKey, V: Value, IK: Metakey, N: Metakey>(
+        &self,
+        handle: &Handle<SortedMapState<K, V>, IK, N>,
+        key: &K,
+    ) -> Result<Option<V>>;
+
+    /// Iterates in ascending key order over the records whose keys lie in `range`
+    fn sorted_map_range<K: OrderedKey, V: Value, IK: Metakey, N: Metakey>(
+        &self,
+        handle: &Handle<SortedMapState<K, V>, IK, N>,
+        range: impl RangeBounds<K>,
+    ) -> Result<BoxedIteratorOfResult<'_, (K, V)>>;
+
+    /// Returns the record with the greatest key
+    fn sorted_map_last<K: OrderedKey, V: Value, IK: Metakey, N: Metakey>(
+        &self,
+        handle: &Handle<SortedMapState<K, V>, IK, N>,
+    ) -> Result<Option<(K, V)>>;
+
+    /// Returns the record with the smallest key
+    fn sorted_map_first<K: OrderedKey, V: Value, IK: Metakey, N: Metakey>(
+        &self,
+        handle: &Handle<SortedMapState<K, V>, IK, N>,
+    ) -> Result<Option<(K, V)>> {
+        self.sorted_map_range(handle, ..)?.next().transpose()
+    }
+
+    /// Removes and returns the record with the smallest key
+    fn sorted_map_pop_first<K: OrderedKey, V: Value, IK: Metakey, N: Metakey>(
+        &self,
+        handle: &Handle<SortedMapState<K, V>, IK, N>,
+    ) -> Result<Option<(K, V)>> {
+        let first = self.sorted_map_first(handle)?;
+        if let Some((key, _)) = &first {
+            self.sorted_map_remove(handle, key)?;
+        }
+        Ok(first)
+    }
+
+    fn sorted_map_len<K: OrderedKey, V: Value, IK: Metakey, N: Metakey>(
+        &self,
+        handle: &Handle<SortedMapState<K, V>, IK, N>,
+    ) -> Result<usize> {
+        Ok(self.sorted_map_range(handle, ..)?.count())
+    }
+}
+
 pub trait VecOps {
     fn vec_clear<T: Value, IK: Metakey, N: Metakey>(
         &self,
@@ -0,0 +1,174 @@
+//! Incremental checkpoints of the RocksDB backend
+//!
+//! SST files are immutable once written, so a checkpoint only has to store the SST files
+//! that were created since the previous one. A checkpoint directory holds the remaining,
+//! small files of the database (`MANIFEST`, `CURRENT`, `OPTIONS`) and an [IncrementalManifest]
+//! that lists the SST files it consists of. The SST files themselves live in the
+//! [SHARED_CHECKPOINT_DIR] next to the checkpoints, where every checkpoint that references
+//! a file counts towards keeping it around.
+
+use crate::{error::*, SHARED_CHECKPOINT_DIR};
+use prost::Message;
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Mutex,
+    },
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+/// Name of the file that marks a directory as an incremental checkpoint
+pub(super) const MANIFEST_FILE: &str = "INCREMENTAL_MANIFEST";
+
+#[derive(Message)]
+pub(super) struct IncrementalManifest {
+    /// SST file name in the database -> file name in the shared directory
+    #[prost(map = "string, string", tag = "1")]
+    pub sst_files: HashMap<String, String>,
+}
+
+impl IncrementalManifest {
+    /// Loads the manifest of `checkpoint_path`, or `None` for a full checkpoint
+    pub(super) fn load(checkpoint_path: &Path) -> Result<Option<Self>> {
+        let path = checkpoint_path.join(MANIFEST_FILE);
+        if !path.exists() {
+            return Ok(None);
+        }
+        let bytes = fs::read(path)?;
+        Ok(Some(Self::decode(bytes.as_slice())?))
+    }
+
+    pub(super) fn store(&self, checkpoint_path: &Path) -> Result<()> {
+        fs::write(checkpoint_path.join(MANIFEST_FILE), self.encode_to_vec())?;
+        Ok(())
+    }
+}
+
+#[inline]
+fn is_sst_file(name: &str) -> bool {
+    name.ends_with(".sst")
+}
+
+/// Shared directory of the checkpoints that `checkpoint_path` is stored next to
+pub(super) fn shared_dir(checkpoint_path: &Path) -> Result<PathBuf> {
+    let parent = checkpoint_path.parent().context(InvalidPath {
+        path: checkpoint_path,
+    })?;
+    Ok(parent.join(SHARED_CHECKPOINT_DIR))
+}
+
+/// Paths of the files in the shared directory that the checkpoint at `checkpoint_path` references
+pub(super) fn shared_files(checkpoint_path: &Path) -> Result<Vec<PathBuf>> {
+    let shared_dir = shared_dir(checkpoint_path)?;
+    let files = match IncrementalManifest::load(checkpoint_path)? {
+        Some(manifest) => manifest
+            .sst_files
+            .values()
+            .map(|shared| shared_dir.join(shared))
+            .collect(),
+        None => Vec::new(),
+    };
+    Ok(files)
+}
+
+/// A prefix that is unique to a single opened database
+///
+/// RocksDB numbers its files per database, so a database restored from an older checkpoint
+/// creates files with the same names as the checkpoints that were taken after it.
+/// Prefixing shared files keeps those apart.
+pub(super) fn new_session_id() -> String {
+    static SESSIONS: AtomicU64 = AtomicU64::new(0);
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_nanos())
+        .unwrap_or_default();
+    format!(
+        "{:x}{:x}{:x}",
+        nanos,
+        std::process::id(),
+        SESSIONS.fetch_add(1, Ordering::Relaxed)
+    )
+}
+
+/// Splits the regular checkpoint in `staging_path` into an incremental one in `checkpoint_path`
+///
+/// SST files that are not part of `shared_files` yet are moved into the shared directory,
+/// all other files are private to the checkpoint.
+pub(super) fn complete_checkpoint(
+    staging_path: &Path,
+    checkpoint_path: &Path,
+    session: &str,
+    shared_files: &Mutex<HashMap<String, String>>,
+) -> Result<()> {
+    let shared_dir = shared_dir(checkpoint_path)?;
+    fs::create_dir_all(&shared_dir)?;
+    fs::create_dir_all(checkpoint_path)?;
+
+    let mut shared_files = shared_files.lock().unwrap();
+    let mut manifest = IncrementalManifest::default();
+    for entry in fs::read_dir(staging_path)? {
+        let entry = entry?;
+        let file = entry.file_name().to_string_lossy().into_owned();
+        if !is_sst_file(&file) {
+            fs::rename(entry.path(), checkpoint_path.join(&file))?;
+            continue;
+        }
+        let shared = match shared_files.get(&file) {
+            Some(shared) if shared_dir.join(shared).exists() => shared.clone(),
+            _ => {
+                let shared = format!("{}-{}", session, file);
+                fs::rename(entry.path(), shared_dir.join(&shared))?;
+                shared
+            }
+        };
+        manifest.sst_files.insert(file, shared);
+    }
+    fs::remove_dir_all(staging_path)?;
+
+    // the manifest is written last, as it is what marks the checkpoint as complete
+    manifest.store(checkpoint_path)?;
+    *shared_files = manifest.sst_files;
+
+    // checkpoints may have been removed since the previous one. The checkpoint itself is
+    // complete at this point, so failing to clean up after older ones does not fail it.
+    let checkpoints_dir = shared_dir.parent().expect("shared dir without a parent");
+    if let Err(err) = collect_garbage(checkpoints_dir) {
+        eprintln!(
+            "failed to remove unreferenced shared checkpoint files: {}",
+            err
+        );
+    }
+    Ok(())
+}
+
+/// Removes the shared files that are no longer referenced by any checkpoint in `checkpoints_dir`
+fn collect_garbage(checkpoints_dir: &Path) -> Result<()> {
+    let shared_dir = checkpoints_dir.join(SHARED_CHECKPOINT_DIR);
+    let mut references: HashMap<String, usize> = HashMap::new();
+    for entry in fs::read_dir(&shared_dir)? {
+        let name = entry?.file_name().to_string_lossy().into_owned();
+        references.insert(name, 0);
+    }
+
+    for entry in fs::read_dir(checkpoints_dir)? {
+        let entry = entry?;
+        if !entry.file_type()?.is_dir() {
+            continue;
+        }
+        if let Some(manifest) = IncrementalManifest::load(&entry.path())? {
+            for shared in manifest.sst_files.values() {
+                if let Some(count) = references.get_mut(shared) {
+                    *count += 1;
+                }
+            }
+        }
+    }
+
+    for (name, _) in references.into_iter().filter(|(_, count)| *count == 0) {
+        fs::remove_file(shared_dir.join(name))?;
+    }
+    Ok(())
+}
@@ -1,19 +1,21 @@
 use crate::{
-    data::{Key, Metakey, Value},
+    data::{Key, Metakey, OrderedKey, Value},
     error::*,
-    Aggregator, AggregatorState, Backend, Handle, MapState, Reducer, ReducerState, ValueState,
-    VecState,
+    Aggregator, AggregatorState, AsyncCheckpoint, Backend, Handle, MapState, Reducer, ReducerState,
+    SortedMapState, ValueState, VecState, STAGING_SUFFIX,
 };
 
+use incremental::IncrementalManifest;
 use rocksdb::{
-    checkpoint::Checkpoint, ColumnFamily, ColumnFamilyDescriptor, DBPinnableSlice, Options,
-    SliceTransform, WriteBatch, WriteOptions, DB,
+    checkpoint::Checkpoint, ColumnFamily, ColumnFamilyDescriptor, DBPinnableSlice, IteratorMode,
+    Options, SliceTransform, WriteBatch, WriteOptions, DB,
 };
 use std::{
     cell::UnsafeCell,
-    collections::HashSet,
+    collections::{HashMap, HashSet},
     fs,
     path::{Path, PathBuf},
+    sync::{Arc, Mutex},
 };
 
 unsafe impl Send for Rocks {}
@@ -24,6 +26,10 @@ pub struct Rocks {
     inner: UnsafeCell<DB>,
     restored: bool,
     name: String,
+    /// Prefix of the shared files uploaded by this database
+    session: String,
+    /// SST files of the previous checkpoint and their names in the shared directory
+    shared_files: Arc<Mutex<HashMap<String, String>>>,
 }
 
 // we use epochs, so WAL is useless for us
@@ -172,6 +178,8 @@ impl Backend for Rocks {
             inner: UnsafeCell::new(DB::open_cf_descriptors(&opts, &path, cfds)?),
             restored: false,
             name,
+            session: incremental::new_session_id(),
+            shared_files: Arc::new(Mutex::new(HashMap::new())),
         })
     }
 
@@ -186,6 +194,8 @@ impl Backend for Rocks {
             RocksRestoreDirNotEmpty { dir: &(*live_path) }
         );
 
+        let manifest = IncrementalManifest::load(checkpoint_path)?;
+
         let mut target_path: PathBuf = live_path.into();
         target_path.push("__DUMMY"); // the file name is replaced inside the loop below
         for entry in fs::read_dir(checkpoint_path)? {
@@ -196,6 +206,10 @@ impl Backend for Rocks {
                 .expect("Cannot read entry metadata")
                 .is_file());
 
+            if entry.file_name() == incremental::MANIFEST_FILE {
+                continue;
+            }
+
             let source_path = entry.path();
             // replaces the __DUMMY from above the loop
             target_path.set_file_name(
@@ -207,8 +221,22 @@ impl Backend for Rocks {
             fs::copy(&source_path, &target_path)?;
         }
 
+        // reassemble the SST files of an incremental checkpoint from the shared directory
+        let shared_files = match manifest {
+            Some(manifest) => {
+                let shared_dir = incremental::shared_dir(checkpoint_path)?;
+                for (file, shared) in &manifest.sst_files {
+                    fs::copy(shared_dir.join(shared), live_path.join(file))?;
+                }
+                manifest.sst_files
+            }
+            None => HashMap::new(),
+        };
+
         Rocks::create(live_path, name).map(|mut r| {
             r.restored = true;
+            // the restored files are already in the shared directory
+            r.shared_files = Arc::new(Mutex::new(shared_files));
             r
         })
     }
@@ -218,6 +246,17 @@ impl Backend for Rocks {
     }
 
     fn checkpoint(&self, checkpoint_path: &Path) -> Result<()> {
+        self.async_checkpoint(checkpoint_path)?.complete()
+    }
+
+    /// Creates an incremental checkpoint
+    ///
+    /// The synchronous phase creates a regular RocksDB checkpoint next to `checkpoint_path`,
+    /// which only hard links the SST files of the live database. The asynchronous phase then
+    /// moves the SST files that were created since the previous checkpoint into the
+    /// [SHARED_CHECKPOINT_DIR](crate::SHARED_CHECKPOINT_DIR) and references unchanged ones
+    /// from the earlier checkpoints that stored them.
+    fn async_checkpoint(&self, checkpoint_path: &Path) -> Result<AsyncCheckpoint> {
         let db = self.db();
         db.flush()?;
 
@@ -229,7 +268,62 @@ impl Backend for Rocks {
             fs::remove_dir_all(checkpoint_path)?
         }
 
-        checkpointer.create_checkpoint(checkpoint_path)?;
+        let mut staging_path = checkpoint_path.as_os_str().to_owned();
+        staging_path.push(STAGING_SUFFIX);
+        let staging_path = PathBuf::from(staging_path);
+        if staging_path.exists() {
+            fs::remove_dir_all(&staging_path)?;
+        }
+        checkpointer.create_checkpoint(&staging_path)?;
+
+        let checkpoint_path = checkpoint_path.to_path_buf();
+        let session = self.session.clone();
+        let shared_files = self.shared_files.clone();
+        Ok(AsyncCheckpoint::new(move || {
+            incremental::complete_checkpoint(
+                &staging_path,
+                &checkpoint_path,
+                &session,
+                &shared_files,
+            )
+        }))
+    }
+
+    fn handle_ids(&self) -> Result<Vec<String>> {
+        let mut ids: Vec<String> = DB::list_cf(&Options::default(), self.db().path())?
+            .into_iter()
+            .filter(|name| name != "default")
+            .collect();
+        ids.sort();
+        Ok(ids)
+    }
+
+    fn scan_raw(&self, handle_id: &str, f: &mut dyn FnMut(&[u8], &[u8])) -> Result<()> {
+        if let Some(cf) = self.db().cf_handle(handle_id) {
+            for (key, value) in self.db().iterator_cf(cf, IteratorMode::Start) {
+                f(&key, &value);
+            }
+        }
+        Ok(())
+    }
+
+    fn rewrite_values<E: From<ArconStateError>>(
+        &self,
+        handle_id: &str,
+        mut f: impl FnMut(&[u8]) -> std::result::Result<Vec<u8>, E>,
+    ) -> std::result::Result<(), E> {
+        // handles that never stored anything have no column family
+        let cf = match self.db().cf_handle(handle_id) {
+            Some(cf) => cf,
+            None => return Ok(()),
+        };
+        let mut wb = WriteBatch::default();
+        for (key, value) in self.db().iterator_cf(cf, IteratorMode::Start) {
+            wb.put_cf(cf, key, f(&value)?);
+        }
+        self.db()
+            .write_opt(wb, &default_write_opts())
+            .map_err(ArconStateError::from)?;
         Ok(())
     }
 
@@ -241,6 +335,7 @@ impl Backend for Rocks {
         let opts = common_options::<IK, N>();
         self.create_column_family(&handle.id, opts)
             .expect("Could not create column family");
+        self.record_schema_version(&handle.id, handle.version);
     }
 
     fn register_map_handle<'s, K: Key, V: Value, IK: Metakey, N: Metakey>(
@@ -251,6 +346,18 @@ impl Backend for Rocks {
         let opts = common_options::<IK, N>();
         self.create_column_family(&handle.id, opts)
             .expect("Could not create column family");
+        self.record_schema_version(&handle.id, handle.version);
+    }
+
+    fn register_sorted_map_handle<'s, K: OrderedKey, V: Value, IK: Metakey, N: Metakey>(
+        &'s self,
+        handle: &'s mut Handle<SortedMapState<K, V>, IK, N>,
+    ) {
+        handle.registered = true;
+        // no prefix extractor, range scans have to see the keys in total order
+        self.create_column_family(&handle.id, Options::default())
+            .expect("Could not create column family");
+        self.record_schema_version(&handle.id, handle.version);
     }
 
     fn register_vec_handle<'s, T: Value, IK: Metakey, N: Metakey>(
@@ -262,6 +369,7 @@ impl Backend for Rocks {
         opts.set_merge_operator_associative("vec_merge", vec_ops::vec_merge);
         self.create_column_family(&handle.id, opts)
             .expect("Could not create column family");
+        self.record_schema_version(&handle.id, handle.version);
     }
 
     fn register_reducer_handle<'s, T: Value, F: Reducer<T>, IK: Metakey, N: Metakey>(
@@ -274,6 +382,7 @@ impl Backend for Rocks {
         opts.set_merge_operator_associative("reducer_merge", reducer_merge);
         self.create_column_family(&handle.id, opts)
             .expect("Could not create column family");
+        self.record_schema_version(&handle.id, handle.version);
     }
 
     fn register_aggregator_handle<'s, A: Aggregator, IK: Metakey, N: Metakey>(
@@ -286,15 +395,20 @@ impl Backend for Rocks {
         opts.set_merge_operator_associative("aggregator_merge", aggregator_merge);
         self.create_column_family(&handle.id, opts)
             .expect("Could not create column family");
+        self.record_schema_version(&handle.id, handle.version);
     }
 }
 
 mod aggregator_ops;
+mod incremental;
 mod map_ops;
 mod reducer_ops;
+mod sorted_map_ops;
 mod value_ops;
 mod vec_ops;
 
+pub(super) use incremental::shared_files;
+
 #[cfg(test)]
 pub mod tests {
     use super::*;
@@ -465,5 +579,107 @@ pub mod tests {
         }
     }
 
+    #[test]
+    fn incremental_checkpoint_test() {
+        let live_dir = TempDir::new().unwrap();
+        let checkpoints_dir = TempDir::new().unwrap();
+        let restore_dir = TempDir::new().unwrap();
+        let checkpoint = |epoch: u64| checkpoints_dir.path().join(format!("chkp{}", epoch));
+        let shared_files = |epoch: u64| -> HashSet<String> {
+            IncrementalManifest::load(&checkpoint(epoch))
+                .unwrap()
+                .unwrap()
+                .sst_files
+                .into_values()
+                .collect()
+        };
+        let shared_dir_len = || {
+            fs::read_dir(checkpoints_dir.path().join(crate::SHARED_CHECKPOINT_DIR))
+                .unwrap()
+                .count()
+        };
+
+        let db = Rocks::create(live_dir.path(), "testDB".to_string()).unwrap();
+        let column_family = "default";
+
+        db.put(column_family, b"a", b"1").unwrap();
+        db.checkpoint(&checkpoint(0)).unwrap();
+        db.put(column_family, b"a", b"2").unwrap();
+        db.put(column_family, b"b", b"2").unwrap();
+        db.checkpoint(&checkpoint(1)).unwrap();
+
+        // the second checkpoint only adds the file created since the first one
+        let first = shared_files(0);
+        let second = shared_files(1);
+        assert_eq!(first.len(), 1);
+        assert_eq!(second.len(), 2);
+        assert!(second.is_superset(&first));
+        assert_eq!(shared_dir_len(), 2);
+
+        // compacting the overlapping files replaces them, but they are kept for as long as
+        // a checkpoint refers to them
+        db.db().compact_range::<&[u8], &[u8]>(None, None);
+        db.checkpoint(&checkpoint(2)).unwrap();
+        assert!(shared_files(2).is_disjoint(&second));
+        assert_eq!(shared_dir_len(), 3);
+
+        fs::remove_dir_all(checkpoint(0)).unwrap();
+        fs::remove_dir_all(checkpoint(1)).unwrap();
+        db.put(column_family, b"c", b"3").unwrap();
+        db.checkpoint(&checkpoint(3)).unwrap();
+        assert_eq!(
+            shared_dir_len(),
+            shared_files(2).union(&shared_files(3)).count()
+        );
+
+        let restored =
+            Rocks::restore(restore_dir.path(), &checkpoint(2), "testDB".to_string()).unwrap();
+        assert_eq!(
+            restored.get(column_family, b"a").unwrap().unwrap().as_ref(),
+            b"2"
+        );
+        assert_eq!(
+            restored.get(column_family, b"b").unwrap().unwrap().as_ref(),
+            b"2"
+        );
+        assert!(restored.get(column_family, b"c").unwrap().is_none());
+    }
+
+    #[test]
+    fn restore_ignores_staging_test() {
+        let live_dir = TempDir::new().unwrap();
+        let checkpoints_dir = TempDir::new().unwrap();
+        let db = Rocks::create(live_dir.path(), "testDB".to_string()).unwrap();
+        db.put("default", b"a", b"1").unwrap();
+        db.checkpoint(&checkpoints_dir.path().join("checkpoint_testDB_0"))
+            .unwrap();
+
+        // a checkpoint whose asynchronous phase never ran
+        db.put("default", b"a", b"2").unwrap();
+        let checkpoint = db
+            .async_checkpoint(&checkpoints_dir.path().join("checkpoint_testDB_1"))
+            .unwrap();
+        drop(checkpoint);
+        let staging = checkpoints_dir
+            .path()
+            .join(format!("checkpoint_testDB_1{}", STAGING_SUFFIX));
+        assert!(staging.exists());
+
+        let restore_dir = TempDir::new().unwrap();
+        let config = crate::Config {
+            live_state_base_path: restore_dir.path().to_path_buf(),
+            checkpoints_base_path: checkpoints_dir.path().to_path_buf(),
+            backend_ids: vec!["testDB".to_string()],
+            storage: None,
+        };
+        let restored = Rocks::restore_or_create(&config, "testDB".to_string()).unwrap();
+        assert!(restored.was_restored());
+        assert_eq!(
+            restored.get("default", b"a").unwrap().unwrap().as_ref(),
+            b"1"
+        );
+        assert!(!staging.exists());
+    }
+
     common_state_tests!(TestDb::new());
 }
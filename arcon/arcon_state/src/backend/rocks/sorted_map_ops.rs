@@ -0,0 +1,183 @@
+#[cfg(feature = "metrics")]
+use crate::metrics_utils::*;
+use crate::{
+    data::{Metakey, OrderedKey, Value},
+    error::*,
+    handles::BoxedIteratorOfResult,
+    rocks::default_write_opts,
+    serialization::{ordered, protobuf},
+    Handle, Rocks, SortedMapOps, SortedMapState,
+};
+
+use rocksdb::{Direction, IteratorMode, WriteBatch};
+use std::ops::{Bound, RangeBounds};
+
+impl SortedMapOps for Rocks {
+    fn sorted_map_clear<K: OrderedKey, V: Value, IK: Metakey, N: Metakey>(
+        &self,
+        handle: &Handle<SortedMapState<K, V>, IK, N>,
+    ) -> Result<()> {
+        let (start, end) = handle.serialize_range(..)?;
+        let cf = self.get_cf_handle(&handle.id)?;
+
+        let mut wb = WriteBatch::default();
+        for (db_key, _) in self.db().iterator_cf(cf, seek_forward(&start)) {
+            if !below_end(&end, &db_key) {
+                break;
+            }
+            wb.delete_cf(cf, db_key);
+        }
+        self.db().write_opt(wb, &default_write_opts())?;
+
+        Ok(())
+    }
+
+    fn sorted_map_get<K: OrderedKey, V: Value, IK: Metakey, N: Metakey>(
+        &self,
+        handle: &Handle<SortedMapState<K, V>, IK, N>,
+        key: &K,
+    ) -> Result<Option<V>> {
+        let key = handle.serialize_metakeys_and_ordered_key(key)?;
+        if let Some(serialized) = self.get(&handle.id, &key)? {
+            #[cfg(feature = "metrics")]
+            record_bytes_read(handle.name(), serialized.len() as u64, self.name.as_str());
+            let value = protobuf::deserialize(&serialized)?;
+            Ok(Some(value))
+        } else {
+            Ok(None)
+        }
+    }
+
+    fn sorted_map_fast_insert<K: OrderedKey, V: Value, IK: Metakey, N: Metakey>(
+        &self,
+        handle: &Handle<SortedMapState<K, V>, IK, N>,
+        key: K,
+        value: V,
+    ) -> Result<()> {
+        let key = handle.serialize_metakeys_and_ordered_key(&key)?;
+        let serialized = protobuf::serialize(&value)?;
+        #[cfg(feature = "metrics")]
+        record_bytes_written(handle.name(), serialized.len() as u64, self.name.as_str());
+        self.put(&handle.id, key, serialized)?;
+
+        Ok(())
+    }
+
+    fn sorted_map_remove<K: OrderedKey, V: Value, IK: Metakey, N: Metakey>(
+        &self,
+        handle: &Handle<SortedMapState<K, V>, IK, N>,
+        key: &K,
+    ) -> Result<Option<V>> {
+        let key = handle.serialize_metakeys_and_ordered_key(key)?;
+
+        let old = if let Some(slice) = self.get(&handle.id, &key)? {
+            Some(protobuf::deserialize(&slice[..])?)
+        } else {
+            None
+        };
+
+        self.remove(&handle.id, &key)?;
+
+        Ok(old)
+    }
+
+    fn sorted_map_range<K: OrderedKey, V: Value, IK: Metakey, N: Metakey>(
+        &self,
+        handle: &Handle<SortedMapState<K, V>, IK, N>,
+        range: impl RangeBounds<K>,
+    ) -> Result<BoxedIteratorOfResult<'_, (K, V)>> {
+        let (start, end) = handle.serialize_range(range)?;
+        let metakey_size = handle.metakey_size();
+        let cf = self.get_cf_handle(&handle.id)?;
+        // NOTE: the iterator sees the keys in total order because sorted map column families
+        //   are created without a prefix extractor, see Rocks::register_sorted_map_handle
+        let iter = self.db().iterator_cf(cf, seek_forward(&start));
+
+        let iter = iter
+            .skip_while(move |(db_key, _)| match &start {
+                Bound::Excluded(start) => db_key[..] == start[..],
+                _ => false,
+            })
+            .take_while(move |(db_key, _)| below_end(&end, db_key))
+            .map(move |(db_key, serialized_value)| {
+                let key: K = ordered::deserialize(&db_key[metakey_size..])?;
+                let value: V = protobuf::deserialize(&serialized_value)?;
+
+                Ok((key, value))
+            });
+
+        Ok(Box::new(iter))
+    }
+
+    fn sorted_map_last<K: OrderedKey, V: Value, IK: Metakey, N: Metakey>(
+        &self,
+        handle: &Handle<SortedMapState<K, V>, IK, N>,
+    ) -> Result<Option<(K, V)>> {
+        let (start, end) = handle.serialize_range(..)?;
+        let cf = self.get_cf_handle(&handle.id)?;
+        let mode = match &end {
+            Bound::Included(end) | Bound::Excluded(end) => {
+                IteratorMode::From(end, Direction::Reverse)
+            }
+            Bound::Unbounded => IteratorMode::End,
+        };
+
+        let last = self
+            .db()
+            .iterator_cf(cf, mode)
+            .find(|(db_key, _)| below_end(&end, db_key));
+
+        match last {
+            Some((db_key, serialized_value)) if above_start(&start, &db_key) => {
+                let key: K = ordered::deserialize(&db_key[handle.metakey_size()..])?;
+                let value: V = protobuf::deserialize(&serialized_value)?;
+                Ok(Some((key, value)))
+            }
+            _ => Ok(None),
+        }
+    }
+
+    fn sorted_map_len<K: OrderedKey, V: Value, IK: Metakey, N: Metakey>(
+        &self,
+        handle: &Handle<SortedMapState<K, V>, IK, N>,
+    ) -> Result<usize> {
+        let (start, end) = handle.serialize_range(..)?;
+        let cf = self.get_cf_handle(&handle.id)?;
+
+        let len = self
+            .db()
+            .iterator_cf(cf, seek_forward(&start))
+            .skip_while(|(db_key, _)| match &start {
+                Bound::Excluded(start) => db_key[..] == start[..],
+                _ => false,
+            })
+            .take_while(|(db_key, _)| below_end(&end, db_key))
+            .count();
+        Ok(len)
+    }
+}
+
+fn seek_forward(start: &Bound<Vec<u8>>) -> IteratorMode<'_> {
+    match start {
+        Bound::Included(start) | Bound::Excluded(start) => {
+            IteratorMode::From(start, Direction::Forward)
+        }
+        Bound::Unbounded => IteratorMode::Start,
+    }
+}
+
+fn above_start(start: &Bound<Vec<u8>>, db_key: &[u8]) -> bool {
+    match start {
+        Bound::Included(start) => db_key >= &start[..],
+        Bound::Excluded(start) => db_key > &start[..],
+        Bound::Unbounded => true,
+    }
+}
+
+fn below_end(end: &Bound<Vec<u8>>, db_key: &[u8]) -> bool {
+    match end {
+        Bound::Included(end) => db_key <= &end[..],
+        Bound::Excluded(end) => db_key < &end[..],
+        Bound::Unbounded => true,
+    }
+}
@@ -269,6 +269,125 @@ pub mod fixed_bytes {
     }
 }
 
+pub mod ordered {
+    use crate::error::*;
+    use std::ops::Bound;
+
+    /// Serialization whose output sorts bytewise in the same order as the serialized values
+    ///
+    /// Backends keep the records of sorted map state in the order of their serialized keys,
+    /// which therefore have to be serialized with this trait. The serialized key is always the
+    /// last part of a stored key, so variable length encodings don't need a length prefix.
+    pub trait OrderedBytes: Ord + Sized {
+        fn serialize_into(target: &mut Vec<u8>, payload: &Self);
+        fn deserialize(bytes: &[u8]) -> Result<Self>;
+    }
+
+    pub fn serialize_into<T: OrderedBytes>(target: &mut Vec<u8>, payload: &T) {
+        T::serialize_into(target, payload)
+    }
+    pub fn deserialize<T: OrderedBytes>(bytes: &[u8]) -> Result<T> {
+        T::deserialize(bytes)
+    }
+
+    /// Returns the smallest key that is greater than every key starting with `prefix`
+    ///
+    /// There is no such key if the prefix is empty or consists of `0xFF` bytes only.
+    pub fn prefix_successor(prefix: &[u8]) -> Option<Vec<u8>> {
+        let last = prefix.iter().rposition(|b| *b != u8::MAX)?;
+        let mut successor = prefix[..=last].to_vec();
+        successor[last] += 1;
+        Some(successor)
+    }
+
+    /// Serialized bounds of a range of keys sharing `prefix`
+    pub fn serialize_range<T: OrderedBytes>(
+        prefix: &[u8],
+        start: Bound<&T>,
+        end: Bound<&T>,
+    ) -> (Bound<Vec<u8>>, Bound<Vec<u8>>) {
+        let key = |k: &T| {
+            let mut serialized = prefix.to_vec();
+            serialize_into(&mut serialized, k);
+            serialized
+        };
+        let start = match start {
+            Bound::Included(k) => Bound::Included(key(k)),
+            Bound::Excluded(k) => Bound::Excluded(key(k)),
+            Bound::Unbounded => Bound::Included(prefix.to_vec()),
+        };
+        let end = match end {
+            Bound::Included(k) => Bound::Included(key(k)),
+            Bound::Excluded(k) => Bound::Excluded(key(k)),
+            Bound::Unbounded => match prefix_successor(prefix) {
+                Some(successor) => Bound::Excluded(successor),
+                None => Bound::Unbounded,
+            },
+        };
+        (start, end)
+    }
+
+    macro_rules! impl_ordered_bytes_unsigned {
+        ($($t: ty),+) => {$(
+            impl OrderedBytes for $t {
+                fn serialize_into(target: &mut Vec<u8>, payload: &Self) {
+                    target.extend_from_slice(&payload.to_be_bytes());
+                }
+
+                fn deserialize(bytes: &[u8]) -> Result<Self> {
+                    let mut buf = [0; std::mem::size_of::<Self>()];
+                    let needed = buf.len();
+                    let source_len = bytes.len();
+                    ensure!(source_len == needed,
+                        FixedBytesDeserializationError { source_len, needed });
+                    buf.copy_from_slice(bytes);
+                    Ok(Self::from_be_bytes(buf))
+                }
+            }
+        )+};
+    }
+    impl_ordered_bytes_unsigned!(u8, u16, u32, u64, u128);
+
+    macro_rules! impl_ordered_bytes_signed {
+        ($($t: ty => $u: ty),+) => {$(
+            impl OrderedBytes for $t {
+                // flipping the sign bit sorts negative numbers before positive ones
+                fn serialize_into(target: &mut Vec<u8>, payload: &Self) {
+                    let flipped = (*payload as $u) ^ (1 << (<$u>::BITS - 1));
+                    <$u as OrderedBytes>::serialize_into(target, &flipped);
+                }
+
+                fn deserialize(bytes: &[u8]) -> Result<Self> {
+                    let flipped = <$u as OrderedBytes>::deserialize(bytes)?;
+                    Ok((flipped ^ (1 << (<$u>::BITS - 1))) as $t)
+                }
+            }
+        )+};
+    }
+    impl_ordered_bytes_signed!(i8 => u8, i16 => u16, i32 => u32, i64 => u64, i128 => u128);
+
+    impl OrderedBytes for Vec<u8> {
+        fn serialize_into(target: &mut Vec<u8>, payload: &Self) {
+            target.extend_from_slice(payload);
+        }
+
+        fn deserialize(bytes: &[u8]) -> Result<Self> {
+            Ok(bytes.to_vec())
+        }
+    }
+
+    impl OrderedBytes for String {
+        fn serialize_into(target: &mut Vec<u8>, payload: &Self) {
+            target.extend_from_slice(payload.as_bytes());
+        }
+
+        fn deserialize(bytes: &[u8]) -> Result<Self> {
+            String::from_utf8(bytes.to_vec())
+                .map_err(|e| ArconStateError::Unknown { msg: e.to_string() })
+        }
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -334,6 +453,30 @@ mod test {
         assert_eq!(deserialized, payload);
     }
 
+    #[test]
+    fn test_ordered_keys() {
+        fn serialized<T: ordered::OrderedBytes>(payload: &T) -> Vec<u8> {
+            let mut bytes = vec![];
+            ordered::serialize_into(&mut bytes, payload);
+            bytes
+        }
+
+        let numbers = [i64::MIN, -300, -1, 0, 1, 255, 256, i64::MAX];
+        for pair in numbers.windows(2) {
+            assert!(serialized(&pair[0]) < serialized(&pair[1]));
+        }
+        for n in numbers.iter() {
+            assert_eq!(ordered::deserialize::<i64>(&serialized(n)).unwrap(), *n);
+        }
+        assert!(serialized(&255u32) < serialized(&256u32));
+        assert!(serialized(&"ab".to_string()) < serialized(&"b".to_string()));
+
+        assert_eq!(ordered::prefix_successor(&[1, 2]), Some(vec![1, 3]));
+        assert_eq!(ordered::prefix_successor(&[1, 255]), Some(vec![2]));
+        assert_eq!(ordered::prefix_successor(&[255]), None);
+        assert_eq!(ordered::prefix_successor(&[]), None);
+    }
+
     #[test]
     fn test_errors() {
         type X = (u8, u64, i16);
@@ -1,8 +1,10 @@
+#[cfg(feature = "sled_checkpoints")]
+use crate::AsyncCheckpoint;
 use crate::{
-    data::{Key, Metakey, Value},
+    data::{Key, Metakey, OrderedKey, Value},
     error::*,
-    Aggregator, AggregatorState, Backend, Handle, MapState, Reducer, ReducerState, ValueState,
-    VecState,
+    Aggregator, AggregatorState, Backend, Handle, MapState, Reducer, ReducerState, SortedMapState,
+    ValueState, VecState,
 };
 use sled::{open, Batch, Db, IVec, Tree};
 use std::path::Path;
@@ -13,6 +15,9 @@ use std::{
     path::PathBuf,
 };
 
+/// Name of the tree that sled opens for every database
+const DEFAULT_TREE: &str = "__sled__default";
+
 #[derive(Debug)]
 pub struct Sled {
     db: Db,
@@ -117,49 +122,56 @@ impl Backend for Sled {
     fn checkpoint(&self, checkpoint_path: &Path) -> Result<()> {
         // TODO: sled doesn't support checkpoints/snapshots, but that and MVCC is planned
         //   for now we'll just dump it via the export/import mechanism WHICH MAY BE VERY SLOW
-        let export_data = self.db.export();
-
-        let mut p: PathBuf = checkpoint_path.into();
-        if !p.exists() {
-            fs::create_dir_all(&p)?;
-        }
+        write_sled_export(checkpoint_path, self.db.export())
+    }
 
-        p.push("SLED_EXPORT");
-        let out = fs::File::create(&p)?;
-        let mut writer = BufWriter::new(out);
+    #[cfg(feature = "sled_checkpoints")]
+    fn async_checkpoint(&self, checkpoint_path: &Path) -> Result<AsyncCheckpoint> {
+        // without snapshots, the export has to be collected before the backend changes again
+        let export_data: Vec<_> = self
+            .db
+            .export()
+            .into_iter()
+            .map(|(typ, name, vvecs)| (typ, name, vvecs.collect::<Vec<_>>()))
+            .collect();
+        let checkpoint_path = checkpoint_path.to_path_buf();
+        Ok(AsyncCheckpoint::new(move || {
+            write_sled_export(&checkpoint_path, export_data)
+        }))
+    }
 
-        writer.write_all(&export_data.len().to_le_bytes())?;
+    fn handle_ids(&self) -> Result<Vec<String>> {
+        let mut ids: Vec<String> = self
+            .db
+            .tree_names()
+            .iter()
+            .map(|name| String::from_utf8_lossy(name).into_owned())
+            .filter(|name| name != DEFAULT_TREE)
+            .collect();
+        ids.sort();
+        Ok(ids)
+    }
 
-        #[inline]
-        fn write_len_and_bytes(mut w: impl Write, bytes: &[u8]) -> io::Result<()> {
-            w.write_all(&bytes.len().to_le_bytes())?;
-            w.write_all(bytes)?;
-            Ok(())
+    fn scan_raw(&self, handle_id: &str, f: &mut dyn FnMut(&[u8], &[u8])) -> Result<()> {
+        for entry in self.tree(handle_id)?.iter() {
+            let (key, value) = entry?;
+            f(&key, &value);
         }
+        Ok(())
+    }
 
-        for (typ, name, vvecs) in export_data {
-            write_len_and_bytes(&mut writer, &typ)?;
-            write_len_and_bytes(&mut writer, &name)?;
-
-            // we don't know how many elements are in `vvecs`, so let's save the current position,
-            // write a dummy length, write all the elements and then go back and write how many we wrote
-            let length_spot = writer.seek(SeekFrom::Current(0))?;
-            writer.write_all(&0usize.to_le_bytes())?;
-            let mut length = 0usize;
-
-            for vecs in vvecs {
-                length += 1;
-                writer.write_all(&vecs.len().to_le_bytes())?;
-                for vec in vecs {
-                    write_len_and_bytes(&mut writer, &vec)?;
-                }
-            }
-
-            let after_vecs = writer.seek(SeekFrom::Current(0))?;
-            let _ = writer.seek(SeekFrom::Start(length_spot))?;
-            writer.write_all(&length.to_le_bytes())?;
-            let _ = writer.seek(SeekFrom::Start(after_vecs))?;
+    fn rewrite_values<E: From<ArconStateError>>(
+        &self,
+        handle_id: &str,
+        mut f: impl FnMut(&[u8]) -> std::result::Result<Vec<u8>, E>,
+    ) -> std::result::Result<(), E> {
+        let tree = self.tree(handle_id)?;
+        let mut batch = Batch::default();
+        for entry in tree.iter() {
+            let (key, value) = entry.map_err(ArconStateError::from)?;
+            batch.insert(key, f(&value)?);
         }
+        tree.apply_batch(batch).map_err(ArconStateError::from)?;
         Ok(())
     }
 
@@ -168,6 +180,7 @@ impl Backend for Sled {
         handle: &mut Handle<ValueState<T>, IK, N>,
     ) {
         handle.registered = true;
+        self.record_schema_version(&handle.id, handle.version);
     }
 
     fn register_map_handle<K: Key, V: Value, IK: Metakey, N: Metakey>(
@@ -175,6 +188,15 @@ impl Backend for Sled {
         handle: &mut Handle<MapState<K, V>, IK, N>,
     ) {
         handle.registered = true;
+        self.record_schema_version(&handle.id, handle.version);
+    }
+
+    fn register_sorted_map_handle<K: OrderedKey, V: Value, IK: Metakey, N: Metakey>(
+        &self,
+        handle: &mut Handle<SortedMapState<K, V>, IK, N>,
+    ) {
+        handle.registered = true;
+        self.record_schema_version(&handle.id, handle.version);
     }
 
     fn register_vec_handle<T: Value, IK: Metakey, N: Metakey>(
@@ -186,6 +208,7 @@ impl Backend for Sled {
             .expect("Could not get the tree when registering a vec");
         tree.set_merge_operator(vec_ops::vec_merge);
         handle.registered = true;
+        self.record_schema_version(&handle.id, handle.version);
     }
 
     fn register_reducer_handle<T: Value, F: Reducer<T>, IK: Metakey, N: Metakey>(
@@ -197,6 +220,7 @@ impl Backend for Sled {
             .expect("Could not get the tree when registering a reducer");
         tree.set_merge_operator(reducer_ops::make_reducer_merge(handle.extra_data.clone()));
         handle.registered = true;
+        self.record_schema_version(&handle.id, handle.version);
     }
 
     fn register_aggregator_handle<A: Aggregator, IK: Metakey, N: Metakey>(
@@ -210,15 +234,69 @@ impl Backend for Sled {
             handle.extra_data.clone(),
         ));
         handle.registered = true;
+        self.record_schema_version(&handle.id, handle.version);
     }
 }
 
 mod aggregator_ops;
 mod map_ops;
 mod reducer_ops;
+mod sorted_map_ops;
 mod value_ops;
 mod vec_ops;
 
+#[cfg(feature = "sled_checkpoints")]
+fn write_sled_export<I>(
+    checkpoint_path: &Path,
+    export_data: Vec<(Vec<u8>, Vec<u8>, I)>,
+) -> Result<()>
+where
+    I: IntoIterator<Item = Vec<Vec<u8>>>,
+{
+    let mut p: PathBuf = checkpoint_path.into();
+    if !p.exists() {
+        fs::create_dir_all(&p)?;
+    }
+
+    p.push("SLED_EXPORT");
+    let out = fs::File::create(&p)?;
+    let mut writer = BufWriter::new(out);
+
+    writer.write_all(&export_data.len().to_le_bytes())?;
+
+    #[inline]
+    fn write_len_and_bytes(mut w: impl Write, bytes: &[u8]) -> io::Result<()> {
+        w.write_all(&bytes.len().to_le_bytes())?;
+        w.write_all(bytes)?;
+        Ok(())
+    }
+
+    for (typ, name, vvecs) in export_data {
+        write_len_and_bytes(&mut writer, &typ)?;
+        write_len_and_bytes(&mut writer, &name)?;
+
+        // we don't know how many elements are in `vvecs`, so let's save the current position,
+        // write a dummy length, write all the elements and then go back and write how many we wrote
+        let length_spot = writer.seek(SeekFrom::Current(0))?;
+        writer.write_all(&0usize.to_le_bytes())?;
+        let mut length = 0usize;
+
+        for vecs in vvecs {
+            length += 1;
+            writer.write_all(&vecs.len().to_le_bytes())?;
+            for vec in vecs {
+                write_len_and_bytes(&mut writer, &vec)?;
+            }
+        }
+
+        let after_vecs = writer.seek(SeekFrom::Current(0))?;
+        let _ = writer.seek(SeekFrom::Start(length_spot))?;
+        writer.write_all(&length.to_le_bytes())?;
+        let _ = writer.seek(SeekFrom::Start(after_vecs))?;
+    }
+    Ok(())
+}
+
 #[cfg(feature = "sled_checkpoints")]
 fn parse_dumped_sled_export(
     dump_path: &Path,
@@ -352,5 +430,41 @@ mod tests {
         assert_eq!(restored_t.get(b"y"), Ok(Some(IVec::from(b"20"))));
     }
 
+    #[cfg(feature = "sled_checkpoints")]
+    #[test]
+    fn test_sled_restore_from_storage() {
+        use crate::{
+            storage::{CheckpointStorage, MemoryObjectStore, ObjectStorage},
+            Config,
+        };
+
+        let dir = TempDir::new().unwrap();
+        let sled = Sled::create(&dir.path().join("live"), "testDB".to_string()).unwrap();
+        sled.db.insert(b"a", b"1").unwrap();
+
+        let chkp_dir = TempDir::new().unwrap();
+        sled.checkpoint(&chkp_dir.path().join("checkpoint_testDB_0"))
+            .unwrap();
+        sled.db.insert(b"a", b"2").unwrap();
+        sled.checkpoint(&chkp_dir.path().join("checkpoint_testDB_1"))
+            .unwrap();
+
+        let storage = ObjectStorage::new(MemoryObjectStore::default(), "bucket", "checkpoints");
+        storage.put_dir(chkp_dir.path(), "").unwrap();
+
+        // the checkpoints are fetched into an empty checkpoint directory
+        let restore_dir = TempDir::new().unwrap();
+        let config = Config {
+            live_state_base_path: restore_dir.path().join("live"),
+            checkpoints_base_path: restore_dir.path().join("checkpoints"),
+            backend_ids: vec!["testDB".to_string()],
+            storage: Some(Arc::new(storage)),
+        };
+        let restored = Sled::restore_or_create(&config, "testDB".to_string()).unwrap();
+
+        assert!(restored.was_restored());
+        assert_eq!(restored.db.get(b"a"), Ok(Some(IVec::from(b"2"))));
+    }
+
     common_state_tests!(TestDb::new());
 }
@@ -0,0 +1,121 @@
+use crate::{
+    data::{Metakey, OrderedKey, Value},
+    error::*,
+    handles::BoxedIteratorOfResult,
+    serialization::{ordered, protobuf},
+    sled::Sled,
+    Handle, SortedMapOps, SortedMapState,
+};
+
+#[cfg(feature = "metrics")]
+use crate::metrics_utils::*;
+
+use std::ops::RangeBounds;
+
+impl SortedMapOps for Sled {
+    fn sorted_map_clear<K: OrderedKey, V: Value, IK: Metakey, N: Metakey>(
+        &self,
+        handle: &Handle<SortedMapState<K, V>, IK, N>,
+    ) -> Result<()> {
+        let prefix = handle.serialize_metakeys()?;
+        self.remove_prefix(&handle.id, prefix)
+    }
+
+    fn sorted_map_get<K: OrderedKey, V: Value, IK: Metakey, N: Metakey>(
+        &self,
+        handle: &Handle<SortedMapState<K, V>, IK, N>,
+        key: &K,
+    ) -> Result<Option<V>> {
+        let key = handle.serialize_metakeys_and_ordered_key(key)?;
+        if let Some(serialized) = self.get(&handle.id, &key)? {
+            #[cfg(feature = "metrics")]
+            record_bytes_read(handle.name(), serialized.len() as u64, self.name.as_str());
+            let value = protobuf::deserialize(&serialized)?;
+            Ok(Some(value))
+        } else {
+            Ok(None)
+        }
+    }
+
+    fn sorted_map_fast_insert<K: OrderedKey, V: Value, IK: Metakey, N: Metakey>(
+        &self,
+        handle: &Handle<SortedMapState<K, V>, IK, N>,
+        key: K,
+        value: V,
+    ) -> Result<()> {
+        let key = handle.serialize_metakeys_and_ordered_key(&key)?;
+        let serialized = protobuf::serialize(&value)?;
+        #[cfg(feature = "metrics")]
+        record_bytes_written(handle.name(), serialized.len() as u64, self.name.as_str());
+        self.put(&handle.id, &key, &serialized)?;
+
+        Ok(())
+    }
+
+    fn sorted_map_remove<K: OrderedKey, V: Value, IK: Metakey, N: Metakey>(
+        &self,
+        handle: &Handle<SortedMapState<K, V>, IK, N>,
+        key: &K,
+    ) -> Result<Option<V>> {
+        let key = handle.serialize_metakeys_and_ordered_key(key)?;
+        let old = match self.remove(&handle.id, &key)? {
+            Some(bytes) => Some(protobuf::deserialize(bytes.as_ref())?),
+            None => None,
+        };
+
+        Ok(old)
+    }
+
+    fn sorted_map_range<K: OrderedKey, V: Value, IK: Metakey, N: Metakey>(
+        &self,
+        handle: &Handle<SortedMapState<K, V>, IK, N>,
+        range: impl RangeBounds<K>,
+    ) -> Result<BoxedIteratorOfResult<'_, (K, V)>> {
+        let bounds = handle.serialize_range(range)?;
+        let metakey_size = handle.metakey_size();
+        let tree = self.tree(&handle.id)?;
+
+        let iter = tree.range(bounds).map(move |entry| {
+            let (db_key, serialized_value) = entry?;
+            let key: K = ordered::deserialize(&db_key[metakey_size..])?;
+            let value: V = protobuf::deserialize(&serialized_value)?;
+
+            Ok((key, value))
+        });
+
+        Ok(Box::new(iter))
+    }
+
+    fn sorted_map_last<K: OrderedKey, V: Value, IK: Metakey, N: Metakey>(
+        &self,
+        handle: &Handle<SortedMapState<K, V>, IK, N>,
+    ) -> Result<Option<(K, V)>> {
+        let bounds = handle.serialize_range(..)?;
+        let tree = self.tree(&handle.id)?;
+
+        match tree.range(bounds).next_back() {
+            Some(entry) => {
+                let (db_key, serialized_value) = entry?;
+                let key: K = ordered::deserialize(&db_key[handle.metakey_size()..])?;
+                let value: V = protobuf::deserialize(&serialized_value)?;
+                Ok(Some((key, value)))
+            }
+            None => Ok(None),
+        }
+    }
+
+    fn sorted_map_len<K: OrderedKey, V: Value, IK: Metakey, N: Metakey>(
+        &self,
+        handle: &Handle<SortedMapState<K, V>, IK, N>,
+    ) -> Result<usize> {
+        let bounds = handle.serialize_range(..)?;
+        let tree = self.tree(&handle.id)?;
+
+        let mut len = 0;
+        for key in tree.range(bounds).keys() {
+            key?;
+            len += 1;
+        }
+        Ok(len)
+    }
+}
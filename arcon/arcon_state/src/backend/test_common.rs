@@ -1,5 +1,6 @@
 use crate::{
-    Aggregator, AggregatorState, Handle, MapState, Reducer, ReducerState, ValueState, VecState,
+    Aggregator, AggregatorState, Handle, MapState, Reducer, ReducerState, SortedMapState,
+    ValueState, VecState,
 };
 
 #[derive(Debug, Clone)]
@@ -35,6 +36,7 @@ pub struct InMemoryTestStruct<F: Reducer<u32>> {
     pub value: Handle<ValueState<u32>, u32, u32>,
     pub value2: Handle<ValueState<u32>, u32, u32>,
     pub map: Handle<MapState<String, i32>, u32, u32>,
+    pub sorted_map: Handle<SortedMapState<i64, i64>, u32, u32>,
     pub vec: Handle<VecState<u32>, u32, u32>,
     pub reducer: Handle<ReducerState<u32, F>, u32, u32>,
     pub aggregator: Handle<AggregatorState<TestAggregator>, u32, u32>,
@@ -45,6 +47,9 @@ pub fn bundle() -> InMemoryTestStruct<impl Reducer<u32>> {
         value: Handle::value("value").with_item_key(0).with_namespace(0),
         value2: Handle::value("value2").with_item_key(0).with_namespace(0),
         map: Handle::map("map").with_item_key(0).with_namespace(0),
+        sorted_map: Handle::sorted_map("sorted_map")
+            .with_item_key(0)
+            .with_namespace(0),
         vec: Handle::vec("vec").with_item_key(0).with_namespace(0),
         reducer: Handle::reducer("reducer", |a: &u32, b: &u32| *a.max(b))
             .with_item_key(0)
@@ -55,6 +60,11 @@ pub fn bundle() -> InMemoryTestStruct<impl Reducer<u32>> {
     }
 }
 
+/// Collects the keys of a sorted map range
+pub fn range_keys<K, V>(iter: impl Iterator<Item = crate::error::Result<(K, V)>>) -> Vec<K> {
+    iter.map(|entry| entry.unwrap().0).collect()
+}
+
 #[macro_export]
 macro_rules! common_state_tests {
     ($construct_backend: expr) => {
@@ -174,6 +184,48 @@ macro_rules! common_state_tests {
                 assert!(map.is_empty().unwrap());
             }
 
+            #[test]
+            fn sorted_map_state_test() {
+                let db = $construct_backend;
+                let mut bundle = bundle();
+                db.register_sorted_map_handle(&mut bundle.sorted_map);
+                let mut map = bundle.sorted_map.activate(db.clone());
+
+                assert_eq!(map.first().unwrap(), None);
+                assert_eq!(map.last().unwrap(), None);
+
+                for key in &[5i64, -3, 100, 0, -200, 42] {
+                    map.fast_insert(*key, key * 10).unwrap();
+                }
+                // entries of other keys are not part of the range
+                map.set_item_key(1);
+                map.fast_insert(1, 1).unwrap();
+                map.set_item_key(0);
+
+                assert_eq!(
+                    range_keys(map.range(..).unwrap()),
+                    vec![-200, -3, 0, 5, 42, 100]
+                );
+                assert_eq!(range_keys(map.range(-3..42).unwrap()), vec![-3, 0, 5]);
+                assert_eq!(range_keys(map.range(..=0).unwrap()), vec![-200, -3, 0]);
+                assert_eq!(range_keys(map.range(6..).unwrap()), vec![42, 100]);
+                assert_eq!(map.len().unwrap(), 6);
+
+                assert_eq!(map.get(&42).unwrap(), Some(420));
+                assert_eq!(map.first().unwrap(), Some((-200, -2000)));
+                assert_eq!(map.last().unwrap(), Some((100, 1000)));
+
+                assert_eq!(map.pop_first().unwrap(), Some((-200, -2000)));
+                assert_eq!(map.remove(&100).unwrap(), Some(1000));
+                assert_eq!(map.remove(&100).unwrap(), None);
+                assert_eq!(map.last().unwrap(), Some((42, 420)));
+
+                map.clear().unwrap();
+                assert_eq!(map.len().unwrap(), 0);
+                map.set_item_key(1);
+                assert_eq!(map.len().unwrap(), 1);
+            }
+
             #[test]
             fn map_clearing_test() {
                 let db = $construct_backend;
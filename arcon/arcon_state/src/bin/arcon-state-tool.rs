@@ -0,0 +1,101 @@
+//! Offline inspection of arcon state checkpoints
+//!
+//! ```text
+//! arcon-state-tool [--rocks] <checkpoint-dir>              lists the handles and their entry counts
+//! arcon-state-tool [--rocks] <checkpoint-dir> <handle-id>  dumps the entries of a handle as JSON lines
+//! ```
+//!
+//! Keys and values are dumped as hex encoded bytes. Use [arcon_state::StateInspector]
+//! to decode them with the prost types that wrote them.
+
+use arcon_state::{error::Result, Backend, StateInspector};
+use std::{
+    fmt::Write as _,
+    io::{self, Write},
+    path::Path,
+    process,
+};
+
+const USAGE: &str = "usage: arcon-state-tool [--rocks] <checkpoint-dir> [<handle-id>]";
+
+fn main() {
+    let mut args: Vec<String> = std::env::args().skip(1).collect();
+    let rocks = match args.iter().position(|a| a == "--rocks") {
+        Some(pos) => {
+            args.remove(pos);
+            true
+        }
+        None => false,
+    };
+    if args.is_empty() || args.len() > 2 {
+        eprintln!("{}", USAGE);
+        process::exit(2);
+    }
+    let checkpoint = Path::new(&args[0]);
+    let handle_id = args.get(1).map(String::as_str);
+
+    let result = if rocks {
+        run_rocks(checkpoint, handle_id)
+    } else {
+        run_sled(checkpoint, handle_id)
+    };
+    if let Err(err) = result {
+        eprintln!("arcon-state-tool: {}", err);
+        process::exit(1);
+    }
+}
+
+#[cfg(feature = "rocks")]
+fn run_rocks(checkpoint: &Path, handle_id: Option<&str>) -> Result<()> {
+    run::<arcon_state::Rocks>(checkpoint, handle_id)
+}
+
+#[cfg(not(feature = "rocks"))]
+fn run_rocks(_checkpoint: &Path, _handle_id: Option<&str>) -> Result<()> {
+    eprintln!("arcon-state-tool was compiled without the `rocks` feature");
+    process::exit(2);
+}
+
+#[cfg(feature = "sled")]
+fn run_sled(checkpoint: &Path, handle_id: Option<&str>) -> Result<()> {
+    run::<arcon_state::Sled>(checkpoint, handle_id)
+}
+
+#[cfg(not(feature = "sled"))]
+fn run_sled(_checkpoint: &Path, _handle_id: Option<&str>) -> Result<()> {
+    eprintln!("arcon-state-tool was compiled without the `sled` feature");
+    process::exit(2);
+}
+
+fn run<B: Backend>(checkpoint: &Path, handle_id: Option<&str>) -> Result<()> {
+    let inspector = StateInspector::<B>::open(checkpoint)?;
+    let stdout = io::stdout();
+    let mut out = stdout.lock();
+    match handle_id {
+        Some(handle_id) => {
+            for entry in inspector.raw(handle_id)? {
+                writeln!(
+                    out,
+                    "{{\"key\":\"{}\",\"value\":\"{}\"}}",
+                    hex(&entry.key),
+                    hex(&entry.value)
+                )?;
+            }
+        }
+        None => {
+            for handle_id in inspector.handles()? {
+                let entries = inspector.raw(&handle_id)?.len();
+                writeln!(out, "{}\t{}", handle_id, entries)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+fn hex(bytes: &[u8]) -> String {
+    let mut s = String::with_capacity(bytes.len() * 2);
+    for b in bytes {
+        let _ = write!(s, "{:02x}", b);
+    }
+    s
+}
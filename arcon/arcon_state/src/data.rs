@@ -1,4 +1,4 @@
-use crate::backend::serialization::fixed_bytes::FixedBytes;
+use crate::backend::serialization::{fixed_bytes::FixedBytes, ordered::OrderedBytes};
 
 pub trait Value: prost::Message + Default + Clone + 'static {}
 impl<T> Value for T where T: prost::Message + Default + Clone + 'static {}
@@ -8,3 +8,6 @@ impl<T> Key for T where T: prost::Message + Default + Clone + 'static {}
 
 pub trait Metakey: FixedBytes + Copy + Clone + Send + Sync + 'static {}
 impl<T> Metakey for T where T: FixedBytes + Copy + Clone + Send + Sync + 'static {}
+
+pub trait OrderedKey: OrderedBytes + Clone + Send + Sync + 'static {}
+impl<T> OrderedKey for T where T: OrderedBytes + Clone + Send + Sync + 'static {}
@@ -0,0 +1,141 @@
+use crate::{
+    data::{Key, Value},
+    error::*,
+    Backend, Handle, MapState, ValueState, VecState,
+};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+static INSPECTOR_COUNT: AtomicUsize = AtomicUsize::new(0);
+
+/// A key and value exactly as a backend stores them
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RawEntry {
+    pub key: Vec<u8>,
+    pub value: Vec<u8>,
+}
+
+/// Removes the live directory of an inspector once its backend is closed
+#[derive(Debug)]
+struct LiveDir(PathBuf);
+
+impl Drop for LiveDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.0);
+    }
+}
+
+/// Offline access to the state of a checkpoint
+///
+/// The checkpoint is restored into a temporary directory that is removed once the
+/// inspector is dropped, so the checkpoint itself is never modified.
+#[derive(Debug)]
+pub struct StateInspector<B: Backend> {
+    // dropped before the live directory it lives in
+    backend: B,
+    _live_dir: LiveDir,
+}
+
+impl<B: Backend> StateInspector<B> {
+    /// Opens the checkpoint in `checkpoint_path`
+    pub fn open(checkpoint_path: &Path) -> Result<Self> {
+        let live_path = std::env::temp_dir().join(format!(
+            "arcon-state-inspect-{}-{}",
+            std::process::id(),
+            INSPECTOR_COUNT.fetch_add(1, Ordering::Relaxed)
+        ));
+        let live_dir = LiveDir(live_path);
+        let name = checkpoint_path
+            .file_name()
+            .map(|n| n.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let backend = B::restore(&live_dir.0, checkpoint_path, name)?;
+        Ok(StateInspector {
+            backend,
+            _live_dir: live_dir,
+        })
+    }
+
+    /// Returns the restored backend
+    pub fn backend(&self) -> &B {
+        &self.backend
+    }
+
+    /// Returns the ids of all handles stored in the checkpoint
+    pub fn handles(&self) -> Result<Vec<String>> {
+        self.backend.handle_ids()
+    }
+
+    /// Returns every entry of `handle_id` without decoding it
+    pub fn raw(&self, handle_id: &str) -> Result<Vec<RawEntry>> {
+        let mut entries = Vec::new();
+        self.backend.scan_raw(handle_id, &mut |key, value| {
+            entries.push(RawEntry {
+                key: key.to_vec(),
+                value: value.to_vec(),
+            })
+        })?;
+        Ok(entries)
+    }
+
+    /// Decodes the value of the value handle `handle_id`
+    pub fn value<V: Value>(&self, handle_id: &str) -> Result<Option<V>> {
+        let mut handle = Handle::<ValueState<V>>::value(handle_id);
+        self.backend.register_value_handle(&mut handle);
+        self.backend.value_get(&handle)
+    }
+
+    /// Decodes the entries of the map handle `handle_id`
+    pub fn map<K: Key, V: Value>(&self, handle_id: &str) -> Result<Vec<(K, V)>> {
+        let mut handle = Handle::<MapState<K, V>>::map(handle_id);
+        self.backend.register_map_handle(&mut handle);
+        self.backend.map_iter(&handle)?.collect()
+    }
+
+    /// Decodes the elements of the vec handle `handle_id`
+    pub fn vec<V: Value>(&self, handle_id: &str) -> Result<Vec<V>> {
+        let mut handle = Handle::<VecState<V>>::vec(handle_id);
+        self.backend.register_vec_handle(&mut handle);
+        self.backend.vec_get(&handle)
+    }
+}
+
+#[cfg(all(test, feature = "sled_checkpoints"))]
+mod tests {
+    use super::*;
+    use crate::{backend::SCHEMA_HANDLE_ID, Sled};
+
+    #[test]
+    fn inspect_sled_checkpoint() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let checkpoint = dir.path().join("checkpoint");
+        {
+            let backend = Sled::create(&dir.path().join("live"), "test".into()).unwrap();
+            let mut map = Handle::<MapState<u64, u64>>::map("counts");
+            backend.register_map_handle(&mut map);
+            backend.map_fast_insert(&map, 1, 10).unwrap();
+            backend.map_fast_insert(&map, 2, 20).unwrap();
+
+            let mut vec = Handle::<VecState<u32>>::vec("events");
+            backend.register_vec_handle(&mut vec);
+            backend.vec_append(&vec, 7).unwrap();
+
+            backend.checkpoint(&checkpoint).unwrap();
+        }
+
+        let inspector = StateInspector::<Sled>::open(&checkpoint).unwrap();
+        assert_eq!(
+            inspector.handles().unwrap(),
+            vec![SCHEMA_HANDLE_ID, "counts", "events"]
+        );
+        assert_eq!(inspector.raw("counts").unwrap().len(), 2);
+
+        let mut counts = inspector.map::<u64, u64>("counts").unwrap();
+        counts.sort_unstable();
+        assert_eq!(counts, vec![(1, 10), (2, 20)]);
+        assert_eq!(inspector.vec::<u32>("events").unwrap(), vec![7]);
+    }
+}
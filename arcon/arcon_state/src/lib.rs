@@ -6,6 +6,14 @@ pub mod backend;
 pub mod data;
 /// Error utilities
 pub mod error;
+/// Offline inspection of checkpoints
+pub mod inspect;
+/// Storage for checkpoints
+pub mod storage;
 
 #[doc(hidden)]
 pub use crate::backend::*;
+pub use crate::inspect::{RawEntry, StateInspector};
+pub use crate::storage::{
+    CheckpointStorage, LocalStorage, MemoryObjectStore, ObjectStorage, ObjectStoreClient,
+};
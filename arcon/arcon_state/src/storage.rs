@@ -0,0 +1,422 @@
+//! Storage that keeps checkpoints away from the machine that took them
+//!
+//! Backends always write their checkpoints to a local directory first. A [CheckpointStorage]
+//! keeps a copy of that directory, so that the checkpoints survive the loss of the machine.
+//! Objects are addressed by `/` separated keys relative to the root of the storage, which
+//! mirror the paths relative to the local checkpoint directory.
+
+use crate::error::*;
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt::Debug,
+    fs,
+    io::{self, Read, Write},
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+};
+
+/// An object-store style storage for checkpoints
+pub trait CheckpointStorage: Debug + Send + Sync + 'static {
+    /// Stores `data` under `key`, replacing any previous object
+    fn put(&self, key: &str, data: &[u8]) -> Result<()>;
+
+    /// Stores the contents of `reader` under `key`, replacing any previous object
+    ///
+    /// The default implementation reads the whole object into memory first. Storages
+    /// that can write an object piece by piece should override it.
+    fn put_reader(&self, key: &str, reader: &mut dyn Read) -> Result<()> {
+        let mut data = Vec::new();
+        reader.read_to_end(&mut data)?;
+        self.put(key, &data)
+    }
+
+    /// Returns the object stored under `key`, if there is one
+    fn get(&self, key: &str) -> Result<Option<Vec<u8>>>;
+
+    /// Returns the keys of all objects below the directory `prefix` in ascending order
+    ///
+    /// An empty prefix lists every object of the storage.
+    fn list(&self, prefix: &str) -> Result<Vec<String>>;
+
+    /// Deletes the object stored under `key`, if there is one
+    fn delete(&self, key: &str) -> Result<()>;
+
+    /// Stores the files of the local directory `dir` below `prefix`
+    fn put_dir(&self, dir: &Path, prefix: &str) -> Result<()> {
+        for (relative, path) in local_files(dir)? {
+            self.put_reader(&join_key(prefix, &relative), &mut fs::File::open(path)?)?;
+        }
+        Ok(())
+    }
+
+    /// Writes the objects below `prefix` into the local directory `dir`
+    ///
+    /// Returns whether there was any object below `prefix`.
+    fn get_dir(&self, prefix: &str, dir: &Path) -> Result<bool> {
+        let keys = self.list(prefix)?;
+        for key in &keys {
+            if let Some(data) = self.get(key)? {
+                let path = dir.join(strip_key(prefix, key));
+                if let Some(parent) = path.parent() {
+                    fs::create_dir_all(parent)?;
+                }
+                fs::write(path, data)?;
+            }
+        }
+        Ok(!keys.is_empty())
+    }
+
+    /// Deletes every object below `prefix`
+    fn delete_dir(&self, prefix: &str) -> Result<()> {
+        for key in self.list(prefix)? {
+            self.delete(&key)?;
+        }
+        Ok(())
+    }
+
+    /// Makes the objects below `prefix` match the files of the local directory `dir`
+    ///
+    /// Only files that are missing below `prefix` are stored, so this is meant for
+    /// directories whose files never change once written, such as the
+    /// [SHARED_CHECKPOINT_DIR](crate::SHARED_CHECKPOINT_DIR) of incremental checkpoints.
+    fn mirror_dir(&self, dir: &Path, prefix: &str) -> Result<()> {
+        let mut stored: BTreeMap<String, String> = self
+            .list(prefix)?
+            .into_iter()
+            .map(|key| (strip_key(prefix, &key).to_string(), key))
+            .collect();
+        for (relative, path) in local_files(dir)? {
+            if stored.remove(&relative).is_none() {
+                self.put_reader(&join_key(prefix, &relative), &mut fs::File::open(path)?)?;
+            }
+        }
+        for key in stored.values() {
+            self.delete(key)?;
+        }
+        Ok(())
+    }
+}
+
+/// Joins `name` onto the key `prefix`
+fn join_key(prefix: &str, name: &str) -> String {
+    let prefix = prefix.trim_end_matches('/');
+    if prefix.is_empty() {
+        name.to_string()
+    } else if name.is_empty() {
+        prefix.to_string()
+    } else {
+        format!("{}/{}", prefix, name)
+    }
+}
+
+/// Returns `key` relative to the directory `prefix` it is stored below
+fn strip_key<'a>(prefix: &str, key: &'a str) -> &'a str {
+    let prefix = prefix.trim_end_matches('/');
+    if prefix.is_empty() {
+        key
+    } else {
+        key[prefix.len()..].trim_start_matches('/')
+    }
+}
+
+/// Returns the files below `dir` with their `/` separated paths relative to `dir`
+fn local_files(dir: &Path) -> Result<Vec<(String, PathBuf)>> {
+    fn visit(dir: &Path, relative: &str, files: &mut Vec<(String, PathBuf)>) -> Result<()> {
+        for entry in fs::read_dir(dir)? {
+            let entry = entry?;
+            let name = entry.file_name();
+            let name = name.to_str().context(InvalidPath { path: entry.path() })?;
+            let relative = join_key(relative, name);
+            if entry.file_type()?.is_dir() {
+                visit(&entry.path(), &relative, files)?;
+            } else {
+                files.push((relative, entry.path()));
+            }
+        }
+        Ok(())
+    }
+    let mut files = Vec::new();
+    visit(dir, "", &mut files)?;
+    Ok(files)
+}
+
+/// A [CheckpointStorage] that keeps checkpoints in a local directory
+///
+/// Pointing `root` at a mounted network file system is enough to keep the
+/// checkpoints off the machine.
+#[derive(Debug, Clone)]
+pub struct LocalStorage {
+    root: PathBuf,
+}
+
+impl LocalStorage {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        LocalStorage { root: root.into() }
+    }
+
+    fn path(&self, key: &str) -> PathBuf {
+        self.root.join(key)
+    }
+}
+
+impl CheckpointStorage for LocalStorage {
+    fn put(&self, key: &str, data: &[u8]) -> Result<()> {
+        let path = self.path(key);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        // write to a temporary file first so that readers never see a partial object
+        let mut tmp_path = path.clone().into_os_string();
+        tmp_path.push(".tmp");
+        fs::write(&tmp_path, data)?;
+        fs::rename(tmp_path, path)?;
+        Ok(())
+    }
+
+    fn put_reader(&self, key: &str, reader: &mut dyn Read) -> Result<()> {
+        let path = self.path(key);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let mut tmp_path = path.clone().into_os_string();
+        tmp_path.push(".tmp");
+        let mut file = fs::File::create(&tmp_path)?;
+        io::copy(reader, &mut file)?;
+        file.flush()?;
+        fs::rename(tmp_path, path)?;
+        Ok(())
+    }
+
+    fn get(&self, key: &str) -> Result<Option<Vec<u8>>> {
+        match fs::read(self.path(key)) {
+            Ok(data) => Ok(Some(data)),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    fn list(&self, prefix: &str) -> Result<Vec<String>> {
+        let dir = self.path(prefix);
+        if !dir.is_dir() {
+            return Ok(Vec::new());
+        }
+        let mut keys: Vec<String> = local_files(&dir)?
+            .into_iter()
+            .map(|(relative, _)| join_key(prefix, &relative))
+            .collect();
+        keys.sort();
+        Ok(keys)
+    }
+
+    fn delete(&self, key: &str) -> Result<()> {
+        match fs::remove_file(self.path(key)) {
+            Err(err) if err.kind() != io::ErrorKind::NotFound => Err(err.into()),
+            _ => Ok(()),
+        }
+    }
+
+    fn delete_dir(&self, prefix: &str) -> Result<()> {
+        match fs::remove_dir_all(self.path(prefix)) {
+            Err(err) if err.kind() != io::ErrorKind::NotFound => Err(err.into()),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Client of an S3 compatible object store
+///
+/// Implement it on top of the S3 client of your choice to keep checkpoints in an
+/// object store through [ObjectStorage]. [MemoryObjectStore] is an in-process
+/// stand-in for testing.
+pub trait ObjectStoreClient: Debug + Send + Sync + 'static {
+    fn put_object(&self, bucket: &str, key: &str, body: Vec<u8>) -> Result<()>;
+    /// Stores the contents of `body` as an object
+    ///
+    /// The default implementation reads the whole object into memory first. Clients
+    /// should override it with a streaming or multipart upload where they can.
+    fn put_object_from(&self, bucket: &str, key: &str, body: &mut dyn Read) -> Result<()> {
+        let mut data = Vec::new();
+        body.read_to_end(&mut data)?;
+        self.put_object(bucket, key, data)
+    }
+    /// Returns the object, or `None` if `bucket` has no object under `key`
+    fn get_object(&self, bucket: &str, key: &str) -> Result<Option<Vec<u8>>>;
+    /// Returns the keys of all objects in `bucket` that start with `prefix`
+    fn list_objects(&self, bucket: &str, prefix: &str) -> Result<Vec<String>>;
+    /// Deletes the object, deleting a missing object is not an error
+    fn delete_object(&self, bucket: &str, key: &str) -> Result<()>;
+}
+
+/// A [CheckpointStorage] that keeps checkpoints below `prefix` in a bucket of an object store
+#[derive(Debug, Clone)]
+pub struct ObjectStorage<C: ObjectStoreClient> {
+    client: C,
+    bucket: String,
+    prefix: String,
+}
+
+impl<C: ObjectStoreClient> ObjectStorage<C> {
+    pub fn new(client: C, bucket: impl Into<String>, prefix: impl Into<String>) -> Self {
+        ObjectStorage {
+            client,
+            bucket: bucket.into(),
+            prefix: prefix.into().trim_matches('/').to_string(),
+        }
+    }
+
+    fn object_key(&self, key: &str) -> String {
+        join_key(&self.prefix, key)
+    }
+}
+
+impl<C: ObjectStoreClient> CheckpointStorage for ObjectStorage<C> {
+    fn put(&self, key: &str, data: &[u8]) -> Result<()> {
+        self.client
+            .put_object(&self.bucket, &self.object_key(key), data.to_vec())
+    }
+
+    fn put_reader(&self, key: &str, reader: &mut dyn Read) -> Result<()> {
+        self.client
+            .put_object_from(&self.bucket, &self.object_key(key), reader)
+    }
+
+    fn get(&self, key: &str) -> Result<Option<Vec<u8>>> {
+        self.client.get_object(&self.bucket, &self.object_key(key))
+    }
+
+    fn list(&self, prefix: &str) -> Result<Vec<String>> {
+        // object stores list by string prefix, so make sure to only match whole directories
+        let dir = self.object_key(prefix.trim_end_matches('/'));
+        let object_prefix = if dir.is_empty() {
+            dir
+        } else {
+            format!("{}/", dir)
+        };
+        let mut keys: Vec<String> = self
+            .client
+            .list_objects(&self.bucket, &object_prefix)?
+            .iter()
+            .map(|key| strip_key(&self.prefix, key).to_string())
+            .collect();
+        keys.sort();
+        Ok(keys)
+    }
+
+    fn delete(&self, key: &str) -> Result<()> {
+        self.client
+            .delete_object(&self.bucket, &self.object_key(key))
+    }
+}
+
+/// An in-process [ObjectStoreClient]
+///
+/// Clones share their buckets, which lets tests hand the same store to an
+/// application that restores the checkpoints of another one.
+#[derive(Debug, Clone, Default)]
+pub struct MemoryObjectStore {
+    buckets: Arc<Mutex<HashMap<String, BTreeMap<String, Vec<u8>>>>>,
+}
+
+impl ObjectStoreClient for MemoryObjectStore {
+    fn put_object(&self, bucket: &str, key: &str, body: Vec<u8>) -> Result<()> {
+        let mut buckets = self.buckets.lock().unwrap();
+        buckets
+            .entry(bucket.to_string())
+            .or_default()
+            .insert(key.to_string(), body);
+        Ok(())
+    }
+
+    fn get_object(&self, bucket: &str, key: &str) -> Result<Option<Vec<u8>>> {
+        let buckets = self.buckets.lock().unwrap();
+        Ok(buckets.get(bucket).and_then(|b| b.get(key)).cloned())
+    }
+
+    fn list_objects(&self, bucket: &str, prefix: &str) -> Result<Vec<String>> {
+        let buckets = self.buckets.lock().unwrap();
+        Ok(buckets
+            .get(bucket)
+            .map(|b| {
+                b.range(prefix.to_string()..)
+                    .take_while(|(key, _)| key.starts_with(prefix))
+                    .map(|(key, _)| key.clone())
+                    .collect()
+            })
+            .unwrap_or_default())
+    }
+
+    fn delete_object(&self, bucket: &str, key: &str) -> Result<()> {
+        let mut buckets = self.buckets.lock().unwrap();
+        if let Some(b) = buckets.get_mut(bucket) {
+            b.remove(key);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn storage_test(storage: &dyn CheckpointStorage) {
+        storage.put("a/checkpoint_a_0/data", b"0").unwrap();
+        storage.put("a/checkpoint_a_1/data", b"1").unwrap();
+        storage.put("ab/checkpoint_ab_0/data", b"2").unwrap();
+
+        assert_eq!(
+            storage.get("a/checkpoint_a_1/data").unwrap(),
+            Some(b"1".to_vec())
+        );
+        assert_eq!(storage.get("a/checkpoint_a_2/data").unwrap(), None);
+        // prefixes only match whole directories
+        assert_eq!(
+            storage.list("a").unwrap(),
+            vec!["a/checkpoint_a_0/data", "a/checkpoint_a_1/data"]
+        );
+        assert_eq!(storage.list("").unwrap().len(), 3);
+
+        storage.delete_dir("a/checkpoint_a_0").unwrap();
+        assert_eq!(storage.list("a").unwrap(), vec!["a/checkpoint_a_1/data"]);
+        storage.delete("ab/checkpoint_ab_0/data").unwrap();
+        storage.delete("ab/checkpoint_ab_0/data").unwrap();
+        assert!(storage.list("ab").unwrap().is_empty());
+
+        // directories round trip
+        let dir = tempfile::tempdir().unwrap();
+        let source = dir.path().join("source");
+        fs::create_dir_all(source.join("nested")).unwrap();
+        fs::write(source.join("CURRENT"), b"current").unwrap();
+        fs::write(source.join("nested/1.sst"), b"sst").unwrap();
+        storage.put_dir(&source, "b/checkpoint_b_0").unwrap();
+        let target = dir.path().join("target");
+        assert!(storage.get_dir("b/checkpoint_b_0", &target).unwrap());
+        assert_eq!(fs::read(target.join("CURRENT")).unwrap(), b"current");
+        assert_eq!(fs::read(target.join("nested/1.sst")).unwrap(), b"sst");
+        assert!(!storage.get_dir("b/checkpoint_b_1", &target).unwrap());
+
+        // mirroring only adds missing files and drops deleted ones
+        fs::remove_file(source.join("CURRENT")).unwrap();
+        fs::write(source.join("2.sst"), b"sst").unwrap();
+        storage.mirror_dir(&source, "b/checkpoint_b_0").unwrap();
+        assert_eq!(
+            storage.list("b").unwrap(),
+            vec!["b/checkpoint_b_0/2.sst", "b/checkpoint_b_0/nested/1.sst"]
+        );
+    }
+
+    #[test]
+    fn local_storage_test() {
+        let root = tempfile::tempdir().unwrap();
+        storage_test(&LocalStorage::new(root.path()));
+    }
+
+    #[test]
+    fn object_storage_test() {
+        let store = MemoryObjectStore::default();
+        storage_test(&ObjectStorage::new(store.clone(), "bucket", "app/"));
+        // other prefixes of the bucket are left alone
+        store.put_object("bucket", "other/key", Vec::new()).unwrap();
+        let storage = ObjectStorage::new(store, "bucket", "app");
+        assert_eq!(storage.list("").unwrap().len(), 3);
+    }
+}
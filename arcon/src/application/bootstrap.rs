@@ -0,0 +1,186 @@
+use crate::{
+    data::{partition::shard_lookup_with_key, Epoch, StateID},
+    dataflow::{
+        conf::{DefaultBackend, OperatorConf, ParallelismStrategy},
+        constructor::{
+            key_group_state_id, node_state_id, operator_descriptor, OPERATOR_DESCRIPTOR,
+        },
+        dfg::OperatorId,
+    },
+    error::ArconResult,
+    index::ArconState,
+    manager::snapshot::{store_savepoint_catalog, Snapshot},
+    table::{parquet_arrow_reader, RECORD_BATCH_SIZE},
+};
+use arcon_state::Backend;
+use arrow::record_batch::RecordBatch;
+use parquet::arrow::ArrowReader;
+use std::{
+    collections::HashMap,
+    hash::{Hash, Hasher},
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+/// Epoch that bootstrapped savepoints are committed at
+const BOOTSTRAP_EPOCH: u64 = 0;
+
+/// Name of the directory in the savepoint that keeps the live state while bootstrapping
+const LIVE_DIR: &str = "live";
+
+/// State of a single node or key group of the bootstrapped operator
+struct Shard<S, B> {
+    state_id: StateID,
+    state: S,
+    backend: Arc<B>,
+}
+
+/// Builds a savepoint with the keyed state of an operator from batch input
+///
+/// Keys are assigned to the nodes, or key groups of a [ParallelismStrategy::Managed] operator,
+/// that the runtime routes them to. The conf and the `state` constructor must therefore match
+/// those of the operator in the application, which is then started with
+/// [RestoreMode::Savepoint](crate::application::conf::RestoreMode::Savepoint) pointing at the savepoint.
+/// Operators are identified by their position in the dataflow, with the source at 0.
+///
+/// ```no_run
+/// use arcon::prelude::*;
+/// use arrow::array::UInt64Array;
+///
+/// #[derive(ArconState)]
+/// pub struct Counts<B: Backend> {
+///     counts: EagerValue<u64, B>,
+/// }
+///
+/// let mut bootstrap = StateBootstrap::new(
+///     "/tmp/counts_savepoint",
+///     1,
+///     &OperatorConf::default(),
+///     |backend: Arc<Sled>| Counts {
+///         counts: EagerValue::new("_counts", backend),
+///     },
+/// )
+/// .unwrap();
+///
+/// bootstrap
+///     .load_parquet("/tmp/counts.parquet", |bootstrap, batch| {
+///         let ids = batch
+///             .column(0)
+///             .as_any()
+///             .downcast_ref::<UInt64Array>()
+///             .unwrap();
+///         for id in ids.iter().flatten() {
+///             bootstrap.update(&id, |state| Ok(state.counts.rmw(|count| *count += 1)?))?;
+///         }
+///         Ok(())
+///     })
+///     .unwrap();
+///
+/// let savepoint = bootstrap.finish().unwrap();
+/// ```
+pub struct StateBootstrap<S, B = DefaultBackend>
+where
+    S: ArconState,
+    B: Backend,
+{
+    path: PathBuf,
+    shards: Vec<Shard<S, B>>,
+}
+
+impl<S, B> StateBootstrap<S, B>
+where
+    S: ArconState,
+    B: Backend,
+{
+    /// Creates a bootstrap of the operator `operator_id` that writes its savepoint to `path`
+    pub fn new(
+        path: impl Into<PathBuf>,
+        operator_id: OperatorId,
+        conf: &OperatorConf,
+        state: impl Fn(Arc<B>) -> S,
+    ) -> ArconResult<Self> {
+        let path = path.into();
+        let descriptor = operator_descriptor(OPERATOR_DESCRIPTOR, operator_id);
+        let state_ids: Vec<StateID> = match conf.parallelism_strategy {
+            ParallelismStrategy::Static(parallelism) => (0..parallelism as u32)
+                .map(|id| node_state_id(&descriptor, id))
+                .collect(),
+            ParallelismStrategy::Managed => (0..conf.scaling.max_parallelism as u64)
+                .map(|id| key_group_state_id(&descriptor, id))
+                .collect(),
+        };
+
+        let mut shards = Vec::with_capacity(state_ids.len());
+        for state_id in state_ids {
+            let live_path = path.join(LIVE_DIR).join(&state_id);
+            let backend = Arc::new(B::create(&live_path, state_id.clone())?);
+            shards.push(Shard {
+                state: state(backend.clone()),
+                state_id,
+                backend,
+            });
+        }
+        Ok(StateBootstrap { path, shards })
+    }
+
+    /// Runs `f` on the state of `key`, which is hashed the same way as by `key_by`
+    pub fn update<K: Hash>(
+        &mut self,
+        key: &K,
+        f: impl FnOnce(&mut S) -> ArconResult<()>,
+    ) -> ArconResult<()> {
+        let mut hasher = arcon_util::key_hasher();
+        key.hash(&mut hasher);
+        self.update_hashed(hasher.finish(), f)
+    }
+
+    /// Runs `f` on the state of an already hashed `key`
+    pub fn update_hashed(
+        &mut self,
+        key: u64,
+        f: impl FnOnce(&mut S) -> ArconResult<()>,
+    ) -> ArconResult<()> {
+        let shard = shard_lookup_with_key(key, self.shards.len() as u64) as usize;
+        let state = &mut self.shards[shard].state;
+        state.set_key(key);
+        f(state)
+    }
+
+    /// Feeds the record batches of the Parquet file at `path` to `f`
+    pub fn load_parquet(
+        &mut self,
+        path: impl AsRef<Path>,
+        mut f: impl FnMut(&mut Self, &RecordBatch) -> ArconResult<()>,
+    ) -> ArconResult<()> {
+        let mut reader = parquet_arrow_reader(path)?;
+        for batch in reader.get_record_reader(RECORD_BATCH_SIZE)? {
+            f(self, &batch?)?;
+        }
+        Ok(())
+    }
+
+    /// Checkpoints the state of every node into the savepoint and returns its path
+    pub fn finish(mut self) -> ArconResult<PathBuf> {
+        let mut snapshots = HashMap::with_capacity(self.shards.len());
+        for shard in self.shards.iter_mut() {
+            shard.state.persist()?;
+            let relative = Path::new(&shard.state_id)
+                .join(format!("checkpoint_{}_{}", shard.state_id, BOOTSTRAP_EPOCH));
+            shard.backend.checkpoint(&self.path.join(&relative))?;
+            snapshots.insert(
+                shard.state_id.clone(),
+                Snapshot::new(
+                    std::any::type_name::<B>().to_string(),
+                    BOOTSTRAP_EPOCH,
+                    relative.to_string_lossy().into_owned(),
+                ),
+            );
+        }
+        // close the live backends before removing them
+        self.shards.clear();
+        std::fs::remove_dir_all(self.path.join(LIVE_DIR))?;
+
+        store_savepoint_catalog(&self.path, Epoch::new(BOOTSTRAP_EPOCH), snapshots)?;
+        Ok(self.path)
+    }
+}
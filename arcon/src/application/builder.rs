@@ -1,9 +1,14 @@
 use super::conf::ApplicationConf;
 use super::Application;
-use crate::dataflow::{
-    dfg::{DFGNodeKind, GlobalNodeId},
-    stream::Context,
+use crate::{
+    dataflow::{
+        constructor::ErasedComponents,
+        dfg::{DFGNodeKind, GlobalNodeId, OperatorId},
+        stream::Context,
+    },
+    error::ArconResult,
 };
+use std::collections::HashMap;
 
 /// A Builder for Arcon Applications
 ///
@@ -53,17 +58,39 @@ impl ApplicationBuilder {
     ///
     /// Note that this method only builds the application. In order
     /// to start it, see the following [method](Application::run).
+    ///
+    /// # Panics
+    ///
+    /// Panics if the application could not be built, see [ApplicationBuilder::try_build].
     pub fn build(&mut self) -> Application {
+        self.try_build().expect("Failed to build application")
+    }
+
+    /// Build an Arcon application
+    ///
+    /// Returns an error if the restored state of an operator could not be
+    /// migrated to the state schema of the operator.
+    pub fn try_build(&mut self) -> ArconResult<Application> {
         let mut app = Application::with_conf(self.conf.clone());
-        if self.debug {
+        if self.debug || self.ctx.debug.get() {
             app.with_debug_node();
         }
 
-        let mut output_channels = Vec::new();
+        // Components of already built operators, used as output channels by their predecessors
+        let mut built: HashMap<OperatorId, ErasedComponents> = HashMap::new();
 
-        for dfg_node in self.ctx.dfg.graph.iter().rev() {
+        let dfg = self.ctx.dfg.borrow();
+        for dfg_node in dfg.graph.iter().rev() {
             let operator_id = dfg_node.get_operator_id();
             let input_channels = dfg_node.get_input_channels();
+            let output_channels: Vec<ErasedComponents> = (0..dfg_node.num_outputs())
+                .map(|output| {
+                    dfg_node
+                        .get_successor(output)
+                        .and_then(|id| built.get(&id).cloned())
+                        .unwrap_or_default()
+                })
+                .collect();
             let node_ids = dfg_node
                 .get_node_ids()
                 .iter()
@@ -74,25 +101,32 @@ impl ApplicationBuilder {
                 .collect();
             match &dfg_node.kind {
                 DFGNodeKind::Source(source_factory) => {
-                    let sources =
-                        source_factory.build_source(output_channels.clone(), Vec::new(), &mut app);
-                    app.set_source_manager(sources);
+                    let sources = source_factory.build_source(
+                        operator_id,
+                        output_channels,
+                        Vec::new(),
+                        &mut app,
+                    );
+                    app.add_source_manager(sources);
                 }
                 DFGNodeKind::Node(constructor) => {
                     let components = constructor.build_nodes(
                         node_ids,
                         input_channels.to_vec(),
-                        output_channels.clone(),
+                        output_channels,
                         Vec::new(),
                         &mut app,
+                    )?;
+                    built.insert(
+                        operator_id,
+                        components.iter().map(|(_, c)| c.clone()).collect(),
                     );
-                    output_channels = components.iter().map(|(_, c)| c.clone()).collect();
                 }
                 DFGNodeKind::Placeholder => {
                     panic!("Critical Error, Stream built incorrectly");
                 }
             }
         }
-        app
+        Ok(app)
     }
 }
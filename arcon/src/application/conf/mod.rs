@@ -1,11 +1,12 @@
 pub mod logger;
 
+use arcon_state::CheckpointStorage;
 use kompact::{
     net::buffers::BufferConfig,
     prelude::{DeadletterBox, KompactConfig, NetworkConfig},
 };
 use logger::{file_logger, term_logger, ArconLogger, LoggerType};
-use std::path::PathBuf;
+use std::{path::PathBuf, sync::Arc};
 
 /// Types of modes that `arcon` may run in
 #[derive(Clone, Debug)]
@@ -22,6 +23,65 @@ pub struct DistributedConf {
     peers: Vec<String>, // ["192.168.1.1:2000",  "192.168.1.2:2000"]
 }
 
+/// Decides which state an Arcon Application starts with
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize))]
+pub enum RestoreMode {
+    /// Start from empty state
+    Disabled,
+    /// Restore state and source offsets from the newest epoch in the
+    /// [SnapshotCatalog](crate::prelude::SnapshotCatalog) of the checkpoint
+    /// directory, or start from empty state if there is none
+    Latest,
+    /// Restore state and source offsets from the savepoint in the given directory
+    ///
+    /// See [Application::savepoint](crate::application::Application::savepoint).
+    Savepoint(PathBuf),
+}
+
+impl Default for RestoreMode {
+    fn default() -> Self {
+        RestoreMode::Disabled
+    }
+}
+
+/// Decides which committed checkpoints are deleted as newer epochs are committed
+///
+/// An epoch is deleted as soon as it violates any of the limits, but the newest
+/// committed epoch is always kept. By default no checkpoints are deleted.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct RetentionPolicy {
+    /// Maximum number of committed epochs to keep
+    pub max_epochs: Option<usize>,
+    /// Maximum age in milliseconds of a committed epoch
+    pub max_age: Option<u64>,
+    /// Maximum size in bytes of all committed checkpoints
+    pub max_size: Option<u64>,
+}
+
+/// Decides how nodes with several input channels checkpoint an epoch
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize))]
+pub enum CheckpointMode {
+    /// Channels that have delivered an epoch marker are blocked until every
+    /// channel has delivered it, and state is checkpointed once they are aligned
+    Aligned,
+    /// The first epoch marker is forwarded right away and state is checkpointed
+    /// together with the messages the remaining channels deliver before their marker
+    ///
+    /// Keeps checkpoint latency bounded when channels are skewed or backpressured.
+    /// Operators with a managed parallelism always align their channels.
+    Unaligned,
+}
+
+impl Default for CheckpointMode {
+    fn default() -> Self {
+        CheckpointMode::Aligned
+    }
+}
+
 /// Configuration for an Arcon Application
 #[derive(Clone, Debug)]
 #[cfg_attr(feature = "serde", derive(serde::Deserialize))]
@@ -38,9 +98,31 @@ pub struct ApplicationConf {
     /// [LoggerType] for kompact related logging
     #[cfg_attr(feature = "serde", serde(default))]
     pub kompact_logger_type: LoggerType,
+    /// Whether the application restores its state from [checkpoints_dir](ApplicationConf::checkpoints_dir)
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub restore: RestoreMode,
+    /// [RetentionPolicy] for the checkpoints of the application
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub retention: RetentionPolicy,
+    /// [CheckpointMode] of the nodes of the application
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub checkpoint_mode: CheckpointMode,
+    /// Amount of threads that complete the checkpoints of the nodes of the application
+    #[cfg_attr(feature = "serde", serde(default = "checkpoint_threads_default"))]
+    pub checkpoint_threads: usize,
+    /// [CheckpointStorage] that committed checkpoints are copied to
+    ///
+    /// Checkpoints are always taken in [checkpoints_dir](ApplicationConf::checkpoints_dir).
+    /// With a storage, restoring fetches them from the storage instead, so that they
+    /// survive the loss of the machine.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub checkpoint_storage: Option<Arc<dyn CheckpointStorage>>,
     /// Generation interval in milliseconds for Epochs
     #[cfg_attr(feature = "serde", serde(default = "epoch_interval_default"))]
     pub epoch_interval: u64,
+    /// Time in milliseconds that [Application::savepoint](crate::application::Application::savepoint) waits for a savepoint
+    #[cfg_attr(feature = "serde", serde(default = "savepoint_timeout_default"))]
+    pub savepoint_timeout: u64,
     /// Generation interval in milliseconds for Watermarks at sources
     #[cfg_attr(feature = "serde", serde(default = "watermark_interval_default"))]
     pub watermark_interval: u64,
@@ -52,6 +134,9 @@ pub struct ApplicationConf {
     /// Interval in milliseconds for sending off metrics from nodes
     #[cfg_attr(feature = "serde", serde(default = "node_metrics_interval_default"))]
     pub node_metrics_interval: u64,
+    /// Interval in milliseconds at which nodes fire due processing-time timers
+    #[cfg_attr(feature = "serde", serde(default = "processing_time_interval_default"))]
+    pub processing_time_interval: u64,
     /// Amount of buffers pre-allocated to a BufferPool
     #[cfg_attr(feature = "serde", serde(default = "buffer_pool_size_default"))]
     pub buffer_pool_size: usize,
@@ -102,10 +187,17 @@ impl Default for ApplicationConf {
             base_dir: base_dir_default(),
             arcon_logger_type: Default::default(),
             kompact_logger_type: Default::default(),
+            restore: Default::default(),
+            retention: Default::default(),
+            checkpoint_mode: Default::default(),
+            checkpoint_threads: checkpoint_threads_default(),
+            checkpoint_storage: None,
             watermark_interval: watermark_interval_default(),
             epoch_interval: epoch_interval_default(),
+            savepoint_timeout: savepoint_timeout_default(),
             max_key: max_key_default(),
             node_metrics_interval: node_metrics_interval_default(),
+            processing_time_interval: processing_time_interval_default(),
             buffer_pool_size: buffer_pool_size_default(),
             buffer_pool_limit: buffer_pool_limit_default(),
             channel_batch_size: channel_batch_size_default(),
@@ -134,6 +226,11 @@ impl ApplicationConf {
         buf.push("checkpoints");
         buf
     }
+    pub fn savepoints_dir(&self) -> PathBuf {
+        let mut buf = self.base_dir.clone();
+        buf.push("savepoints");
+        buf
+    }
 
     pub fn arcon_logger(&self) -> ArconLogger {
         match self.arcon_logger_type {
@@ -174,9 +271,11 @@ impl ApplicationConf {
 
         // inject checkpoint_dir into Kompact
         let component_cfg = format!(
-            "{{ checkpoint_dir = {:?}, node_metrics_interval = {} }}",
+            "{{ checkpoint_dir = {:?}, node_metrics_interval = {}, processing_time_interval = {}, unaligned_checkpoints = {} }}",
             self.checkpoints_dir(),
-            self.node_metrics_interval
+            self.node_metrics_interval,
+            self.processing_time_interval,
+            self.checkpoint_mode == CheckpointMode::Unaligned
         );
 
         if let Some(kompact_logger) = self.kompact_logger() {
@@ -204,9 +303,11 @@ impl ApplicationConf {
 
         // inject checkpoint_dir into Kompact
         let component_cfg = format!(
-            "{{ checkpoint_dir = {:?}, node_metrics_interval = {} }}",
+            "{{ checkpoint_dir = {:?}, node_metrics_interval = {}, processing_time_interval = {}, unaligned_checkpoints = {} }}",
             self.checkpoints_dir(),
-            self.node_metrics_interval
+            self.node_metrics_interval,
+            self.processing_time_interval,
+            self.checkpoint_mode == CheckpointMode::Unaligned
         );
 
         if let Some(kompact_logger) = self.kompact_logger() {
@@ -276,6 +377,11 @@ fn epoch_interval_default() -> u64 {
     25000
 }
 
+fn savepoint_timeout_default() -> u64 {
+    // in milliseconds
+    300000
+}
+
 fn watermark_interval_default() -> u64 {
     // in milliseconds
     250
@@ -290,6 +396,11 @@ fn node_metrics_interval_default() -> u64 {
     250
 }
 
+pub(crate) fn processing_time_interval_default() -> u64 {
+    // in milliseconds
+    100
+}
+
 fn buffer_pool_size_default() -> usize {
     1024
 }
@@ -307,6 +418,10 @@ fn allocator_capacity_default() -> usize {
     5368709120
 }
 
+fn checkpoint_threads_default() -> usize {
+    std::cmp::max(1, num_cpus::get())
+}
+
 fn kompact_threads_default() -> usize {
     std::cmp::max(1, num_cpus::get())
 }
@@ -37,22 +37,34 @@
 use crate::metrics::log_recorder::LogRecorder;
 
 use crate::{
-    application::{conf::logger::ArconLogger, conf::ExecutionMode},
+    application::{
+        conf::logger::ArconLogger,
+        conf::{ExecutionMode, RestoreMode},
+    },
     buffer::event::PoolInfo,
+    data::Epoch,
     dataflow::constructor::{ErasedComponent, ErasedSourceManager},
+    error::Error,
     manager::{
-        epoch::{EpochEvent, EpochManager},
-        snapshot::SnapshotManager,
+        epoch::{EpochEvent, EpochManager, SavepointRequest},
+        snapshot::{fetch_snapshot, Snapshot, SnapshotCatalog, SnapshotManager},
     },
     prelude::*,
-    stream::node::{debug::DebugNode, source::SourceEvent},
+    stream::node::{checkpoint::CheckpointPool, debug::DebugNode, source::SourceEvent},
 };
 use arcon_allocator::Allocator;
-use std::sync::{Arc, Mutex};
+use std::{
+    any::TypeId,
+    collections::HashMap,
+    path::PathBuf,
+    sync::{Arc, Mutex},
+};
 
+pub mod bootstrap;
 pub mod builder;
 pub mod conf;
 
+pub use bootstrap::StateBootstrap;
 pub use builder::ApplicationBuilder;
 pub use conf::ApplicationConf;
 
@@ -72,16 +84,22 @@ pub struct Application {
     pub(crate) arcon_logger: ArconLogger,
     start_flag: bool,
     pub(crate) runtime: Runtime,
-    // Type erased Arc<Component<DebugNode<A>>>
-    pub(crate) debug_node: Option<ErasedComponent>,
-    // Type erased Arc<dyn AbstractComponent<Message = ArconMessage<A>>>
-    pub(crate) abstract_debug_node: Option<ErasedComponent>,
-    /// SourceManager component for this application
-    pub(crate) source_manager: Option<ErasedSourceManager>,
+    // Type erased Arc<Component<DebugNode<A>>> keyed by the TypeId of A
+    pub(crate) debug_nodes: HashMap<TypeId, ErasedComponent>,
+    // Type erased Arc<dyn AbstractComponent<Message = ArconMessage<A>>> keyed by the TypeId of A
+    pub(crate) abstract_debug_nodes: HashMap<TypeId, ErasedComponent>,
+    /// SourceManager components for this application
+    pub(crate) source_managers: Vec<ErasedSourceManager>,
     /// EpochManager component for this application
     pub(crate) epoch_manager: Option<Arc<Component<EpochManager>>>,
     /// SnapshotManager component for this application
     pub(crate) snapshot_manager: Arc<Component<SnapshotManager>>,
+    /// Threads that complete the checkpoints of the nodes of this application
+    checkpoint_pool: CheckpointPool,
+    /// Catalog of the checkpoints or savepoint the application restores its state from
+    restore_catalog: SnapshotCatalog,
+    /// The committed epoch the application restores its state from
+    pub(crate) restored_epoch: Option<Epoch>,
 }
 
 impl Default for Application {
@@ -93,7 +111,7 @@ impl Default for Application {
 
 impl Application {
     /// Creates a new Application using the given ApplicationConf
-    fn new(conf: ApplicationConf) -> Self {
+    pub(crate) fn new(conf: ApplicationConf) -> Self {
         #[cfg(all(feature = "prometheus_exporter", feature = "metrics", not(test)))]
         {
             PrometheusBuilder::new()
@@ -118,17 +136,56 @@ impl Application {
         let timeout = std::time::Duration::from_millis(500);
 
         let runtime = Runtime::new(&conf, &arcon_logger);
-        let snapshot_manager = runtime.ctrl_system.create(SnapshotManager::new);
+        // The catalog state is restored from. Only the catalog of the checkpoint
+        // directory is continued, savepoints must never be subject to retention.
+        let (restore_catalog, catalog) = match &conf.restore {
+            RestoreMode::Latest => {
+                let catalog = match &conf.checkpoint_storage {
+                    Some(storage) => {
+                        SnapshotCatalog::fetch(storage.as_ref(), &conf.checkpoints_dir())
+                    }
+                    None => SnapshotCatalog::load(conf.checkpoints_dir()),
+                }
+                .expect("Failed to load the snapshot catalog");
+                (catalog.clone(), catalog)
+            }
+            RestoreMode::Savepoint(path) => {
+                let savepoint =
+                    SnapshotCatalog::load(path).expect("Failed to load the savepoint catalog");
+                assert!(
+                    savepoint.latest().is_some(),
+                    "No savepoint found in {}",
+                    path.display()
+                );
+                (savepoint, SnapshotCatalog::default())
+            }
+            RestoreMode::Disabled => (SnapshotCatalog::default(), SnapshotCatalog::default()),
+        };
+        let restored_epoch = restore_catalog.latest();
+        let checkpoints_dir = conf.checkpoints_dir();
+        let retention = conf.retention.clone();
+        let storage = conf.checkpoint_storage.clone();
+        let snapshot_manager = runtime.ctrl_system.create(|| {
+            SnapshotManager::new(checkpoints_dir, catalog, retention).with_storage(storage)
+        });
+
+        if let Some(epoch) = restored_epoch {
+            info!(arcon_logger, "Restoring application from {:?}", epoch);
+        }
 
         let epoch_manager = match conf.execution_mode {
             ExecutionMode::Local => {
                 let snapshot_manager_ref = snapshot_manager.actor_ref().hold().expect("fail");
                 let epoch_manager = runtime.ctrl_system.create(|| {
-                    EpochManager::new(
+                    let mut epoch_manager = EpochManager::new(
                         conf.epoch_interval,
                         snapshot_manager_ref,
                         arcon_logger.clone(),
-                    )
+                    );
+                    if let Some(epoch) = restored_epoch {
+                        epoch_manager.resume_after(epoch);
+                    }
+                    epoch_manager
                 });
                 runtime
                     .ctrl_system
@@ -147,6 +204,8 @@ impl Application {
             .wait_timeout(timeout)
             .expect("SnapshotManager comp never started!");
 
+        let checkpoint_pool = CheckpointPool::new(conf.checkpoint_threads);
+
         Self {
             conf,
             allocator,
@@ -154,11 +213,14 @@ impl Application {
             arcon_logger,
             start_flag: false,
             runtime,
-            debug_node: None,
-            abstract_debug_node: None,
-            source_manager: None,
+            debug_nodes: HashMap::new(),
+            abstract_debug_nodes: HashMap::new(),
+            source_managers: Vec::new(),
             snapshot_manager,
             epoch_manager,
+            checkpoint_pool,
+            restore_catalog,
+            restored_epoch,
         }
     }
 
@@ -188,8 +250,13 @@ impl Application {
     pub fn debug_node_enabled(&self) -> bool {
         self.debug_node_flag
     }
-    pub(crate) fn set_source_manager(&mut self, source_manager: ErasedSourceManager) {
-        self.source_manager = Some(source_manager);
+    pub(crate) fn add_source_manager(&mut self, source_manager: ErasedSourceManager) {
+        self.source_managers.push(source_manager);
+    }
+
+    /// Returns the pool that nodes complete their checkpoints on
+    pub(crate) fn checkpoint_pool(&self) -> &CheckpointPool {
+        &self.checkpoint_pool
     }
 
     pub(crate) fn epoch_manager(&self) -> ActorRefStrong<EpochEvent> {
@@ -217,12 +284,38 @@ impl Application {
         &self.snapshot_manager
     }
 
+    /// Returns the snapshot `state_id` is restored from, if the application is restoring
+    ///
+    /// Snapshots that are missing locally are fetched from the checkpoint storage.
+    pub(crate) fn restore_snapshot(&self, state_id: &str) -> Option<Snapshot> {
+        let epoch = self.restored_epoch?;
+        let snapshot = self.restore_catalog.snapshot(epoch, state_id).cloned()?;
+        if let Some(storage) = &self.conf.checkpoint_storage {
+            fetch_snapshot(storage.as_ref(), &self.conf.checkpoints_dir(), &snapshot)
+                .expect("Failed to fetch snapshot from the checkpoint storage");
+        }
+        Some(snapshot)
+    }
+
+    /// Returns the catalog of epochs the application has committed so far
+    pub fn snapshot_catalog(&self) -> SnapshotCatalog {
+        self.snapshot_manager
+            .on_definition(|cd| cd.catalog().clone())
+    }
+
+    /// Returns the first epoch the application processes
+    pub(crate) fn start_epoch(&self) -> Epoch {
+        self.restored_epoch
+            .map(|epoch| Epoch::new(epoch.epoch + 1))
+            .unwrap_or_else(|| Epoch::new(0))
+    }
+
     /// Fetch DebugNode component of the [Application]
     ///
-    /// Returns `None` if the [Application] was not configured with a DebugNode.
-    /// Note that it is up to the user to make sure `A` is of correct type.
+    /// Debug streams of the same type share a DebugNode.
+    /// Returns `None` if the [Application] has no DebugNode for `A`.
     pub fn get_debug_node<A: ArconType>(&self) -> Option<Arc<Component<DebugNode<A>>>> {
-        self.debug_node.as_ref().map(|erased_comp| {
+        self.debug_nodes.get(&TypeId::of::<A>()).map(|erased_comp| {
             erased_comp
                 .clone()
                 .downcast::<Component<DebugNode<A>>>()
@@ -230,6 +323,49 @@ impl Application {
         })
     }
 
+    /// Create a savepoint of the running application
+    ///
+    /// Injects an epoch right away, waits until every node and source has acknowledged it,
+    /// and copies the resulting snapshots into `name` under [ApplicationConf::savepoints_dir].
+    /// Unlike checkpoints, savepoints are never deleted by the retention policy. An
+    /// application may be started from the returned path using [RestoreMode::Savepoint].
+    /// Fails if the savepoint is not created within [ApplicationConf::savepoint_timeout].
+    pub fn savepoint(&self, name: &str) -> ArconResult<PathBuf> {
+        if !self.start_flag {
+            return Err(Error::Unsupported {
+                msg: "Savepoints require a running Application".to_string(),
+            });
+        }
+        let path = self.conf.savepoints_dir().join(name);
+        if path.exists() {
+            return Err(Error::Unsupported {
+                msg: format!("Savepoint {} already exists", path.display()),
+            });
+        }
+        let epoch_manager = match &self.epoch_manager {
+            Some(epoch_manager) => epoch_manager,
+            None => {
+                return Err(Error::Unsupported {
+                    msg: "Savepoints are only supported in local execution mode".to_string(),
+                })
+            }
+        };
+        let (promise, future) = promise();
+        epoch_manager
+            .actor_ref()
+            .tell(EpochEvent::Savepoint(SavepointRequest { path, promise }));
+        let timeout = std::time::Duration::from_millis(self.conf.savepoint_timeout);
+        match future.wait_timeout(timeout) {
+            Ok(result) => result,
+            Err(_) => Err(Error::Io {
+                error: std::io::Error::new(
+                    std::io::ErrorKind::TimedOut,
+                    format!("Savepoint {} timed out", name),
+                ),
+            }),
+        }
+    }
+
     /// Run the application and block until it terminates
     pub fn run_and_block(mut self) {
         self.start();
@@ -246,12 +382,13 @@ impl Application {
     fn start(&mut self) {
         assert!(!self.start_flag, "The Application has already been started");
 
-        // Send start message to manager component
-        match &self.source_manager {
-            Some(source_manager) => {
-                source_manager.actor_ref().tell(SourceEvent::Start);
-            }
-            None => panic!("Something went wrong, no source manager has been created!"),
+        // Send start message to manager components
+        assert!(
+            !self.source_managers.is_empty(),
+            "Something went wrong, no source manager has been created!"
+        );
+        for source_manager in &self.source_managers {
+            source_manager.actor_ref().tell(SourceEvent::Start);
         }
 
         // Start epoch manager to begin the injection of epochs into the application.
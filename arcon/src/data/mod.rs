@@ -110,6 +110,78 @@ impl<A: ArconType> ArconElement<A> {
     }
 }
 
+/// Record of one of the two inputs of a connected stream
+#[derive(Oneof, Clone)]
+pub enum CoEvent<L: ArconType, R: ArconType> {
+    /// Record of the left input
+    #[prost(message, tag = "1")]
+    Left(L),
+    /// Record of the right input
+    #[prost(message, tag = "2")]
+    Right(R),
+}
+
+/// Wrapper type that carries the records of two connected streams
+///
+/// Like [ArconEventWrapper], the inner value is only optional due to protobuf.
+#[derive(Message, Clone)]
+pub struct CoElement<L: ArconType, R: ArconType> {
+    #[prost(oneof = "CoEvent::<L, R>", tags = "1, 2")]
+    inner: Option<CoEvent<L, R>>,
+}
+
+impl<L: ArconType, R: ArconType> CoElement<L, R> {
+    /// Creates a CoElement holding a record of the left input
+    pub fn left(data: L) -> Self {
+        CoElement {
+            inner: Some(CoEvent::Left(data)),
+        }
+    }
+
+    /// Creates a CoElement holding a record of the right input
+    pub fn right(data: R) -> Self {
+        CoElement {
+            inner: Some(CoEvent::Right(data)),
+        }
+    }
+
+    pub fn unwrap(self) -> CoEvent<L, R> {
+        self.inner
+            .expect("CoElement.inner is None. Prost deserialization error?")
+    }
+
+    pub fn unwrap_ref(&self) -> &CoEvent<L, R> {
+        self.inner
+            .as_ref()
+            .expect("CoElement.inner is None. Prost deserialization error?")
+    }
+}
+
+impl<L: ArconType, R: ArconType> ArconType for CoElement<L, R> {
+    const RELIABLE_SER_ID: SerId = ser_id::RELIABLE_CO_ELEMENT_ID;
+    const VERSION_ID: VersionId = 1;
+}
+
+/// A pair of records that were joined on the same key
+#[derive(Message, Clone)]
+pub struct Joined<L: ArconType, R: ArconType> {
+    #[prost(message, required, tag = "1")]
+    pub left: L,
+    #[prost(message, required, tag = "2")]
+    pub right: R,
+}
+
+impl<L: ArconType, R: ArconType> Joined<L, R> {
+    pub fn new(left: L, right: R) -> Self {
+        Joined { left, right }
+    }
+}
+
+impl<L: ArconType, R: ArconType> ArconType for Joined<L, R> {
+    const RELIABLE_SER_ID: SerId = ser_id::RELIABLE_JOINED_ID;
+    const VERSION_ID: VersionId = 1;
+}
+
 /// Watermark message containing a [u64] timestamp
 #[derive(Message, Clone, Copy, Ord, PartialOrd, Eq, PartialEq)]
 pub struct Watermark {
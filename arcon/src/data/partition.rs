@@ -36,6 +36,24 @@ pub fn shard_lookup_with_key(hashed_key: u64, total_shards: u64) -> u64 {
     key * total_shards / MAX_KEY
 }
 
+/// Splits `total_groups` key groups into `parallelism` contiguous ranges of groups
+///
+/// Every range is non-empty and the ranges together cover all groups.
+pub fn key_group_ranges(total_groups: u64, parallelism: u64) -> Vec<KeyRange> {
+    assert!(
+        parallelism > 0 && parallelism <= total_groups,
+        "parallelism must be within 1..={}",
+        total_groups
+    );
+    (0..parallelism)
+        .map(|index| {
+            let start = index * total_groups / parallelism;
+            let end = (index + 1) * total_groups / parallelism - 1;
+            KeyRange::new(start, end)
+        })
+        .collect()
+}
+
 /// A Shard is responsible for a contiguous range of keys
 #[derive(Debug)]
 pub struct Shard {
@@ -65,9 +83,20 @@ pub struct KeyRange {
 impl KeyRange {
     /// Creates a new KeyRange
     pub fn new(start: u64, end: u64) -> KeyRange {
-        assert!(start < end, "start range has to be smaller than end range");
+        assert!(start <= end, "start range cannot be larger than end range");
         KeyRange { start, end }
     }
+
+    /// Returns true if `key` falls within the range
+    #[inline]
+    pub fn contains(&self, key: u64) -> bool {
+        key >= self.start && key <= self.end
+    }
+
+    /// Returns an iterator over all keys in the range
+    pub fn iter(&self) -> impl Iterator<Item = u64> {
+        self.start..=self.end
+    }
 }
 
 #[cfg(test)]
@@ -114,4 +143,24 @@ mod tests {
         }
         assert_eq!(hit, [true, true, true, true]);
     }
+
+    #[test]
+    fn key_group_ranges_test() {
+        let ranges = key_group_ranges(8, 3);
+        assert_eq!(ranges[0], KeyRange::new(0, 1));
+        assert_eq!(ranges[1], KeyRange::new(2, 4));
+        assert_eq!(ranges[2], KeyRange::new(5, 7));
+
+        let ranges = key_group_ranges(4, 4);
+        assert!(ranges
+            .iter()
+            .enumerate()
+            .all(|(i, r)| r.start == i as u64 && r.end == i as u64));
+
+        // every group is owned by exactly one range
+        let ranges = key_group_ranges(16, 5);
+        for group in 0..16 {
+            assert_eq!(ranges.iter().filter(|r| r.contains(group)).count(), 1);
+        }
+    }
 }
@@ -11,3 +11,7 @@ pub const RELIABLE_F32_ID: SerId = 59;
 pub const RELIABLE_F64_ID: SerId = 61;
 pub const RELIABLE_STRING_ID: SerId = 63;
 pub const RELIABLE_BOOLEAN_ID: SerId = 65;
+
+// Serialisation IDs for Arcon wrapper types
+pub const RELIABLE_CO_ELEMENT_ID: SerId = 67;
+pub const RELIABLE_JOINED_ID: SerId = 69;
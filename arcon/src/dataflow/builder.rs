@@ -3,7 +3,10 @@ use crate::{
     dataflow::conf::{DefaultBackend, OperatorConf, SourceConf},
     index::{ArconState, EMPTY_STATE_ID},
     stream::{
-        operator::Operator,
+        operator::{
+            co::{CoOperator, CoOperatorAdapter},
+            Operator,
+        },
         source::Source,
         time::{ArconTime, Time},
     },
@@ -53,6 +56,53 @@ impl<OP: Operator, Backend: arcon_state::Backend> OperatorBuilder<OP, Backend> {
     }
 }
 
+/// CoOperator Builder
+///
+/// Counterpart of [OperatorBuilder] for operators with two inputs.
+///
+/// ```no_run
+/// use arcon::prelude::*;
+/// # struct Join;
+/// # impl CoOperator for Join {
+/// #     type LEFT = u64;
+/// #     type RIGHT = u64;
+/// #     type OUT = u64;
+/// #     type TimerState = ArconNever;
+/// #     type OperatorState = EmptyState;
+/// #     type ElementIterator = std::iter::Once<ArconElement<u64>>;
+/// #     fn handle_left(&mut self, e: ArconElement<u64>, _: &mut OperatorContext<ArconNever, EmptyState>) -> ArconResult<Self::ElementIterator> { Ok(std::iter::once(e)) }
+/// #     fn handle_right(&mut self, e: ArconElement<u64>, _: &mut OperatorContext<ArconNever, EmptyState>) -> ArconResult<Self::ElementIterator> { Ok(std::iter::once(e)) }
+/// #     arcon::ignore_timeout!();
+/// # }
+/// let builder = CoOperatorBuilder {
+///    operator: Arc::new(|| Join),
+///    state: Arc::new(|_backend: Arc<Sled>| EmptyState),
+///    conf: Default::default(),
+/// };
+///```
+#[derive(Clone)]
+pub struct CoOperatorBuilder<OP: CoOperator, Backend = DefaultBackend> {
+    /// CoOperator Constructor
+    pub operator: Arc<dyn Fn() -> OP + Send + Sync + 'static>,
+    /// State Constructor
+    pub state: Arc<dyn Fn(Arc<Backend>) -> OP::OperatorState + Send + Sync + 'static>,
+    /// Operator Config
+    pub conf: OperatorConf,
+}
+
+impl<OP: CoOperator + 'static, B: Backend> From<CoOperatorBuilder<OP, B>>
+    for OperatorBuilder<CoOperatorAdapter<OP>, B>
+{
+    fn from(builder: CoOperatorBuilder<OP, B>) -> Self {
+        let operator = builder.operator;
+        OperatorBuilder {
+            operator: Arc::new(move || CoOperatorAdapter::new(operator())),
+            state: builder.state,
+            conf: builder.conf,
+        }
+    }
+}
+
 type SourceIndex = usize;
 type TotalSources = usize;
 
@@ -130,4 +180,18 @@ pub enum Assigner {
         length: Time,
         late_arrival: Time,
     },
+    /// Windows of a key that are closed after `gap` time without elements
+    ///
+    /// Sessions that come within `gap` of each other are merged.
+    Session {
+        gap: Time,
+        late_arrival: Time,
+    },
+    /// Windows of a key that hold `size` elements and start every `slide` elements
+    Count {
+        size: u64,
+        slide: u64,
+    },
+    /// A single window per key that only emits results when its trigger fires
+    Global,
 }
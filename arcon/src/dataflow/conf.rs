@@ -1,5 +1,10 @@
 use super::builder::Assigner;
-use crate::{data::ArconType, stream::time::ArconTime};
+use crate::{
+    data::ArconType,
+    error::{ArconResult, Error},
+    index::StateSchema,
+    stream::time::ArconTime,
+};
 use std::sync::Arc;
 
 #[cfg(all(feature = "hardware_counters", target_os = "linux"))]
@@ -26,6 +31,9 @@ pub enum ParallelismStrategy {
     /// Use a static number of Arcon nodes
     Static(usize),
     /// Tells the runtime to manage the parallelism
+    ///
+    /// The operator is rescaled at epoch boundaries according
+    /// to the [ScalingConf] of its [OperatorConf].
     Managed,
 }
 
@@ -62,6 +70,12 @@ pub struct OperatorConf {
     pub parallelism_strategy: ParallelismStrategy,
     /// Defines the type of Stream, by default streams are Keyed in Arcon.
     pub stream_kind: StreamKind,
+    /// Scaling configuration used if the parallelism strategy is Managed
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub scaling: ScalingConf,
+    /// Versions of the state value types and how to migrate older ones on restore
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub state_schema: StateSchema,
     #[cfg(all(feature = "hardware_counters", target_os = "linux"))]
     pub perf_events: PerfEvents,
 }
@@ -80,6 +94,73 @@ impl OperatorConf {
     }
 }
 
+/// Scaling configuration for operators using [ParallelismStrategy::Managed]
+///
+/// The key space of a managed operator is split into `max_parallelism` key groups.
+/// Each running node owns a contiguous range of key groups and the ranges are
+/// redistributed whenever the runtime decides to scale the operator.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct ScalingConf {
+    /// Number of nodes the operator starts with
+    pub initial_parallelism: usize,
+    /// Lower bound of nodes
+    pub min_parallelism: usize,
+    /// Upper bound of nodes, also the number of key groups
+    pub max_parallelism: usize,
+    /// Average amount of elements per node and epoch that triggers a scale up
+    pub scale_up_threshold: u64,
+    /// Average amount of elements per node and epoch that triggers a scale down
+    pub scale_down_threshold: u64,
+}
+
+impl ScalingConf {
+    /// Checks that `1 <= min_parallelism <= initial_parallelism <= max_parallelism`
+    pub fn validate(&self) -> ArconResult<()> {
+        if self.min_parallelism == 0
+            || self.min_parallelism > self.initial_parallelism
+            || self.initial_parallelism > self.max_parallelism
+        {
+            return Err(Error::Unsupported {
+                msg: format!(
+                    "ScalingConf requires 1 <= min_parallelism ({}) <= initial_parallelism ({}) <= max_parallelism ({})",
+                    self.min_parallelism, self.initial_parallelism, self.max_parallelism
+                ),
+            });
+        }
+        Ok(())
+    }
+
+    /// Returns the parallelism the operator should run with given
+    /// the current parallelism and the total amount of elements processed during an epoch.
+    ///
+    /// The parallelism stays unchanged as long as the average load per node
+    /// is within the configured thresholds.
+    pub fn target_parallelism(&self, current: usize, epoch_load: u64) -> usize {
+        let avg = epoch_load / current.max(1) as u64;
+        if avg <= self.scale_up_threshold && avg >= self.scale_down_threshold {
+            return current;
+        }
+        let target_load = (self.scale_up_threshold + self.scale_down_threshold) / 2;
+        let target = (epoch_load + target_load - 1) / target_load.max(1);
+        (target as usize).clamp(self.min_parallelism, self.max_parallelism)
+    }
+}
+
+impl Default for ScalingConf {
+    fn default() -> Self {
+        let max_parallelism = num_cpus::get() * 2;
+        Self {
+            initial_parallelism: num_cpus::get(),
+            min_parallelism: 1,
+            max_parallelism,
+            scale_up_threshold: 500_000,
+            scale_down_threshold: 50_000,
+        }
+    }
+}
+
 pub type TimestampExtractor<A> = Arc<dyn Fn(&A) -> u64 + Send + Sync>;
 
 /// Source Configuration
@@ -125,3 +206,58 @@ impl<S: ArconType> Default for SourceConf<S> {
 pub struct WindowConf {
     pub assigner: Assigner,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scaling_target_test() {
+        let conf = ScalingConf {
+            initial_parallelism: 2,
+            min_parallelism: 1,
+            max_parallelism: 8,
+            scale_up_threshold: 1000,
+            scale_down_threshold: 100,
+        };
+        // within thresholds
+        assert_eq!(conf.target_parallelism(2, 1000), 2);
+        // overloaded nodes
+        assert_eq!(conf.target_parallelism(2, 4000), 8);
+        assert_eq!(conf.target_parallelism(2, 2750), 5);
+        // idle nodes
+        assert_eq!(conf.target_parallelism(4, 0), 1);
+        assert_eq!(conf.target_parallelism(4, 300), 1);
+        // bounded by max
+        assert_eq!(conf.target_parallelism(8, 100_000), 8);
+    }
+
+    #[test]
+    fn scaling_validate_test() {
+        let conf = ScalingConf {
+            initial_parallelism: 2,
+            min_parallelism: 1,
+            max_parallelism: 8,
+            ..Default::default()
+        };
+        assert!(conf.validate().is_ok());
+        assert!(ScalingConf {
+            min_parallelism: 0,
+            ..conf.clone()
+        }
+        .validate()
+        .is_err());
+        assert!(ScalingConf {
+            min_parallelism: 4,
+            ..conf.clone()
+        }
+        .validate()
+        .is_err());
+        assert!(ScalingConf {
+            initial_parallelism: 9,
+            ..conf
+        }
+        .validate()
+        .is_err());
+    }
+}
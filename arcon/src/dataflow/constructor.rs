@@ -1,27 +1,41 @@
 use crate::{
-    application::Application,
+    application::{conf::RestoreMode, Application},
     buffer::event::PoolInfo,
-    data::{flight_serde::FlightSerde, ArconMessage, ArconType, NodeID},
+    data::{
+        flight_serde::FlightSerde, partition::key_group_ranges, ArconMessage, ArconType, NodeID,
+        RawArconMessage, StateID,
+    },
     dataflow::{
         builder::{KeyBuilder, OperatorBuilder, SourceBuilderType},
-        conf::SourceConf,
-        dfg::{ChannelKind, GlobalNodeId},
+        conf::{ParallelismStrategy, SourceConf},
+        dfg::{ChannelKind, GlobalNodeId, OperatorId},
     },
+    error::ArconResult,
     manager::{
-        node::{NodeManager, NodeManagerPort},
+        epoch::EpochEvent,
+        node::{ManagedOperator, NodeManager, NodeManagerPort, NodeSpawner},
         source::{SourceManager, SourceManagerPort},
     },
     stream::{
         channel::{
-            strategy::{forward::Forward, keyed::Keyed, *},
+            strategy::{
+                forward::Forward,
+                keyed::{Keyed, KeyedRoute, RoutingTable},
+                *,
+            },
             Channel,
         },
         node::{
             debug::DebugNode,
+            managed::{KeyGroup, ManagedNode, ManagedScope},
             source::{SourceEvent, SourceNode},
+            unaligned::{in_flight_state_id, read_in_flight},
             Node, NodeState,
         },
-        operator::Operator,
+        operator::{
+            side::{ErasedSideChannel, OutputTag, SideChannel, SideOutputs},
+            Operator,
+        },
         source::Source,
         time::ArconTime,
     },
@@ -34,8 +48,10 @@ use kompact::{
     },
 };
 use std::{
-    any::Any,
-    path::{Path, PathBuf},
+    any::{Any, TypeId},
+    cell::{RefCell, RefMut},
+    collections::HashMap,
+    path::Path,
     rc::Rc,
     sync::Arc,
 };
@@ -82,6 +98,18 @@ fn channel_strategy<T: ArconType>(
             }
         }
         ChannelKind::Keyed => {
+            let key_builder = key_builder.expect("Keyed ChannelStrategy must have KeyBuilder");
+            // Operators with a managed parallelism expose a shared route instead of their nodes
+            if components.len() == 1 && paths.is_empty() {
+                if let Ok(route) = components[0].clone().downcast::<KeyedRoute<T>>() {
+                    return ChannelStrategy::Keyed(Keyed::with_route(
+                        route,
+                        node_id,
+                        pool_info,
+                        key_builder,
+                    ));
+                }
+            }
             let mut channels = Vec::new();
             for component in components {
                 let target_node = component
@@ -94,12 +122,7 @@ fn channel_strategy<T: ArconType>(
             for path in paths {
                 channels.push(Channel::Remote(path, FlightSerde::Reliable));
             }
-            ChannelStrategy::Keyed(Keyed::new(
-                channels,
-                node_id,
-                pool_info,
-                key_builder.expect("Keyed ChannelStrategy must have KeyBuilder"),
-            ))
+            ChannelStrategy::Keyed(Keyed::new(channels, node_id, pool_info, key_builder))
         }
         ChannelKind::Console => ChannelStrategy::Console,
         ChannelKind::Mute => ChannelStrategy::Mute,
@@ -108,63 +131,391 @@ fn channel_strategy<T: ArconType>(
     }
 }
 
+/// Configuration of an output of an operator
+#[derive(Clone)]
+pub(crate) struct OutputSpec<T: ArconType> {
+    channel_kind: ChannelKind,
+    key_builder: Option<KeyBuilder<T>>,
+    /// Inputs of a multi-input operator use disjoint ranges of NodeID's
+    sender_offset: u32,
+}
+
+impl<T: ArconType> Default for OutputSpec<T> {
+    fn default() -> Self {
+        Self {
+            channel_kind: ChannelKind::default(),
+            key_builder: None,
+            sender_offset: 0,
+        }
+    }
+}
+
+/// Outputs of an operator paired with the components they send to
+type Outputs<T> = Vec<(OutputSpec<T>, ErasedComponents)>;
+
+fn pair_outputs<T: ArconType>(
+    specs: &RefCell<Vec<OutputSpec<T>>>,
+    mut components: Vec<ErasedComponents>,
+) -> Outputs<T> {
+    let specs = specs.borrow();
+    components.resize(specs.len(), Vec::new());
+    specs.iter().cloned().zip(components).collect()
+}
+
+/// Creates a ChannelStrategy that sends to all outputs of an operator
+fn output_strategy<T: ArconType>(
+    outputs: &[(OutputSpec<T>, ErasedComponents)],
+    paths: &[ActorPath],
+    node_id: NodeID,
+    pool_info: PoolInfo,
+) -> ChannelStrategy<T> {
+    assert!(
+        paths.is_empty() || outputs.len() == 1,
+        "Remote channels are only supported for operators with a single output"
+    );
+    let mut strategies: Vec<ChannelStrategy<T>> = outputs
+        .iter()
+        .map(|(spec, components)| {
+            let mut strategy = channel_strategy(
+                components.clone(),
+                paths.to_vec(),
+                node_id,
+                pool_info.clone(),
+                spec.channel_kind,
+                spec.key_builder.clone(),
+            );
+            strategy.set_sender_offset(spec.sender_offset);
+            strategy
+        })
+        .collect();
+    if strategies.len() == 1 {
+        strategies.remove(0)
+    } else {
+        ChannelStrategy::Fanout(strategies)
+    }
+}
+
+/// Descriptor that the operators of a dataflow are named by
+pub(crate) const OPERATOR_DESCRIPTOR: &str = "Operator";
+
+/// Returns the descriptor of the operator `operator_id`
+pub(crate) fn operator_descriptor(descriptor: &str, operator_id: OperatorId) -> String {
+    format!("{}_{}", descriptor, operator_id)
+}
+
+/// Returns the state id of the node `node_id` of an operator with a static parallelism
+pub(crate) fn node_state_id(operator_descriptor: &str, node_id: u32) -> StateID {
+    format!("{}_{}", operator_descriptor, node_id)
+}
+
+/// Returns the state id of the key group `key_group` of a managed operator
+pub(crate) fn key_group_state_id(operator_descriptor: &str, key_group: u64) -> StateID {
+    format!("{}_kg{}", operator_descriptor, key_group)
+}
+
+/// Creates the backend of `descriptor` under the state directory of the application
+///
+/// In restore mode, live state left behind by a previous run is discarded and the
+/// backend is restored from its snapshot of the restored epoch, if there is one.
+fn restore_backend<B: Backend>(app: &Application, descriptor: &str) -> Arc<B> {
+    let mut live_path = app.arcon_conf().state_dir();
+    live_path.push(descriptor);
+
+    if app.arcon_conf().restore == RestoreMode::Disabled {
+        return Arc::new(B::create(&live_path, descriptor.to_string()).unwrap());
+    }
+    if live_path.exists() {
+        std::fs::remove_dir_all(&live_path).unwrap();
+    }
+    let backend = match app.restore_snapshot(descriptor) {
+        Some(snapshot) => B::restore(
+            &live_path,
+            Path::new(&snapshot.snapshot_path),
+            descriptor.to_string(),
+        ),
+        None => B::create(&live_path, descriptor.to_string()),
+    };
+    Arc::new(backend.unwrap())
+}
+
+/// Loads the in-flight messages that an unaligned checkpoint of the restored epoch recorded for `descriptor`
+fn restore_in_flight<A: ArconType>(app: &Application, descriptor: &str) -> Vec<RawArconMessage<A>> {
+    match app.restore_snapshot(&in_flight_state_id(descriptor)) {
+        Some(snapshot) => read_in_flight(Path::new(&snapshot.snapshot_path))
+            .expect("Failed to restore in-flight messages"),
+        None => Vec::new(),
+    }
+}
+
+fn create_debug_node<T: ArconType>(application: &mut Application) -> ErasedComponent {
+    // Debug streams of the same type, such as combined ones, share a single DebugNode
+    if let Some(debug_node) = application.abstract_debug_nodes.get(&TypeId::of::<T>()) {
+        return debug_node.clone();
+    }
+    let debug_node = DebugNode::<T>::new();
+    let debug_component = application.data_system().create(|| debug_node);
+    application
+        .data_system()
+        .start_notify(&debug_component)
+        .wait_timeout(std::time::Duration::from_millis(5000))
+        .expect("Failed to start Node Component");
+
+    application
+        .debug_nodes
+        .insert(TypeId::of::<T>(), debug_component.clone());
+    let debug_component: Arc<dyn AbstractComponent<Message = ArconMessage<T>>> = debug_component;
+    let erased = Arc::new(debug_component) as ErasedComponent;
+
+    application
+        .abstract_debug_nodes
+        .insert(TypeId::of::<T>(), erased.clone());
+    erased
+}
+
+/// An output of an operator
+#[derive(Clone)]
+enum OutputSlot<T: ArconType> {
+    /// Receives the elements returned by the operator
+    Main(OutputSpec<T>),
+    /// Receives the elements the operator emits to an [OutputTag]
+    Side(Rc<dyn SideOutputFactory>),
+}
+
+/// Creates the channel of a side output for a node
+type SideChannelBuilder = Arc<dyn Fn(NodeID, PoolInfo) -> Box<dyn ErasedSideChannel> + Send + Sync>;
+
+/// Type erased side output of an operator
+pub(crate) trait SideOutputFactory {
+    fn channel_builder(
+        &self,
+        components: ErasedComponents,
+        app: &mut Application,
+    ) -> SideChannelBuilder;
+}
+
+/// A side output identified by an [OutputTag]
+struct SideOutput<T: ArconType> {
+    id: String,
+    spec: RefCell<OutputSpec<T>>,
+}
+
+impl<T: ArconType> SideOutputFactory for SideOutput<T> {
+    fn channel_builder(
+        &self,
+        mut components: ErasedComponents,
+        app: &mut Application,
+    ) -> SideChannelBuilder {
+        let spec = self.spec.borrow().clone();
+        if components.is_empty()
+            && matches!(spec.channel_kind, ChannelKind::Forward)
+            && app.debug_node_enabled()
+        {
+            components.push(create_debug_node::<T>(app));
+        }
+        let id = self.id.clone();
+        let outputs = vec![(spec, components)];
+        Arc::new(move |node_id, pool_info| {
+            let strategy = output_strategy(&outputs, &[], node_id, pool_info);
+            Box::new(SideChannel::new(id.clone(), strategy))
+        })
+    }
+}
+
+/// Main and side outputs of an operator paired with the components they send to
+struct NodeOutputs<T: ArconType> {
+    main: Outputs<T>,
+    side: Vec<SideChannelBuilder>,
+}
+
+impl<T: ArconType> NodeOutputs<T> {
+    fn channel_strategy(
+        &self,
+        paths: &[ActorPath],
+        node_id: NodeID,
+        pool_info: PoolInfo,
+    ) -> ChannelStrategy<T> {
+        output_strategy(&self.main, paths, node_id, pool_info)
+    }
+
+    fn side_outputs(&self, node_id: NodeID, pool_info: PoolInfo) -> SideOutputs {
+        SideOutputs::new(
+            self.side
+                .iter()
+                .map(|builder| builder(node_id, pool_info.clone()))
+                .collect(),
+        )
+    }
+}
+
+/// Configures the side outputs of an operator with a specific [OutputTag]
+///
+/// The records of the side output are sent by the nodes of `parent`.
+pub(crate) struct SideOutputConstructor<P: ArconType, T: ArconType> {
+    id: String,
+    parent: Rc<dyn TypedNodeFactory<P>>,
+    outputs: RefCell<HashMap<usize, Rc<SideOutput<T>>>>,
+}
+
+impl<P: ArconType, T: ArconType> SideOutputConstructor<P, T> {
+    pub fn new(tag: &OutputTag<T>, parent: Rc<dyn TypedNodeFactory<P>>) -> Self {
+        Self {
+            id: tag.id().to_string(),
+            parent,
+            outputs: RefCell::new(HashMap::new()),
+        }
+    }
+
+    /// Adds an output to the parent operator and returns its index
+    pub fn register(&self, spec: OutputSpec<T>) -> usize {
+        let side = Rc::new(SideOutput {
+            id: self.id.clone(),
+            spec: RefCell::new(spec),
+        });
+        let output = self.parent.add_side_output(side.clone());
+        self.outputs.borrow_mut().insert(output, side);
+        output
+    }
+
+    fn update(&self, output: usize, f: impl FnOnce(&mut OutputSpec<T>)) {
+        f(&mut self.outputs.borrow()[&output].spec.borrow_mut());
+    }
+}
+
 pub(crate) trait TypedNodeFactory<T: ArconType>: NodeFactory {
-    fn set_key_builder(&mut self, key_builder: KeyBuilder<T>);
-    fn set_channel_kind(&mut self, channel_kind: ChannelKind);
+    /// Adds an output that is configured like `from` and returns its index
+    fn add_output(&self, from: usize) -> usize;
+    /// Adds a side output to the operator and returns its index
+    fn add_side_output(&self, side: Rc<dyn SideOutputFactory>) -> usize;
+    fn set_key_builder(&self, output: usize, key_builder: KeyBuilder<T>);
+    fn set_channel_kind(&self, output: usize, channel_kind: ChannelKind);
+    fn set_sender_offset(&self, output: usize, offset: u32);
     fn untype(self: Rc<Self>) -> Rc<dyn NodeFactory>;
 }
 
 impl<OP: Operator<OUT = T>, B: Backend, T: ArconType> TypedNodeFactory<T>
     for NodeConstructor<OP, B>
 {
-    fn set_key_builder(&mut self, key_builder: KeyBuilder<T>) {
-        self.out_key_builder = Some(key_builder);
-        self.channel_kind = ChannelKind::Keyed;
+    fn add_output(&self, from: usize) -> usize {
+        let spec = self.main_output(from).clone();
+        let mut outputs = self.outputs.borrow_mut();
+        outputs.push(OutputSlot::Main(spec));
+        outputs.len() - 1
+    }
+    fn add_side_output(&self, side: Rc<dyn SideOutputFactory>) -> usize {
+        let mut outputs = self.outputs.borrow_mut();
+        outputs.push(OutputSlot::Side(side));
+        outputs.len() - 1
     }
-    fn set_channel_kind(&mut self, channel_kind: ChannelKind) {
-        self.channel_kind = channel_kind;
+    fn set_key_builder(&self, output: usize, key_builder: KeyBuilder<T>) {
+        let mut spec = self.main_output(output);
+        spec.key_builder = Some(key_builder);
+        spec.channel_kind = ChannelKind::Keyed;
+    }
+    fn set_channel_kind(&self, output: usize, channel_kind: ChannelKind) {
+        self.main_output(output).channel_kind = channel_kind;
+    }
+    fn set_sender_offset(&self, output: usize, offset: u32) {
+        self.main_output(output).sender_offset = offset;
     }
     fn untype(self: Rc<Self>) -> Rc<dyn NodeFactory> {
         self
     }
 }
 
+impl<P: ArconType, T: ArconType> TypedNodeFactory<T> for SideOutputConstructor<P, T> {
+    fn add_output(&self, from: usize) -> usize {
+        let spec = self.outputs.borrow()[&from].spec.borrow().clone();
+        self.register(spec)
+    }
+    fn add_side_output(&self, side: Rc<dyn SideOutputFactory>) -> usize {
+        self.parent.add_side_output(side)
+    }
+    fn set_key_builder(&self, output: usize, key_builder: KeyBuilder<T>) {
+        self.update(output, |spec| {
+            spec.key_builder = Some(key_builder);
+            spec.channel_kind = ChannelKind::Keyed;
+        });
+    }
+    fn set_channel_kind(&self, output: usize, channel_kind: ChannelKind) {
+        self.update(output, |spec| spec.channel_kind = channel_kind);
+    }
+    fn set_sender_offset(&self, output: usize, offset: u32) {
+        self.update(output, |spec| spec.sender_offset = offset);
+    }
+    fn untype(self: Rc<Self>) -> Rc<dyn NodeFactory> {
+        self.parent.clone().untype()
+    }
+}
+
+impl<P: ArconType, T: ArconType> NodeFactory for SideOutputConstructor<P, T> {
+    fn build_nodes(
+        &self,
+        node_ids: Vec<GlobalNodeId>,
+        in_channels: Vec<NodeID>,
+        outputs: Vec<ErasedComponents>,
+        paths: Vec<ActorPath>,
+        application: &mut Application,
+    ) -> ArconResult<Vec<(GlobalNodeId, ErasedComponent)>> {
+        // Side outputs are sent by the nodes of the operator itself
+        self.parent
+            .build_nodes(node_ids, in_channels, outputs, paths, application)
+    }
+}
+
 pub(crate) trait TypedSourceFactory<T: ArconType>: SourceFactory {
-    fn set_key_builder(&mut self, key_builder: KeyBuilder<T>);
-    fn set_channel_kind(&mut self, channel_kind: ChannelKind);
+    /// Adds an output that is configured like `from` and returns its index
+    fn add_output(&self, from: usize) -> usize;
+    fn set_key_builder(&self, output: usize, key_builder: KeyBuilder<T>);
+    fn set_channel_kind(&self, output: usize, channel_kind: ChannelKind);
+    fn set_sender_offset(&self, output: usize, offset: u32);
     fn untype(self: Rc<Self>) -> Rc<dyn SourceFactory>;
 }
 
 impl<S: Source<Item = T>, B: Backend, T: ArconType> TypedSourceFactory<T>
     for SourceConstructor<S, B>
 {
-    fn set_key_builder(&mut self, key_builder: KeyBuilder<T>) {
-        self.key_builder = Some(key_builder);
-        self.channel_kind = ChannelKind::Keyed;
+    fn add_output(&self, from: usize) -> usize {
+        add_output(&self.outputs, from)
+    }
+    fn set_key_builder(&self, output: usize, key_builder: KeyBuilder<T>) {
+        let spec = &mut self.outputs.borrow_mut()[output];
+        spec.key_builder = Some(key_builder);
+        spec.channel_kind = ChannelKind::Keyed;
     }
-    fn set_channel_kind(&mut self, channel_kind: ChannelKind) {
-        self.channel_kind = channel_kind;
+    fn set_channel_kind(&self, output: usize, channel_kind: ChannelKind) {
+        self.outputs.borrow_mut()[output].channel_kind = channel_kind;
+    }
+    fn set_sender_offset(&self, output: usize, offset: u32) {
+        self.outputs.borrow_mut()[output].sender_offset = offset;
     }
     fn untype(self: Rc<Self>) -> Rc<dyn SourceFactory> {
         self
     }
 }
 
+fn add_output<T: ArconType>(outputs: &RefCell<Vec<OutputSpec<T>>>, from: usize) -> usize {
+    let mut outputs = outputs.borrow_mut();
+    let spec = outputs[from].clone();
+    outputs.push(spec);
+    outputs.len() - 1
+}
+
 pub trait NodeFactory {
     fn build_nodes(
         &self,
         node_ids: Vec<GlobalNodeId>,
         in_channels: Vec<NodeID>,
-        components: ErasedComponents,
+        outputs: Vec<ErasedComponents>,
         paths: Vec<ActorPath>,
         application: &mut Application,
-    ) -> Vec<(GlobalNodeId, ErasedComponent)>;
+    ) -> ArconResult<Vec<(GlobalNodeId, ErasedComponent)>>;
 }
 
 pub trait SourceFactory {
     fn build_source(
         &self,
-        components: ErasedComponents,
+        operator_id: OperatorId,
+        outputs: Vec<ErasedComponents>,
         paths: Vec<ActorPath>,
         application: &mut Application,
     ) -> ErasedSourceManager;
@@ -175,31 +526,41 @@ impl<OP: Operator + 'static, B: Backend> NodeFactory for NodeConstructor<OP, B>
         &self,
         node_ids: Vec<GlobalNodeId>,
         in_channels: Vec<NodeID>,
-        mut components: ErasedComponents,
+        outputs: Vec<ErasedComponents>,
         paths: Vec<ActorPath>,
         app: &mut Application,
-    ) -> Vec<(GlobalNodeId, ErasedComponent)> {
+    ) -> ArconResult<Vec<(GlobalNodeId, ErasedComponent)>> {
         // Initialize state and manager
         let state_dir = app.arcon_conf().state_dir();
         self.init_state_dir(&state_dir);
 
-        let node_manager = self.create_node_manager(app, &in_channels);
+        let operator_id = node_ids
+            .first()
+            .map(|id| id.operator_id)
+            .unwrap_or_default();
+        let descriptor = self.operator_descriptor(operator_id);
+        let node_manager = self.create_node_manager(app, &descriptor, &in_channels);
+
+        let outputs = self.prepare_outputs(outputs, &paths, app);
 
-        if components.is_empty() && paths.is_empty() && app.debug_node_enabled() {
-            components.push(self.create_debug_node(app));
+        if let ParallelismStrategy::Managed = self.builder.conf.parallelism_strategy {
+            return self.build_managed_nodes(
+                operator_id,
+                in_channels,
+                outputs,
+                paths,
+                node_manager,
+                app,
+            );
         }
         for node_id in node_ids {
             // Create the Nodes arguments
-            let node_descriptor = format!("{}_{}", self.descriptor, node_id.node_id.id);
-            let backend = self.create_backend(node_descriptor.clone(), state_dir.clone());
-            let channel_strategy = channel_strategy(
-                components.clone(),
-                paths.clone(),
-                node_id.node_id,
-                app.get_pool_info().clone(),
-                self.channel_kind,
-                self.out_key_builder.clone(),
-            );
+            let node_descriptor = node_state_id(&descriptor, node_id.node_id.id);
+            let backend = restore_backend::<B>(app, &node_descriptor);
+            self.builder.conf.state_schema.apply(backend.as_ref())?;
+            let in_flight = restore_in_flight(app, &node_descriptor);
+            let channel_strategy =
+                outputs.channel_strategy(&paths, node_id.node_id, app.get_pool_info());
 
             // Build the Node
             let node = Node::new(
@@ -215,7 +576,11 @@ impl<OP: Operator + 'static, B: Backend> NodeFactory for NodeConstructor<OP, B>
                 self.builder.conf.perf_events.clone(),
                 node_id,
                 self.in_key_builder.clone(),
-            );
+            )
+            .with_epoch(app.start_epoch())
+            .with_in_flight(in_flight)
+            .with_checkpoint_worker(app.checkpoint_pool().assign())
+            .with_side_outputs(outputs.side_outputs(node_id.node_id, app.get_pool_info()));
             // Create the node and connect it to the NodeManager
             self.create_node_component(app, node, &node_manager);
         }
@@ -227,11 +592,183 @@ impl<OP: Operator + 'static, B: Backend> NodeFactory for NodeConstructor<OP, B>
 
         // Fetch all created Nodes on this NodeManager and return them as Erased
         // for the next stage..
-        node_manager.on_definition(|cd| {
+        Ok(node_manager.on_definition(|cd| {
             cd.nodes
                 .iter()
                 .map(|(id, (comp, _))| (*id, Arc::new(comp.clone()) as ErasedComponent))
                 .collect()
+        }))
+    }
+}
+
+impl<OP: Operator + 'static, B: Backend> NodeConstructor<OP, B> {
+    /// Build the nodes of an operator with a Managed parallelism strategy
+    ///
+    /// The key space is split into `max_parallelism` key groups that are distributed
+    /// over `initial_parallelism` nodes. Instead of the nodes, a [KeyedRoute] is
+    /// returned which upstream nodes use to route elements onto the key groups.
+    fn build_managed_nodes(
+        &self,
+        operator_id: usize,
+        in_channels: Vec<NodeID>,
+        outputs: NodeOutputs<OP::OUT>,
+        paths: Vec<ActorPath>,
+        node_manager: Arc<Component<NodeManager<OP, B>>>,
+        app: &mut Application,
+    ) -> ArconResult<Vec<(GlobalNodeId, ErasedComponent)>> {
+        let scaling = self.builder.conf.scaling.clone();
+        scaling.validate()?;
+        let total_groups = scaling.max_parallelism as u64;
+        assert!(
+            scaling.max_parallelism < app.get_pool_info().capacity,
+            "max_parallelism must be lower than the pool capacity"
+        );
+        let epoch_manager = app.epoch_manager();
+
+        let mut key_groups = Vec::with_capacity(scaling.max_parallelism);
+        for id in 0..total_groups {
+            let descriptor = key_group_state_id(&self.operator_descriptor(operator_id), id);
+            let backend = restore_backend::<B>(app, &descriptor);
+            self.builder.conf.state_schema.apply(backend.as_ref())?;
+            let state = self.builder.state.clone()(backend.clone());
+            epoch_manager.tell(EpochEvent::Register(descriptor.clone()));
+            key_groups.push(KeyGroup::new(id, descriptor, backend, state));
+        }
+
+        let scope = Arc::new(ManagedScope::new());
+        let route = Arc::new(KeyedRoute::new());
+        let spawner = self.node_spawner(operator_id, in_channels, outputs, paths, app);
+
+        let ranges = key_group_ranges(total_groups, scaling.initial_parallelism as u64);
+        let mut channels = Vec::with_capacity(ranges.len());
+        let mut table = vec![0; scaling.max_parallelism];
+        let mut key_groups = key_groups.into_iter();
+
+        for (index, range) in ranges.into_iter().enumerate() {
+            let groups: Vec<_> = key_groups.by_ref().take(range.iter().count()).collect();
+            for group in groups.iter() {
+                table[group.id as usize] = index;
+            }
+            let node_id = NodeID::new(range.start as u32);
+            let managed = ManagedNode::new(range, total_groups, groups, scope.clone());
+            let node = spawner(node_id, managed).with_epoch(app.start_epoch());
+            let node_comp = self.create_node_component(app, node, &node_manager);
+            let actor_ref = node_comp.actor_ref().hold().expect("failed to fetch");
+            channels.push(Channel::Local(actor_ref));
+        }
+        route.schedule(
+            app.start_epoch(),
+            RoutingTable {
+                channels,
+                key_groups: table,
+            },
+        );
+
+        let managed = ManagedOperator::new(
+            scaling.clone(),
+            operator_id,
+            total_groups,
+            scope,
+            route.clone(),
+            spawner,
+            epoch_manager,
+        );
+        node_manager.on_definition(|cd| cd.set_managed(scaling.initial_parallelism, managed));
+
+        app.ctrl_system()
+            .start_notify(&node_manager)
+            .wait_timeout(std::time::Duration::from_millis(2000))
+            .expect("Failed to start NodeManager");
+
+        let id = GlobalNodeId {
+            operator_id,
+            node_id: NodeID::new(0),
+        };
+        Ok(vec![(id, route as ErasedComponent)])
+    }
+
+    /// Separates the main outputs of the operator from its side outputs
+    fn prepare_outputs(
+        &self,
+        mut components: Vec<ErasedComponents>,
+        paths: &[ActorPath],
+        app: &mut Application,
+    ) -> NodeOutputs<OP::OUT> {
+        let slots = self.outputs.borrow();
+        components.resize(slots.len(), Vec::new());
+        let mut outputs = NodeOutputs {
+            main: Vec::new(),
+            side: Vec::new(),
+        };
+        for (slot, mut components) in slots.iter().zip(components) {
+            match slot {
+                OutputSlot::Main(spec) => {
+                    if components.is_empty()
+                        && paths.is_empty()
+                        && matches!(spec.channel_kind, ChannelKind::Forward)
+                        && app.debug_node_enabled()
+                    {
+                        components.push(create_debug_node::<OP::OUT>(app));
+                    }
+                    outputs.main.push((spec.clone(), components));
+                }
+                OutputSlot::Side(side) => outputs.side.push(side.channel_builder(components, app)),
+            }
+        }
+        outputs
+    }
+
+    /// Returns a function that creates nodes of a managed operator
+    fn node_spawner(
+        &self,
+        operator_id: usize,
+        in_channels: Vec<NodeID>,
+        outputs: NodeOutputs<OP::OUT>,
+        paths: Vec<ActorPath>,
+        app: &Application,
+    ) -> NodeSpawner<OP, B> {
+        let descriptor = self.operator_descriptor(operator_id);
+        let builder = self.builder.clone();
+        let in_key_builder = self.in_key_builder.clone();
+        let state_dir = app.arcon_conf().state_dir();
+        let pool_info = app.get_pool_info();
+        let logger = app.arcon_logger.clone();
+        let epoch_manager = app.epoch_manager();
+        let checkpoint_pool = app.checkpoint_pool().clone();
+        let restore = app.arcon_conf().restore != RestoreMode::Disabled;
+
+        Box::new(move |node_id, managed| {
+            // Node-local state such as buffered messages is never handed over,
+            // so each generation uses a backend of its own.
+            let node_descriptor = format!("{}_{}_g{}", descriptor, node_id.id, managed.generation);
+            let mut node_dir = state_dir.clone();
+            node_dir.push(&node_descriptor);
+            // Live state left behind by a previous run is not part of any checkpoint
+            if restore && node_dir.exists() {
+                std::fs::remove_dir_all(&node_dir).unwrap();
+            }
+            let backend = builder.create_backend(node_dir, node_descriptor.clone());
+            let channel_strategy = outputs.channel_strategy(&paths, node_id, pool_info.clone());
+            Node::new(
+                node_descriptor,
+                channel_strategy,
+                builder.operator.clone()(),
+                builder.state.clone()(backend.clone()),
+                NodeState::new(node_id, in_channels.clone(), backend.clone()),
+                backend,
+                logger.clone(),
+                epoch_manager.clone(),
+                #[cfg(all(feature = "hardware_counters", target_os = "linux", not(test)))]
+                builder.conf.perf_events.clone(),
+                GlobalNodeId {
+                    operator_id,
+                    node_id,
+                },
+                in_key_builder.clone(),
+            )
+            .with_side_outputs(outputs.side_outputs(node_id, pool_info.clone()))
+            .with_checkpoint_worker(checkpoint_pool.assign())
+            .with_managed(managed)
         })
     }
 }
@@ -239,10 +776,9 @@ impl<OP: Operator + 'static, B: Backend> NodeFactory for NodeConstructor<OP, B>
 #[derive(Clone)]
 pub(crate) struct NodeConstructor<OP: Operator + 'static, B: Backend> {
     descriptor: String,
-    channel_kind: ChannelKind,
     builder: Arc<OperatorBuilder<OP, B>>,
     in_key_builder: Option<KeyBuilder<OP::IN>>,
-    out_key_builder: Option<KeyBuilder<OP::OUT>>,
+    outputs: RefCell<Vec<OutputSlot<OP::OUT>>>,
 }
 
 impl<OP: Operator + 'static, B: Backend> NodeConstructor<OP, B> {
@@ -253,19 +789,32 @@ impl<OP: Operator + 'static, B: Backend> NodeConstructor<OP, B> {
     ) -> NodeConstructor<OP, B> {
         NodeConstructor {
             descriptor,
-            channel_kind: ChannelKind::default(),
             builder,
             in_key_builder,
-            out_key_builder: None,
+            outputs: RefCell::new(vec![OutputSlot::Main(OutputSpec::default())]),
         }
     }
 
+    fn main_output(&self, output: usize) -> RefMut<'_, OutputSpec<OP::OUT>> {
+        RefMut::map(self.outputs.borrow_mut(), |outputs| {
+            match &mut outputs[output] {
+                OutputSlot::Main(spec) => spec,
+                OutputSlot::Side(_) => panic!("Output {} is a side output", output),
+            }
+        })
+    }
+
+    /// Returns the descriptor of the operator with the given id
+    fn operator_descriptor(&self, operator_id: OperatorId) -> String {
+        operator_descriptor(&self.descriptor, operator_id)
+    }
+
     fn create_node_component(
         &self,
         application: &mut Application,
         node: Node<OP, B>,
         node_manager: &Arc<Component<NodeManager<OP, B>>>,
-    ) {
+    ) -> Arc<dyn AbstractComponent<Message = ArconMessage<OP::IN>>> {
         let node_id = node.node_id;
         let node_comp = application.data_system().create(|| node);
         let required_ref: RequiredRef<NodeManagerPort> = node_comp.required_ref();
@@ -284,41 +833,19 @@ impl<OP: Operator + 'static, B: Backend> NodeConstructor<OP, B> {
             // Insert the created Node into the NodeManager
             cd.nodes.insert(node_id, (node_comp.clone(), required_ref));
         });
-    }
-
-    fn create_debug_node(&self, application: &mut Application) -> ErasedComponent {
-        let debug_node = DebugNode::<OP::OUT>::new();
-        let debug_component = application.data_system().create(|| debug_node);
-        application
-            .data_system()
-            .start_notify(&debug_component)
-            .wait_timeout(std::time::Duration::from_millis(5000))
-            .expect("Failed to start Node Component");
-
-        application.debug_node = Some(debug_component.clone());
-        let debug_component: Arc<dyn AbstractComponent<Message = ArconMessage<OP::OUT>>> =
-            debug_component;
-        let erased = Arc::new(debug_component) as ErasedComponent;
-
-        application.abstract_debug_node = Some(erased.clone());
-        erased
-    }
-
-    fn create_backend(&self, node_descriptor: String, mut state_dir: PathBuf) -> Arc<B> {
-        state_dir.push(&node_descriptor);
-        self.builder
-            .create_backend(state_dir, node_descriptor.clone())
+        node_comp
     }
 
     /// Initializes things like state directory and NodeManager
     fn create_node_manager(
         &self,
         application: &mut Application,
+        descriptor: &str,
         in_channels: &[NodeID],
     ) -> Arc<Component<NodeManager<OP, B>>> {
         // Define the NodeManager
         let manager = NodeManager::<OP, B>::new(
-            self.descriptor.clone(),
+            descriptor.to_string(),
             application.data_system().clone(),
             in_channels.to_vec(),
             application.arcon_logger.clone(),
@@ -347,8 +874,7 @@ pub(crate) struct SourceConstructor<S: Source + 'static, B: Backend> {
     descriptor: String,
     builder_type: SourceBuilderType<S, B>,
     time: ArconTime,
-    channel_kind: ChannelKind,
-    key_builder: Option<KeyBuilder<S::Item>>,
+    outputs: RefCell<Vec<OutputSpec<S::Item>>>,
 }
 
 impl<S: Source + 'static, B: Backend> SourceConstructor<S, B> {
@@ -361,8 +887,7 @@ impl<S: Source + 'static, B: Backend> SourceConstructor<S, B> {
             descriptor,
             builder_type,
             time,
-            channel_kind: ChannelKind::default(),
-            key_builder: None,
+            outputs: RefCell::new(vec![OutputSpec::default()]),
         }
     }
 
@@ -370,18 +895,27 @@ impl<S: Source + 'static, B: Backend> SourceConstructor<S, B> {
     fn create_source_manager(
         &self,
         app: &mut Application,
+        descriptor: String,
         backend: Arc<B>,
     ) -> Arc<Component<SourceManager<B>>> {
         let watermark_interval = app.arcon_conf().watermark_interval;
         let manager = SourceManager::new(
-            self.descriptor.clone(),
+            descriptor,
             self.time,
             watermark_interval,
             app.epoch_manager(),
             backend,
             app.arcon_logger.clone(),
         );
-        app.ctrl_system().create(|| manager)
+        let manager_comp = app.ctrl_system().create(|| manager);
+
+        // Connect SourceManager to the SnapshotManager of the application
+        app.snapshot_manager().on_definition(|scd| {
+            manager_comp.on_definition(|cd| {
+                biconnect_ports(&mut scd.manager_port, &mut cd.snapshot_manager_port);
+            });
+        });
+        manager_comp
     }
 
     fn start_source_manager(
@@ -394,7 +928,7 @@ impl<S: Source + 'static, B: Backend> SourceConstructor<S, B> {
         // Set source reference at the EpochManager
         if let Some(epoch_manager) = &application.epoch_manager {
             epoch_manager.on_definition(|cd| {
-                cd.source_manager = Some(source_ref);
+                cd.source_managers.push(source_ref);
             });
         }
 
@@ -409,15 +943,16 @@ impl<S: Source + 'static, B: Backend> SourceConstructor<S, B> {
 impl<S: Source + 'static, B: Backend> SourceFactory for SourceConstructor<S, B> {
     fn build_source(
         &self,
-        components: ErasedComponents,
+        operator_id: OperatorId,
+        outputs: Vec<ErasedComponents>,
         paths: Vec<ActorPath>,
         app: &mut Application,
     ) -> ErasedSourceManager {
-        let mut state_dir = app.arcon_conf().state_dir();
-        state_dir.push("source_manager");
-        let backend = Arc::new(B::create(&state_dir, String::from("source_manager")).unwrap());
+        let outputs = pair_outputs(&self.outputs, outputs);
+        let descriptor = format!("{}_{}", self.descriptor, operator_id);
+        let backend = restore_backend::<B>(app, &descriptor);
 
-        let source_manager = self.create_source_manager(app, backend.clone());
+        let source_manager = self.create_source_manager(app, descriptor.clone(), backend.clone());
 
         match &self.builder_type {
             SourceBuilderType::Single(builder) => {
@@ -425,13 +960,11 @@ impl<S: Source + 'static, B: Backend> SourceFactory for SourceConstructor<S, B>
                 let source_conf = builder.conf.clone();
                 let source_index = 0;
                 let source = source_cons(backend);
-                let channel_strategy = channel_strategy(
-                    components.clone(),
-                    paths,
+                let channel_strategy = output_strategy(
+                    &outputs,
+                    &paths,
                     NodeID::new(source_index as u32),
                     app.get_pool_info(),
-                    self.channel_kind,
-                    self.key_builder.clone(),
                 );
                 create_source_node(
                     app,
@@ -439,6 +972,7 @@ impl<S: Source + 'static, B: Backend> SourceFactory for SourceConstructor<S, B>
                     source_index,
                     source,
                     source_conf,
+                    &descriptor,
                     &source_manager,
                 );
             }
@@ -448,13 +982,11 @@ impl<S: Source + 'static, B: Backend> SourceFactory for SourceConstructor<S, B>
                 for source_index in 0..builder.parallelism {
                     let source_conf = builder.conf.clone();
                     let source = source_cons(backend.clone(), source_index, parallelism);
-                    let channel_strategy = channel_strategy(
-                        components.clone(),
-                        paths.clone(),
+                    let channel_strategy = output_strategy(
+                        &outputs,
+                        &paths,
                         NodeID::new(source_index as u32),
                         app.get_pool_info(),
-                        self.channel_kind,
-                        self.key_builder.clone(),
                     );
                     create_source_node(
                         app,
@@ -462,6 +994,7 @@ impl<S: Source + 'static, B: Backend> SourceFactory for SourceConstructor<S, B>
                         source_index,
                         source,
                         source_conf,
+                        &descriptor,
                         &source_manager,
                     );
                 }
@@ -479,16 +1012,23 @@ fn create_source_node<S, B>(
     source_index: usize,
     source: S,
     source_conf: SourceConf<S::Item>,
+    descriptor: &str,
     source_manager_comp: &Arc<Component<SourceManager<B>>>,
 ) where
     S: Source,
     B: Backend,
 {
+    // Each source keeps its offset in a backend of its own
+    let state_id = format!("{}_{}", descriptor, source_index);
+    let backend = restore_backend::<B>(app, &state_id);
     let source_node = SourceNode::new(
         source_index,
         source,
         source_conf,
         channel_strategy,
+        state_id,
+        backend,
+        app.epoch_manager(),
         app.arcon_logger.clone(),
     );
     let source_node_comp = app.data_system().create(|| source_node);
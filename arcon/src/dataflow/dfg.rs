@@ -53,6 +53,36 @@ impl DFG {
     pub fn get_mut(&mut self, id: &OperatorId) -> &mut DFGNode {
         self.graph.get_mut(*id).unwrap()
     }
+
+    /// Returns the [`OperatorId`] the next inserted [`DFGNode`] will receive.
+    pub fn next_id(&self) -> OperatorId {
+        self.graph.len()
+    }
+
+    /// Moves all nodes of `other` into this graph.
+    ///
+    /// The nodes of `other` receive new identifiers which are offset by the
+    /// number of nodes in this graph. The offset is returned.
+    pub fn merge(&mut self, other: DFG) -> OperatorId {
+        let offset = self.graph.len();
+        for mut node in other.graph {
+            node.operator_id += offset;
+            for edge in node.outputs.iter_mut().flatten() {
+                edge.successor += offset;
+            }
+            self.graph.push(node);
+        }
+        offset
+    }
+}
+
+/// An outgoing edge of a [DFGNode]
+#[derive(Clone)]
+pub struct DFGEdge {
+    /// The operator receiving the output
+    successor: OperatorId,
+    /// NodeID's of the receiving operator
+    channels: Vec<NodeID>,
 }
 
 /// A logical node in the dataflow graph.
@@ -62,7 +92,8 @@ pub struct DFGNode {
     pub kind: DFGNodeKind,
     operator_id: OperatorId,
     paralellism: usize,
-    outgoing_channels: Vec<NodeID>,
+    /// Outgoing edges of the node, indexed by output.
+    outputs: Vec<Option<DFGEdge>>,
     /// Ingoing edges to a node.
     ingoing: Vec<NodeID>,
     channel_kind: ChannelKind,
@@ -79,7 +110,7 @@ impl DFGNode {
             kind,
             operator_id,
             paralellism,
-            outgoing_channels: Vec::new(),
+            outputs: Vec::new(),
             ingoing,
             channel_kind: Default::default(),
         }
@@ -97,9 +128,36 @@ impl DFGNode {
             .collect()
     }
 
-    /// Sets the number of outgoing channels from this node
-    pub fn set_outgoing_channels(&mut self, outgoing_channels: Vec<NodeID>) {
-        self.outgoing_channels = outgoing_channels;
+    /// Connects `output` of this node to the operator `successor`
+    pub fn set_output(&mut self, output: usize, successor: &DFGNode) {
+        if self.outputs.len() <= output {
+            self.outputs.resize(output + 1, None);
+        }
+        self.outputs[output] = Some(DFGEdge {
+            successor: successor.get_operator_id(),
+            channels: successor.get_node_ids(),
+        });
+    }
+
+    /// Returns the number of outputs of this node
+    pub fn num_outputs(&self) -> usize {
+        self.outputs.len()
+    }
+
+    /// Returns the operator that receives `output` of this node
+    pub fn get_successor(&self, output: usize) -> Option<OperatorId> {
+        self.outputs
+            .get(output)
+            .and_then(|edge| edge.as_ref().map(|e| e.successor))
+    }
+
+    /// Returns the NodeID's that `output` of this node sends messages to
+    #[allow(dead_code)]
+    pub fn get_outgoing_channels(&self, output: usize) -> Vec<NodeID> {
+        self.outputs
+            .get(output)
+            .and_then(|edge| edge.as_ref().map(|e| e.channels.clone()))
+            .unwrap_or_default()
     }
 
     /// Returns the ChannelKind
@@ -90,6 +90,7 @@ pub struct Sink<A: ArconType> {
 impl<A: ArconType> ToSinkExt<A> for Stream<A> {
     fn print(mut self) -> Sink<A> {
         self.set_channel_kind(ChannelKind::Console);
+        self.move_last_node();
         Sink {
             stream: self,
             debug: false,
@@ -97,6 +98,8 @@ impl<A: ArconType> ToSinkExt<A> for Stream<A> {
     }
     fn debug(mut self) -> Sink<A> {
         self.set_channel_kind(ChannelKind::Forward);
+        self.move_last_node();
+        self.ctx.debug.set(true);
         Sink {
             stream: self,
             debug: true,
@@ -104,6 +107,7 @@ impl<A: ArconType> ToSinkExt<A> for Stream<A> {
     }
     fn ignore(mut self) -> Sink<A> {
         self.set_channel_kind(ChannelKind::Mute);
+        self.move_last_node();
         Sink {
             stream: self,
             debug: false,
@@ -119,6 +123,7 @@ impl<A: ArconType> ToSinkExt<A> for Stream<A> {
             },
         });
         stream.set_channel_kind(ChannelKind::Mute);
+        stream.move_last_node();
         Sink {
             stream,
             debug: false,
@@ -138,8 +143,7 @@ pub trait ToBuilderExt: private::Sealed {
 }
 
 impl<T: ArconType> ToBuilderExt for Sink<T> {
-    fn builder(mut self) -> ApplicationBuilder {
-        self.stream.move_last_node();
+    fn builder(self) -> ApplicationBuilder {
         ApplicationBuilder::new(self.stream.ctx, self.debug)
     }
 }
@@ -46,11 +46,11 @@ where
     let time = builder_type.time();
     let manager_constructor =
         SourceConstructor::new(String::from("source_manager"), builder_type, time);
-    let mut ctx = Context::default();
+    let ctx = Context::default();
     let typed_source_factory: Rc<dyn TypedSourceFactory<S::Item>> = Rc::new(manager_constructor);
     let operator_id = 0; // source is the first operator
     let dfg_node = DFGNode::new(DFGNodeKind::Placeholder, operator_id, parallelism, vec![]);
-    ctx.dfg.insert(dfg_node);
+    ctx.dfg.borrow_mut().insert(dfg_node);
     Stream::new(ctx, typed_source_factory)
 }
 
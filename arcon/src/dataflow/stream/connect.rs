@@ -0,0 +1,69 @@
+use crate::{
+    data::{ArconType, CoElement, CoEvent},
+    dataflow::{
+        builder::CoOperatorBuilder,
+        stream::{KeyBuilder, KeyedStream, MapExt, OperatorExt, Stream},
+    },
+    stream::operator::co::CoOperator,
+};
+use std::sync::Arc;
+
+/// Represents two streams that feed a [CoOperator]
+pub struct ConnectedStream<L: ArconType, R: ArconType> {
+    stream: Stream<CoElement<L, R>>,
+}
+
+impl<L: ArconType, R: ArconType> ConnectedStream<L, R> {
+    /// Add a [CoOperator] that processes the records of both streams
+    pub fn operator<OP: CoOperator<LEFT = L, RIGHT = R> + 'static>(
+        self,
+        builder: CoOperatorBuilder<OP>,
+    ) -> Stream<OP::OUT> {
+        self.stream.operator(builder.into())
+    }
+}
+
+impl<T: ArconType> Stream<T> {
+    /// Connect this stream with `other` in order to process both using a [CoOperator]
+    ///
+    /// Records of this stream are passed to [CoOperator::handle_left] and
+    /// records of `other` to [CoOperator::handle_right].
+    pub fn connect<R: ArconType>(self, other: Stream<R>) -> ConnectedStream<T, R> {
+        let left_key = self.key_builder.clone();
+        let right_key = other.key_builder.clone();
+        assert_eq!(
+            left_key.is_some(),
+            right_key.is_some(),
+            "Either both or none of the connected streams must be keyed"
+        );
+
+        let left = self.map(CoElement::left);
+        let right = other.map(CoElement::right);
+        let mut stream = left.union(right);
+
+        if let (Some(left_key), Some(right_key)) = (left_key, right_key) {
+            stream.set_key_builder(KeyBuilder {
+                extractor: Arc::new(
+                    move |element: &CoElement<T, R>| match element.unwrap_ref() {
+                        CoEvent::Left(data) => left_key.get_key(data),
+                        CoEvent::Right(data) => right_key.get_key(data),
+                    },
+                ),
+            });
+        }
+
+        ConnectedStream { stream }
+    }
+}
+
+impl<T: ArconType> KeyedStream<T> {
+    /// Merge `other` into this stream, see [Stream::union]
+    pub fn union(self, other: KeyedStream<T>) -> KeyedStream<T> {
+        KeyedStream::from(self.stream.union(other.stream))
+    }
+
+    /// Connect this stream with `other` while keeping both keyed, see [Stream::connect]
+    pub fn connect<R: ArconType>(self, other: KeyedStream<R>) -> ConnectedStream<T, R> {
+        self.stream.connect(other.stream)
+    }
+}
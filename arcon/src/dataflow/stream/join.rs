@@ -0,0 +1,88 @@
+use crate::{
+    data::{ArconType, Joined},
+    dataflow::{
+        builder::{Assigner, CoOperatorBuilder},
+        conf::OperatorConf,
+        stream::{ConnectedStream, KeyedStream, Stream},
+    },
+    stream::{
+        operator::join::{IntervalJoin, IntervalJoinState, WindowJoin, WindowJoinState},
+        time::Time,
+    },
+};
+use std::sync::Arc;
+
+/// Two keyed streams that are about to be joined
+pub struct JoinedStreams<L: ArconType, R: ArconType> {
+    connected: ConnectedStream<L, R>,
+    conf: OperatorConf,
+}
+
+impl<L: ArconType, R: ArconType> JoinedStreams<L, R> {
+    /// Sets the configuration of the join operator
+    pub fn conf(mut self, conf: OperatorConf) -> Self {
+        self.conf = conf;
+        self
+    }
+
+    /// Join records of the same key that fall into the same window
+    ///
+    /// # Example
+    /// ```no_run
+    /// use arcon::prelude::*;
+    /// let clicks = (0..100u64)
+    ///     .to_stream(|conf| conf.set_arcon_time(ArconTime::Process))
+    ///     .key_by(|x: &u64| x);
+    /// let impressions = (0..100u64)
+    ///     .to_stream(|conf| conf.set_arcon_time(ArconTime::Process))
+    ///     .key_by(|x: &u64| x);
+    /// let stream: Stream<Joined<u64, u64>> = clicks.join(impressions).window(Assigner::Tumbling {
+    ///     length: Time::seconds(10),
+    ///     late_arrival: Time::seconds(0),
+    /// });
+    /// ```
+    pub fn window(self, assigner: Assigner) -> Stream<Joined<L, R>> {
+        self.connected.operator(CoOperatorBuilder {
+            operator: Arc::new(move || WindowJoin::new(assigner)),
+            state: Arc::new(WindowJoinState::new),
+            conf: self.conf,
+        })
+    }
+
+    /// Join records of the same key where the timestamp of the right record
+    /// lies within `[left - lower, left + upper]`
+    ///
+    /// # Example
+    /// ```no_run
+    /// use arcon::prelude::*;
+    /// let clicks = (0..100u64)
+    ///     .to_stream(|conf| conf.set_arcon_time(ArconTime::Process))
+    ///     .key_by(|x: &u64| x);
+    /// let impressions = (0..100u64)
+    ///     .to_stream(|conf| conf.set_arcon_time(ArconTime::Process))
+    ///     .key_by(|x: &u64| x);
+    /// let stream: Stream<Joined<u64, u64>> = clicks
+    ///     .join(impressions)
+    ///     .between(Time::seconds(5), Time::seconds(5));
+    /// ```
+    pub fn between(self, lower: Time, upper: Time) -> Stream<Joined<L, R>> {
+        self.connected.operator(CoOperatorBuilder {
+            operator: Arc::new(move || IntervalJoin::new(lower, upper)),
+            state: Arc::new(IntervalJoinState::new),
+            conf: self.conf,
+        })
+    }
+}
+
+impl<T: ArconType> KeyedStream<T> {
+    /// Join this stream with `other` on their keys
+    ///
+    /// The join is completed with either a [window](JoinedStreams::window)
+    /// or an [interval](JoinedStreams::between).
+    pub fn join<R: ArconType>(self, other: KeyedStream<R>) -> JoinedStreams<T, R> {
+        JoinedStreams {
+            connected: self.connect(other),
+            conf: OperatorConf::default(),
+        }
+    }
+}
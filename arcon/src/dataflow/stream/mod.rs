@@ -1,4 +1,6 @@
+pub mod connect;
 pub mod filter;
+pub mod join;
 pub mod map;
 pub mod operator;
 pub mod partition;
@@ -9,15 +11,21 @@ pub mod arrow;
 pub mod keyed;
 
 use crate::{
-    data::ArconType,
+    data::{ArconType, NodeID},
     dataflow::{
         constructor::*,
-        dfg::{ChannelKind, DFGNodeKind, OperatorId, DFG},
+        dfg::{ChannelKind, DFGNode, DFGNodeKind, OperatorId, DFG},
     },
+    stream::operator::side::OutputTag,
+};
+use std::{
+    cell::{Cell, RefCell},
+    rc::Rc,
 };
-use std::rc::Rc;
 
+pub use connect::ConnectedStream;
 pub use filter::FilterExt;
+pub use join::JoinedStreams;
 pub use map::MapExt;
 pub use operator::OperatorExt;
 pub use partition::PartitionExt;
@@ -27,53 +35,293 @@ pub use keyed::KeyedStream;
 
 /// Represents a possibly infinite stream of records
 pub struct Stream<T: ArconType> {
-    // ID of the node which outputs this stream.
-    prev_dfg_id: OperatorId,
     pub(crate) ctx: Context,
     key_builder: Option<KeyBuilder<T>>,
+    /// Operators whose output make up the stream
+    ///
+    /// A stream has more than one input once it has been combined with other streams.
+    inputs: Vec<StreamInput<T>>,
+}
+
+/// An operator that outputs records onto a [Stream]
+struct StreamInput<T: ArconType> {
+    // ID of the node which outputs this stream.
+    prev_dfg_id: OperatorId,
+    // Index of the output of the node that this stream is fed by.
+    output: usize,
     last_node: Option<Rc<dyn TypedNodeFactory<T>>>,
     source: Option<Rc<dyn TypedSourceFactory<T>>>,
 }
 
+impl<T: ArconType> StreamInput<T> {
+    fn set_channel_kind(&self, channel_kind: ChannelKind) {
+        if let Some(ref node_factory) = self.last_node {
+            node_factory.set_channel_kind(self.output, channel_kind);
+        } else if let Some(ref source_factory) = self.source {
+            source_factory.set_channel_kind(self.output, channel_kind);
+        } else {
+            panic!("Nothing to configure ChannelKind on!");
+        }
+    }
+
+    fn set_key_builder(&self, key_builder: KeyBuilder<T>) {
+        if let Some(ref node_factory) = self.last_node {
+            node_factory.set_key_builder(self.output, key_builder);
+        } else if let Some(ref source_factory) = self.source {
+            source_factory.set_key_builder(self.output, key_builder);
+        } else {
+            panic!("Nothing to apply key_by on!");
+        }
+    }
+
+    fn set_sender_offset(&self, offset: u32) {
+        if let Some(ref node_factory) = self.last_node {
+            node_factory.set_sender_offset(self.output, offset);
+        } else if let Some(ref source_factory) = self.source {
+            source_factory.set_sender_offset(self.output, offset);
+        }
+    }
+
+    /// Returns an input that reads from a new output of the same node
+    fn split(&self) -> StreamInput<T> {
+        let output = if let Some(ref node_factory) = self.last_node {
+            node_factory.add_output(self.output)
+        } else if let Some(ref source_factory) = self.source {
+            source_factory.add_output(self.output)
+        } else {
+            panic!("Cannot split a stream whose operator has been finalized");
+        };
+        StreamInput {
+            prev_dfg_id: self.prev_dfg_id,
+            output,
+            last_node: self.last_node.clone(),
+            source: self.source.clone(),
+        }
+    }
+}
+
 impl<T: ArconType> Stream<T> {
     /// Move the optional last_node/source-Factory into the DFG struct, will no longer be mutable after this.
+    ///
+    /// Branches of a split stream share their factory, so the first branch to finalize it wins.
     pub(crate) fn move_last_node(&mut self) {
-        if let Some(node) = self.last_node.take() {
-            let prev_dfg_node = self.ctx.dfg.get_mut(&self.prev_dfg_id);
-            assert!(matches!(&prev_dfg_node.kind, DFGNodeKind::Placeholder)); // Make sure nothing bad has happened
-            prev_dfg_node.kind = DFGNodeKind::Node(node.untype());
-        } else if let Some(source) = self.source.take() {
-            let prev_dfg_node = self.ctx.dfg.get_mut(&self.prev_dfg_id);
-            assert!(matches!(&prev_dfg_node.kind, DFGNodeKind::Placeholder)); // Make sure nothing bad has happened
-            prev_dfg_node.kind = DFGNodeKind::Source(source.untype());
+        let mut dfg = self.ctx.dfg.borrow_mut();
+        for input in self.inputs.iter_mut() {
+            let kind = if let Some(node) = input.last_node.take() {
+                DFGNodeKind::Node(node.untype())
+            } else if let Some(source) = input.source.take() {
+                DFGNodeKind::Source(source.untype())
+            } else {
+                continue;
+            };
+            let prev_dfg_node = dfg.get_mut(&input.prev_dfg_id);
+            if let DFGNodeKind::Placeholder = prev_dfg_node.kind {
+                prev_dfg_node.kind = kind;
+            }
         }
     }
     pub(crate) fn set_channel_kind(&mut self, channel_kind: ChannelKind) {
-        if let Some(ref mut node_factory) = self.last_node {
-            Rc::get_mut(node_factory)
-                .unwrap()
-                .set_channel_kind(channel_kind);
-        } else if let Some(ref mut source_factory) = self.source {
-            Rc::get_mut(source_factory)
-                .unwrap()
-                .set_channel_kind(channel_kind);
-        } else {
-            panic!("Nothing to configure ChannelKind on!");
+        for input in self.inputs.iter() {
+            input.set_channel_kind(channel_kind);
+        }
+    }
+
+    pub(crate) fn set_key_builder(&mut self, key_builder: KeyBuilder<T>) {
+        for input in self.inputs.iter() {
+            input.set_key_builder(key_builder.clone());
+        }
+        self.key_builder = Some(key_builder);
+    }
+
+    /// Move the inputs of the stream into the DFG
+    ///
+    /// Returns the NodeID's the next operator receives messages from. Inputs are given disjoint
+    /// ranges of sender ids so that `next` can align markers across all of them.
+    pub(crate) fn connect_inputs(&mut self) -> Vec<NodeID> {
+        let mut incoming_channels = Vec::new();
+        for input in self.inputs.iter() {
+            let offset = incoming_channels.len() as u32;
+            input.set_sender_offset(offset);
+            let dfg = self.ctx.dfg.borrow();
+            incoming_channels.extend(
+                dfg.get(&input.prev_dfg_id)
+                    .get_node_ids()
+                    .into_iter()
+                    .map(|id| NodeID::new(id.id + offset)),
+            );
+        }
+        self.move_last_node();
+        incoming_channels
+    }
+
+    /// Link the inputs of the stream to the [DFGNode] of `next`
+    pub(crate) fn set_successor(&mut self, next: &DFGNode) {
+        let mut dfg = self.ctx.dfg.borrow_mut();
+        for input in self.inputs.iter() {
+            dfg.get_mut(&input.prev_dfg_id)
+                .set_output(input.output, next);
+        }
+    }
+
+    /// Returns a Stream that consists of the output of a new DFG node
+    pub(crate) fn with_node<OUT: ArconType>(
+        self,
+        dfg_id: OperatorId,
+        node: Rc<dyn TypedNodeFactory<OUT>>,
+    ) -> Stream<OUT> {
+        Stream {
+            ctx: self.ctx,
+            key_builder: None,
+            inputs: vec![StreamInput {
+                prev_dfg_id: dfg_id,
+                output: 0,
+                last_node: Some(node),
+                source: None,
+            }],
         }
     }
 
     pub(crate) fn new(ctx: Context, source: Rc<dyn TypedSourceFactory<T>>) -> Self {
         Self {
-            prev_dfg_id: 0,
             ctx,
-            last_node: None,
             key_builder: None,
-            source: Some(source),
+            inputs: vec![StreamInput {
+                prev_dfg_id: 0,
+                output: 0,
+                last_node: None,
+                source: Some(source),
+            }],
+        }
+    }
+
+    /// Merge `other` into this stream
+    ///
+    /// The records of both streams flow into the next operator of the returned stream.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use arcon::prelude::*;
+    /// let left: Stream<u64> = (0..100u64).to_stream(|conf| {
+    ///     conf.set_arcon_time(ArconTime::Process);
+    /// });
+    /// let right: Stream<u64> = (100..200u64).to_stream(|conf| {
+    ///     conf.set_arcon_time(ArconTime::Process);
+    /// });
+    /// let stream: Stream<u64> = left.union(right).map(|x| x + 1);
+    /// ```
+    pub fn union(mut self, mut other: Stream<T>) -> Stream<T> {
+        if !Rc::ptr_eq(&self.ctx.dfg, &other.ctx.dfg) {
+            // Graphs that are shared with other branches must stay in place
+            if Rc::strong_count(&self.ctx.dfg) == 1 && Rc::strong_count(&other.ctx.dfg) > 1 {
+                std::mem::swap(&mut self.inputs, &mut other.inputs);
+                std::mem::swap(&mut self.ctx, &mut other.ctx);
+            }
+            let dfg = Rc::try_unwrap(other.ctx.dfg)
+                .unwrap_or_else(|_| panic!("Cannot union two streams that have both been split"))
+                .into_inner();
+            let offset = self.ctx.dfg.borrow_mut().merge(dfg);
+            self.ctx
+                .debug
+                .set(self.ctx.debug.get() || other.ctx.debug.get());
+            for input in other.inputs.iter_mut() {
+                input.prev_dfg_id += offset;
+            }
+        }
+        self.inputs.extend(other.inputs);
+        if self.key_builder.is_none() || other.key_builder.is_none() {
+            self.key_builder = None;
+        }
+        self
+    }
+
+    /// Split the stream into two branches that both receive every record
+    ///
+    /// # Example
+    /// ```no_run
+    /// use arcon::prelude::*;
+    /// let stream: Stream<u64> = (0..100u64).to_stream(|conf| {
+    ///     conf.set_arcon_time(ArconTime::Process);
+    /// });
+    /// let (evens, odds) = stream.split();
+    /// let evens = evens.filter(|x| x % 2 == 0);
+    /// let odds = odds.filter(|x| x % 2 == 1);
+    /// ```
+    pub fn split(self) -> (Stream<T>, Stream<T>) {
+        let branch = Stream {
+            ctx: self.ctx.clone(),
+            key_builder: self.key_builder.clone(),
+            inputs: self.inputs.iter().map(StreamInput::split).collect(),
+        };
+        (self, branch)
+    }
+}
+
+impl<T: ArconType> Stream<T> {
+    /// Returns the stream of records that the operator of this stream emits to `tag`
+    ///
+    /// # Example
+    /// ```no_run
+    /// use arcon::prelude::*;
+    ///
+    /// fn window_sum(buffer: &[u64]) -> u64 {
+    ///     buffer.iter().sum()
+    /// }
+    ///
+    /// let late_data = OutputTag::<u64>::new("late_data");
+    /// let tag = late_data.clone();
+    /// let windows: Stream<u64> = (0..100u64)
+    ///     .to_stream(|conf| {
+    ///         conf.set_arcon_time(ArconTime::Event);
+    ///         conf.set_timestamp_extractor(|x: &u64| *x);
+    ///     })
+    ///     .operator(OperatorBuilder {
+    ///         operator: Arc::new(move || {
+    ///             let conf = WindowConf {
+    ///                 assigner: Assigner::Tumbling {
+    ///                     length: Time::seconds(10),
+    ///                     late_arrival: Time::seconds(0),
+    ///                 },
+    ///             };
+    ///             WindowAssigner::new(conf).with_late_data(tag.clone())
+    ///         }),
+    ///         state: Arc::new(|backend| {
+    ///             let index = AppenderWindow::new(backend.clone(), &window_sum);
+    ///             WindowState::new(index, backend)
+    ///         }),
+    ///         conf: Default::default(),
+    ///     });
+    /// let late: Stream<u64> = windows.side_output(&late_data);
+    /// ```
+    pub fn side_output<S: ArconType>(&self, tag: &OutputTag<S>) -> Stream<S> {
+        assert_eq!(
+            self.inputs.len(),
+            1,
+            "Side outputs are only available on streams of a single operator"
+        );
+        let input = &self.inputs[0];
+        let parent = input
+            .last_node
+            .clone()
+            .expect("Side outputs are only available on streams of an operator");
+        let side = SideOutputConstructor::new(tag, parent);
+        let output = side.register(OutputSpec::default());
+        Stream {
+            ctx: self.ctx.clone(),
+            key_builder: None,
+            inputs: vec![StreamInput {
+                prev_dfg_id: input.prev_dfg_id,
+                output,
+                last_node: Some(Rc::new(side)),
+                source: None,
+            }],
         }
     }
 }
 
-#[derive(Default)]
+/// Dataflow graph that is shared between all branches of a stream
+#[derive(Default, Clone)]
 pub(crate) struct Context {
-    pub(crate) dfg: DFG,
+    pub(crate) dfg: Rc<RefCell<DFG>>,
+    /// Set if any branch of the graph ends in a debug sink
+    pub(crate) debug: Rc<Cell<bool>>,
 }
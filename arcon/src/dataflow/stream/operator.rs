@@ -42,20 +42,25 @@ impl<T: ArconType> OperatorExt<T> for Stream<T> {
         mut self,
         builder: OperatorBuilder<OP>,
     ) -> Stream<OP::OUT> {
-        // No more mutations on the previous node, move it from the stream.current_node to the DFG Graph
-        self.move_last_node();
+        // No more mutations on the previous nodes, move them from the stream into the DFG Graph
+        let incoming_channels = self.connect_inputs();
 
         let paralellism = match builder.conf.parallelism_strategy {
             ParallelismStrategy::Static(num) => num,
-            _ => unreachable!("Managed Parallelism not Supported yet"),
+            ParallelismStrategy::Managed => {
+                assert!(
+                    self.key_builder.is_some(),
+                    "Managed parallelism requires a keyed stream"
+                );
+                // Every key group acts as a logical node towards downstream operators
+                builder.conf.scaling.max_parallelism
+            }
         };
 
-        let prev_dfg_node = self.ctx.dfg.get_mut(&self.prev_dfg_id);
-        let incoming_channels = prev_dfg_node.get_node_ids();
-        let operator_id = prev_dfg_node.get_operator_id() + 1;
+        let operator_id = self.ctx.dfg.borrow().next_id();
 
         let node_constructor = NodeConstructor::<OP, DefaultBackend>::new(
-            format!("Operator_{}", operator_id),
+            String::from(OPERATOR_DESCRIPTOR),
             Arc::new(builder),
             self.key_builder.take(),
         );
@@ -66,17 +71,10 @@ impl<T: ArconType> OperatorExt<T> for Stream<T> {
             paralellism,
             incoming_channels,
         );
-        prev_dfg_node.set_outgoing_channels(dfg_node.get_node_ids());
-        let next_dfg_id = self.ctx.dfg.insert(dfg_node);
+        self.set_successor(&dfg_node);
+        let next_dfg_id = self.ctx.dfg.borrow_mut().insert(dfg_node);
 
-        self.prev_dfg_id = next_dfg_id;
-        Stream {
-            prev_dfg_id: self.prev_dfg_id,
-            ctx: self.ctx,
-            last_node: Some(Rc::new(node_constructor)),
-            key_builder: None,
-            source: None,
-        }
+        self.with_node(next_dfg_id, Rc::new(node_constructor))
     }
 }
 
@@ -1,7 +1,7 @@
 use crate::data::ArconType;
 use crate::dataflow::stream::{KeyBuilder, KeyedStream, Stream};
 use crate::util::ArconFnBounds;
-use std::{hash::Hash, hash::Hasher, rc::Rc, sync::Arc};
+use std::{hash::Hash, hash::Hasher, sync::Arc};
 
 /// Extension trait for partitioning schemes
 pub trait PartitionExt<T: ArconType> {
@@ -38,17 +38,7 @@ impl<T: ArconType> PartitionExt<T> for Stream<T> {
                 hasher.finish()
             }),
         };
-        if let Some(ref mut node_factory) = self.last_node {
-            let node_factory = Rc::get_mut(node_factory).unwrap();
-            node_factory.set_key_builder(key_builder.clone());
-            self.key_builder = Some(key_builder);
-        } else if let Some(ref mut source_factory) = self.source {
-            let source_factory = Rc::get_mut(source_factory).unwrap();
-            source_factory.set_key_builder(key_builder.clone());
-            self.key_builder = Some(key_builder);
-        } else {
-            panic!("Nothing to apply key_by on!");
-        }
+        self.set_key_builder(key_builder);
         KeyedStream::from(self)
     }
 }
@@ -12,6 +12,7 @@ pub mod timer;
 
 use arcon_state::error::ArconStateError;
 use arrow::error::ArrowError;
+use parquet::errors::ParquetError;
 use snafu::{Backtrace, Snafu};
 use std::{io, io::ErrorKind};
 
@@ -103,6 +104,15 @@ impl From<ArconStateError> for Error {
     }
 }
 
+impl From<prost::DecodeError> for Error {
+    fn from(error: prost::DecodeError) -> Self {
+        Error::Corruption {
+            msg: error.to_string(),
+            backtrace: snafu::GenerateBacktrace::generate(),
+        }
+    }
+}
+
 impl From<ArrowError> for Error {
     fn from(error: ArrowError) -> Self {
         let msg = error.to_string();
@@ -115,3 +125,11 @@ impl From<ArrowError> for Error {
         }
     }
 }
+
+impl From<ParquetError> for Error {
+    fn from(error: ParquetError) -> Self {
+        Error::Unsupported {
+            msg: error.to_string(),
+        }
+    }
+}
@@ -5,7 +5,7 @@ use crate::{
 };
 use arcon_state::{
     backend::{
-        handles::{ActiveHandle, Handle},
+        handles::{ActiveHandle, BoxedIteratorOfResult, Handle},
         Backend, VecState,
     },
     data::Value,
@@ -35,6 +35,12 @@ where
         let handle: ActiveHandle<B, VecState<V>, u64> = handle.activate(backend);
         EagerAppender { handle }
     }
+
+    /// Returns the appended values without removing them
+    #[inline]
+    pub fn get(&self) -> Result<Vec<V>> {
+        self.handle.get()
+    }
 }
 
 impl<V, B> IndexOps for EagerAppender<V, B>
@@ -69,6 +75,13 @@ where
         Ok(stored)
     }
     #[inline]
+    fn consume_iter(&mut self) -> Result<BoxedIteratorOfResult<'_, V>> {
+        // the iterator owns the fetched bytes, so the entry can be cleared right away
+        let stored = self.handle.iter()?;
+        self.handle.clear()?;
+        Ok(stored)
+    }
+    #[inline]
     fn len(&self) -> usize {
         self.handle.len().unwrap_or(0)
     }
@@ -1,58 +1,43 @@
 use crate::{
     error::ArconResult,
-    index::{AppenderIndex, HashTable, IndexOps, IndexValue},
+    index::{AppenderIndex, IndexOps, IndexValue},
     table::ImmutableTable,
 };
 use arcon_state::{
-    backend::{handles::ActiveHandle, Backend, VecState},
+    backend::{
+        handles::{ActiveHandle, BoxedIteratorOfResult, Handle},
+        Backend, ValueState, VecState,
+    },
     error::*,
 };
-use prost::*;
-use std::ops::{Deref, DerefMut};
+use std::{collections::HashMap, sync::Arc, vec};
 
 const DEFAULT_APPENDER_SIZE: usize = 1024;
 
 pub mod eager;
 
-#[derive(Clone, Message)]
-pub struct ProstVec<V: IndexValue> {
-    #[prost(message, repeated, tag = "1")]
-    data: Vec<V>,
-}
-
-impl<V> Deref for ProstVec<V>
-where
-    V: IndexValue,
-{
-    type Target = Vec<V>;
-
-    fn deref(&self) -> &Self::Target {
-        &self.data
-    }
-}
-
-impl<V> DerefMut for ProstVec<V>
-where
-    V: IndexValue,
-{
-    fn deref_mut(&mut self) -> &mut Self::Target {
-        &mut self.data
-    }
-}
-
 /// An Index suitable for Non-associative Windows
 ///
-/// A backing [VecState] acts as an overflow vector when
-/// the data no longer fits in the specified in-memory capacity.
+/// Appended values are buffered in memory until the index holds more than its
+/// capacity, at which point all buffered values are spilled to a backing [VecState].
+/// Every spill of a key is kept as a separate segment, so that consuming the key
+/// only loads one segment into memory at a time.
 pub struct LazyAppender<V, B>
 where
     V: IndexValue,
     B: Backend,
 {
     current_key: u64,
-    /// A handle to the VecState
-    handle: ActiveHandle<B, VecState<V>>,
-    hash_table: HashTable<u64, ProstVec<V>, B>,
+    /// A handle to the VecState, namespaced by the segment the values were spilled in
+    handle: ActiveHandle<B, VecState<V>, u64, u64>,
+    /// Amount of segments spilled per key
+    segments: ActiveHandle<B, ValueState<u64>, u64>,
+    /// Values per key that have not yet been spilled to the backend
+    tails: HashMap<u64, Vec<V>>,
+    /// Total amount of values kept in `tails`
+    buffered: usize,
+    /// Max amount of values kept in memory
+    capacity: usize,
 }
 
 impl<V, B> LazyAppender<V, B>
@@ -60,6 +45,74 @@ where
     V: IndexValue,
     B: Backend,
 {
+    /// Creates a LazyAppender with default capacity
+    pub fn new(id: impl Into<String>, backend: Arc<B>) -> Self {
+        Self::with_capacity(id, backend, DEFAULT_APPENDER_SIZE)
+    }
+
+    /// Creates a LazyAppender that keeps at most `capacity` values in memory
+    pub fn with_capacity(id: impl Into<String>, backend: Arc<B>, capacity: usize) -> Self {
+        let id = id.into();
+        let mut segments = Handle::value(format!("{}_segments", id)).with_item_key(0);
+        backend.register_value_handle(&mut segments);
+        let segments = segments.activate(backend.clone());
+
+        let mut handle = Handle::vec(id).with_item_key(0).with_namespace(0);
+        backend.register_vec_handle(&mut handle);
+        let handle: ActiveHandle<B, VecState<V>, u64, u64> = handle.activate(backend);
+
+        LazyAppender {
+            current_key: 0,
+            handle,
+            segments,
+            tails: HashMap::new(),
+            buffered: 0,
+            capacity,
+        }
+    }
+
+    /// Moves every buffered value into a new segment of its key
+    fn spill(&mut self) -> Result<()> {
+        for (key, tail) in self.tails.drain() {
+            self.segments.set_item_key(key);
+            let segment = self.segments.get()?.unwrap_or(0);
+            self.handle.set_item_key(key);
+            self.handle.set_namespace(segment);
+            self.handle.add_all(tail)?;
+            self.segments.fast_set(segment + 1)?;
+        }
+        self.handle.set_item_key(self.current_key);
+        self.segments.set_item_key(self.current_key);
+        self.buffered = 0;
+        Ok(())
+    }
+
+    /// Returns the amount of segments spilled for the current key
+    fn segment_count(&self) -> Result<u64> {
+        Ok(self.segments.get()?.unwrap_or(0))
+    }
+
+    /// Loads the values of `segment` of the current key
+    fn load_segment(&mut self, segment: u64) -> Result<Vec<V>> {
+        self.handle.set_namespace(segment);
+        self.handle.get()
+    }
+
+    /// Removes every spilled segment of the current key
+    fn clear_segments(&mut self) -> Result<()> {
+        for segment in 0..self.segment_count()? {
+            self.handle.set_namespace(segment);
+            self.handle.clear()?;
+        }
+        self.segments.clear()
+    }
+
+    /// Removes the buffered values of the current key
+    fn take_tail(&mut self) -> Vec<V> {
+        let tail = self.tails.remove(&self.current_key).unwrap_or_default();
+        self.buffered -= tail.len();
+        tail
+    }
 }
 
 impl<V, B> IndexOps for LazyAppender<V, B>
@@ -68,11 +121,13 @@ where
     B: Backend,
 {
     fn persist(&mut self) -> ArconResult<()> {
-        // for each modified, set handle key and drain
-        unimplemented!();
+        self.spill()?;
+        Ok(())
     }
     fn set_key(&mut self, key: u64) {
         self.current_key = key;
+        self.handle.set_item_key(key);
+        self.segments.set_item_key(key);
     }
     fn table(&mut self) -> ArconResult<Option<ImmutableTable>> {
         Ok(None)
@@ -85,20 +140,98 @@ where
     B: Backend,
 {
     #[inline]
-    fn append(&mut self, _: V) -> Result<()> {
-        unimplemented!();
+    fn append(&mut self, value: V) -> Result<()> {
+        self.tails.entry(self.current_key).or_default().push(value);
+        self.buffered += 1;
+        if self.buffered > self.capacity {
+            self.spill()?;
+        }
+        Ok(())
     }
     #[inline]
     fn consume(&mut self) -> Result<Vec<V>> {
-        unimplemented!();
+        self.consume_iter()?.collect()
+    }
+    /// Returns the values of the current key one segment at a time
+    ///
+    /// The spilled values are only removed once the iterator has returned all of them,
+    /// so that an iterator that fails or is dropped early leaves them in the index.
+    fn consume_iter(&mut self) -> Result<BoxedIteratorOfResult<'_, V>> {
+        let segments = self.segment_count()?;
+        Ok(Box::new(Consume {
+            appender: self,
+            next_segment: 0,
+            segments,
+            values: Vec::new().into_iter(),
+            done: false,
+        }))
     }
     #[inline]
     fn len(&self) -> usize {
-        unimplemented!();
+        let buffered = self.tails.get(&self.current_key).map_or(0, Vec::len);
+        let mut spilled = 0;
+        for segment in 0..self.segment_count().unwrap_or(0) {
+            self.handle.inner.set_namespace(segment);
+            spilled += self.handle.len().unwrap_or(0);
+        }
+        spilled + buffered
     }
     #[inline]
     fn is_empty(&self) -> bool {
-        unimplemented!();
+        self.len() == 0
+    }
+}
+
+/// Iterator returned by [LazyAppender::consume_iter]
+struct Consume<'a, V, B>
+where
+    V: IndexValue,
+    B: Backend,
+{
+    appender: &'a mut LazyAppender<V, B>,
+    /// Next segment to load
+    next_segment: u64,
+    /// Amount of segments spilled for the key
+    segments: u64,
+    /// Values of the segment or the buffered values being returned
+    values: vec::IntoIter<V>,
+    /// Set once the buffered values are taken or an error is returned
+    done: bool,
+}
+
+impl<'a, V, B> Iterator for Consume<'a, V, B>
+where
+    V: IndexValue,
+    B: Backend,
+{
+    type Item = Result<V>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(value) = self.values.next() {
+                return Some(Ok(value));
+            }
+            if self.done {
+                return None;
+            }
+            let values = if self.next_segment < self.segments {
+                self.next_segment += 1;
+                self.appender.load_segment(self.next_segment - 1)
+            } else {
+                // spilled values come first as they were appended before the buffered ones
+                self.done = true;
+                self.appender
+                    .clear_segments()
+                    .map(|_| self.appender.take_tail())
+            };
+            match values {
+                Ok(values) => self.values = values.into_iter(),
+                Err(err) => {
+                    self.done = true;
+                    return Some(Err(err));
+                }
+            }
+        }
     }
 }
 
@@ -108,7 +241,6 @@ mod tests {
     use crate::test_utils::temp_backend;
     use arcon_state::Sled;
     use eager::EagerAppender;
-    use std::sync::Arc;
 
     fn index_test(mut index: impl AppenderIndex<u64>) -> Result<()> {
         index.set_key(0);
@@ -141,4 +273,54 @@ mod tests {
         let index = EagerAppender::new("appender", backend);
         assert!(index_test(index).is_ok());
     }
+
+    #[test]
+    fn lazy_appender_test() {
+        let backend = Arc::new(temp_backend::<Sled>());
+        let index = LazyAppender::new("appender", backend);
+        assert!(index_test(index).is_ok());
+    }
+
+    #[test]
+    fn lazy_appender_spill_test() -> Result<()> {
+        let backend = Arc::new(temp_backend::<Sled>());
+        let mut index = LazyAppender::with_capacity("appender", backend, 8);
+
+        for i in 0..20u64 {
+            index.set_key(i % 2);
+            index.append(i)?;
+            assert!(index.buffered <= 8);
+        }
+
+        index.set_key(0);
+        assert_eq!(index.len(), 10);
+        let evens: Vec<u64> = index.consume_iter()?.collect::<Result<_>>()?;
+        assert_eq!(evens, (0..20u64).step_by(2).collect::<Vec<_>>());
+        assert!(index.is_empty());
+
+        // every spill of a key is kept in its own segment
+        index.set_key(1);
+        assert!(index.segment_count()? > 1);
+        index.persist().unwrap();
+        assert_eq!(index.buffered, 0);
+        assert_eq!(index.consume()?, (1..20u64).step_by(2).collect::<Vec<_>>());
+
+        Ok(())
+    }
+
+    #[test]
+    fn lazy_appender_partial_consume_test() -> Result<()> {
+        let backend = Arc::new(temp_backend::<Sled>());
+        let mut index = LazyAppender::with_capacity("appender", backend, 4);
+        for i in 0..10u64 {
+            index.append(i)?;
+        }
+
+        // values are kept until the iterator has returned all of them
+        assert_eq!(index.consume_iter()?.next().transpose()?, Some(0));
+        assert_eq!(index.len(), 10);
+        assert_eq!(index.consume()?, (0..10u64).collect::<Vec<_>>());
+        assert!(index.is_empty());
+        Ok(())
+    }
 }
@@ -1,5 +1,11 @@
 use crate::error::ArconResult;
-use crate::{index::IndexOps, table::ImmutableTable};
+use crate::{
+    index::{
+        ttl::{self, Ttl, TtlTracker},
+        IndexOps, TtlIndex,
+    },
+    table::ImmutableTable,
+};
 use arcon_state::{
     backend::{
         handles::{ActiveHandle, BoxedIteratorOfResult, Handle},
@@ -18,6 +24,8 @@ where
 {
     /// Map Handle
     handle: ActiveHandle<B, MapState<K, V>>,
+    /// Expiration timestamps of the records if a [Ttl] is set
+    ttl: Option<TtlTracker<K, B>>,
 }
 
 impl<K, V, B> EagerHashTable<K, V, B>
@@ -30,31 +38,72 @@ where
         let mut handle = Handle::map(id.into());
         backend.register_map_handle(&mut handle);
         let handle = handle.activate(backend);
-        Self { handle }
+        Self { handle, ttl: None }
     }
     /// Insert a key-value record
     #[inline(always)]
     pub fn put(&mut self, key: K, value: V) -> Result<()> {
+        if let Some(tracker) = &self.ttl {
+            tracker.touch(&key)?;
+        }
         self.handle.fast_insert(key, value)
     }
 
     #[inline(always)]
     pub fn get(&self, k: &K) -> Result<Option<V>> {
+        if self.expire(k)? {
+            return Ok(None);
+        }
         self.handle.get(k)
     }
 
     #[inline(always)]
     pub fn remove(&self, k: &K) -> Result<Option<V>> {
+        if self.expire(k)? {
+            return Ok(None);
+        }
+        if let Some(tracker) = &self.ttl {
+            tracker.remove(k)?;
+        }
         self.handle.remove(k)
     }
     #[inline(always)]
     pub fn contains(&self, k: &K) -> Result<bool> {
+        if self.expire(k)? {
+            return Ok(false);
+        }
         self.handle.contains(k)
     }
     #[inline(always)]
     pub fn iter(&self) -> Result<BoxedIteratorOfResult<(K, V)>> {
+        self.purge_expired()?;
         self.handle.iter()
     }
+
+    /// Removes the record of `key` if it has expired
+    ///
+    /// Returns true if the record was removed.
+    #[inline]
+    fn expire(&self, key: &K) -> Result<bool> {
+        if !ttl::is_expired(&self.ttl, key)? {
+            return Ok(false);
+        }
+        self.handle.fast_remove(key)?;
+        if let Some(tracker) = &self.ttl {
+            tracker.remove(key)?;
+        }
+        Ok(true)
+    }
+
+    /// Removes every expired record
+    fn purge_expired(&self) -> Result<()> {
+        if let Some(tracker) = &self.ttl {
+            for key in tracker.take_expired()? {
+                self.handle.fast_remove(&key)?;
+            }
+        }
+        Ok(())
+    }
 }
 
 impl<K, V, B> IndexOps for EagerHashTable<K, V, B>
@@ -64,6 +113,7 @@ where
     B: Backend,
 {
     fn persist(&mut self) -> ArconResult<()> {
+        self.purge_expired()?;
         Ok(())
     }
     fn set_key(&mut self, _: u64) {}
@@ -71,3 +121,24 @@ where
         Ok(None)
     }
 }
+
+impl<K, V, B> TtlIndex for EagerHashTable<K, V, B>
+where
+    K: Key,
+    V: Value,
+    B: Backend,
+{
+    fn set_ttl(&mut self, ttl: Ttl) {
+        TtlTracker::configure(
+            &mut self.ttl,
+            &self.handle.inner.id,
+            ttl,
+            &self.handle.backend,
+        );
+    }
+    fn set_time(&mut self, time: u64) {
+        if let Some(tracker) = &mut self.ttl {
+            tracker.set_time(time);
+        }
+    }
+}
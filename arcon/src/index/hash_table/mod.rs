@@ -6,7 +6,14 @@ use std::{
     hash::{BuildHasher, Hash, Hasher},
 };
 
-use crate::{error::ArconResult, index::IndexOps, table::ImmutableTable};
+use crate::{
+    error::ArconResult,
+    index::{
+        ttl::{self, Ttl, TtlTracker},
+        IndexOps, TtlIndex,
+    },
+    table::ImmutableTable,
+};
 use arcon_state::{
     backend::{
         handles::{ActiveHandle, Handle},
@@ -71,6 +78,8 @@ where
     raw_table: UnsafeCell<RawTable<K, V>>,
     /// HashTable Handle
     handle: ActiveHandle<B, MapState<K, V>>,
+    /// Expiration timestamps of the records if a [Ttl] is set
+    ttl: Option<TtlTracker<K, B>>,
 }
 
 #[inline]
@@ -99,6 +108,7 @@ where
                 DEFAULT_READ_LANE_SIZE,
             )),
             handle,
+            ttl: None,
         }
     }
 
@@ -120,6 +130,7 @@ where
             hash_builder: DefaultHashBuilder::default(),
             raw_table: UnsafeCell::new(RawTable::with_capacity(mod_capacity, read_capacity)),
             handle,
+            ttl: None,
         }
     }
 
@@ -226,6 +237,13 @@ where
         // (1). Probe RawTable and Remove if it exists
         // (2). Delete from Backend
 
+        if self.expire(k)? {
+            return Ok(None);
+        }
+        if let Some(tracker) = &self.ttl {
+            tracker.remove(k)?;
+        }
+
         let table = self.raw_table_mut();
         let hash = make_hash(&self.hash_builder, &k);
 
@@ -241,9 +259,44 @@ where
         }
     }
 
+    /// Removes the record of `key` if it has expired
+    ///
+    /// Returns true if the record was removed.
+    #[inline]
+    fn expire(&self, key: &K) -> Result<bool> {
+        if !ttl::is_expired(&self.ttl, key)? {
+            return Ok(false);
+        }
+        self.purge(key)?;
+        Ok(true)
+    }
+
+    /// Removes the record of `key` from the RawTable, the Backend and the ttl tracker
+    fn purge(&self, key: &K) -> Result<()> {
+        let hash = make_hash(&self.hash_builder, key);
+        let _ = self.raw_table_mut().remove(hash, |x| key.eq(x.0.borrow()));
+        self.backend_remove_fast(key)?;
+        if let Some(tracker) = &self.ttl {
+            tracker.remove(key)?;
+        }
+        Ok(())
+    }
+
+    /// Restarts the ttl of `key` if a [Ttl] is set
+    #[inline]
+    fn touch(&self, key: &K) -> Result<()> {
+        match &self.ttl {
+            Some(tracker) => tracker.touch(key),
+            None => Ok(()),
+        }
+    }
+
     /// Fetch a value by Key
     #[inline(always)]
     pub fn get(&self, key: &K) -> Result<Option<&V>> {
+        if self.expire(key)? {
+            return Ok(None);
+        }
         let hash = make_hash(&self.hash_builder, key);
 
         // Attempt to find the value by probing the RawTable
@@ -273,6 +326,7 @@ where
     /// Insert a key-value record into the RawTable
     #[inline(always)]
     pub fn put(&mut self, key: K, value: V) -> Result<()> {
+        self.touch(&key)?;
         let hash = make_hash(&self.hash_builder, &key);
         self.insert(key, value, hash)
     }
@@ -290,6 +344,10 @@ where
         F: FnOnce(&mut V),
         P: FnOnce() -> V,
     {
+        // An expired record is replaced by a default one
+        self.expire(key)?;
+        self.touch(key)?;
+
         let hash = make_hash(&self.hash_builder, key);
         // In best case, we find the record in the RawTable's MOD lane
         // and modify the record in place.
@@ -360,6 +418,11 @@ where
         unsafe {
             self.handle.insert_all_by_ref(table.iter_modified())?;
         };
+        if let Some(tracker) = &self.ttl {
+            for key in tracker.take_expired()? {
+                self.purge(&key)?;
+            }
+        }
         Ok(())
     }
     fn set_key(&mut self, _: u64) {}
@@ -368,6 +431,27 @@ where
     }
 }
 
+impl<K, V, B> TtlIndex for HashTable<K, V, B>
+where
+    K: Key + Eq + Hash,
+    V: Value,
+    B: Backend,
+{
+    fn set_ttl(&mut self, ttl: Ttl) {
+        TtlTracker::configure(
+            &mut self.ttl,
+            &self.handle.inner.id,
+            ttl,
+            &self.handle.backend,
+        );
+    }
+    fn set_time(&mut self, time: u64) {
+        if let Some(tracker) = &mut self.ttl {
+            tracker.set_time(time);
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
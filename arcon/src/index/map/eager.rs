@@ -0,0 +1,109 @@
+use crate::{
+    error::ArconResult,
+    index::{IndexOps, MapIndex},
+    table::ImmutableTable,
+};
+use arcon_state::{
+    backend::{
+        handles::{ActiveHandle, BoxedIteratorOfResult, Handle},
+        Backend, MapState,
+    },
+    data::{Key, Value},
+    error::*,
+};
+use std::sync::Arc;
+
+/// A MapIndex that operates directly on the backing [MapState]
+pub struct EagerMap<K, V, B>
+where
+    K: Key,
+    V: Value,
+    B: Backend,
+{
+    /// A handle to the MapState
+    handle: ActiveHandle<B, MapState<K, V>, u64>,
+}
+
+impl<K, V, B> EagerMap<K, V, B>
+where
+    K: Key,
+    V: Value,
+    B: Backend,
+{
+    /// Creates an EagerMap
+    pub fn new(id: impl Into<String>, backend: Arc<B>) -> Self {
+        let mut handle = Handle::map(id.into()).with_item_key(0);
+        backend.register_map_handle(&mut handle);
+        let handle: ActiveHandle<B, MapState<K, V>, u64> = handle.activate(backend);
+        EagerMap { handle }
+    }
+}
+
+impl<K, V, B> MapIndex<K, V> for EagerMap<K, V, B>
+where
+    K: Key,
+    V: Value,
+    B: Backend,
+{
+    #[inline]
+    fn put(&mut self, key: &K, value: V) -> Result<()> {
+        self.handle.fast_insert_by_ref(key, &value)
+    }
+    #[inline]
+    fn get(&self, key: &K) -> Result<Option<V>> {
+        self.handle.get(key)
+    }
+    #[inline]
+    fn take(&mut self, key: &K) -> Result<Option<V>> {
+        self.handle.remove(key)
+    }
+    #[inline]
+    fn clear(&mut self, key: &K) -> Result<()> {
+        self.handle.fast_remove(key)
+    }
+    #[inline]
+    fn contains(&self, key: &K) -> Result<bool> {
+        self.handle.contains(key)
+    }
+    #[inline]
+    fn len(&self) -> usize {
+        self.handle.len().unwrap_or(0)
+    }
+    #[inline]
+    fn is_empty(&self) -> bool {
+        self.handle.is_empty().unwrap_or(true)
+    }
+    #[inline]
+    fn iter(&self) -> Result<BoxedIteratorOfResult<'_, (K, V)>> {
+        self.handle.iter()
+    }
+    #[inline]
+    fn keys(&self) -> Result<BoxedIteratorOfResult<'_, K>> {
+        self.handle.keys()
+    }
+    fn rmw<F>(&mut self, key: &K, mut f: F) -> Result<()>
+    where
+        F: FnMut(&mut V) + Sized,
+    {
+        let mut value = self.handle.get(key)?.unwrap_or_default();
+        f(&mut value);
+        self.handle.fast_insert_by_ref(key, &value)
+    }
+}
+
+impl<K, V, B> IndexOps for EagerMap<K, V, B>
+where
+    K: Key,
+    V: Value,
+    B: Backend,
+{
+    fn persist(&mut self) -> ArconResult<()> {
+        Ok(())
+    }
+    fn set_key(&mut self, key: u64) {
+        self.handle.set_item_key(key);
+    }
+    fn table(&mut self) -> ArconResult<Option<ImmutableTable>> {
+        Ok(None)
+    }
+}
@@ -0,0 +1,276 @@
+use super::{IndexOps, MapIndex};
+use crate::error::ArconResult;
+use crate::table::ImmutableTable;
+use arcon_state::{
+    backend::{
+        handles::{ActiveHandle, BoxedIteratorOfResult, Handle},
+        Backend, MapState,
+    },
+    data::{Key, Value},
+    error::*,
+};
+use std::{collections::HashMap, hash::Hash, sync::Arc};
+
+mod eager;
+
+pub use eager::EagerMap;
+
+const DEFAULT_LAZY_MAP_CAPACITY: usize = 1024;
+
+/// A Lazy MapIndex
+///
+/// Modifications are buffered in memory and written to the backing [MapState]
+/// once more than `capacity` records are buffered or the index is persisted.
+pub struct LazyMap<K, V, B>
+where
+    K: Key + Eq + Hash,
+    V: Value,
+    B: Backend,
+{
+    current_key: u64,
+    /// A handle to the MapState
+    handle: ActiveHandle<B, MapState<K, V>, u64>,
+    /// Buffered modifications per key where `None` marks a removed record
+    modified: HashMap<u64, HashMap<K, Option<V>>>,
+    /// Total amount of records in `modified`
+    modified_len: usize,
+    /// Max amount of buffered records
+    capacity: usize,
+}
+
+impl<K, V, B> LazyMap<K, V, B>
+where
+    K: Key + Eq + Hash,
+    V: Value,
+    B: Backend,
+{
+    /// Creates a LazyMap with default capacity
+    pub fn new(id: impl Into<String>, backend: Arc<B>) -> Self {
+        Self::with_capacity(id, backend, DEFAULT_LAZY_MAP_CAPACITY)
+    }
+
+    /// Creates a LazyMap that buffers at most `capacity` modified records
+    pub fn with_capacity(id: impl Into<String>, backend: Arc<B>, capacity: usize) -> Self {
+        let mut handle = Handle::map(id.into()).with_item_key(0);
+        backend.register_map_handle(&mut handle);
+        let handle: ActiveHandle<B, MapState<K, V>, u64> = handle.activate(backend);
+
+        LazyMap {
+            current_key: 0,
+            handle,
+            modified: HashMap::new(),
+            modified_len: 0,
+            capacity,
+        }
+    }
+
+    #[inline]
+    fn current_modified(&self) -> Option<&HashMap<K, Option<V>>> {
+        self.modified.get(&self.current_key)
+    }
+
+    /// Buffers a modification of the current map
+    fn modify(&mut self, key: K, value: Option<V>) -> Result<Option<Option<V>>> {
+        let old = self
+            .modified
+            .entry(self.current_key)
+            .or_default()
+            .insert(key, value);
+        if old.is_none() {
+            self.modified_len += 1;
+            if self.modified_len > self.capacity {
+                self.drain_modified()?;
+            }
+        }
+        Ok(old)
+    }
+
+    /// Writes every buffered modification to the backing [MapState]
+    fn drain_modified(&mut self) -> Result<()> {
+        for (item_key, records) in self.modified.drain() {
+            self.handle.set_item_key(item_key);
+            for (key, value) in records {
+                match value {
+                    Some(value) => self.handle.fast_insert(key, value)?,
+                    None => self.handle.fast_remove(&key)?,
+                }
+            }
+        }
+        self.handle.set_item_key(self.current_key);
+        self.modified_len = 0;
+        Ok(())
+    }
+}
+
+impl<K, V, B> MapIndex<K, V> for LazyMap<K, V, B>
+where
+    K: Key + Eq + Hash,
+    V: Value,
+    B: Backend,
+{
+    #[inline]
+    fn put(&mut self, key: &K, value: V) -> Result<()> {
+        self.modify(key.clone(), Some(value))?;
+        Ok(())
+    }
+    #[inline]
+    fn get(&self, key: &K) -> Result<Option<V>> {
+        match self.current_modified().and_then(|m| m.get(key)) {
+            Some(value) => Ok(value.clone()),
+            None => self.handle.get(key),
+        }
+    }
+    #[inline]
+    fn take(&mut self, key: &K) -> Result<Option<V>> {
+        let old = self.get(key)?;
+        self.modify(key.clone(), None)?;
+        Ok(old)
+    }
+    #[inline]
+    fn clear(&mut self, key: &K) -> Result<()> {
+        self.modify(key.clone(), None)?;
+        Ok(())
+    }
+    #[inline]
+    fn contains(&self, key: &K) -> Result<bool> {
+        match self.current_modified().and_then(|m| m.get(key)) {
+            Some(value) => Ok(value.is_some()),
+            None => self.handle.contains(key),
+        }
+    }
+    fn len(&self) -> usize {
+        let stored = self.handle.len().unwrap_or(0);
+        let modified = match self.current_modified() {
+            Some(modified) => modified,
+            None => return stored,
+        };
+        // buffered records either add to, replace or remove stored ones
+        modified.iter().fold(stored, |len, (key, value)| {
+            match (value.is_some(), self.handle.contains(key).unwrap_or(false)) {
+                (true, false) => len + 1,
+                (false, true) => len - 1,
+                _ => len,
+            }
+        })
+    }
+    #[inline]
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+    fn iter(&self) -> Result<BoxedIteratorOfResult<'_, (K, V)>> {
+        let stored = self.handle.iter()?;
+        match self.current_modified() {
+            Some(modified) => {
+                let stored = stored.filter(move |entry| match entry {
+                    Ok((key, _)) => !modified.contains_key(key),
+                    Err(_) => true,
+                });
+                let buffered = modified
+                    .iter()
+                    .filter_map(|(key, value)| value.clone().map(|v| Ok((key.clone(), v))));
+                Ok(Box::new(stored.chain(buffered)))
+            }
+            None => Ok(stored),
+        }
+    }
+    fn rmw<F>(&mut self, key: &K, mut f: F) -> Result<()>
+    where
+        F: FnMut(&mut V) + Sized,
+    {
+        // In best case, the record is already buffered and is modified in place
+        if let Some(Some(value)) = self
+            .modified
+            .get_mut(&self.current_key)
+            .and_then(|m| m.get_mut(key))
+        {
+            f(value);
+            return Ok(());
+        }
+
+        let mut value = self.get(key)?.unwrap_or_default();
+        f(&mut value);
+        self.modify(key.clone(), Some(value))?;
+        Ok(())
+    }
+}
+
+impl<K, V, B> IndexOps for LazyMap<K, V, B>
+where
+    K: Key + Eq + Hash,
+    V: Value,
+    B: Backend,
+{
+    fn persist(&mut self) -> ArconResult<()> {
+        self.drain_modified()?;
+        Ok(())
+    }
+    fn set_key(&mut self, key: u64) {
+        self.current_key = key;
+        self.handle.set_item_key(key);
+    }
+    fn table(&mut self) -> ArconResult<Option<ImmutableTable>> {
+        Ok(None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::temp_backend;
+    use arcon_state::Sled;
+
+    fn index_test(mut index: impl MapIndex<u64, u64>) -> Result<()> {
+        index.set_key(0);
+        assert!(index.is_empty());
+        index.put(&1, 10)?;
+        index.put(&2, 20)?;
+        index.rmw(&1, |v| *v += 1)?;
+        index.rmw(&3, |v| *v += 3)?;
+        assert_eq!(index.get(&1)?, Some(11));
+        assert_eq!(index.get(&3)?, Some(3));
+        assert_eq!(index.len(), 3);
+
+        // maps of different keys are independent
+        index.set_key(1);
+        assert!(index.is_empty());
+        assert_eq!(index.get(&1)?, None);
+        index.put(&1, 100)?;
+
+        index.set_key(0);
+        assert_eq!(index.take(&2)?, Some(20));
+        assert!(!index.contains(&2)?);
+        index.clear(&3)?;
+        let entries: Vec<(u64, u64)> = index.iter()?.collect::<Result<_>>()?;
+        assert_eq!(entries, vec![(1, 11)]);
+        let keys: Vec<u64> = index.keys()?.collect::<Result<_>>()?;
+        assert_eq!(keys, vec![1]);
+
+        index.persist().unwrap();
+        index.set_key(1);
+        assert_eq!(index.get(&1)?, Some(100));
+        assert_eq!(index.len(), 1);
+
+        Ok(())
+    }
+
+    #[test]
+    fn lazy_map_index_test() {
+        let backend = Arc::new(temp_backend::<Sled>());
+        let index: LazyMap<u64, u64, _> = LazyMap::new("mymap", backend);
+        assert!(index_test(index).is_ok());
+    }
+
+    #[test]
+    fn lazy_map_drain_test() {
+        let backend = Arc::new(temp_backend::<Sled>());
+        let index: LazyMap<u64, u64, _> = LazyMap::with_capacity("mymap", backend, 1);
+        assert!(index_test(index).is_ok());
+    }
+
+    #[test]
+    fn eager_map_index_test() {
+        let backend = Arc::new(temp_backend::<Sled>());
+        let index: EagerMap<u64, u64, _> = EagerMap::new("mymap", backend);
+        assert!(index_test(index).is_ok());
+    }
+}
@@ -1,14 +1,18 @@
-#[allow(dead_code)]
 pub mod appender;
 pub mod hash_table;
+pub mod map;
+pub mod schema;
+pub mod sorted_map;
+pub mod ttl;
 pub mod value;
 pub mod window;
 
-use crate::error::ArconResult;
+use crate::error::{ArconResult, Error};
 use crate::stream::operator::window::WindowContext;
 use crate::ArconType;
 use crate::{data::arrow::ToArrow, manager::snapshot::Snapshot, table::ImmutableTable};
 use arcon_state::{
+    backend::handles::BoxedIteratorOfResult,
     data::{Key, Value},
     error::Result,
     Backend,
@@ -19,8 +23,12 @@ pub trait IndexValue: Value + ToArrow {}
 impl<T> IndexValue for T where T: Value + ToArrow {}
 
 pub use self::{
-    appender::eager::EagerAppender,
+    appender::{eager::EagerAppender, LazyAppender},
     hash_table::{eager::EagerHashTable, HashTable},
+    map::{EagerMap, LazyMap},
+    schema::StateSchema,
+    sorted_map::SortedMapIndex,
+    ttl::{Ttl, TtlIndex, TtlTime},
     value::{EagerValue, LazyValue, LocalValue},
     window::appender::AppenderWindow,
     window::arrow::ArrowWindow,
@@ -57,6 +65,9 @@ pub trait ArconState: Send + 'static {
     fn persist(&mut self) -> ArconResult<()>;
     fn set_key(&mut self, key: u64);
 
+    /// Set the current event time for the indexes with an event-time [Ttl]
+    fn set_time(&mut self, _time: u64) {}
+
     /// Returns a Vec of registered tables
     fn tables(&mut self) -> Vec<ImmutableTable>;
 
@@ -119,8 +130,13 @@ where
     /// Consumes the Appender
     ///
     /// Safety: Note that this call loads the data eagerly and may lead to problems if there is a
-    /// lack of system memory.
+    /// lack of system memory. See [AppenderIndex::consume_iter] for a lazy alternative.
     fn consume(&mut self) -> Result<Vec<V>>;
+    /// Consumes the Appender and returns an iterator that decodes the values one at a time
+    fn consume_iter(&mut self) -> Result<BoxedIteratorOfResult<'_, V>> {
+        let values = self.consume()?;
+        Ok(Box::new(values.into_iter().map(Ok)))
+    }
     /// Returns the length of the Appender
     fn len(&self) -> usize;
     /// Method to check whether an Appender is empty
@@ -172,12 +188,22 @@ where
     fn take(&mut self, key: &K) -> Result<Option<V>>;
     /// Clear value by key
     fn clear(&mut self, key: &K) -> Result<()>;
+    /// Checks whether the Map contains the key
+    fn contains(&self, key: &K) -> Result<bool>;
     /// Length of the current Map
     fn len(&self) -> usize;
     /// Checks whether the Map is empty
     fn is_empty(&self) -> bool;
+    /// Iterates over the key-value records of the current Map
+    fn iter(&self) -> Result<BoxedIteratorOfResult<'_, (K, V)>>;
+    /// Iterates over the keys of the current Map
+    fn keys(&self) -> Result<BoxedIteratorOfResult<'_, K>> {
+        Ok(Box::new(self.iter()?.map(|entry| entry.map(|(k, _)| k))))
+    }
     /// Read-Modify-Write operation
-    fn rmw<F>(&mut self, key: &K, value: V)
+    ///
+    /// If the key does not exist, `f` is applied to V::Default which is then inserted.
+    fn rmw<F>(&mut self, key: &K, f: F) -> Result<()>
     where
         F: FnMut(&mut V) + Sized;
 }
@@ -195,4 +221,20 @@ pub trait WindowIndex: Send + Sized + IndexOps + 'static {
     fn result(&mut self, ctx: WindowContext) -> ArconResult<Self::OUT>;
     /// Clears the window state for the passed context
     fn clear(&mut self, ctx: WindowContext) -> ArconResult<()>;
+    /// Moves the window state of `from` into `into`, leaving `from` empty
+    ///
+    /// Used by merging windows such as sessions.
+    fn merge(&mut self, from: WindowContext, into: WindowContext) -> ArconResult<()>;
+    /// Lets `evictor` remove elements from the window state of `ctx`
+    ///
+    /// Only supported by indexes that keep the elements of a window.
+    fn evict(
+        &mut self,
+        _ctx: WindowContext,
+        _evictor: &mut dyn FnMut(&mut Vec<Self::IN>),
+    ) -> ArconResult<()> {
+        Err(Error::Unsupported {
+            msg: "Window index does not keep elements to evict".to_string(),
+        })
+    }
 }
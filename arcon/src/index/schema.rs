@@ -0,0 +1,333 @@
+use crate::{
+    data::{ArconType, VersionId},
+    error::{ArconResult, Error},
+};
+use arcon_state::{backend::serialization::fixed_bytes, Backend};
+use prost::Message;
+use std::{collections::BTreeMap, fmt, sync::Arc};
+
+/// Converts a single value of an older version into the length-delimited encoding of the current version
+type Migration = Arc<dyn Fn(&[u8], &mut Vec<u8>) -> ArconResult<()> + Send + Sync>;
+
+#[derive(Clone, Default)]
+struct HandleSchema {
+    version: VersionId,
+    /// Whether the handle is a vec handle storing a sequence of values per key
+    sequence: bool,
+    /// Migrations keyed by the version they migrate from, along with the version they produce
+    migrations: BTreeMap<VersionId, (VersionId, Migration)>,
+}
+
+/// Describes the value types that an operator keeps in its state handles
+///
+/// Every handle registered in the schema records the [VersionId] of its value type
+/// in the backend. Whenever an operator is restored with a newer version, the stored
+/// values are migrated step by step, starting with the registered migration for the
+/// version they were written with, until they reach the newest declared version.
+/// Handles without a recorded version are assumed to be at version 1.
+///
+/// Only the values of value, map and vec handles are migrated, keys are left untouched.
+///
+/// ```no_run
+/// use arcon::prelude::*;
+///
+/// #[derive(Arcon, prost::Message, Clone)]
+/// #[arcon(reliable_ser_id = 13, version = 1)]
+/// pub struct SumV1 {
+///     #[prost(uint64, tag = "1")]
+///     pub sum: u64,
+/// }
+///
+/// #[derive(Arcon, prost::Message, Clone)]
+/// #[arcon(reliable_ser_id = 13, version = 2)]
+/// pub struct SumV2 {
+///     #[prost(uint64, tag = "1")]
+///     pub sum: u64,
+///     #[prost(uint64, tag = "2")]
+///     pub count: u64,
+/// }
+///
+/// let schema = StateSchema::default().migration::<SumV2>("sums", 1, |bytes| {
+///     let old = SumV1::decode(bytes)?;
+///     Ok(SumV2 { sum: old.sum, count: 0 })
+/// });
+/// ```
+#[derive(Clone, Default)]
+pub struct StateSchema {
+    handles: BTreeMap<String, HandleSchema>,
+}
+
+impl StateSchema {
+    /// Declares that the values of `handle_id` are of type `V`
+    pub fn value<V: ArconType>(mut self, handle_id: impl Into<String>) -> Self {
+        self.handles.entry(handle_id.into()).or_default().version = V::VERSION_ID;
+        self
+    }
+
+    /// Declares that the values of the value or map handle `handle_id` are of type `V`
+    /// and registers how values written with version `from` are turned into a `V`
+    ///
+    /// `migrate` receives the protobuf encoding of a single old value. Migrations of the
+    /// same handle may be chained, e.g. from version 1 to 2 and from version 2 to 3.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `from` is not lower than the version of `V`.
+    pub fn migration<V: ArconType>(
+        self,
+        handle_id: impl Into<String>,
+        from: VersionId,
+        migrate: impl Fn(&[u8]) -> ArconResult<V> + Send + Sync + 'static,
+    ) -> Self {
+        self.add_migration(handle_id.into(), false, from, migrate)
+    }
+
+    /// Same as [StateSchema::migration] but for the elements of the vec handle `handle_id`
+    pub fn vec_migration<V: ArconType>(
+        self,
+        handle_id: impl Into<String>,
+        from: VersionId,
+        migrate: impl Fn(&[u8]) -> ArconResult<V> + Send + Sync + 'static,
+    ) -> Self {
+        self.add_migration(handle_id.into(), true, from, migrate)
+    }
+
+    fn add_migration<V: ArconType>(
+        mut self,
+        handle_id: String,
+        sequence: bool,
+        from: VersionId,
+        migrate: impl Fn(&[u8]) -> ArconResult<V> + Send + Sync + 'static,
+    ) -> Self {
+        assert!(
+            from < V::VERSION_ID,
+            "migration of {} must go from a lower version than {}",
+            handle_id,
+            V::VERSION_ID
+        );
+        let migration: Migration = Arc::new(move |bytes, out| {
+            migrate(bytes)?
+                .encode_length_delimited(out)
+                .expect("Vec has no capacity limit");
+            Ok(())
+        });
+        let handle = self.handles.entry(handle_id).or_default();
+        handle.version = handle.version.max(V::VERSION_ID);
+        handle.sequence = sequence;
+        handle.migrations.insert(from, (V::VERSION_ID, migration));
+        self
+    }
+
+    /// Migrates the values of every declared handle to its current version
+    ///
+    /// The recorded version is updated after every step, so a failed step is retried
+    /// from the last completed one.
+    pub(crate) fn apply<B: Backend>(&self, backend: &B) -> ArconResult<()> {
+        for (handle_id, schema) in &self.handles {
+            let mut stored = backend.schema_version(handle_id)?.unwrap_or(1);
+            while stored != schema.version {
+                let (to, migration) =
+                    schema
+                        .migrations
+                        .get(&stored)
+                        .ok_or_else(|| Error::Unsupported {
+                            msg: format!(
+                                "No migration of state {} from version {} to {}",
+                                handle_id, stored, schema.version
+                            ),
+                        })?;
+                backend.rewrite_values(handle_id, |bytes| {
+                    migrate_values(bytes, schema.sequence, migration)
+                })?;
+                backend.set_schema_version(handle_id, *to)?;
+                stored = *to;
+            }
+            backend.set_schema_version(handle_id, schema.version)?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Debug for StateSchema {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map()
+            .entries(self.handles.iter().map(|(id, h)| (id, h.version)))
+            .finish()
+    }
+}
+
+/// Runs `migration` on every length-delimited value in `bytes`
+///
+/// Vec handles prefix their values with the amount of elements, which is kept as is.
+fn migrate_values(mut bytes: &[u8], sequence: bool, migration: &Migration) -> ArconResult<Vec<u8>> {
+    let mut out = Vec::with_capacity(bytes.len());
+    if sequence {
+        let elements: usize = fixed_bytes::deserialize_from(&mut bytes)?;
+        fixed_bytes::serialize_into(&mut out, &elements)?;
+    }
+    while !bytes.is_empty() {
+        let len = prost::encoding::decode_varint(&mut bytes)? as usize;
+        if len > bytes.len() {
+            return Err(Error::Corruption {
+                msg: format!("value of {} bytes exceeds the stored {}", len, bytes.len()),
+                backtrace: snafu::GenerateBacktrace::generate(),
+            });
+        }
+        let (value, rest) = bytes.split_at(len);
+        migration(value, &mut out)?;
+        bytes = rest;
+    }
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::temp_backend;
+    use arcon_state::{
+        backend::{
+            handles::{ActiveHandle, Handle},
+            MapState, VecState,
+        },
+        Sled,
+    };
+
+    #[derive(Arcon, prost::Message, Clone)]
+    #[arcon(reliable_ser_id = 13, version = 1)]
+    pub struct SumV1 {
+        #[prost(uint64, tag = "1")]
+        pub sum: u64,
+    }
+
+    #[derive(Arcon, prost::Message, Clone)]
+    #[arcon(reliable_ser_id = 13, version = 2)]
+    pub struct SumV2 {
+        #[prost(uint64, tag = "1")]
+        pub sum: u64,
+        #[prost(uint64, tag = "2")]
+        pub count: u64,
+    }
+
+    #[derive(Arcon, prost::Message, Clone)]
+    #[arcon(reliable_ser_id = 13, version = 3)]
+    pub struct SumV3 {
+        #[prost(uint64, tag = "1")]
+        pub sum: u64,
+        #[prost(uint64, tag = "2")]
+        pub count: u64,
+        #[prost(uint64, tag = "3")]
+        pub max: u64,
+    }
+
+    fn sum_schema() -> StateSchema {
+        StateSchema::default()
+            .migration::<SumV2>("sums", 1, |bytes| {
+                let old = SumV1::decode(bytes)?;
+                Ok(SumV2 {
+                    sum: old.sum,
+                    count: 1,
+                })
+            })
+            .vec_migration::<SumV2>("history", 1, |bytes| {
+                let old = SumV1::decode(bytes)?;
+                Ok(SumV2 {
+                    sum: old.sum,
+                    count: 1,
+                })
+            })
+    }
+
+    #[test]
+    fn schema_migration_test() {
+        let backend = Arc::new(temp_backend::<Sled>());
+
+        let mut handle = Handle::<MapState<u64, SumV1>>::map("sums");
+        backend.register_map_handle(&mut handle);
+        let sums: ActiveHandle<Sled, MapState<u64, SumV1>> = handle.activate(backend.clone());
+        sums.fast_insert(1, SumV1 { sum: 10 }).unwrap();
+        sums.fast_insert(2, SumV1 { sum: 20 }).unwrap();
+
+        let mut handle = Handle::<VecState<SumV1>>::vec("history");
+        backend.register_vec_handle(&mut handle);
+        let history: ActiveHandle<Sled, VecState<SumV1>> = handle.activate(backend.clone());
+        history.append(SumV1 { sum: 1 }).unwrap();
+        history.append(SumV1 { sum: 2 }).unwrap();
+
+        sum_schema().apply(backend.as_ref()).unwrap();
+        assert_eq!(backend.schema_version("sums").unwrap(), Some(2));
+        assert_eq!(backend.schema_version("history").unwrap(), Some(2));
+
+        let mut handle = Handle::<MapState<u64, SumV2>>::map("sums");
+        backend.register_map_handle(&mut handle);
+        let sums: ActiveHandle<Sled, MapState<u64, SumV2>> = handle.activate(backend.clone());
+        let migrated = sums.get(&2).unwrap().unwrap();
+        assert_eq!((migrated.sum, migrated.count), (20, 1));
+
+        let mut handle = Handle::<VecState<SumV2>>::vec("history");
+        backend.register_vec_handle(&mut handle);
+        let history: ActiveHandle<Sled, VecState<SumV2>> = handle.activate(backend.clone());
+        let sums: Vec<u64> = history.get().unwrap().iter().map(|v| v.sum).collect();
+        assert_eq!(sums, vec![1, 2]);
+
+        // a second restore finds the values already at the current version
+        sum_schema().apply(backend.as_ref()).unwrap();
+        assert_eq!(sums_count(&backend), 1);
+    }
+
+    fn sums_count(backend: &Arc<Sled>) -> u64 {
+        let mut handle = Handle::<MapState<u64, SumV2>>::map("sums");
+        backend.register_map_handle(&mut handle);
+        let sums: ActiveHandle<Sled, MapState<u64, SumV2>> = handle.activate(backend.clone());
+        sums.get(&1).unwrap().unwrap().count
+    }
+
+    #[test]
+    fn schema_missing_migration_test() {
+        let backend = temp_backend::<Sled>();
+        backend.set_schema_version("sums", 3).unwrap();
+        assert!(sum_schema().apply(&backend).is_err());
+
+        let schema = StateSchema::default().value::<SumV1>("fresh");
+        schema.apply(&backend).unwrap();
+        assert_eq!(backend.schema_version("fresh").unwrap(), Some(1));
+    }
+
+    #[test]
+    fn schema_chained_migration_test() {
+        let backend = Arc::new(temp_backend::<Sled>());
+
+        let mut handle = Handle::<MapState<u64, SumV1>>::map("sums");
+        backend.register_map_handle(&mut handle);
+        let sums: ActiveHandle<Sled, MapState<u64, SumV1>> = handle.activate(backend.clone());
+        sums.fast_insert(1, SumV1 { sum: 10 }).unwrap();
+
+        let schema = sum_schema().migration::<SumV3>("sums", 2, |bytes| {
+            let old = SumV2::decode(bytes)?;
+            Ok(SumV3 {
+                sum: old.sum,
+                count: old.count,
+                max: old.sum,
+            })
+        });
+        schema.apply(backend.as_ref()).unwrap();
+        assert_eq!(backend.schema_version("sums").unwrap(), Some(3));
+
+        let mut handle = Handle::<MapState<u64, SumV3>>::map("sums");
+        backend.register_map_handle(&mut handle);
+        let sums: ActiveHandle<Sled, MapState<u64, SumV3>> = handle.activate(backend.clone());
+        let migrated = sums.get(&1).unwrap().unwrap();
+        assert_eq!((migrated.sum, migrated.count, migrated.max), (10, 1, 10));
+    }
+
+    #[test]
+    fn schema_registered_version_test() {
+        let backend = temp_backend::<Sled>();
+        let mut handle = Handle::<MapState<u64, SumV2>>::map("sums").with_version(2);
+        backend.register_map_handle(&mut handle);
+        assert_eq!(backend.schema_version("sums").unwrap(), Some(2));
+
+        // values written with the newest version are left as is
+        sum_schema().apply(&backend).unwrap();
+        assert_eq!(backend.schema_version("sums").unwrap(), Some(2));
+    }
+}
@@ -0,0 +1,187 @@
+use super::IndexOps;
+use crate::error::ArconResult;
+use crate::table::ImmutableTable;
+use arcon_state::{
+    backend::{
+        handles::{ActiveHandle, BoxedIteratorOfResult, Handle},
+        Backend, SortedMapState,
+    },
+    data::{OrderedKey, Value},
+    error::*,
+};
+use std::{ops::RangeBounds, sync::Arc};
+
+/// An index that keeps the records of each key sorted by `K`
+///
+/// Records are written straight to the backing [SortedMapState] whose keys are
+/// encoded so that the backend orders them the same way as `K`. This makes it
+/// possible to, for example, buffer out-of-order events by timestamp and
+/// [pop](SortedMapIndex::pop_first) them once the watermark has passed.
+pub struct SortedMapIndex<K, V, B>
+where
+    K: OrderedKey,
+    V: Value,
+    B: Backend,
+{
+    /// A handle to the SortedMapState
+    handle: ActiveHandle<B, SortedMapState<K, V>, u64>,
+}
+
+impl<K, V, B> SortedMapIndex<K, V, B>
+where
+    K: OrderedKey,
+    V: Value,
+    B: Backend,
+{
+    /// Creates a SortedMapIndex
+    pub fn new(id: impl Into<String>, backend: Arc<B>) -> Self {
+        let mut handle = Handle::sorted_map(id.into()).with_item_key(0);
+        backend.register_sorted_map_handle(&mut handle);
+        let handle: ActiveHandle<B, SortedMapState<K, V>, u64> = handle.activate(backend);
+
+        SortedMapIndex { handle }
+    }
+
+    /// Inserts a record, replacing any previous value of `key`
+    #[inline]
+    pub fn put(&mut self, key: K, value: V) -> Result<()> {
+        self.handle.fast_insert(key, value)
+    }
+
+    /// Returns the value of `key`
+    #[inline]
+    pub fn get(&self, key: &K) -> Result<Option<V>> {
+        self.handle.get(key)
+    }
+
+    /// Removes `key` and returns its value
+    #[inline]
+    pub fn remove(&mut self, key: &K) -> Result<Option<V>> {
+        self.handle.remove(key)
+    }
+
+    /// Iterates over the records within `range` in ascending key order
+    #[inline]
+    pub fn range(&self, range: impl RangeBounds<K>) -> Result<BoxedIteratorOfResult<'_, (K, V)>> {
+        self.handle.range(range)
+    }
+
+    /// Iterates over all records in ascending key order
+    #[inline]
+    pub fn iter(&self) -> Result<BoxedIteratorOfResult<'_, (K, V)>> {
+        self.handle.range(..)
+    }
+
+    /// Returns the record with the smallest key
+    #[inline]
+    pub fn first(&self) -> Result<Option<(K, V)>> {
+        self.handle.first()
+    }
+
+    /// Returns the record with the largest key
+    #[inline]
+    pub fn last(&self) -> Result<Option<(K, V)>> {
+        self.handle.last()
+    }
+
+    /// Removes and returns the record with the smallest key
+    #[inline]
+    pub fn pop_first(&mut self) -> Result<Option<(K, V)>> {
+        self.handle.pop_first()
+    }
+
+    /// Removes and returns every record with a key within `range`
+    pub fn drain_range(&mut self, range: impl RangeBounds<K>) -> Result<Vec<(K, V)>> {
+        let drained: Vec<(K, V)> = self.handle.range(range)?.collect::<Result<_>>()?;
+        for (key, _) in drained.iter() {
+            self.handle.remove(key)?;
+        }
+        Ok(drained)
+    }
+
+    /// Removes all records of the current key
+    #[inline]
+    pub fn clear(&mut self) -> Result<()> {
+        self.handle.clear()
+    }
+
+    #[inline]
+    pub fn len(&self) -> Result<usize> {
+        self.handle.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> Result<bool> {
+        Ok(self.first()?.is_none())
+    }
+}
+
+impl<K, V, B> IndexOps for SortedMapIndex<K, V, B>
+where
+    K: OrderedKey,
+    V: Value,
+    B: Backend,
+{
+    fn persist(&mut self) -> ArconResult<()> {
+        Ok(())
+    }
+    fn set_key(&mut self, key: u64) {
+        self.handle.set_item_key(key);
+    }
+    fn table(&mut self) -> ArconResult<Option<ImmutableTable>> {
+        Ok(None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::temp_backend;
+    use arcon_state::Sled;
+
+    #[test]
+    fn sorted_map_index_test() -> Result<()> {
+        let backend = Arc::new(temp_backend::<Sled>());
+        let mut index: SortedMapIndex<u64, String, _> = SortedMapIndex::new("sorted", backend);
+
+        // events arrive out of order
+        for ts in &[30u64, 10, 50, 20, 40] {
+            index.put(*ts, format!("event_{}", ts))?;
+        }
+        index.set_key(1);
+        index.put(5, "other".to_string())?;
+        index.set_key(0);
+
+        assert_eq!(index.len()?, 5);
+        assert_eq!(index.first()?, Some((10, "event_10".to_string())));
+        assert_eq!(index.last()?, Some((50, "event_50".to_string())));
+        let keys: Vec<u64> = index
+            .range(20..50)?
+            .map(|entry| entry.map(|(ts, _)| ts))
+            .collect::<Result<_>>()?;
+        assert_eq!(keys, vec![20, 30, 40]);
+
+        // emit everything up until the watermark
+        let watermark = 30;
+        let mut emitted = Vec::new();
+        while let Some((ts, _)) = index.first()? {
+            if ts > watermark {
+                break;
+            }
+            index.pop_first()?;
+            emitted.push(ts);
+        }
+        assert_eq!(emitted, vec![10, 20, 30]);
+
+        let drained = index.drain_range(..=40)?;
+        assert_eq!(drained, vec![(40, "event_40".to_string())]);
+        assert_eq!(index.len()?, 1);
+        assert_eq!(index.get(&50)?, Some("event_50".to_string()));
+
+        index.set_key(1);
+        assert_eq!(index.pop_first()?, Some((5, "other".to_string())));
+        assert!(index.is_empty()?);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,427 @@
+use crate::{index::SortedMapIndex, util::get_system_time};
+use arcon_state::{
+    backend::{
+        handles::{ActiveHandle, Handle},
+        serialization::protobuf,
+        Backend, MapState,
+    },
+    data::Key,
+    error::*,
+};
+use std::{cell::RefCell, collections::HashMap, ops::Bound, sync::Arc, time::Duration};
+
+/// Notion of time that a [Ttl] is measured in
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TtlTime {
+    /// Entries expire as the watermark of the operator advances
+    Event,
+    /// Entries expire as the wall-clock time advances
+    Processing,
+}
+
+/// Time-to-live of the entries of an index
+///
+/// An entry expires once `duration` has passed since it was last written.
+/// Expired entries are treated as absent and are purged when they are read
+/// and when the index is persisted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Ttl {
+    pub duration: Duration,
+    pub time: TtlTime,
+}
+
+impl Ttl {
+    /// Creates a Ttl measured in processing time
+    pub fn processing_time(duration: Duration) -> Self {
+        Ttl {
+            duration,
+            time: TtlTime::Processing,
+        }
+    }
+
+    /// Creates a Ttl measured in event time, where timestamps are milliseconds
+    pub fn event_time(duration: Duration) -> Self {
+        Ttl {
+            duration,
+            time: TtlTime::Event,
+        }
+    }
+}
+
+/// Indexes whose entries may be configured to expire
+///
+/// `#[derive(ArconState)]` calls these methods for fields with a `#[ttl]` attribute:
+///
+/// ```no_run
+/// use arcon::prelude::*;
+///
+/// #[derive(ArconState)]
+/// pub struct Sessions<B: Backend> {
+///     #[ttl = "30m"]
+///     clicks: LazyValue<u64, B>,
+///     #[ttl(event_time = "1h")]
+///     visits: HashTable<u64, u64, B>,
+/// }
+/// ```
+///
+/// Durations are written as an integer followed by one of the units `ms`, `s`, `m`, `h` or `d`.
+pub trait TtlIndex {
+    /// Expire entries once `ttl` has passed since they were last written
+    fn set_ttl(&mut self, ttl: Ttl);
+    /// Set the current event time that entries with a [TtlTime::Event] ttl expire by
+    fn set_time(&mut self, time: u64);
+}
+
+/// Keeps track of the expiration timestamps of the entries of an index
+///
+/// Timestamps are kept in memory and flushed to the backend when expired keys are taken,
+/// which the indexes do when they are persisted. The backend keeps them in a MapState
+/// next to the state of the index and, ordered by time, in a [SortedMapIndex] so that
+/// expired keys are found without scanning every tracked key.
+pub(crate) struct TtlTracker<K, B>
+where
+    K: Key,
+    B: Backend,
+{
+    ttl: Ttl,
+    /// Current event time
+    event_time: u64,
+    /// Expirations that were accessed or changed, by serialized key
+    expirations: RefCell<HashMap<Vec<u8>, Expiration<K>>>,
+    /// Expiration timestamp per key of the index
+    handle: ActiveHandle<B, MapState<K, u64>>,
+    /// Keys of the index by the big-endian timestamp they expire at followed by the serialized key
+    by_time: RefCell<SortedMapIndex<Vec<u8>, K, B>>,
+}
+
+struct Expiration<K> {
+    key: K,
+    /// Expiration timestamp of `key`, None if it is no longer tracked
+    expires_at: Option<u64>,
+    /// Whether `expires_at` has yet to be flushed to the backend
+    dirty: bool,
+}
+
+impl<K, B> TtlTracker<K, B>
+where
+    K: Key,
+    B: Backend,
+{
+    fn new(id: &str, ttl: Ttl, backend: Arc<B>) -> Self {
+        let mut handle = Handle::map(format!("{}_ttl", id));
+        backend.register_map_handle(&mut handle);
+
+        TtlTracker {
+            ttl,
+            event_time: 0,
+            expirations: RefCell::new(HashMap::new()),
+            handle: handle.activate(backend.clone()),
+            by_time: RefCell::new(SortedMapIndex::new(format!("{}_ttl_by_time", id), backend)),
+        }
+    }
+
+    /// Sets the ttl of `tracker`, creating the tracker of the index `id` if needed
+    pub fn configure(tracker: &mut Option<Self>, id: &str, ttl: Ttl, backend: &Arc<B>) {
+        match tracker {
+            Some(tracker) => tracker.ttl = ttl,
+            None => *tracker = Some(TtlTracker::new(id, ttl, backend.clone())),
+        }
+    }
+
+    #[inline]
+    pub fn set_time(&mut self, time: u64) {
+        self.event_time = time;
+    }
+
+    #[inline]
+    fn now(&self) -> u64 {
+        match self.ttl.time {
+            TtlTime::Event => self.event_time,
+            TtlTime::Processing => get_system_time(),
+        }
+    }
+
+    /// Returns the timestamp that an entry written now expires at
+    #[inline]
+    fn expires_at(&self) -> u64 {
+        self.now()
+            .saturating_add(self.ttl.duration.as_millis() as u64)
+    }
+
+    fn set(&self, key: &K, expires_at: Option<u64>) -> Result<()> {
+        let expiration = Expiration {
+            key: key.clone(),
+            expires_at,
+            dirty: true,
+        };
+        self.expirations
+            .borrow_mut()
+            .insert(protobuf::serialize(key)?, expiration);
+        Ok(())
+    }
+
+    /// Restarts the ttl of `key`
+    #[inline]
+    pub fn touch(&self, key: &K) -> Result<()> {
+        self.set(key, Some(self.expires_at()))
+    }
+
+    /// Returns true if `key` has expired
+    ///
+    /// Keys without an expiration timestamp, such as keys that were written before the
+    /// ttl was configured, are given one when they are first accessed.
+    #[inline]
+    pub fn is_expired(&self, key: &K) -> Result<bool> {
+        let serialized = protobuf::serialize(key)?;
+        let mut expirations = self.expirations.borrow_mut();
+        let expires_at = match expirations.get(&serialized) {
+            Some(expiration) => expiration.expires_at,
+            None => {
+                let (expires_at, dirty) = match self.handle.get(key)? {
+                    Some(expires_at) => (expires_at, false),
+                    None => (self.expires_at(), true),
+                };
+                let expiration = Expiration {
+                    key: key.clone(),
+                    expires_at: Some(expires_at),
+                    dirty,
+                };
+                expirations.insert(serialized, expiration);
+                Some(expires_at)
+            }
+        };
+        Ok(matches!(expires_at, Some(expires_at) if expires_at <= self.now()))
+    }
+
+    /// Stops tracking `key`
+    #[inline]
+    pub fn remove(&self, key: &K) -> Result<()> {
+        self.set(key, None)
+    }
+
+    /// Writes the expirations that changed since the last flush to the backend
+    fn flush(&self) -> Result<()> {
+        let mut expirations = self.expirations.borrow_mut();
+        let mut by_time = self.by_time.borrow_mut();
+        for (serialized, expiration) in expirations.iter_mut().filter(|(_, e)| e.dirty) {
+            match expiration.expires_at {
+                Some(expires_at) => {
+                    self.handle
+                        .fast_insert_by_ref(&expiration.key, &expires_at)?;
+                    by_time.put(time_key(expires_at, serialized), expiration.key.clone())?;
+                }
+                // the entry of the previous timestamp in `by_time` is dropped once it is due
+                None => self.handle.fast_remove(&expiration.key)?,
+            }
+            expiration.dirty = false;
+        }
+        expirations.retain(|_, expiration| expiration.expires_at.is_some());
+        Ok(())
+    }
+
+    /// Stops tracking and returns every expired key
+    pub fn take_expired(&self) -> Result<Vec<K>> {
+        self.flush()?;
+        let end = match self.now().checked_add(1) {
+            Some(end) => Bound::Excluded(end.to_be_bytes().to_vec()),
+            None => Bound::Unbounded,
+        };
+        let due = self
+            .by_time
+            .borrow_mut()
+            .drain_range((Bound::Unbounded, end))?;
+
+        let mut expirations = self.expirations.borrow_mut();
+        let mut expired = Vec::new();
+        for (time_key, key) in due {
+            let (timestamp, serialized) = time_key.split_at(8);
+            let mut expires_at = [0; 8];
+            expires_at.copy_from_slice(timestamp);
+            // entries of timestamps that were restarted or removed since are stale
+            let current = match expirations.get(serialized) {
+                Some(expiration) => expiration.expires_at,
+                None => self.handle.get(&key)?,
+            };
+            if current == Some(u64::from_be_bytes(expires_at)) {
+                self.handle.fast_remove(&key)?;
+                expirations.remove(serialized);
+                expired.push(key);
+            }
+        }
+        Ok(expired)
+    }
+}
+
+/// Key of an expiration in [TtlTracker::by_time]
+#[inline]
+fn time_key(expires_at: u64, serialized_key: &[u8]) -> Vec<u8> {
+    let mut time_key = Vec::with_capacity(8 + serialized_key.len());
+    time_key.extend_from_slice(&expires_at.to_be_bytes());
+    time_key.extend_from_slice(serialized_key);
+    time_key
+}
+
+/// Returns true if `key` has expired according to an optional tracker
+#[inline]
+pub(crate) fn is_expired<K: Key, B: Backend>(
+    tracker: &Option<TtlTracker<K, B>>,
+    key: &K,
+) -> Result<bool> {
+    match tracker {
+        Some(tracker) => tracker.is_expired(key),
+        None => Ok(false),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::ArconResult;
+    use crate::index::{EagerHashTable, EagerValue, HashTable, IndexOps, LazyValue, ValueIndex};
+    use crate::test_utils::temp_backend;
+    use arcon_state::Sled;
+
+    fn value_ttl_test(mut index: impl ValueIndex<u64> + TtlIndex) -> Result<()> {
+        index.set_ttl(Ttl::event_time(Duration::from_millis(10)));
+        index.set_key(0);
+        index.put(1)?;
+        index.set_key(1);
+        index.set_time(5);
+        index.put(2)?;
+
+        index.set_time(10);
+        index.set_key(0);
+        assert_eq!(index.get()?, None);
+        index.set_key(1);
+        assert_eq!(index.get()?.unwrap().as_ref(), &2);
+
+        // writes restart the ttl
+        index.rmw(|v| *v += 1)?;
+        index.set_time(19);
+        assert_eq!(index.get()?.unwrap().as_ref(), &3);
+        index.set_time(20);
+        assert_eq!(index.take()?, None);
+        Ok(())
+    }
+
+    #[test]
+    fn lazy_value_ttl_test() {
+        let backend = Arc::new(temp_backend::<Sled>());
+        let index: LazyValue<u64, _> = LazyValue::new("myvalue", backend);
+        assert!(value_ttl_test(index).is_ok());
+    }
+
+    #[test]
+    fn eager_value_ttl_test() {
+        let backend = Arc::new(temp_backend::<Sled>());
+        let index: EagerValue<u64, _> = EagerValue::new("myvalue", backend);
+        assert!(value_ttl_test(index).is_ok());
+    }
+
+    #[test]
+    fn hash_table_ttl_test() -> ArconResult<()> {
+        let backend = Arc::new(temp_backend::<Sled>());
+        let mut table: HashTable<u64, u64, _> = HashTable::new("table", backend);
+        table.set_ttl(Ttl::event_time(Duration::from_millis(10)));
+        table.put(1, 1)?;
+        table.put(2, 2)?;
+        table.set_time(5);
+        table.rmw(&2, || 0, |v| *v += 1)?;
+
+        table.set_time(10);
+        assert_eq!(table.get(&1)?, None);
+        // an expired record is replaced by a default one
+        table.rmw(&1, || 0, |v| *v += 10)?;
+        table.persist()?;
+        assert_eq!(table.get(&1)?, Some(&10));
+        assert_eq!(table.get(&2)?, Some(&3));
+
+        // persisting purges the expired records from the backend
+        table.set_time(100);
+        table.persist()?;
+        let (len, _) = table.full_iter()?;
+        assert_eq!(len, 0);
+        Ok(())
+    }
+
+    #[test]
+    fn eager_hash_table_ttl_test() -> ArconResult<()> {
+        let backend = Arc::new(temp_backend::<Sled>());
+        let mut table: EagerHashTable<u64, u64, _> = EagerHashTable::new("table", backend);
+        table.set_ttl(Ttl::processing_time(Duration::from_secs(3600)));
+        table.put(1, 1)?;
+        assert_eq!(table.get(&1)?, Some(1));
+
+        // with a zero ttl, records expire as soon as they are written
+        table.set_ttl(Ttl::processing_time(Duration::from_millis(0)));
+        table.put(2, 2)?;
+        assert!(!table.contains(&2)?);
+        assert_eq!(table.get(&1)?, Some(1));
+        table.persist()?;
+        assert_eq!(table.iter()?.count(), 1);
+        Ok(())
+    }
+
+    #[test]
+    fn ttl_tracker_test() -> Result<()> {
+        let backend = Arc::new(temp_backend::<Sled>());
+        let mut tracker = None;
+        let ttl = Ttl::event_time(Duration::from_millis(10));
+        TtlTracker::<u64, _>::configure(&mut tracker, "tracker", ttl, &backend);
+        let mut tracker = tracker.unwrap();
+        for key in 0..4u64 {
+            tracker.set_time(key * 10);
+            tracker.touch(&key)?;
+        }
+        tracker.set_time(5);
+        assert!(tracker.take_expired()?.is_empty());
+
+        // only the expired prefix is taken, skipping the restarted timestamp of key 0
+        tracker.touch(&0)?;
+        tracker.set_time(20);
+        assert_eq!(tracker.take_expired()?, vec![0, 1]);
+        assert_eq!(tracker.by_time.borrow().len()?, 2);
+
+        tracker.remove(&2)?;
+        tracker.set_time(100);
+        assert_eq!(tracker.take_expired()?, vec![3]);
+        Ok(())
+    }
+
+    #[test]
+    fn untracked_key_ttl_test() -> ArconResult<()> {
+        let backend = Arc::new(temp_backend::<Sled>());
+        let mut table: HashTable<u64, u64, _> = HashTable::new("table", backend);
+        // written before the ttl is configured
+        table.put(1, 1)?;
+        table.persist()?;
+
+        table.set_ttl(Ttl::event_time(Duration::from_millis(10)));
+        table.set_time(5);
+        assert_eq!(table.get(&1)?, Some(&1));
+        table.set_time(15);
+        table.persist()?;
+        assert_eq!(table.get(&1)?, None);
+        Ok(())
+    }
+
+    #[test]
+    fn eager_value_clear_ttl_test() -> Result<()> {
+        let backend = Arc::new(temp_backend::<Sled>());
+        let mut index: EagerValue<u64, _> = EagerValue::new("myvalue", backend.clone());
+        index.set_ttl(Ttl::event_time(Duration::from_millis(10)));
+        index.put(1)?;
+        index.persist().unwrap();
+
+        let mut handle = Handle::<MapState<u64, u64>>::map("myvalue_ttl");
+        backend.register_map_handle(&mut handle);
+        let expirations = handle.activate(backend);
+        assert_eq!(expirations.get(&0)?, Some(10));
+
+        // clearing the value stops tracking its expiration
+        index.clear()?;
+        index.persist().unwrap();
+        assert_eq!(expirations.get(&0)?, None);
+        Ok(())
+    }
+}
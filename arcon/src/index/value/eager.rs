@@ -1,6 +1,9 @@
 use crate::{
     error::ArconResult,
-    index::{IndexOps, IndexValue, ValueIndex},
+    index::{
+        ttl::{self, Ttl, TtlTracker},
+        IndexOps, IndexValue, TtlIndex, ValueIndex,
+    },
     table::ImmutableTable,
 };
 use arcon_state::{
@@ -20,6 +23,8 @@ where
     /// A handle to the ValueState
     handle: ActiveHandle<B, MapState<u64, V>>,
     current_key: u64,
+    /// Expiration timestamps of the values if a [Ttl] is set
+    ttl: Option<TtlTracker<u64, B>>,
 }
 
 impl<V, B> EagerValue<V, B>
@@ -37,7 +42,33 @@ where
         EagerValue {
             handle,
             current_key: 0,
+            ttl: None,
+        }
+    }
+
+    /// Removes the value of the current key if it has expired
+    ///
+    /// Returns true if the value was removed.
+    #[inline]
+    fn expire(&self) -> Result<bool> {
+        if !ttl::is_expired(&self.ttl, &self.current_key)? {
+            return Ok(false);
+        }
+        self.handle.fast_remove(&self.current_key)?;
+        if let Some(tracker) = &self.ttl {
+            tracker.remove(&self.current_key)?;
+        }
+        Ok(true)
+    }
+
+    /// Removes every expired value
+    fn purge_expired(&self) -> Result<()> {
+        if let Some(tracker) = &self.ttl {
+            for key in tracker.take_expired()? {
+                self.handle.fast_remove(&key)?;
+            }
         }
+        Ok(())
     }
 }
 
@@ -47,18 +78,32 @@ where
     B: Backend,
 {
     fn put(&mut self, value: V) -> Result<()> {
+        if let Some(tracker) = &self.ttl {
+            tracker.touch(&self.current_key)?;
+        }
         self.handle.fast_insert(self.current_key, value)
     }
     fn get(&self) -> Result<Option<Cow<V>>> {
+        if self.expire()? {
+            return Ok(None);
+        }
         let value = self.handle.get(&self.current_key)?;
         Ok(value.map(Cow::Owned))
     }
     fn take(&mut self) -> Result<Option<V>> {
+        if self.expire()? {
+            return Ok(None);
+        }
+        if let Some(tracker) = &self.ttl {
+            tracker.remove(&self.current_key)?;
+        }
         self.handle.remove(&self.current_key)
     }
     fn clear(&mut self) -> Result<()> {
-        let _ = self.take()?;
-        Ok(())
+        if let Some(tracker) = &self.ttl {
+            tracker.remove(&self.current_key)?;
+        }
+        self.handle.fast_remove(&self.current_key)
     }
     fn rmw<F>(&mut self, mut f: F) -> Result<()>
     where
@@ -81,12 +126,14 @@ where
     B: Backend,
 {
     fn persist(&mut self) -> ArconResult<()> {
+        self.purge_expired()?;
         Ok(())
     }
     fn set_key(&mut self, key: u64) {
         self.current_key = key;
     }
     fn table(&mut self) -> ArconResult<Option<ImmutableTable>> {
+        self.purge_expired()?;
         let mut table = V::table();
         let values = self.handle.values()?;
         table
@@ -98,3 +145,23 @@ where
         Ok(Some(imut))
     }
 }
+
+impl<V, B> TtlIndex for EagerValue<V, B>
+where
+    V: IndexValue,
+    B: Backend,
+{
+    fn set_ttl(&mut self, ttl: Ttl) {
+        TtlTracker::configure(
+            &mut self.ttl,
+            &self.handle.inner.id,
+            ttl,
+            &self.handle.backend,
+        );
+    }
+    fn set_time(&mut self, time: u64) {
+        if let Some(tracker) = &mut self.ttl {
+            tracker.set_time(time);
+        }
+    }
+}
@@ -1,4 +1,4 @@
-use super::{HashTable, IndexOps, IndexValue, ValueIndex};
+use super::{HashTable, IndexOps, IndexValue, Ttl, TtlIndex, ValueIndex};
 use crate::error::ArconResult;
 use crate::table::ImmutableTable;
 use arcon_state::{error::*, Backend};
@@ -95,6 +95,21 @@ where
     }
 }
 
+impl<V, B> TtlIndex for LazyValue<V, B>
+where
+    V: IndexValue,
+    B: Backend,
+{
+    #[inline]
+    fn set_ttl(&mut self, ttl: Ttl) {
+        self.hash_table.set_ttl(ttl);
+    }
+    #[inline]
+    fn set_time(&mut self, time: u64) {
+        self.hash_table.set_time(time);
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
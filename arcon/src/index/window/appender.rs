@@ -27,7 +27,8 @@ where
     pub fn new(backend: Arc<B>, materializer: F) -> Self {
         let mut handle = Handle::vec("window_handle")
             .with_item_key(0)
-            .with_namespace(0);
+            .with_namespace(0)
+            .with_version(IN::VERSION_ID);
 
         backend.register_vec_handle(&mut handle);
 
@@ -73,6 +74,33 @@ where
         self.handle.clear()?;
         Ok(())
     }
+
+    fn merge(&mut self, from: WindowContext, into: WindowContext) -> ArconResult<()> {
+        self.handle.set_item_key(from.key);
+        self.handle.set_namespace(from.index);
+        let buf = self.handle.get()?;
+        self.handle.clear()?;
+
+        self.handle.set_item_key(into.key);
+        self.handle.set_namespace(into.index);
+        self.handle.add_all(buf)?;
+        Ok(())
+    }
+
+    fn evict(
+        &mut self,
+        ctx: WindowContext,
+        evictor: &mut dyn FnMut(&mut Vec<Self::IN>),
+    ) -> ArconResult<()> {
+        self.handle.set_item_key(ctx.key);
+        self.handle.set_namespace(ctx.index);
+
+        let mut buf = self.handle.get()?;
+        evictor(&mut buf);
+        self.handle.clear()?;
+        self.handle.add_all(buf)?;
+        Ok(())
+    }
 }
 impl<IN, OUT, F, B> IndexOps for AppenderWindow<IN, OUT, F, B>
 where
@@ -37,7 +37,8 @@ where
     pub fn new(backend: Arc<B>, udf: F) -> Self {
         let mut handle = Handle::vec("window_handle")
             .with_item_key(0)
-            .with_namespace(0);
+            .with_namespace(0)
+            .with_version(IN::VERSION_ID);
 
         backend.register_vec_handle(&mut handle);
 
@@ -95,6 +96,23 @@ where
 
         Ok(())
     }
+
+    fn merge(&mut self, from: WindowContext, into: WindowContext) -> ArconResult<()> {
+        self.handle.set_item_key(from.key);
+        self.handle.set_namespace(from.index);
+        let mut batches = self.handle.get()?;
+        self.handle.clear()?;
+
+        // in-memory batches are moved into the backend of the merged window
+        if let Some(mut table) = self.map.remove(&from) {
+            batches.append(&mut table.raw_batches()?);
+        }
+
+        self.handle.set_item_key(into.key);
+        self.handle.set_namespace(into.index);
+        self.handle.add_all(batches)?;
+        Ok(())
+    }
 }
 impl<IN, OUT, F, B> IndexOps for ArrowWindow<IN, OUT, F, B>
 where
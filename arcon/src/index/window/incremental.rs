@@ -1,13 +1,16 @@
 use crate::data::ArconType;
 use crate::stream::operator::window::WindowContext;
 use crate::{
-    error::ArconResult,
+    error::{ArconResult, Error},
     index::{IndexOps, WindowIndex},
     prelude::*,
+    reportable_error,
     table::ImmutableTable,
     util::{prost_helpers::ProstOption, ArconFnBounds},
 };
-use arcon_state::{backend::handles::ActiveHandle, Aggregator, AggregatorState, Backend};
+use arcon_state::{
+    backend::handles::ActiveHandle, Aggregator, AggregatorState, Backend, ValueState,
+};
 
 #[derive(Clone)]
 pub struct IncrementalWindowAggregator<IN, OUT, INIT, AGG>
@@ -31,7 +34,7 @@ where
 {
     type Input = IN;
     type Accumulator = ProstOption<OUT>; // this should be an option, but prost
-    type Result = OUT;
+    type Result = Option<OUT>;
 
     fn create_accumulator(&self) -> Self::Accumulator {
         None.into()
@@ -55,8 +58,7 @@ where
     }
 
     fn accumulator_into_result(&self, acc: Self::Accumulator) -> Self::Result {
-        let opt: Option<_> = acc.into();
-        opt.expect("uninitialized incremental window")
+        acc.into()
     }
 }
 
@@ -64,6 +66,9 @@ where
 type AggState<IN, OUT, INIT, AGG> =
     AggregatorState<IncrementalWindowAggregator<IN, OUT, INIT, AGG>>;
 
+// Function that combines the partial results of two windows
+type MergeFn<OUT> = Arc<dyn Fn(&OUT, &OUT) -> OUT + Send + Sync>;
+
 /// A window index that incrementally aggregates elements
 ///
 /// Used for associative and commutative operations
@@ -77,6 +82,9 @@ where
     B: Backend,
 {
     aggregator: ActiveHandle<B, AggState<IN, OUT, INIT, AGG>, u64, u64>,
+    /// Partial results of windows that have been merged into a window
+    merged: ActiveHandle<B, ValueState<OUT>, u64, u64>,
+    merge: Option<MergeFn<OUT>>,
 }
 
 impl<IN, OUT, INIT, AGG, B> IncrementalWindow<IN, OUT, INIT, AGG, B>
@@ -97,13 +105,61 @@ where
             },
         )
         .with_item_key(0)
-        .with_namespace(0);
+        .with_namespace(0)
+        .with_version(OUT::VERSION_ID);
 
         backend.register_aggregator_handle(&mut aggregator);
 
-        let aggregator = aggregator.activate(backend);
+        let aggregator = aggregator.activate(backend.clone());
+
+        let mut merged = Handle::value("incremental_window_merged_state")
+            .with_item_key(0)
+            .with_namespace(0)
+            .with_version(OUT::VERSION_ID);
+
+        backend.register_value_handle(&mut merged);
+
+        let merged = merged.activate(backend);
+
+        Self {
+            aggregator,
+            merged,
+            merge: None,
+        }
+    }
+
+    /// Sets the function that combines the results of two windows
+    ///
+    /// Required for merging windows such as sessions.
+    pub fn with_merge(mut self, merge: impl Fn(&OUT, &OUT) -> OUT + Send + Sync + 'static) -> Self {
+        self.merge = Some(Arc::new(merge));
+        self
+    }
+
+    fn set_context(&mut self, ctx: WindowContext) {
+        self.aggregator.set_item_key(ctx.key);
+        self.aggregator.set_namespace(ctx.index);
+        self.merged.set_item_key(ctx.key);
+        self.merged.set_namespace(ctx.index);
+    }
+
+    /// Returns the combined result of the window in the active context
+    fn partial_result(&self) -> ArconResult<Option<OUT>> {
+        let current = self.aggregator.get()?;
+        match self.merged.get()? {
+            Some(merged) => Ok(Some(self.combine(merged, current)?)),
+            None => Ok(current),
+        }
+    }
 
-        Self { aggregator }
+    fn combine(&self, fst: OUT, snd: Option<OUT>) -> ArconResult<OUT> {
+        match (&self.merge, snd) {
+            (_, None) => Ok(fst),
+            (Some(merge), Some(snd)) => Ok(merge(&fst, &snd)),
+            (None, Some(_)) => Err(Error::Unsupported {
+                msg: "merging incremental windows requires a merge function".to_string(),
+            }),
+        }
     }
 }
 
@@ -127,18 +183,33 @@ where
     }
 
     fn result(&mut self, ctx: WindowContext) -> ArconResult<Self::OUT> {
-        self.aggregator.set_item_key(ctx.key);
-        self.aggregator.set_namespace(ctx.index);
+        self.set_context(ctx);
 
-        let result = self.aggregator.get()?;
-        Ok(result)
+        match self.partial_result()? {
+            Some(result) => Ok(result),
+            None => reportable_error!("uninitialized incremental window"),
+        }
     }
 
     fn clear(&mut self, ctx: WindowContext) -> ArconResult<()> {
-        self.aggregator.set_item_key(ctx.key);
-        self.aggregator.set_namespace(ctx.index);
+        self.set_context(ctx);
 
         let _ = self.aggregator.clear()?;
+        self.merged.clear()?;
+        Ok(())
+    }
+
+    fn merge(&mut self, from: WindowContext, into: WindowContext) -> ArconResult<()> {
+        self.set_context(from);
+        let partial = self.partial_result()?;
+        self.aggregator.clear()?;
+        self.merged.clear()?;
+
+        if let Some(partial) = partial {
+            self.set_context(into);
+            let merged = self.combine(partial, self.merged.get()?)?;
+            self.merged.fast_set(merged)?;
+        }
         Ok(())
     }
 }
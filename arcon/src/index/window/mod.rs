@@ -58,4 +58,60 @@ mod tests {
         let sum_two = window.result(WindowContext::new(1, 1)).unwrap();
         assert_eq!(sum_two, 190);
     }
+
+    #[test]
+    fn merge_appender_window_test() {
+        let backend = Arc::new(temp_backend::<Sled>());
+
+        fn materializer(buffer: &[i32]) -> i32 {
+            buffer.iter().sum()
+        }
+
+        let mut window = AppenderWindow::new(backend, &materializer);
+
+        for i in 0..10 {
+            let _ = window.on_element(i, WindowContext::new(0, 0));
+            let _ = window.on_element(i, WindowContext::new(0, 1));
+        }
+
+        window
+            .merge(WindowContext::new(0, 1), WindowContext::new(0, 0))
+            .unwrap();
+
+        assert_eq!(window.result(WindowContext::new(0, 0)).unwrap(), 90);
+        assert_eq!(window.result(WindowContext::new(0, 1)).unwrap(), 0);
+    }
+
+    #[test]
+    fn merge_incremental_window_test() {
+        let backend = Arc::new(temp_backend::<Sled>());
+
+        fn init(i: i32) -> u64 {
+            i as u64
+        }
+        fn aggregation(i: i32, agg: &u64) -> u64 {
+            agg + i as u64
+        }
+
+        let mut window =
+            IncrementalWindow::new(backend, &init, &aggregation).with_merge(|a, b| a + b);
+
+        for i in 0..10 {
+            let _ = window.on_element(i, WindowContext::new(0, 0));
+            let _ = window.on_element(i, WindowContext::new(0, 1));
+            let _ = window.on_element(i, WindowContext::new(0, 2));
+        }
+
+        window
+            .merge(WindowContext::new(0, 1), WindowContext::new(0, 0))
+            .unwrap();
+        window
+            .merge(WindowContext::new(0, 2), WindowContext::new(0, 0))
+            .unwrap();
+        // Elements that arrive after a merge are added on top of the merged result
+        let _ = window.on_element(5, WindowContext::new(0, 0));
+
+        assert_eq!(window.result(WindowContext::new(0, 0)).unwrap(), 140);
+        assert!(window.result(WindowContext::new(0, 1)).is_err());
+    }
 }
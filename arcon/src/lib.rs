@@ -77,7 +77,7 @@ pub use arcon_state::*;
 // Imports below are exposed for ``arcon_macros``
 
 #[doc(hidden)]
-pub use crate::index::{ArconState, IndexOps};
+pub use crate::index::{ArconState, IndexOps, Ttl, TtlIndex};
 #[doc(hidden)]
 pub use arcon_state::error::ArconStateError;
 
@@ -87,7 +87,10 @@ pub use crate::data::{ArconType, VersionId};
 pub use crate::{
     data::arrow::ToArrow,
     error::ArconResult,
-    table::{ImmutableTable, MutableTable, RecordBatchBuilder, RECORD_BATCH_SIZE},
+    table::{
+        state_table, write_arrow_file, ImmutableTable, MutableTable, RecordBatchBuilder,
+        RECORD_BATCH_SIZE,
+    },
 };
 #[doc(hidden)]
 pub use arrow::{
@@ -156,25 +159,38 @@ pub mod test_utils {
 /// Helper module that imports everything related to arcon into scope
 pub mod prelude {
     pub use crate::{
-        application::conf::{logger::LoggerType, ApplicationConf},
-        application::{Application, ApplicationBuilder},
-        data::{ArconElement, ArconNever, ArconType, StateID, VersionId},
+        application::conf::{
+            logger::LoggerType, ApplicationConf, CheckpointMode, RestoreMode, RetentionPolicy,
+        },
+        application::{Application, ApplicationBuilder, StateBootstrap},
+        data::{
+            ArconElement, ArconNever, ArconType, CoElement, CoEvent, Joined, StateID, VersionId,
+        },
         dataflow::{
-            builder::{Assigner, OperatorBuilder, SourceBuilder},
-            conf::{OperatorConf, ParallelismStrategy, SourceConf, StreamKind, WindowConf},
+            builder::{Assigner, CoOperatorBuilder, OperatorBuilder, SourceBuilder},
+            conf::{
+                OperatorConf, ParallelismStrategy, ScalingConf, SourceConf, StreamKind, WindowConf,
+            },
             dfg::ChannelKind,
             sink::{Sink, ToBuilderExt, ToSinkExt},
             source::{LocalFileSource, ToStreamExt},
             stream::{
-                FilterExt, KeyBuilder, KeyedStream, MapExt, OperatorExt, PartitionExt, Stream,
+                ConnectedStream, FilterExt, JoinedStreams, KeyBuilder, KeyedStream, MapExt,
+                OperatorExt, PartitionExt, Stream,
             },
         },
-        manager::snapshot::Snapshot,
+        manager::snapshot::{CommittedEpoch, Snapshot, SnapshotCatalog},
         stream::{
             operator::{
+                co::CoOperator,
                 function::{Filter, FlatMap, Map, MapInPlace},
+                join::{IntervalJoin, IntervalJoinState, WindowJoin, WindowJoinState},
+                side::OutputTag,
                 sink::local_file::LocalFileSink,
-                window::{WindowAssigner, WindowState},
+                window::{
+                    CountEvictor, CountTrigger, EventTimeTrigger, Evictor, Trigger, TriggerResult,
+                    TriggerWindow, WindowAssigner, WindowState,
+                },
                 Operator, OperatorContext,
             },
             source::{schema::ProtoSchema, Source},
@@ -200,7 +216,7 @@ pub mod prelude {
         prelude::{Channel as KompactChannel, *},
     };
 
-    pub use super::{Arrow, MutableTable, ToArrow};
+    pub use super::{state_table, write_arrow_file, Arrow, MutableTable, ToArrow};
     pub use arrow::{datatypes::Schema, record_batch::RecordBatch};
 
     pub use arcon_state as state;
@@ -208,13 +224,16 @@ pub mod prelude {
     #[cfg(feature = "rocksdb")]
     pub use arcon_state::Rocks;
     pub use arcon_state::{
-        Aggregator, AggregatorState, Backend, BackendType, Handle, MapState, ReducerState, Sled,
-        ValueState, VecState,
+        Aggregator, AggregatorState, Backend, BackendType, CheckpointStorage, Handle, InMemory,
+        LocalStorage, MapState, ObjectStorage, ObjectStoreClient, RawEntry, ReducerState, Sled,
+        SortedMapState, StateInspector, ValueState, VecState,
     };
 
     pub use crate::index::{
-        AppenderIndex, AppenderWindow, ArrowWindow, EagerAppender, EagerHashTable, EagerValue,
-        EmptyState, HashTable, IncrementalWindow, IndexOps, LazyValue, LocalValue, ValueIndex,
+        AppenderIndex, AppenderWindow, ArrowWindow, EagerAppender, EagerHashTable, EagerMap,
+        EagerValue, EmptyState, HashTable, IncrementalWindow, IndexOps, LazyAppender, LazyMap,
+        LazyValue, LocalValue, MapIndex, SortedMapIndex, StateSchema, Ttl, TtlIndex, TtlTime,
+        ValueIndex,
     };
 
     pub use prost::*;
@@ -1,19 +1,60 @@
 use crate::{
     application::conf::logger::ArconLogger,
     data::{Epoch, StateID},
+    error::{ArconResult, Error},
     stream::node::source::SourceEvent,
 };
+use fxhash::FxHashMap;
 use kompact::prelude::*;
-use std::collections::HashSet;
+use std::{collections::HashSet, path::PathBuf};
 
+/// Notification that an epoch has been acknowledged by all state ids of the application
 #[derive(Debug)]
-pub struct EpochCommit(pub Epoch);
+pub struct EpochCommit {
+    pub epoch: Epoch,
+    /// State ids that acknowledged the epoch
+    pub state_ids: Vec<StateID>,
+    /// Savepoint to create from the snapshots of the epoch
+    pub savepoint: Option<SavepointRequest>,
+}
+
+/// A request to create a savepoint in `path` from the next epoch
+#[derive(Debug)]
+pub struct SavepointRequest {
+    pub path: PathBuf,
+    /// Fulfilled with the path of the savepoint once it has been created
+    pub promise: KPromise<ArconResult<PathBuf>>,
+}
+
+/// A callback that is executed right before an epoch marker is injected
+///
+/// The callback receives the epoch that is about to be injected and is
+/// guaranteed to run before any node observes that epoch.
+pub struct EpochBarrier(Box<dyn FnOnce(Epoch) + Send>);
+
+impl EpochBarrier {
+    pub fn new(f: impl FnOnce(Epoch) + Send + 'static) -> Self {
+        EpochBarrier(Box::new(f))
+    }
+}
+
+impl std::fmt::Debug for EpochBarrier {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "EpochBarrier")
+    }
+}
 
 #[derive(Debug)]
 pub enum EpochEvent {
     /// Acknowledgement that `StateID` has committed a checkpoint for epoch `Epoch`
     Ack(StateID, Epoch),
+    /// `StateID` failed to checkpoint epoch `Epoch`, which therefore is never committed
+    Abort(StateID, Epoch),
     Register(StateID),
+    /// Run a barrier before injecting the next epoch
+    Barrier(EpochBarrier),
+    /// Inject an epoch right away and create a savepoint once it is committed
+    Savepoint(SavepointRequest),
     Halt,
 }
 
@@ -26,16 +67,24 @@ pub struct EpochManager {
     next_epoch: u64,
     /// Interval in millis to schedule injection timer
     epoch_interval: u64,
-    /// Reference to the SourceManager
-    pub(crate) source_manager: Option<ActorRefStrong<SourceEvent>>,
+    /// References to the SourceManagers of the application
+    pub(crate) source_managers: Vec<ActorRefStrong<SourceEvent>>,
+    /// Number of SourceManagers whose sources have ended
+    halted_sources: usize,
     /// Kompact Timer
     epoch_timeout: Option<ScheduledTimer>,
     /// Set of known state ids the EpochManager expects acknowledgements from
     known_state_ids: HashSet<StateID>,
     /// The epoch that is currently in process of being comitted
-    ongoing_epoch_commit: u64,
+    pub(crate) ongoing_epoch_commit: u64,
     /// Set of Acks for a commit process
     epoch_acks: HashSet<(StateID, Epoch)>,
+    /// Epochs whose checkpoint failed for some state id
+    aborted_epochs: HashSet<Epoch>,
+    /// Barriers to execute before the next epoch is injected
+    barriers: Vec<EpochBarrier>,
+    /// Savepoints waiting for their epoch to be committed
+    pub(crate) savepoints: FxHashMap<Epoch, SavepointRequest>,
     /// Actor Reference to the SnapshotManager
     snapshot_manager: ActorRefStrong<EpochCommit>,
     logger: ArconLogger,
@@ -52,20 +101,28 @@ impl EpochManager {
             next_epoch: 0,
             known_state_ids: HashSet::new(),
             epoch_acks: HashSet::new(),
+            aborted_epochs: HashSet::new(),
+            barriers: Vec::new(),
+            savepoints: FxHashMap::default(),
             ongoing_epoch_commit: 0,
             epoch_interval,
             snapshot_manager,
-            source_manager: None,
+            source_managers: Vec::new(),
+            halted_sources: 0,
             epoch_timeout: None,
             logger,
         }
     }
+    /// Continue the epoch sequence after a restored `epoch`
+    pub(crate) fn resume_after(&mut self, epoch: Epoch) {
+        self.next_epoch = epoch.epoch + 1;
+        self.ongoing_epoch_commit = epoch.epoch + 1;
+    }
     fn handle_timeout(&mut self, timeout_id: ScheduledTimer) -> Handled {
         match self.epoch_timeout {
             Some(ref timeout) if *timeout == timeout_id => {
-                if let Some(source_manager) = &self.source_manager {
-                    source_manager.tell(SourceEvent::Epoch(Epoch::new(self.next_epoch)));
-                    self.next_epoch += 1;
+                if !self.source_managers.is_empty() {
+                    self.inject_epoch();
                 } else {
                     #[cfg(not(test))]
                     error!(self.logger, "SourceManager was never set");
@@ -79,19 +136,27 @@ impl EpochManager {
             } // can happen during restart or teardown
         }
     }
+    /// Inject the next epoch at all sources and return it
+    fn inject_epoch(&mut self) -> Epoch {
+        let epoch = Epoch::new(self.next_epoch);
+        for EpochBarrier(barrier) in self.barriers.drain(..) {
+            barrier(epoch);
+        }
+        for source_manager in &self.source_managers {
+            source_manager.tell(SourceEvent::Epoch(epoch));
+        }
+        self.next_epoch += 1;
+        epoch
+    }
     fn handle_epoch_event(&mut self, event: EpochEvent) {
         match event {
             EpochEvent::Ack(state_id, epoch) => {
                 // verify the state_id
                 if self.known_state_ids.contains(&state_id) {
-                    // Make sure the epoch is for the ongoing checkpoint
-                    if epoch.epoch == self.ongoing_epoch_commit {
+                    // Acks of later epochs are kept until the ongoing checkpoint completes
+                    if epoch.epoch >= self.ongoing_epoch_commit {
                         self.epoch_acks.insert((state_id, epoch));
-                        if self.epoch_acks.len() == self.known_state_ids.len() {
-                            self.ongoing_epoch_commit = epoch.epoch + 1;
-                            self.snapshot_manager.tell(EpochCommit(epoch));
-                            self.epoch_acks.clear();
-                        }
+                        self.commit_completed_epochs();
                     }
                 } else {
                     info!(
@@ -100,6 +165,21 @@ impl EpochManager {
                     );
                 }
             }
+            EpochEvent::Abort(state_id, epoch) => {
+                if !self.known_state_ids.contains(&state_id) {
+                    info!(
+                        self.logger,
+                        "Ignoring EpochEvent from unknown StateID {}", state_id
+                    );
+                } else if epoch.epoch >= self.ongoing_epoch_commit {
+                    error!(
+                        self.logger,
+                        "Aborting {:?} after the checkpoint of {} failed", epoch, state_id
+                    );
+                    self.aborted_epochs.insert(epoch);
+                    self.commit_completed_epochs();
+                }
+            }
             EpochEvent::Register(state_id) => {
                 if self.known_state_ids.contains(&state_id) {
                     // TODO: make whole system shutdown?
@@ -108,21 +188,82 @@ impl EpochManager {
                     self.known_state_ids.insert(state_id);
                 }
             }
+            EpochEvent::Barrier(barrier) => {
+                self.barriers.push(barrier);
+            }
+            EpochEvent::Savepoint(request) => {
+                if self.source_managers.is_empty() {
+                    let _ = request.promise.fulfil(Err(Error::Unsupported {
+                        msg: "Cannot create a savepoint without sources".to_string(),
+                    }));
+                    return;
+                }
+                let epoch = self.inject_epoch();
+                info!(self.logger, "Creating savepoint from {:?}", epoch);
+                self.savepoints.insert(epoch, request);
+            }
             EpochEvent::Halt => {
-                if let Some(source_manager) = &self.source_manager {
-                    // Send a final epoch marker before revoking the timer
-                    info!(
-                        self.logger,
-                        "EpochManager sending final epoch marker {:?}", self.next_epoch
-                    );
-                    source_manager.tell(SourceEvent::Epoch(Epoch::new(self.next_epoch)));
-                    if let Some(timeout) = self.epoch_timeout.take() {
-                        self.cancel_timer(timeout);
-                    }
-                } else {
+                if self.source_managers.is_empty() {
                     error!(self.logger, "SourceManager was never set");
+                    return;
                 }
+                self.halted_sources += 1;
+                // Sources that have ended keep forwarding epochs until all sources are done
+                if self.halted_sources < self.source_managers.len() {
+                    return;
+                }
+                // Send a final epoch marker before revoking the timer
+                info!(
+                    self.logger,
+                    "EpochManager sending final epoch marker {:?}", self.next_epoch
+                );
+                for source_manager in &self.source_managers {
+                    source_manager.tell(SourceEvent::Epoch(Epoch::new(self.next_epoch)));
+                }
+                // Savepoints may still inject epochs after the final one
+                self.next_epoch += 1;
+                if let Some(timeout) = self.epoch_timeout.take() {
+                    self.cancel_timer(timeout);
+                }
+            }
+        }
+    }
+
+    /// Commit the ongoing epoch, and any epochs after it, once all known state ids have acked them
+    ///
+    /// Aborted epochs are skipped.
+    fn commit_completed_epochs(&mut self) {
+        loop {
+            let ongoing = Epoch::new(self.ongoing_epoch_commit);
+            if self.aborted_epochs.remove(&ongoing) {
+                self.epoch_acks.retain(|(_, epoch)| *epoch != ongoing);
+                self.ongoing_epoch_commit += 1;
+                if let Some(savepoint) = self.savepoints.remove(&ongoing) {
+                    let _ = savepoint.promise.fulfil(Err(Error::Io {
+                        error: std::io::Error::new(
+                            std::io::ErrorKind::Other,
+                            format!("Checkpoint of {:?} failed", ongoing),
+                        ),
+                    }));
+                }
+                continue;
+            }
+            let state_ids: Vec<StateID> = self
+                .epoch_acks
+                .iter()
+                .filter(|(_, epoch)| *epoch == ongoing)
+                .map(|(state_id, _)| state_id.clone())
+                .collect();
+            if self.known_state_ids.is_empty() || state_ids.len() < self.known_state_ids.len() {
+                return;
             }
+            self.epoch_acks.retain(|(_, epoch)| *epoch != ongoing);
+            self.ongoing_epoch_commit += 1;
+            self.snapshot_manager.tell(EpochCommit {
+                epoch: ongoing,
+                state_ids,
+                savepoint: self.savepoints.remove(&ongoing),
+            });
         }
     }
 }
@@ -1,12 +1,25 @@
 use crate::{
     application::conf::logger::ArconLogger,
-    data::{ArconMessage, Epoch, NodeID, StateID, Watermark},
-    dataflow::dfg::GlobalNodeId,
+    data::{partition::key_group_ranges, ArconMessage, Epoch, NodeID, StateID, Watermark},
+    dataflow::{conf::ScalingConf, dfg::GlobalNodeId},
     error::*,
     index::EMPTY_STATE_ID,
-    manager::snapshot::{Snapshot, SnapshotEvent, SnapshotManagerPort},
+    manager::{
+        epoch::{EpochBarrier, EpochEvent},
+        snapshot::{Snapshot, SnapshotEvent, SnapshotManagerPort},
+    },
     prelude::OperatorBuilder,
-    stream::operator::Operator,
+    stream::{
+        channel::{
+            strategy::keyed::{KeyedRoute, RoutingTable},
+            Channel,
+        },
+        node::{
+            managed::{ManagedNode, ManagedScope},
+            Node,
+        },
+        operator::Operator,
+    },
 };
 
 #[cfg(feature = "metrics")]
@@ -29,15 +42,92 @@ pub enum NodeManagerEvent {
     Watermark(NodeID, Watermark),
     Epoch(NodeID, Epoch),
     Checkpoint(NodeID, SnapshotEvent),
+    /// Number of elements a Node processed during an epoch
+    Load(NodeID, Epoch, u64),
+    /// The Node has handed over its key groups and stopped processing
+    Retired(NodeID),
+}
+
+/// Enum representing events that a NodeManager may send to its nodes
+#[derive(Clone, Debug)]
+pub enum NodeEvent {
+    /// Key groups have been released by retiring nodes
+    KeyGroupsReleased,
 }
 
 /// A [kompact] port for bidirectional communication between a Node and its NodeManager
 pub struct NodeManagerPort {}
 impl Port for NodeManagerPort {
-    type Indication = ();
+    type Indication = NodeEvent;
     type Request = NodeManagerEvent;
 }
 
+/// Function used by a NodeManager to create nodes during runtime
+pub(crate) type NodeSpawner<OP, B> =
+    Box<dyn Fn(NodeID, ManagedNode<OP, B>) -> Node<OP, B> + Send + 'static>;
+
+/// Runtime scaling of an operator with a Managed parallelism strategy
+///
+/// The key space of the operator is split into a fixed amount of key groups where
+/// each Node owns a contiguous [KeyRange](crate::data::partition::KeyRange) of groups.
+/// Once all nodes have reported their load for an epoch, the [ScalingConf] decides
+/// whether the operator should run with a different amount of nodes. A rescale
+/// replaces the current nodes with a new generation:
+///
+/// 1. Nodes of the new generation are created and wait for their key groups.
+/// 2. An [EpochBarrier] picks the next epoch `E` as the point of the switch and
+///    schedules a new [RoutingTable] for upstream nodes from `E` and onwards.
+/// 3. Nodes of the current generation complete epoch `E`, release their key groups and retire.
+/// 4. The new generation adopts the key groups and continues from epoch `E + 1`.
+pub(crate) struct ManagedOperator<OP, B>
+where
+    OP: Operator + 'static,
+    B: Backend,
+{
+    conf: ScalingConf,
+    operator_id: usize,
+    total_groups: u64,
+    generation: u64,
+    scope: Arc<ManagedScope<OP, B>>,
+    route: Arc<KeyedRoute<OP::IN>>,
+    spawner: NodeSpawner<OP, B>,
+    epoch_manager: ActorRefStrong<EpochEvent>,
+    /// Reported loads per epoch (reports, total elements)
+    loads: FxHashMap<Epoch, (usize, u64)>,
+    /// Nodes of the previous generation that have not retired yet
+    retiring: FxHashMap<NodeID, AbstractNode<OP::IN>>,
+}
+
+impl<OP, B> ManagedOperator<OP, B>
+where
+    OP: Operator + 'static,
+    B: Backend,
+{
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        conf: ScalingConf,
+        operator_id: usize,
+        total_groups: u64,
+        scope: Arc<ManagedScope<OP, B>>,
+        route: Arc<KeyedRoute<OP::IN>>,
+        spawner: NodeSpawner<OP, B>,
+        epoch_manager: ActorRefStrong<EpochEvent>,
+    ) -> Self {
+        Self {
+            conf,
+            operator_id,
+            total_groups,
+            generation: 0,
+            scope,
+            route,
+            spawner,
+            epoch_manager,
+            loads: FxHashMap::default(),
+            retiring: FxHashMap::default(),
+        }
+    }
+}
+
 pub struct NodeManagerState {
     watermarks: HashMap<NodeID, Watermark>,
     epochs: HashMap<NodeID, Epoch>,
@@ -100,6 +190,8 @@ where
     latest_snapshot: Option<Snapshot>,
     builder: Arc<OperatorBuilder<OP, B>>,
     logger: ArconLogger,
+    /// Set if the operator has a Managed parallelism strategy
+    managed: Option<ManagedOperator<OP, B>>,
 }
 
 impl<OP, B> NodeManager<OP, B>
@@ -135,9 +227,17 @@ where
             latest_snapshot: None,
             logger,
             builder,
+            managed: None,
         }
     }
 
+    /// Let the NodeManager scale its nodes during runtime
+    pub(crate) fn set_managed(&mut self, parallelism: usize, managed: ManagedOperator<OP, B>) {
+        self.node_parallelism = parallelism;
+        self.max_node_parallelism = managed.conf.max_parallelism;
+        self.managed = Some(managed);
+    }
+
     /// Helper method to check if the NodeManager is responsible for any state
     /// that should go to the SnapshotManager.
     ///
@@ -159,9 +259,143 @@ where
                 debug!(self.logger, "Reporting Checkpoint from Node ID {:?}", id);
                 self.snapshot_manager_port.trigger(s);
             }
+            NodeManagerEvent::Load(_, epoch, load) => {
+                self.handle_load(epoch, load);
+            }
+            NodeManagerEvent::Retired(id) => {
+                self.handle_retired(id);
+            }
         }
         Ok(())
     }
+
+    fn handle_load(&mut self, epoch: Epoch, load: u64) {
+        let managed = match &mut self.managed {
+            Some(managed) => managed,
+            None => return,
+        };
+        let (reports, total) = {
+            let entry = managed.loads.entry(epoch).or_insert((0, 0));
+            entry.0 += 1;
+            entry.1 += load;
+            *entry
+        };
+        if reports < self.nodes.len() {
+            return;
+        }
+        managed.loads.retain(|e, _| *e > epoch);
+
+        if !managed.retiring.is_empty() || !managed.scope.is_idle() {
+            return;
+        }
+        let target = managed
+            .conf
+            .target_parallelism(self.node_parallelism, total);
+        if target != self.node_parallelism {
+            info!(
+                self.logger,
+                "Rescaling {} from {} to {} nodes after {} elements in epoch {}",
+                self.state_id,
+                self.node_parallelism,
+                target,
+                total,
+                epoch.epoch
+            );
+            self.rescale(target);
+        }
+    }
+
+    /// Replace the current nodes with a new generation of `parallelism` nodes
+    fn rescale(&mut self, parallelism: usize) {
+        let managed = match &mut self.managed {
+            Some(managed) => managed,
+            None => return,
+        };
+        managed.generation += 1;
+        managed.scope.begin(managed.generation);
+        managed.loads.clear();
+
+        for (id, node) in self.nodes.drain() {
+            managed.retiring.insert(id.node_id, node);
+        }
+
+        let ranges = key_group_ranges(managed.total_groups, parallelism as u64);
+        let mut channels = Vec::with_capacity(ranges.len());
+        let mut key_groups = vec![0; managed.total_groups as usize];
+
+        for (index, range) in ranges.into_iter().enumerate() {
+            for group in range.iter() {
+                key_groups[group as usize] = index;
+            }
+            let node_id = NodeID::new(range.start as u32);
+            let managed_node = ManagedNode::takeover(
+                range,
+                managed.total_groups,
+                managed.generation,
+                managed.scope.clone(),
+            );
+            let node = (managed.spawner)(node_id, managed_node);
+            let node_comp = self.data_system.create(|| node);
+            let manager_port = &mut self.manager_port;
+            node_comp.on_definition(|cd| {
+                biconnect_ports(manager_port, &mut cd.node_manager_port);
+            });
+            let required_ref: RequiredRef<NodeManagerPort> = node_comp.required_ref();
+            let actor_ref = node_comp.actor_ref().hold().expect("failed to fetch");
+            channels.push(Channel::Local(actor_ref));
+
+            self.data_system.start(&node_comp);
+
+            let node_comp: Arc<dyn AbstractComponent<Message = ArconMessage<OP::IN>>> = node_comp;
+            self.nodes.insert(
+                GlobalNodeId {
+                    operator_id: managed.operator_id,
+                    node_id,
+                },
+                (node_comp, required_ref),
+            );
+        }
+
+        let table = RoutingTable {
+            channels,
+            key_groups,
+        };
+        let scope = managed.scope.clone();
+        let route = managed.route.clone();
+        managed
+            .epoch_manager
+            .tell(EpochEvent::Barrier(EpochBarrier::new(move |epoch| {
+                scope.set_epoch(epoch);
+                route.schedule(epoch, table);
+            })));
+
+        self.node_parallelism = parallelism;
+
+        #[cfg(feature = "metrics")]
+        gauge!("nodes", self.nodes.len() as f64 ,"node_manager" => self.state_id.clone());
+    }
+
+    fn handle_retired(&mut self, id: NodeID) {
+        let managed = match &mut self.managed {
+            Some(managed) => managed,
+            None => return,
+        };
+        if let Some((comp, required_ref)) = managed.retiring.remove(&id) {
+            self.manager_port.disconnect_port(required_ref);
+            self.data_system.kill(comp);
+        }
+        if managed.retiring.is_empty() {
+            debug!(
+                self.logger,
+                "Nodes of {} retired, handing over key groups to generation {}",
+                self.state_id,
+                managed.generation
+            );
+            managed.scope.retired();
+            managed.loads.clear();
+            self.manager_port.trigger(NodeEvent::KeyGroupsReleased);
+        }
+    }
 }
 
 impl<OP, B> ComponentLifecycle for NodeManager<OP, B>
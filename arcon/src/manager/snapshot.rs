@@ -1,8 +1,22 @@
-use super::epoch::EpochCommit;
-use crate::data::{Epoch, StateID};
+use super::epoch::{EpochCommit, SavepointRequest};
+use crate::{
+    application::conf::RetentionPolicy,
+    data::{Epoch, StateID},
+    error::ArconResult,
+    stream::node::checkpoint::CheckpointWorker,
+};
+use arcon_state::{shared_checkpoint_files, Backend, CheckpointStorage, SHARED_CHECKPOINT_DIR};
 use fxhash::FxHashMap;
 use kompact::prelude::*;
-use std::{collections::HashSet, sync::mpsc::Sender};
+use prost::Message;
+use std::{
+    collections::{HashMap, HashSet},
+    path::{Path, PathBuf},
+    sync::{mpsc::Sender, Arc},
+};
+
+/// Name of the file in the checkpoint directory that keeps the [SnapshotCatalog]
+pub const CATALOG_FILE: &str = "snapshot_catalog";
 
 #[derive(Debug, Clone)]
 pub enum SnapshotEvent {
@@ -11,10 +25,13 @@ pub enum SnapshotEvent {
     Register(StateID),
 }
 
-#[derive(Clone, Debug)]
+#[derive(prost::Message, Clone, PartialEq)]
 pub struct Snapshot {
+    #[prost(uint64)]
     pub epoch: u64,
+    #[prost(string)]
     pub snapshot_path: String,
+    #[prost(string)]
     pub backend_name: String,
 }
 
@@ -28,6 +45,379 @@ impl Snapshot {
     }
 }
 
+/// The snapshots of all state ids of an application for a committed epoch
+#[derive(prost::Message, Clone, PartialEq)]
+pub struct CommittedEpoch {
+    #[prost(uint64)]
+    pub epoch: u64,
+    #[prost(map = "string, message")]
+    pub snapshots: HashMap<StateID, Snapshot>,
+    /// Time in milliseconds since the UNIX epoch at which the epoch was committed
+    #[prost(uint64)]
+    pub committed_at: u64,
+    /// Total size in bytes of the snapshots
+    #[prost(uint64)]
+    pub size: u64,
+}
+
+/// Catalog of the committed epochs of an application
+///
+/// The [SnapshotManager] persists the catalog as [CATALOG_FILE] in the checkpoint
+/// directory of the application every time an epoch is committed. Only epochs
+/// found in the catalog are guaranteed to have a consistent set of snapshots.
+///
+/// ```no_run
+/// use arcon::prelude::*;
+///
+/// let conf = ApplicationConf::default();
+/// let catalog = SnapshotCatalog::load(conf.checkpoints_dir()).unwrap();
+/// for epoch in catalog.epochs() {
+///     let committed = catalog.get(epoch).unwrap();
+///     println!("{:?} has {} snapshots", epoch, committed.snapshots.len());
+/// }
+/// ```
+#[derive(prost::Message, Clone, PartialEq)]
+pub struct SnapshotCatalog {
+    /// Committed epochs in ascending order
+    #[prost(message, repeated)]
+    epochs: Vec<CommittedEpoch>,
+}
+
+impl SnapshotCatalog {
+    /// Loads the catalog that is kept in `checkpoints_dir`
+    ///
+    /// Relative snapshot paths, as used by savepoints, are resolved against `checkpoints_dir`.
+    /// Returns an empty catalog if the directory has none.
+    pub fn load(checkpoints_dir: impl AsRef<Path>) -> ArconResult<Self> {
+        let dir = checkpoints_dir.as_ref();
+        let path = dir.join(CATALOG_FILE);
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let bytes = std::fs::read(path)?;
+        let mut catalog = Self::decode(bytes.as_slice())?;
+        catalog.resolve(dir);
+        Ok(catalog)
+    }
+
+    /// Loads the catalog that is kept in `storage`
+    ///
+    /// Snapshot paths are resolved against the local `checkpoints_dir`, which snapshots
+    /// are fetched into by [fetch_snapshot]. Returns an empty catalog if the storage has none.
+    pub fn fetch(storage: &dyn CheckpointStorage, checkpoints_dir: &Path) -> ArconResult<Self> {
+        match storage.get(CATALOG_FILE)? {
+            Some(bytes) => {
+                let mut catalog = Self::decode(bytes.as_slice())?;
+                catalog.resolve(checkpoints_dir);
+                Ok(catalog)
+            }
+            None => Ok(Self::default()),
+        }
+    }
+
+    /// Resolves relative snapshot paths against `dir`
+    fn resolve(&mut self, dir: &Path) {
+        for committed in self.epochs.iter_mut() {
+            for snapshot in committed.snapshots.values_mut() {
+                if Path::new(&snapshot.snapshot_path).is_relative() {
+                    snapshot.snapshot_path = dir
+                        .join(&snapshot.snapshot_path)
+                        .to_string_lossy()
+                        .into_owned();
+                }
+            }
+        }
+    }
+
+    /// Persists the catalog in `checkpoints_dir`
+    ///
+    /// The catalog is written to a temporary file first, so that a crash never
+    /// leaves a partially written catalog behind.
+    pub(crate) fn store(&self, checkpoints_dir: &Path) -> ArconResult<()> {
+        std::fs::create_dir_all(checkpoints_dir)?;
+        let path = checkpoints_dir.join(CATALOG_FILE);
+        let tmp_path = checkpoints_dir.join(format!("{}.tmp", CATALOG_FILE));
+        std::fs::write(&tmp_path, self.encode_to_vec())?;
+        std::fs::rename(tmp_path, path)?;
+        Ok(())
+    }
+
+    /// Persists the catalog in `storage`, with snapshot paths relative to `checkpoints_dir`
+    ///
+    /// Relative paths let the catalog be fetched into another checkpoint directory,
+    /// such as the one of a replacement machine.
+    pub(crate) fn upload(
+        &self,
+        storage: &dyn CheckpointStorage,
+        checkpoints_dir: &Path,
+    ) -> ArconResult<()> {
+        let mut catalog = self.clone();
+        for committed in catalog.epochs.iter_mut() {
+            for snapshot in committed.snapshots.values_mut() {
+                snapshot.snapshot_path = storage_key(checkpoints_dir, &snapshot.snapshot_path)?;
+            }
+        }
+        storage.put(CATALOG_FILE, &catalog.encode_to_vec())?;
+        Ok(())
+    }
+
+    /// Returns the committed epochs in ascending order
+    pub fn epochs(&self) -> Vec<Epoch> {
+        self.epochs.iter().map(|c| Epoch::new(c.epoch)).collect()
+    }
+
+    /// Returns the newest committed epoch
+    pub fn latest(&self) -> Option<Epoch> {
+        self.epochs.last().map(|c| Epoch::new(c.epoch))
+    }
+
+    /// Returns the snapshots of a committed `epoch`
+    pub fn get(&self, epoch: Epoch) -> Option<&CommittedEpoch> {
+        self.epochs
+            .binary_search_by_key(&epoch.epoch, |c| c.epoch)
+            .ok()
+            .map(|index| &self.epochs[index])
+    }
+
+    /// Returns the snapshot of `state_id` for a committed `epoch`
+    pub fn snapshot(&self, epoch: Epoch, state_id: &str) -> Option<&Snapshot> {
+        self.get(epoch)
+            .and_then(|committed| committed.snapshots.get(state_id))
+    }
+
+    /// Loads the backend of `state_id` at a committed `epoch` into `live_path`
+    pub fn load_backend<B: Backend>(
+        &self,
+        epoch: Epoch,
+        state_id: &str,
+        live_path: &Path,
+    ) -> ArconResult<B> {
+        match self.snapshot(epoch, state_id) {
+            Some(snapshot) => Ok(B::restore(
+                live_path,
+                Path::new(&snapshot.snapshot_path),
+                state_id.to_string(),
+            )?),
+            None => crate::reportable_error!(
+                "No snapshot of {} for committed epoch {:?}",
+                state_id,
+                epoch
+            ),
+        }
+    }
+
+    /// Adds a committed epoch to the catalog
+    pub(crate) fn insert(&mut self, epoch: Epoch, snapshots: HashMap<StateID, Snapshot>) {
+        let size = snapshots
+            .values()
+            .map(|snapshot| snapshot_size(Path::new(&snapshot.snapshot_path)))
+            .sum();
+        let committed = CommittedEpoch {
+            epoch: epoch.epoch,
+            snapshots,
+            committed_at: crate::util::get_system_time(),
+            size,
+        };
+        match self.epochs.binary_search_by_key(&epoch.epoch, |c| c.epoch) {
+            Ok(index) => self.epochs[index] = committed,
+            Err(index) => self.epochs.insert(index, committed),
+        }
+    }
+
+    /// Removes a committed epoch from the catalog
+    pub(crate) fn remove(&mut self, epoch: Epoch) -> Option<CommittedEpoch> {
+        self.epochs
+            .binary_search_by_key(&epoch.epoch, |c| c.epoch)
+            .ok()
+            .map(|index| self.epochs.remove(index))
+    }
+
+    /// Returns the epochs that violate `policy` at time `now`, never including the newest epoch
+    pub(crate) fn expired(&self, policy: &RetentionPolicy, now: u64) -> Vec<Epoch> {
+        let retained = match self.epochs.split_last() {
+            Some((_, older)) => older,
+            None => return Vec::new(),
+        };
+        let over_count = policy
+            .max_epochs
+            .map(|max| self.epochs.len().saturating_sub(max.max(1)))
+            .unwrap_or(0);
+        let mut total_size: u64 = self.epochs.iter().map(|c| c.size).sum();
+
+        let mut expired = Vec::new();
+        // Oldest epochs are checked first so that the size limit removes those first
+        for (index, committed) in retained.iter().enumerate() {
+            let too_many = index < over_count;
+            let too_old = policy
+                .max_age
+                .map(|max_age| committed.committed_at + max_age < now)
+                .unwrap_or(false);
+            let too_large = policy
+                .max_size
+                .map(|max_size| total_size > max_size)
+                .unwrap_or(false);
+            if too_many || too_old || too_large {
+                total_size -= committed.size;
+                expired.push(Epoch::new(committed.epoch));
+            }
+        }
+        expired
+    }
+}
+
+/// Copies the snapshots of `epoch` into a self-contained savepoint at `path`
+///
+/// The savepoint keeps a [SnapshotCatalog] with the single epoch, whose snapshot
+/// paths are relative to `path` so that the savepoint can be moved around.
+fn create_savepoint(
+    path: &Path,
+    epoch: Epoch,
+    snapshots: &HashMap<StateID, Snapshot>,
+) -> ArconResult<()> {
+    let mut savepoint_snapshots = HashMap::with_capacity(snapshots.len());
+    for (state_id, snapshot) in snapshots {
+        let source = Path::new(&snapshot.snapshot_path);
+        let name = source
+            .file_name()
+            .map(|n| n.to_os_string())
+            .unwrap_or_default();
+        let relative = Path::new(state_id).join(name);
+        copy_dir(source, &path.join(&relative))?;
+
+        // incremental snapshots reference files shared with the other snapshots of the state
+        let shared_files = shared_checkpoint_files(source)?;
+        if !shared_files.is_empty() {
+            let target = path.join(state_id).join(SHARED_CHECKPOINT_DIR);
+            std::fs::create_dir_all(&target)?;
+            for file in shared_files {
+                if let Some(name) = file.file_name() {
+                    std::fs::copy(&file, target.join(name))?;
+                }
+            }
+        }
+
+        let mut snapshot = snapshot.clone();
+        snapshot.snapshot_path = relative.to_string_lossy().into_owned();
+        savepoint_snapshots.insert(state_id.clone(), snapshot);
+    }
+    store_savepoint_catalog(path, epoch, savepoint_snapshots)
+}
+
+/// Stores the catalog of a savepoint at `path` whose `snapshots` are relative to `path`
+pub(crate) fn store_savepoint_catalog(
+    path: &Path,
+    epoch: Epoch,
+    snapshots: HashMap<StateID, Snapshot>,
+) -> ArconResult<()> {
+    let mut catalog = SnapshotCatalog::default();
+    catalog.insert(epoch, snapshots);
+    // Relative paths are not resolved by insert, so measure the savepoint directory instead
+    catalog.epochs[0].size = dir_size(path);
+    catalog.store(path)
+}
+
+/// Returns the key in the checkpoint storage of `path` below `checkpoints_dir`
+fn storage_key(checkpoints_dir: &Path, path: &str) -> ArconResult<String> {
+    match Path::new(path).strip_prefix(checkpoints_dir) {
+        Ok(relative) => {
+            let parts: Vec<_> = relative
+                .components()
+                .map(|c| c.as_os_str().to_string_lossy())
+                .collect();
+            Ok(parts.join("/"))
+        }
+        Err(_) => crate::reportable_error!(
+            "Snapshot {} is not in the checkpoint directory {}",
+            path,
+            checkpoints_dir.display()
+        ),
+    }
+}
+
+/// Copies `snapshot` and the files it shares with other snapshots of its state into `storage`
+fn upload_snapshot(
+    storage: &dyn CheckpointStorage,
+    checkpoints_dir: &Path,
+    snapshot: &Snapshot,
+) -> ArconResult<()> {
+    let path = Path::new(&snapshot.snapshot_path);
+    storage.put_dir(
+        path,
+        &storage_key(checkpoints_dir, &snapshot.snapshot_path)?,
+    )?;
+
+    let shared = path.with_file_name(SHARED_CHECKPOINT_DIR);
+    if shared.is_dir() {
+        let key = storage_key(checkpoints_dir, &shared.to_string_lossy())?;
+        storage.mirror_dir(&shared, &key)?;
+    }
+    Ok(())
+}
+
+/// Fetches `snapshot` from `storage` unless it is already in the local `checkpoints_dir`
+///
+/// Snapshots outside of `checkpoints_dir`, such as those of savepoints, are left alone.
+pub(crate) fn fetch_snapshot(
+    storage: &dyn CheckpointStorage,
+    checkpoints_dir: &Path,
+    snapshot: &Snapshot,
+) -> ArconResult<()> {
+    let path = Path::new(&snapshot.snapshot_path);
+    if path.exists() || !path.starts_with(checkpoints_dir) {
+        return Ok(());
+    }
+    let key = storage_key(checkpoints_dir, &snapshot.snapshot_path)?;
+    if !storage.get_dir(&key, path)? {
+        return crate::reportable_error!("Snapshot {} is missing in the checkpoint storage", key);
+    }
+
+    let shared = path.with_file_name(SHARED_CHECKPOINT_DIR);
+    if !shared.exists() {
+        let key = storage_key(checkpoints_dir, &shared.to_string_lossy())?;
+        storage.get_dir(&key, &shared)?;
+    }
+    Ok(())
+}
+
+/// Recursively copies the directory `from` to `to`
+fn copy_dir(from: &Path, to: &Path) -> ArconResult<()> {
+    std::fs::create_dir_all(to)?;
+    for entry in std::fs::read_dir(from)? {
+        let entry = entry?;
+        let target = to.join(entry.file_name());
+        if entry.file_type()?.is_dir() {
+            copy_dir(&entry.path(), &target)?;
+        } else {
+            std::fs::copy(entry.path(), target)?;
+        }
+    }
+    Ok(())
+}
+
+/// Returns the size in bytes of the snapshot at `path`, including the shared files it references
+fn snapshot_size(path: &Path) -> u64 {
+    let shared: u64 = shared_checkpoint_files(path)
+        .map(|files| files.iter().map(|file| dir_size(file.as_path())).sum())
+        .unwrap_or(0);
+    dir_size(path) + shared
+}
+
+/// Returns the total size in bytes of the files under `path`
+fn dir_size(path: &Path) -> u64 {
+    match std::fs::metadata(path) {
+        Ok(metadata) if metadata.is_dir() => std::fs::read_dir(path)
+            .map(|entries| {
+                entries
+                    .filter_map(|entry| entry.ok())
+                    .map(|entry| dir_size(&entry.path()))
+                    .sum()
+            })
+            .unwrap_or(0),
+        Ok(metadata) => metadata.len(),
+        Err(_) => 0,
+    }
+}
+
 pub struct SnapshotManagerPort;
 
 impl Port for SnapshotManagerPort {
@@ -46,10 +436,23 @@ pub struct SnapshotManager {
     ///
     /// Used to verify that users do not watch for state ids that do not exist
     pub(crate) registered_state_ids: HashSet<StateID>,
-    /// Snapshot catalog of committed snapshots
-    uncommitted_catalog: FxHashMap<Epoch, FxHashMap<StateID, Snapshot>>,
     /// Snapshot catalog of uncommitted snapshots
-    committed_catalog: FxHashMap<Epoch, FxHashMap<StateID, Snapshot>>,
+    uncommitted_catalog: FxHashMap<Epoch, HashMap<StateID, Snapshot>>,
+    /// Committed epochs that still wait for the snapshots of some state ids
+    ///
+    /// Snapshots and epoch commits reach the SnapshotManager through different paths.
+    pending_commits: FxHashMap<Epoch, EpochCommit>,
+    /// Snapshot catalog of committed snapshots
+    committed_catalog: SnapshotCatalog,
+    /// Directory where the committed catalog is persisted
+    checkpoints_dir: PathBuf,
+    /// Decides which committed checkpoints are deleted
+    retention: RetentionPolicy,
+    /// Storage that committed checkpoints are copied to
+    storage: Option<Arc<dyn CheckpointStorage>>,
+    /// Creates savepoints, copies checkpoints to the storage and deletes expired ones
+    /// off the component thread
+    worker: CheckpointWorker,
     /// A map matching state ids to a channel Sender
     pub(crate) channels: FxHashMap<StateID, Sender<Snapshot>>,
     /// A map of component subscribers per State ID
@@ -57,38 +460,176 @@ pub struct SnapshotManager {
 }
 
 impl SnapshotManager {
-    pub fn new() -> Self {
+    /// Creates a SnapshotManager that continues `catalog` and persists it in `checkpoints_dir`
+    pub fn new(
+        checkpoints_dir: PathBuf,
+        catalog: SnapshotCatalog,
+        retention: RetentionPolicy,
+    ) -> Self {
         Self {
             ctx: ComponentContext::uninitialised(),
             manager_port: ProvidedPort::uninitialised(),
             registered_state_ids: HashSet::new(),
             uncommitted_catalog: FxHashMap::default(),
-            committed_catalog: FxHashMap::default(),
+            pending_commits: FxHashMap::default(),
+            committed_catalog: catalog,
+            checkpoints_dir,
+            retention,
+            storage: None,
+            worker: CheckpointWorker::new("snapshot_manager".to_string()),
             channels: FxHashMap::default(),
             subscribers: FxHashMap::default(),
         }
     }
 
+    /// Copies committed checkpoints and the catalog to `storage`
+    pub fn with_storage(mut self, storage: Option<Arc<dyn CheckpointStorage>>) -> Self {
+        self.storage = storage;
+        self
+    }
+
+    /// Returns the catalog of committed epochs
+    pub fn catalog(&self) -> &SnapshotCatalog {
+        &self.committed_catalog
+    }
+
     fn handle_epoch_commit(&mut self, commit: EpochCommit) {
-        let epoch = commit.0;
-
-        if let Some(snapshot_map) = self.uncommitted_catalog.remove(&epoch) {
-            for (state_id, snapshot) in &snapshot_map {
-                // check for component subscribers
-                if let Some(subscribers) = self.subscribers.get(state_id) {
-                    for sub in subscribers {
-                        sub.tell(snapshot.clone());
+        let epoch = commit.epoch;
+        self.pending_commits.insert(epoch, commit);
+        self.try_commit(epoch);
+    }
+
+    /// Moves the snapshots of `epoch` into the committed catalog once all of them have arrived
+    fn try_commit(&mut self, epoch: Epoch) {
+        let complete = match (
+            self.pending_commits.get(&epoch),
+            self.uncommitted_catalog.get(&epoch),
+        ) {
+            (Some(commit), Some(snapshot_map)) => commit
+                .state_ids
+                .iter()
+                .all(|state_id| snapshot_map.contains_key(state_id)),
+            (Some(commit), None) => commit.state_ids.is_empty(),
+            _ => false,
+        };
+        if !complete {
+            return;
+        }
+        let commit = self.pending_commits.remove(&epoch).unwrap();
+        let snapshot_map = self.uncommitted_catalog.remove(&epoch).unwrap_or_default();
+
+        for (state_id, snapshot) in &snapshot_map {
+            // check for component subscribers
+            if let Some(subscribers) = self.subscribers.get(state_id) {
+                for sub in subscribers {
+                    sub.tell(snapshot.clone());
+                }
+            }
+
+            // check for channel subscriptions
+            if let Some(channel) = self.channels.get(state_id) {
+                channel.send(snapshot.clone()).unwrap();
+            }
+        }
+        if let Some(SavepointRequest { path, promise }) = commit.savepoint {
+            let log = self.ctx.log().clone();
+            let snapshots = snapshot_map.clone();
+            // the job runs before the checkpoints it copies can expire
+            let _ = self.worker.execute(move || {
+                let result = create_savepoint(&path, epoch, &snapshots);
+                if let Err(err) = &result {
+                    error!(log, "Failed to create savepoint: {}", err);
+                }
+                let _ = promise.fulfil(result.map(|_| path));
+                Ok(())
+            });
+        }
+
+        // insert snapshot map into the committed catalog and persist it
+        self.committed_catalog.insert(epoch, snapshot_map);
+        let expired = self.expire_epochs();
+        if let Err(err) = self.committed_catalog.store(&self.checkpoints_dir) {
+            error!(
+                self.ctx.log(),
+                "Failed to persist snapshot catalog: {}", err
+            );
+        }
+        self.complete_commit(epoch, expired);
+    }
+
+    /// Removes the committed epochs that violate the retention policy from the catalog
+    fn expire_epochs(&mut self) -> Vec<CommittedEpoch> {
+        let now = crate::util::get_system_time();
+        self.committed_catalog
+            .expired(&self.retention, now)
+            .into_iter()
+            .filter_map(|epoch| self.committed_catalog.remove(epoch))
+            .collect()
+    }
+
+    /// Copies the snapshots of `epoch` and the catalog to the checkpoint storage, if there is one,
+    /// and deletes the checkpoints of `expired` epochs in the background
+    ///
+    /// The checkpoints are deleted only once the catalog no longer refers to them. Those in the
+    /// storage are kept as long as the catalog in the storage might still refer to them.
+    fn complete_commit(&self, epoch: Epoch, expired: Vec<CommittedEpoch>) {
+        let log = self.ctx.log().clone();
+        let checkpoints_dir = self.checkpoints_dir.clone();
+        let storage = self.storage.clone();
+        let catalog = storage.as_ref().map(|_| self.committed_catalog.clone());
+        let _ = self.worker.execute(move || {
+            let uploaded = match (&storage, catalog) {
+                (Some(storage), Some(catalog)) => {
+                    // the snapshots are copied before the catalog refers to them
+                    let result = catalog
+                        .get(epoch)
+                        .into_iter()
+                        .flat_map(|committed| committed.snapshots.values())
+                        .try_for_each(|s| upload_snapshot(storage.as_ref(), &checkpoints_dir, s))
+                        .and_then(|_| catalog.upload(storage.as_ref(), &checkpoints_dir));
+                    if let Err(err) = &result {
+                        error!(
+                            log,
+                            "Failed to copy checkpoints of {:?} to storage: {}", epoch, err
+                        );
                     }
+                    result.is_ok()
                 }
+                _ => false,
+            };
 
-                // check for channel subscriptions
-                if let Some(channel) = self.channels.get(state_id) {
-                    channel.send(snapshot.clone()).unwrap();
+            for committed in expired {
+                for snapshot in committed.snapshots.values() {
+                    let path = Path::new(&snapshot.snapshot_path);
+                    if let Err(err) = std::fs::remove_dir_all(path) {
+                        if err.kind() != std::io::ErrorKind::NotFound {
+                            error!(
+                                log,
+                                "Failed to delete checkpoint {}: {}", snapshot.snapshot_path, err
+                            );
+                        }
+                    }
+                    if let (Some(storage), true) = (&storage, uploaded) {
+                        let result = storage_key(&checkpoints_dir, &snapshot.snapshot_path)
+                            .and_then(|key| storage.delete_dir(&key).map_err(Into::into));
+                        if let Err(err) = result {
+                            error!(
+                                log,
+                                "Failed to delete checkpoint {} from storage: {}",
+                                snapshot.snapshot_path,
+                                err
+                            );
+                        }
+                    }
                 }
+                debug!(
+                    log,
+                    "Deleted checkpoints of {:?}",
+                    Epoch::new(committed.epoch)
+                );
             }
-            // insert snapshot map into the committed catalog
-            self.committed_catalog.insert(epoch, snapshot_map);
-        }
+            Ok(())
+        });
     }
 }
 
@@ -114,9 +655,10 @@ impl Provide<SnapshotManagerPort> for SnapshotManager {
                 let snapshot_map = self
                     .uncommitted_catalog
                     .entry(epoch)
-                    .or_insert_with(FxHashMap::default);
+                    .or_insert_with(HashMap::new);
 
                 snapshot_map.insert(id, snapshot);
+                self.try_commit(epoch);
             }
             SnapshotEvent::Register(id) => {
                 if self.registered_state_ids.contains(&id) {
@@ -133,3 +675,182 @@ impl Provide<SnapshotManagerPort> for SnapshotManager {
 }
 
 ignore_lifecycle!(SnapshotManager);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Waits until the worker of `manager` has completed the jobs submitted so far
+    fn await_worker(manager: &Arc<Component<SnapshotManager>>) {
+        let done = manager.on_definition(|cd| cd.worker.execute(|| Ok(())));
+        done.wait().unwrap();
+    }
+
+    fn snapshots(epoch: u64, state_ids: &[&str]) -> HashMap<StateID, Snapshot> {
+        state_ids
+            .iter()
+            .map(|id| {
+                let path = format!("{}/checkpoint_{}_{}", id, id, epoch);
+                (
+                    id.to_string(),
+                    Snapshot::new("Sled".to_string(), epoch, path),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn snapshot_catalog_test() -> ArconResult<()> {
+        let dir = tempfile::tempdir().unwrap();
+        assert!(SnapshotCatalog::load(dir.path())?.latest().is_none());
+
+        let mut catalog = SnapshotCatalog::default();
+        catalog.insert(Epoch::new(2), snapshots(2, &["a", "b"]));
+        catalog.insert(Epoch::new(1), snapshots(1, &["a", "b"]));
+        catalog.store(dir.path())?;
+
+        let catalog = SnapshotCatalog::load(dir.path())?;
+        assert_eq!(catalog.epochs(), vec![Epoch::new(1), Epoch::new(2)]);
+        assert_eq!(catalog.latest(), Some(Epoch::new(2)));
+        assert_eq!(catalog.get(Epoch::new(1)).unwrap().snapshots.len(), 2);
+        let snapshot = catalog.snapshot(Epoch::new(2), "b").unwrap();
+        // Relative snapshot paths are resolved against the catalog directory
+        let expected = dir.path().join("b/checkpoint_b_2");
+        assert_eq!(Path::new(&snapshot.snapshot_path), expected);
+        assert!(catalog.snapshot(Epoch::new(3), "a").is_none());
+        Ok(())
+    }
+
+    #[test]
+    fn retention_policy_test() {
+        // Epochs 0..5 committed one second apart, each with 10 bytes of snapshots
+        let mut catalog = SnapshotCatalog::default();
+        for epoch in 0..5 {
+            catalog.insert(Epoch::new(epoch), snapshots(epoch, &["a"]));
+            let committed = catalog.epochs.last_mut().unwrap();
+            committed.committed_at = epoch * 1000;
+            committed.size = 10;
+        }
+        let expired = |policy: RetentionPolicy| -> Vec<u64> {
+            catalog
+                .expired(&policy, 5000)
+                .iter()
+                .map(|e| e.epoch)
+                .collect()
+        };
+
+        assert!(expired(RetentionPolicy::default()).is_empty());
+        let max_epochs = RetentionPolicy {
+            max_epochs: Some(2),
+            ..Default::default()
+        };
+        assert_eq!(expired(max_epochs), vec![0, 1, 2]);
+        let max_age = RetentionPolicy {
+            max_age: Some(2500),
+            ..Default::default()
+        };
+        assert_eq!(expired(max_age), vec![0, 1, 2]);
+        let max_size = RetentionPolicy {
+            max_size: Some(25),
+            ..Default::default()
+        };
+        assert_eq!(expired(max_size), vec![0, 1, 2]);
+        // The newest epoch is kept even if it violates the policy
+        let keep_none = RetentionPolicy {
+            max_epochs: Some(0),
+            max_age: Some(0),
+            max_size: Some(0),
+        };
+        assert_eq!(expired(keep_none), vec![0, 1, 2, 3]);
+    }
+
+    #[test]
+    fn snapshot_manager_retention_test() {
+        let dir = tempfile::tempdir().unwrap();
+        let retention = RetentionPolicy {
+            max_epochs: Some(1),
+            ..Default::default()
+        };
+        let system = KompactConfig::default().build().unwrap();
+        let manager = system.create(|| {
+            SnapshotManager::new(dir.path().to_path_buf(), Default::default(), retention)
+        });
+
+        let mut paths = Vec::new();
+        for epoch in 0..3 {
+            let path = dir.path().join(format!("a/checkpoint_a_{}", epoch));
+            std::fs::create_dir_all(&path).unwrap();
+            let snapshot = Snapshot::new(
+                "Sled".to_string(),
+                epoch,
+                path.to_string_lossy().into_owned(),
+            );
+            manager.on_definition(|cd| {
+                let _ = cd.handle(SnapshotEvent::Snapshot("a".to_string(), snapshot));
+                cd.handle_epoch_commit(EpochCommit {
+                    epoch: Epoch::new(epoch),
+                    state_ids: vec!["a".to_string()],
+                    savepoint: None,
+                });
+            });
+            paths.push(path);
+        }
+        await_worker(&manager);
+
+        assert!(!paths[0].exists());
+        assert!(!paths[1].exists());
+        assert!(paths[2].exists());
+        let catalog = SnapshotCatalog::load(dir.path()).unwrap();
+        assert_eq!(catalog.epochs(), vec![Epoch::new(2)]);
+        system.shutdown().unwrap();
+    }
+
+    #[test]
+    fn snapshot_manager_storage_test() {
+        let dir = tempfile::tempdir().unwrap();
+        let storage = Arc::new(arcon_state::LocalStorage::new(dir.path().join("remote")));
+        let retention = RetentionPolicy {
+            max_epochs: Some(1),
+            ..Default::default()
+        };
+        let checkpoints_dir = dir.path().join("checkpoints");
+        let system = KompactConfig::default().build().unwrap();
+        let manager = system.create(|| {
+            SnapshotManager::new(checkpoints_dir.clone(), Default::default(), retention)
+                .with_storage(Some(storage.clone()))
+        });
+
+        for epoch in 0..2 {
+            let path = checkpoints_dir.join(format!("a/checkpoint_a_{}", epoch));
+            std::fs::create_dir_all(&path).unwrap();
+            std::fs::write(path.join("data"), epoch.to_string()).unwrap();
+            let snapshot = Snapshot::new(
+                "Sled".to_string(),
+                epoch,
+                path.to_string_lossy().into_owned(),
+            );
+            manager.on_definition(|cd| {
+                let _ = cd.handle(SnapshotEvent::Snapshot("a".to_string(), snapshot));
+                cd.handle_epoch_commit(EpochCommit {
+                    epoch: Epoch::new(epoch),
+                    state_ids: vec!["a".to_string()],
+                    savepoint: None,
+                });
+            });
+        }
+        await_worker(&manager);
+
+        // Expired checkpoints are deleted from the storage as well
+        assert_eq!(storage.list("a").unwrap(), vec!["a/checkpoint_a_1/data"]);
+
+        // The stored catalog resolves against the checkpoint directory of another machine
+        let restore_dir = tempfile::tempdir().unwrap();
+        let catalog = SnapshotCatalog::fetch(storage.as_ref(), restore_dir.path()).unwrap();
+        assert_eq!(catalog.epochs(), vec![Epoch::new(1)]);
+        let snapshot = catalog.snapshot(Epoch::new(1), "a").unwrap();
+        fetch_snapshot(storage.as_ref(), restore_dir.path(), snapshot).unwrap();
+        let data = std::fs::read(Path::new(&snapshot.snapshot_path).join("data")).unwrap();
+        assert_eq!(data, b"1");
+        system.shutdown().unwrap();
+    }
+}
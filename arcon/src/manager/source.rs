@@ -1,4 +1,7 @@
-use super::epoch::EpochEvent;
+use super::{
+    epoch::EpochEvent,
+    snapshot::{SnapshotEvent, SnapshotManagerPort},
+};
 #[cfg(feature = "metrics")]
 use metrics::{gauge, register_gauge};
 
@@ -15,6 +18,8 @@ use std::sync::Arc;
 pub enum SourceManagerEvent {
     /// Signal the end of a Source Stream
     End,
+    /// A source has completed a checkpoint
+    Checkpoint(SnapshotEvent),
 }
 
 pub struct SourceManagerPort;
@@ -30,6 +35,8 @@ pub(crate) struct SourceManager<B: Backend> {
     /// Component Context
     ctx: ComponentContext<Self>,
     manager_port: ProvidedPort<SourceManagerPort>,
+    /// Port to the SnapshotManager of the application
+    pub(crate) snapshot_manager_port: RequiredPort<SnapshotManagerPort>,
     /// What type of time that is used.
     ///
     /// Either Event or Processing
@@ -44,6 +51,8 @@ pub(crate) struct SourceManager<B: Backend> {
     /// May contain more than 1 component if the source supports parallelism
     pub(crate) sources: Vec<Arc<dyn AbstractComponent<Message = SourceEvent>>>,
     pub source_refs: Vec<ActorRefStrong<SourceEvent>>,
+    /// Number of sources that have ended
+    ended_sources: usize,
     /// A shared backend for sources
     _backend: Arc<B>,
     /// Reference to the EpochManager
@@ -67,12 +76,14 @@ impl<B: Backend> SourceManager<B> {
         Self {
             ctx: ComponentContext::uninitialised(),
             manager_port: ProvidedPort::uninitialised(),
+            snapshot_manager_port: RequiredPort::uninitialised(),
             arcon_time,
             watermark_interval,
             watermark_timeout: None,
             state_id,
             sources: Vec::new(),
             source_refs: Vec::new(),
+            ended_sources: 0,
             _backend: backend,
             epoch_manager,
             logger,
@@ -151,9 +162,24 @@ where
     fn handle(&mut self, event: SourceManagerEvent) -> Handled {
         match event {
             SourceManagerEvent::End => {
-                self.epoch_manager.tell(EpochEvent::Halt);
+                self.ended_sources += 1;
+                if self.ended_sources == self.sources.len() {
+                    self.epoch_manager.tell(EpochEvent::Halt);
+                }
+            }
+            SourceManagerEvent::Checkpoint(snapshot_event) => {
+                self.snapshot_manager_port.trigger(snapshot_event);
             }
         }
         Handled::Ok
     }
 }
+
+impl<B> Require<SnapshotManagerPort> for SourceManager<B>
+where
+    B: Backend,
+{
+    fn handle(&mut self, _: Never) -> Handled {
+        unreachable!("can't be instantiated!");
+    }
+}
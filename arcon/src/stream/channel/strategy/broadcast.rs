@@ -20,6 +20,8 @@ where
     curr_buffer: BufferWriter<ArconEventWrapper<A>>,
     /// An Identifier that is embedded in each outgoing message
     sender_id: NodeID,
+    /// Offset that is added to the sender identifier
+    sender_offset: u32,
     /// Struct holding information regarding the BufferPool
     pool_info: PoolInfo,
 }
@@ -54,6 +56,7 @@ where
             channels: channels.into_iter().map(Arc::new).collect(),
             curr_buffer,
             sender_id,
+            sender_offset: 0,
             pool_info,
         }
     }
@@ -153,6 +156,18 @@ where
     pub fn num_channels(&self) -> usize {
         self.channels.len()
     }
+
+    #[inline]
+    pub fn set_sender_id(&mut self, sender_id: NodeID) {
+        self.sender_id = NodeID::new(sender_id.id + self.sender_offset);
+    }
+
+    /// Offset the sender identifier so that it is unique among the inputs of the receiver
+    #[inline]
+    pub fn set_sender_offset(&mut self, offset: u32) {
+        self.sender_id = NodeID::new(self.sender_id.id - self.sender_offset + offset);
+        self.sender_offset = offset;
+    }
 }
 
 #[cfg(test)]
@@ -19,6 +19,8 @@ where
     channel: Arc<Channel<A>>,
     /// An identifier that is embedded with outgoing messages
     sender_id: NodeID,
+    /// Offset that is added to the sender identifier
+    sender_offset: u32,
     /// Struct holding information regarding the BufferPool
     pool_info: PoolInfo,
 }
@@ -44,6 +46,7 @@ where
             curr_buffer,
             channel: Arc::new(channel),
             sender_id,
+            sender_offset: 0,
             pool_info,
         }
     }
@@ -72,6 +75,18 @@ where
             }
         }
     }
+    #[inline]
+    pub fn set_sender_id(&mut self, sender_id: NodeID) {
+        self.sender_id = NodeID::new(sender_id.id + self.sender_offset);
+    }
+
+    /// Offset the sender identifier so that it is unique among the inputs of the receiver
+    #[inline]
+    pub fn set_sender_offset(&mut self, offset: u32) {
+        self.sender_id = NodeID::new(self.sender_id.id - self.sender_offset + offset);
+        self.sender_offset = offset;
+    }
+
     #[inline]
     fn push_event(&mut self, event: ArconEvent<A>) -> Option<ArconMessage<A>> {
         self.curr_buffer.push(event.into()).map(|e| {
@@ -2,12 +2,79 @@ use crate::{
     buffer::event::{BufferPool, BufferWriter, PoolInfo},
     data::{
         partition::shard_lookup_with_key, ArconEvent, ArconEventWrapper, ArconMessage, ArconType,
-        NodeID,
+        Epoch, NodeID,
     },
     dataflow::stream::KeyBuilder,
     stream::channel::Channel,
 };
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
+
+/// Maps key groups onto a set of channels
+#[derive(Clone)]
+pub struct RoutingTable<A: ArconType> {
+    /// Channels to route onto
+    pub channels: Vec<Channel<A>>,
+    /// Channel index for each key group
+    pub key_groups: Vec<usize>,
+}
+
+struct RouteInner<A: ArconType> {
+    table: Option<RoutingTable<A>>,
+    activation: Epoch,
+    version: u64,
+}
+
+/// A routing table shared between a managed operator and its upstream Keyed strategies
+///
+/// Updates are scheduled for a specific [Epoch]. Each Keyed strategy switches to the
+/// new table right after it has forwarded the marker of that epoch, which makes the
+/// epoch a consistent cut for the rescaled operator.
+pub struct KeyedRoute<A: ArconType> {
+    inner: Mutex<RouteInner<A>>,
+}
+
+impl<A: ArconType> KeyedRoute<A> {
+    /// Creates an empty route
+    pub fn new() -> Self {
+        Self {
+            inner: Mutex::new(RouteInner {
+                table: None,
+                activation: Epoch::new(0),
+                version: 0,
+            }),
+        }
+    }
+
+    /// Schedule a new routing table to be used from `activation` and onwards
+    pub fn schedule(&self, activation: Epoch, table: RoutingTable<A>) {
+        let mut inner = self.inner.lock().unwrap();
+        inner.table = Some(table);
+        inner.activation = activation;
+        inner.version += 1;
+    }
+
+    /// Returns the latest routing table and its version
+    pub fn latest(&self) -> Option<(u64, RoutingTable<A>)> {
+        let inner = self.inner.lock().unwrap();
+        inner.table.clone().map(|t| (inner.version, t))
+    }
+
+    /// Returns a newer routing table if one is active for `epoch`
+    fn poll(&self, version: u64, epoch: Epoch) -> Option<(u64, RoutingTable<A>)> {
+        let inner = self.inner.lock().unwrap();
+        if inner.version > version && inner.activation <= epoch {
+            inner.table.clone().map(|t| (inner.version, t))
+        } else {
+            None
+        }
+    }
+}
+
+impl<A: ArconType> Default for KeyedRoute<A> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
 
 /// A Channel Strategy for Keyed Data Streams
 ///
@@ -20,8 +87,19 @@ where
     buffer_pool: BufferPool<ArconEventWrapper<A>>,
     /// Number of ranges on the contiguous key space
     key_ranges: u64,
+    /// Channel index for each key range
+    key_groups: Vec<usize>,
+    /// Shared route and the version currently in use
+    route: Option<(Arc<KeyedRoute<A>>, u64)>,
+    /// The last forwarded epoch marker
+    ///
+    /// Rerouting is deferred until the next event that is not a marker of the
+    /// same epoch, as a managed node sends one marker per key group.
+    last_epoch: Option<Epoch>,
     /// An identifier that is embedded with outgoing messages
     sender_id: NodeID,
+    /// Offset that is added to the sender identifier
+    sender_offset: u32,
     /// Extract the Key from A
     key_builder: KeyBuilder<A>,
     buffers: Vec<BufferWriter<ArconEventWrapper<A>>>,
@@ -63,13 +141,69 @@ where
         Keyed {
             buffer_pool,
             key_ranges: channels_len,
+            key_groups: (0..channels.len()).collect(),
+            route: None,
+            last_epoch: None,
             sender_id,
+            sender_offset: 0,
             channels: channels.into_iter().map(Arc::new).collect::<Vec<_>>(),
             buffers,
             _pool_info: pool_info,
             key_builder,
         }
     }
+
+    /// Creates a Keyed strategy that routes key groups according to a shared [KeyedRoute]
+    pub fn with_route(
+        route: Arc<KeyedRoute<A>>,
+        sender_id: NodeID,
+        pool_info: PoolInfo,
+        key_builder: KeyBuilder<A>,
+    ) -> Keyed<A> {
+        let (version, table) = route
+            .latest()
+            .expect("KeyedRoute must be initialised before use");
+        let mut keyed = Keyed::new(table.channels, sender_id, pool_info, key_builder);
+        keyed.key_ranges = table.key_groups.len() as u64;
+        keyed.key_groups = table.key_groups;
+        keyed.route = Some((route, version));
+        keyed
+    }
+
+    /// Switch to a newer routing table if one has been scheduled for `epoch`
+    ///
+    /// Must only be called once all buffers have been flushed by an epoch marker.
+    fn reroute(&mut self, epoch: Epoch) {
+        let update = match &self.route {
+            Some((route, version)) => route.poll(*version, epoch),
+            None => None,
+        };
+        if let Some((version, table)) = update {
+            self.buffers.truncate(table.channels.len());
+            while self.buffers.len() < table.channels.len() {
+                self.buffers.push(self.buffer_pool.get());
+            }
+            self.channels = table.channels.into_iter().map(Arc::new).collect();
+            self.key_ranges = table.key_groups.len() as u64;
+            self.key_groups = table.key_groups;
+            if let Some((_, v)) = &mut self.route {
+                *v = version;
+            }
+        }
+    }
+
+    #[inline]
+    pub fn set_sender_id(&mut self, sender_id: NodeID) {
+        self.sender_id = NodeID::new(sender_id.id + self.sender_offset);
+    }
+
+    /// Offset the sender identifier so that it is unique among the inputs of the receiver
+    #[inline]
+    pub fn set_sender_offset(&mut self, offset: u32) {
+        self.sender_id = NodeID::new(self.sender_id.id - self.sender_offset + offset);
+        self.sender_offset = offset;
+    }
+
     #[inline]
     fn push_event(&mut self, index: usize, event: ArconEvent<A>) -> Option<ArconMessage<A>> {
         let writer = &mut self.buffers[index];
@@ -92,15 +226,22 @@ where
 
     #[inline]
     pub fn add(&mut self, event: ArconEvent<A>) -> Vec<(Arc<Channel<A>>, ArconMessage<A>)> {
+        if let Some(epoch) = self.last_epoch {
+            if !matches!(&event, ArconEvent::Epoch(e) if *e == epoch) {
+                self.last_epoch = None;
+                self.reroute(epoch);
+            }
+        }
         match &event {
             ArconEvent::Element(e) => {
                 // Get key placement
                 let key = self.key_builder.get_key(&e.data);
                 // Calculate which key range index is responsible for this key
-                let index = shard_lookup_with_key(key, self.key_ranges);
+                let range = shard_lookup_with_key(key, self.key_ranges);
+                let index = self.key_groups[range as usize];
 
-                self.push_event(index as usize, event)
-                    .map(move |msg| vec![(self.channels[index as usize].clone(), msg)])
+                self.push_event(index, event)
+                    .map(move |msg| vec![(self.channels[index].clone(), msg)])
                     .unwrap_or_else(Vec::new)
             }
             _ => {
@@ -134,6 +275,11 @@ where
                         }
                     }
                 }
+                if let ArconEvent::Epoch(epoch) = &event {
+                    if self.route.is_some() {
+                        self.last_epoch = Some(*epoch);
+                    }
+                }
                 outputs
             }
         }
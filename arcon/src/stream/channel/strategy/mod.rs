@@ -1,7 +1,7 @@
 use crate::{
     data::{
         flight_serde::{reliable_remote::ReliableSerde, FlightSerde},
-        ArconEvent, ArconMessage, ArconType,
+        ArconEvent, ArconMessage, ArconType, NodeID,
     },
     stream::channel::Channel,
 };
@@ -27,6 +27,8 @@ where
     Broadcast(broadcast::Broadcast<A>),
     /// Partition data to a set of `Channels` based on keyed hash
     Keyed(keyed::Keyed<A>),
+    /// Pass every event to each of the strategies
+    Fanout(Vec<ChannelStrategy<A>>),
     /// A strategy that prints to the console
     Console,
     /// A strategy that simply does nothing
@@ -49,6 +51,10 @@ where
             ChannelStrategy::Forward(s) => s.add(event),
             ChannelStrategy::Keyed(s) => s.add(event),
             ChannelStrategy::Broadcast(s) => s.add(event),
+            ChannelStrategy::Fanout(strategies) => strategies
+                .iter_mut()
+                .flat_map(|s| s.push(event.clone()))
+                .collect(),
             ChannelStrategy::Console => {
                 println!("{:?}", event);
                 Vec::new()
@@ -57,6 +63,40 @@ where
         }
     }
 
+    /// Sets the sender identifier embedded in outgoing messages
+    ///
+    /// Events that are still buffered will be sent with the new identifier.
+    #[inline]
+    pub fn set_sender_id(&mut self, sender_id: NodeID) {
+        match self {
+            ChannelStrategy::Forward(s) => s.set_sender_id(sender_id),
+            ChannelStrategy::Keyed(s) => s.set_sender_id(sender_id),
+            ChannelStrategy::Broadcast(s) => s.set_sender_id(sender_id),
+            ChannelStrategy::Fanout(strategies) => strategies
+                .iter_mut()
+                .for_each(|s| s.set_sender_id(sender_id)),
+            ChannelStrategy::Console => (),
+            ChannelStrategy::Mute => (),
+        }
+    }
+
+    /// Sets an offset that is added to the sender identifier
+    ///
+    /// Used when the receiver has several inputs whose senders would otherwise share identifiers.
+    #[inline]
+    pub fn set_sender_offset(&mut self, offset: u32) {
+        match self {
+            ChannelStrategy::Forward(s) => s.set_sender_offset(offset),
+            ChannelStrategy::Keyed(s) => s.set_sender_offset(offset),
+            ChannelStrategy::Broadcast(s) => s.set_sender_offset(offset),
+            ChannelStrategy::Fanout(strategies) => strategies
+                .iter_mut()
+                .for_each(|s| s.set_sender_offset(offset)),
+            ChannelStrategy::Console => (),
+            ChannelStrategy::Mute => (),
+        }
+    }
+
     /// Returns number of outgoing channels
     #[inline]
     #[allow(dead_code)]
@@ -65,6 +105,9 @@ where
             ChannelStrategy::Forward(_) => 1,
             ChannelStrategy::Broadcast(s) => s.num_channels(),
             ChannelStrategy::Keyed(s) => s.num_channels(),
+            ChannelStrategy::Fanout(strategies) => {
+                strategies.iter().map(|s| s.num_channels()).sum()
+            }
             ChannelStrategy::Console => 0,
             ChannelStrategy::Mute => 0,
         }
@@ -0,0 +1,146 @@
+use crate::error::ArconResult;
+use arcon_state::AsyncCheckpoint;
+use kompact::prelude::{promise, Fulfillable, KFuture};
+use std::{
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        mpsc::{channel, Sender},
+        Arc, Mutex,
+    },
+    thread::{Builder, JoinHandle},
+};
+
+type Job = Box<dyn FnOnce() + Send>;
+
+/// Completes the asynchronous phase of checkpoints on a thread of its own
+///
+/// Jobs are completed in the order they were submitted, as backends that share
+/// files between their checkpoints require.
+pub(crate) struct CheckpointWorker {
+    sender: Mutex<Option<Sender<Job>>>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl CheckpointWorker {
+    pub fn new(name: String) -> Self {
+        let (sender, receiver) = channel::<Job>();
+        let handle = Builder::new()
+            .name(name)
+            .spawn(move || {
+                for job in receiver {
+                    job();
+                }
+            })
+            .expect("Failed to spawn checkpoint thread");
+        CheckpointWorker {
+            sender: Mutex::new(Some(sender)),
+            handle: Some(handle),
+        }
+    }
+
+    /// Submit checkpoints to be completed after all previously submitted ones
+    pub fn submit(&self, checkpoints: Vec<AsyncCheckpoint>) -> KFuture<ArconResult<()>> {
+        self.execute(move || {
+            checkpoints
+                .into_iter()
+                .try_for_each(|checkpoint| checkpoint.complete())
+                .map_err(Into::into)
+        })
+    }
+
+    /// Run `job` after all previously submitted jobs
+    pub fn execute<T, F>(&self, job: F) -> KFuture<ArconResult<T>>
+    where
+        T: Send + Sized + 'static,
+        F: FnOnce() -> ArconResult<T> + Send + 'static,
+    {
+        let (promise, future) = promise();
+        let job: Job = Box::new(move || {
+            let _ = promise.fulfil(job());
+        });
+        self.sender
+            .lock()
+            .unwrap()
+            .as_ref()
+            .expect("CheckpointWorker is shut down")
+            .send(job)
+            .expect("Checkpoint thread terminated");
+        future
+    }
+}
+
+impl Drop for CheckpointWorker {
+    fn drop(&mut self) {
+        // Let the thread finish the checkpoints that are in progress
+        self.sender.lock().unwrap().take();
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+/// A fixed number of [CheckpointWorker]s shared by the Nodes of an application
+///
+/// Every Node is assigned a single worker, which keeps its checkpoints in order.
+#[derive(Clone)]
+pub(crate) struct CheckpointPool {
+    workers: Arc<Vec<Arc<CheckpointWorker>>>,
+    next: Arc<AtomicUsize>,
+}
+
+impl CheckpointPool {
+    pub fn new(threads: usize) -> Self {
+        let workers = (0..threads.max(1))
+            .map(|index| Arc::new(CheckpointWorker::new(format!("checkpoint-{}", index))))
+            .collect();
+        CheckpointPool {
+            workers: Arc::new(workers),
+            next: Arc::new(AtomicUsize::new(0)),
+        }
+    }
+
+    /// Returns the worker for the next Node, assigning the workers in turn
+    pub fn assign(&self) -> Arc<CheckpointWorker> {
+        let index = self.next.fetch_add(1, Ordering::Relaxed) % self.workers.len();
+        self.workers[index].clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+
+    #[test]
+    fn checkpoint_worker_test() {
+        let worker = CheckpointWorker::new("checkpoint_worker_test".to_string());
+        let completed = Arc::new(Mutex::new(Vec::new()));
+        let checkpoint = |id: u64| {
+            let completed = completed.clone();
+            AsyncCheckpoint::new(move || {
+                completed.lock().unwrap().push(id);
+                Ok(())
+            })
+        };
+
+        let first = worker.submit(vec![checkpoint(1), checkpoint(2)]);
+        let failed = worker.submit(vec![AsyncCheckpoint::new(|| {
+            Err(std::io::Error::new(std::io::ErrorKind::Other, "upload failed").into())
+        })]);
+        let last = worker.submit(vec![checkpoint(3)]);
+
+        assert!(last.wait().is_ok());
+        assert!(failed.wait().is_err());
+        assert!(first.wait().is_ok());
+        assert_eq!(*completed.lock().unwrap(), vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn checkpoint_pool_test() {
+        let pool = CheckpointPool::new(2);
+        let first = pool.assign();
+        let second = pool.assign();
+        assert!(!Arc::ptr_eq(&first, &second));
+        assert!(Arc::ptr_eq(&first, &pool.assign()));
+    }
+}
@@ -0,0 +1,277 @@
+use crate::{
+    data::{partition::KeyRange, Epoch},
+    stream::{
+        node::timer::{ArconTimer, Timer},
+        operator::{Operator, OperatorContext},
+    },
+};
+use arcon_state::Backend;
+use fxhash::FxHashMap;
+use std::sync::{Arc, Mutex};
+
+/// A unit of keyed state that can be moved between nodes
+///
+/// Each key group has its own backend, operator state and timer so that
+/// it can be handed over as a whole once the operator is rescaled.
+pub struct KeyGroup<OP: Operator + 'static, B: Backend> {
+    /// Key group identifier
+    pub(crate) id: u64,
+    /// Descriptor used for checkpoints and epoch acknowledgements
+    pub(crate) descriptor: String,
+    /// Backend holding the state of the key group
+    pub(crate) backend: Arc<B>,
+    state: OP::OperatorState,
+    timer: Box<dyn ArconTimer<Value = OP::TimerState>>,
+}
+
+impl<OP: Operator + 'static, B: Backend> KeyGroup<OP, B> {
+    pub fn new(id: u64, descriptor: String, backend: Arc<B>, state: OP::OperatorState) -> Self {
+        let timer_id = format!("_{}_timer", descriptor);
+        let timer = Timer::new(timer_id, backend.clone());
+        Self {
+            id,
+            descriptor,
+            backend,
+            state,
+            timer: Box::new(timer),
+        }
+    }
+
+    /// Swap state and timer of the key group with the ones in `ctx`
+    #[inline]
+    pub(crate) fn swap(&mut self, ctx: &mut OperatorContext<OP::TimerState, OP::OperatorState>) {
+        std::mem::swap(&mut self.state, &mut ctx.state);
+        std::mem::swap(&mut self.timer, &mut ctx.timer);
+    }
+}
+
+/// An ongoing rescale of a managed operator
+pub(crate) struct Rescale<OP: Operator + 'static, B: Backend> {
+    /// Generation of the nodes that take over
+    generation: u64,
+    /// The last epoch that is processed by the previous generation
+    epoch: Option<Epoch>,
+    /// Key groups released by nodes of the previous generation
+    released: FxHashMap<u64, KeyGroup<OP, B>>,
+    /// Set once all nodes of the previous generation have retired
+    retired: bool,
+}
+
+/// State shared between a NodeManager and the nodes of a managed operator
+pub(crate) struct ManagedScope<OP: Operator + 'static, B: Backend> {
+    rescale: Mutex<Option<Rescale<OP, B>>>,
+}
+
+impl<OP: Operator + 'static, B: Backend> ManagedScope<OP, B> {
+    pub fn new() -> Self {
+        Self {
+            rescale: Mutex::new(None),
+        }
+    }
+
+    /// Begin a rescale towards `generation`
+    pub fn begin(&self, generation: u64) {
+        *self.rescale.lock().unwrap() = Some(Rescale {
+            generation,
+            epoch: None,
+            released: FxHashMap::default(),
+            retired: false,
+        });
+    }
+
+    /// Set the last epoch of the previous generation
+    pub fn set_epoch(&self, epoch: Epoch) {
+        if let Some(rescale) = self.rescale.lock().unwrap().as_mut() {
+            rescale.epoch = Some(epoch);
+        }
+    }
+
+    /// Returns true if a node of `generation` should retire after completing `epoch`
+    pub fn should_retire(&self, generation: u64, epoch: Epoch) -> bool {
+        match self.rescale.lock().unwrap().as_ref() {
+            Some(rescale) => rescale.generation > generation && rescale.epoch == Some(epoch),
+            None => false,
+        }
+    }
+
+    /// Hand over key groups to the next generation
+    pub fn release(&self, groups: impl IntoIterator<Item = KeyGroup<OP, B>>) {
+        if let Some(rescale) = self.rescale.lock().unwrap().as_mut() {
+            for group in groups {
+                rescale.released.insert(group.id, group);
+            }
+        }
+    }
+
+    /// Take released key groups in `pending`
+    ///
+    /// Returns the last epoch of the previous generation together with the groups
+    /// that were available. Pending groups that were taken are removed from `pending`.
+    pub fn adopt(&self, pending: &mut Vec<u64>) -> (Option<Epoch>, Vec<KeyGroup<OP, B>>) {
+        let mut guard = self.rescale.lock().unwrap();
+        match guard.as_mut() {
+            Some(rescale) => {
+                let mut adopted = Vec::new();
+                pending.retain(|id| match rescale.released.remove(id) {
+                    Some(group) => {
+                        adopted.push(group);
+                        false
+                    }
+                    None => true,
+                });
+                let epoch = rescale.epoch;
+                if rescale.retired && rescale.released.is_empty() {
+                    *guard = None;
+                }
+                (epoch, adopted)
+            }
+            None => (None, Vec::new()),
+        }
+    }
+
+    /// Returns true if there is no ongoing rescale
+    pub fn is_idle(&self) -> bool {
+        self.rescale.lock().unwrap().is_none()
+    }
+
+    /// Marks that all nodes of the previous generation have retired
+    ///
+    /// The rescale is completed once the remaining key groups have been adopted.
+    pub fn retired(&self) {
+        let mut guard = self.rescale.lock().unwrap();
+        if let Some(rescale) = guard.as_mut() {
+            rescale.retired = true;
+            if rescale.released.is_empty() {
+                *guard = None;
+            }
+        }
+    }
+}
+
+/// Bookkeeping for a Node that executes a managed operator
+pub(crate) struct ManagedNode<OP: Operator + 'static, B: Backend> {
+    /// Key groups owned by the node
+    ///
+    /// The entry of the active key group holds the placeholder state
+    /// while its actual state lives in the OperatorContext.
+    groups: FxHashMap<u64, KeyGroup<OP, B>>,
+    /// Key group currently loaded into the OperatorContext
+    active: Option<u64>,
+    /// Key groups that still have to be handed over by the previous generation
+    pub(crate) pending: Vec<u64>,
+    /// Range of key groups the node is responsible for
+    pub(crate) range: KeyRange,
+    /// Total amount of key groups of the operator
+    total_groups: u64,
+    /// Generation of the node
+    pub(crate) generation: u64,
+    pub(crate) scope: Arc<ManagedScope<OP, B>>,
+    /// Flag indicating that the node has handed over its key groups
+    pub(crate) retired: bool,
+    /// Number of elements processed during the current epoch
+    pub(crate) epoch_load: u64,
+}
+
+impl<OP: Operator + 'static, B: Backend> ManagedNode<OP, B> {
+    /// Creates a node that owns `groups` from the start
+    pub fn new(
+        range: KeyRange,
+        total_groups: u64,
+        groups: Vec<KeyGroup<OP, B>>,
+        scope: Arc<ManagedScope<OP, B>>,
+    ) -> Self {
+        Self {
+            groups: groups.into_iter().map(|g| (g.id, g)).collect(),
+            active: None,
+            pending: Vec::new(),
+            range,
+            total_groups,
+            generation: 0,
+            scope,
+            retired: false,
+            epoch_load: 0,
+        }
+    }
+
+    /// Creates a node that takes over `range` from a previous generation
+    pub fn takeover(
+        range: KeyRange,
+        total_groups: u64,
+        generation: u64,
+        scope: Arc<ManagedScope<OP, B>>,
+    ) -> Self {
+        Self {
+            groups: FxHashMap::default(),
+            active: None,
+            pending: range.iter().collect(),
+            range,
+            total_groups,
+            generation,
+            scope,
+            retired: false,
+            epoch_load: 0,
+        }
+    }
+
+    #[inline]
+    pub fn key_group(&self, key: u64) -> u64 {
+        crate::data::partition::shard_lookup_with_key(key, self.total_groups)
+    }
+
+    /// Returns the identifiers of all owned key groups
+    pub fn group_ids(&self) -> Vec<u64> {
+        let mut ids: Vec<u64> = self.groups.keys().copied().collect();
+        ids.sort_unstable();
+        ids
+    }
+
+    pub fn groups(&self) -> impl Iterator<Item = &KeyGroup<OP, B>> {
+        self.groups.values()
+    }
+
+    pub fn insert(&mut self, group: KeyGroup<OP, B>) {
+        self.groups.insert(group.id, group);
+    }
+
+    /// Load the state of `group` into `ctx`
+    ///
+    /// Returns false if the node does not own the key group.
+    #[inline]
+    pub fn activate(
+        &mut self,
+        group: u64,
+        ctx: &mut OperatorContext<OP::TimerState, OP::OperatorState>,
+    ) -> bool {
+        if self.active == Some(group) {
+            return true;
+        }
+        if !self.groups.contains_key(&group) {
+            return false;
+        }
+        self.deactivate(ctx);
+        if let Some(key_group) = self.groups.get_mut(&group) {
+            key_group.swap(ctx);
+            self.active = Some(group);
+        }
+        true
+    }
+
+    /// Move the state of the active key group from `ctx` back into its entry
+    #[inline]
+    pub fn deactivate(&mut self, ctx: &mut OperatorContext<OP::TimerState, OP::OperatorState>) {
+        if let Some(active) = self.active.take() {
+            if let Some(key_group) = self.groups.get_mut(&active) {
+                key_group.swap(ctx);
+            }
+        }
+    }
+
+    /// Remove all key groups from the node
+    pub fn drain(
+        &mut self,
+        ctx: &mut OperatorContext<OP::TimerState, OP::OperatorState>,
+    ) -> Vec<KeyGroup<OP, B>> {
+        self.deactivate(ctx);
+        self.groups.drain().map(|(_, g)| g).collect()
+    }
+}
@@ -1,11 +1,17 @@
+/// Thread completing the asynchronous phase of checkpoints
+pub(crate) mod checkpoint;
 /// Common code between node types
 pub mod common;
 /// Debug version of [Node]
 pub mod debug;
+/// Key groups of nodes with a managed parallelism
+pub(crate) mod managed;
 /// SourceNode components that drives the execution of sources
 pub mod source;
-/// Event-time timer implementation
+/// Event-time and processing-time timer implementation
 pub(crate) mod timer;
+/// Unaligned checkpoints and their in-flight messages
+pub(crate) mod unaligned;
 
 #[cfg(feature = "metrics")]
 use metrics::{
@@ -15,7 +21,7 @@ use metrics::{
 #[cfg(all(feature = "hardware_counters", target_os = "linux", not(test)))]
 use perf_event::{Builder, Group};
 
-use crate::application::conf::logger::ArconLogger;
+use crate::application::conf::{logger::ArconLogger, processing_time_interval_default};
 use crate::{
     data::{flight_serde::reliable_remote::ReliableSerde, RawArconMessage, *},
     dataflow::builder::KeyBuilder,
@@ -28,19 +34,34 @@ use crate::{
     reportable_error,
     stream::{
         channel::strategy::ChannelStrategy,
-        operator::{Operator, OperatorContext},
+        operator::{
+            side::{Marker, SideOutputs},
+            Operator, OperatorContext,
+        },
     },
 };
 use arcon_macros::ArconState;
-use arcon_state::Backend;
+use arcon_state::{AsyncCheckpoint, Backend};
 use fxhash::*;
-use kompact::prelude::*;
+use kompact::prelude::{Timer as _, *};
 use std::{
     cell::{RefCell, UnsafeCell},
     sync::Arc,
 };
 
-use self::timer::Timer;
+use self::{
+    checkpoint::CheckpointWorker,
+    managed::ManagedNode,
+    timer::{processing_time_now, Timer},
+    unaligned::{in_flight_state_id, write_in_flight, UnalignedCheckpoint},
+};
+
+/// The notion of time a timer is scheduled against
+#[derive(Clone, Copy)]
+enum TimeDomain {
+    EventTime,
+    ProcessingTime,
+}
 
 /// Type alias for a Node description
 pub type NodeDescriptor = String;
@@ -137,6 +158,19 @@ where
     node_metrics: NodeMetrics,
     pub node_id: GlobalNodeId,
     in_key_builder: Option<KeyBuilder<OP::IN>>,
+    /// Key groups of the Node if the operator has a managed parallelism
+    managed: Option<ManagedNode<OP, B>>,
+    /// Worker that completes checkpoints, a thread of its own is started with the first
+    /// checkpoint unless the Node has been assigned one
+    checkpoint_worker: Option<Arc<CheckpointWorker>>,
+    /// Whether the Node checkpoints without aligning its channels
+    unaligned_mode: bool,
+    /// Unaligned checkpoint waiting for the epoch markers of the remaining channels
+    unaligned: Option<UnalignedCheckpoint<OP::IN>>,
+    /// In-flight messages of a restored unaligned checkpoint, replayed when the Node starts
+    restored_in_flight: Vec<RawArconMessage<OP::IN>>,
+    /// Periodic timer that fires due processing-time timers, set while any are pending
+    processing_time_ticks: Option<ScheduledTimer>,
 }
 
 impl<OP, B> Node<OP, B>
@@ -205,7 +239,75 @@ where
             node_metrics: NodeMetrics::new(),
             node_id,
             in_key_builder,
+            managed: None,
+            checkpoint_worker: None,
+            unaligned_mode: false,
+            unaligned: None,
+            restored_in_flight: Vec::new(),
+            processing_time_ticks: None,
+        }
+    }
+
+    /// Let the Node execute a managed operator using the given key groups
+    pub(crate) fn with_managed(mut self, managed: ManagedNode<OP, B>) -> Self {
+        self.managed = Some(managed);
+        self
+    }
+
+    /// Let the Node continue from `epoch`, the epoch after a restored checkpoint
+    pub(crate) fn with_epoch(mut self, epoch: Epoch) -> Self {
+        self.node_state.current_epoch = epoch;
+        self
+    }
+
+    /// Let the Node replay the in-flight messages of a restored unaligned checkpoint
+    pub(crate) fn with_in_flight(mut self, messages: Vec<RawArconMessage<OP::IN>>) -> Self {
+        self.restored_in_flight = messages;
+        self
+    }
+
+    /// Let the Node complete its checkpoints on `worker`, which may be shared with other Nodes
+    pub(crate) fn with_checkpoint_worker(mut self, worker: Arc<CheckpointWorker>) -> Self {
+        self.checkpoint_worker = Some(worker);
+        self
+    }
+
+    /// Let the Node send records of the operator's side outputs
+    pub(crate) fn with_side_outputs(mut self, side_outputs: SideOutputs) -> Self {
+        self.operator_context.get_mut().side_outputs = side_outputs;
+        self
+    }
+
+    /// Try to adopt key groups released by a previous generation of nodes
+    ///
+    /// Returns true once the Node owns all of its key groups. Messages that were
+    /// buffered while waiting are processed before returning.
+    fn adopt_key_groups(&mut self) -> ArconResult<bool> {
+        let managed = match &mut self.managed {
+            Some(managed) if !managed.pending.is_empty() => managed,
+            _ => return Ok(true),
+        };
+        let (epoch, groups) = managed.scope.adopt(&mut managed.pending);
+        for group in groups {
+            debug!(
+                self.logger,
+                "Node {} adopted key group {}", self.descriptor, group.id
+            );
+            managed.insert(group);
         }
+        if !managed.pending.is_empty() {
+            return Ok(false);
+        }
+        if let Some(epoch) = epoch {
+            // Continue where the previous generation stopped
+            self.node_state.current_epoch = Epoch::new(epoch.epoch + 1);
+        }
+        // Adopted key groups may come with pending processing-time timers
+        self.resume_processing_time_ticks();
+        for message in self.node_state.message_buffer().consume()? {
+            self.handle_events(message.sender, message.events)?;
+        }
+        Ok(true)
     }
 
     /// Message handler for both locally and remote sent messages
@@ -225,7 +327,13 @@ where
             return Ok(());
         }
 
-        if self.sender_blocked(message.sender()) {
+        if let Some(managed) = &self.managed {
+            if managed.retired {
+                return Ok(());
+            }
+        }
+
+        if self.sender_blocked(message.sender()) || !self.adopt_key_groups()? {
             self.node_state.message_buffer().append(message.raw())?;
             return Ok(());
         }
@@ -252,8 +360,16 @@ where
         group.enable()?;
 
         match message {
-            MessageContainer::Raw(r) => self.handle_events(r.sender, r.events)?,
-            MessageContainer::Local(l) => self.handle_events(l.sender, l.events)?,
+            MessageContainer::Local(l) if self.unaligned.is_none() => {
+                self.handle_events(l.sender, l.events)?
+            }
+            message => {
+                let raw = message.raw();
+                if let Some(unaligned) = &mut self.unaligned {
+                    unaligned.record(&raw);
+                }
+                self.handle_events(raw.sender, raw.events)?
+            }
         }
 
         #[cfg(all(feature = "hardware_counters", target_os = "linux", not(test)))]
@@ -298,7 +414,8 @@ where
                         None => return reportable_error!("Uninitialised watermark"),
                     };
 
-                    if e.timestamp <= watermark.timestamp {
+                    if e.timestamp <= watermark.timestamp && !self.operator.handles_late_elements()
+                    {
                         continue 'event_loop;
                     }
                     self.handle_element(e)?;
@@ -330,7 +447,13 @@ where
 
     #[inline(always)]
     fn handle_element(&mut self, e: ArconElement<OP::IN>) -> ArconResult<()> {
-        self.set_context(self.get_in_key(&e.data));
+        let key = self.get_in_key(&e.data);
+        if !self.set_context(key) {
+            return reportable_error!("Node {} does not own key {}", self.descriptor, key);
+        }
+        if let Some(managed) = &mut self.managed {
+            managed.epoch_load += 1;
+        }
         for elem in self
             .operator
             .handle_element(e, &mut self.operator_context.borrow_mut())?
@@ -340,10 +463,78 @@ where
         Ok(())
     }
 
+    /// Set the active key of the OperatorContext
+    ///
+    /// Returns false if the key belongs to a key group that this Node does not own.
     #[inline]
-    fn set_context(&mut self, key: u64) {
+    fn set_context(&mut self, key: u64) -> bool {
         let mut context = self.operator_context.borrow_mut();
+        if let Some(managed) = &mut self.managed {
+            let group = managed.key_group(key);
+            if !managed.activate(group, &mut context) {
+                return false;
+            }
+        }
         context.current_key = key;
+        true
+    }
+
+    /// Advance the timers of the Node and handle triggered timeouts
+    fn advance_timers(&mut self, timestamp: u64, domain: TimeDomain) -> ArconResult<()> {
+        let groups = match &self.managed {
+            Some(managed) => managed.group_ids(),
+            None => return self.advance_active_timer(timestamp, domain),
+        };
+        for group in groups {
+            if let Some(managed) = &mut self.managed {
+                managed.activate(group, &mut self.operator_context.borrow_mut());
+            }
+            self.advance_active_timer(timestamp, domain)?;
+        }
+        Ok(())
+    }
+
+    fn advance_active_timer(&mut self, timestamp: u64, domain: TimeDomain) -> ArconResult<()> {
+        let timeouts = {
+            let timer = &mut self.operator_context.borrow_mut().timer;
+            match domain {
+                TimeDomain::EventTime => timer.advance_to(timestamp)?,
+                TimeDomain::ProcessingTime => timer.advance_processing_time(timestamp)?,
+            }
+        };
+
+        for timer_entry in timeouts {
+            self.set_context(timer_entry.key());
+            if let Some(elems) = self
+                .operator
+                .handle_timeout(timer_entry.value(), &mut self.operator_context.borrow_mut())?
+            {
+                for elem in elems {
+                    self.add_outgoing_event(ArconEvent::Element(elem))?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Persist the operator state of every key group the Node owns
+    fn persist_operator_state(&mut self) -> ArconResult<()> {
+        let mut context = self.operator_context.borrow_mut();
+        match &mut self.managed {
+            Some(managed) => {
+                for group in managed.group_ids() {
+                    managed.activate(group, &mut context);
+                    context.state.set_time(context.watermark);
+                    context.state.persist()?;
+                }
+                managed.deactivate(&mut context);
+            }
+            None => {
+                context.state.set_time(context.watermark);
+                context.state.persist()?
+            }
+        }
+        Ok(())
     }
 
     #[inline]
@@ -375,30 +566,15 @@ where
             gauge!("last_watermark_timestamp", new_watermark.timestamp as f64, "node" => self.descriptor.clone());
 
             self.node_state.current_watermark = new_watermark;
+            self.operator_context.borrow_mut().watermark = new_watermark.timestamp;
 
-            let timeouts = self
-                .operator_context
-                .borrow_mut()
-                .timer
-                .advance_to(new_watermark.timestamp)?;
-
-            for timer_entry in timeouts {
-                self.set_context(timer_entry.key());
-                if let Some(elems) = self
-                    .operator
-                    .handle_timeout(timer_entry.value(), &mut self.operator_context.borrow_mut())?
-                {
-                    for elem in elems {
-                        self.add_outgoing_event(ArconEvent::Element(elem))?;
-                    }
-                }
-            }
+            self.advance_timers(new_watermark.timestamp, TimeDomain::EventTime)?;
 
             #[cfg(feature = "metrics")]
             increment_counter!("watermark_counter", "node" => self.descriptor.clone());
 
             // Forward the watermark
-            self.add_outgoing_event(ArconEvent::Watermark(new_watermark))?;
+            self.add_outgoing_marker(ArconEvent::Watermark(new_watermark))?;
         }
         Ok(())
     }
@@ -411,28 +587,42 @@ where
             return Ok(());
         }
 
+        if self.unaligned_mode {
+            return self.handle_unaligned_epoch(e, sender);
+        }
+
         // Add the sender to the blocked set.
         self.node_state.blocked_channels().insert(sender);
 
         // If all senders blocked we can transition to new Epoch
         if self.node_state.blocked_channels().len() == self.node_state.in_channels.len() {
             // Forward the Epoch
-            self.add_outgoing_event(ArconEvent::Epoch(self.node_state.current_epoch))?;
+            self.add_outgoing_marker(ArconEvent::Epoch(self.node_state.current_epoch))?;
 
             // persist internal node state for this node
             self.node_state.persist()?;
 
             // persist possible operator state..
-            self.operator_context.borrow_mut().state.persist()?;
-
-            // Perform the actual checkpoint
-            self.checkpoint()?;
+            self.persist_operator_state()?;
+
+            // Take a checkpoint, the epoch is acknowledged once it has been written
+            let epoch = self.node_state.current_epoch;
+            let pending = self.checkpoint()?;
+            if self.is_retiring(epoch) {
+                // The Node stops once it has handed over its key groups
+                let mut snapshots = Vec::with_capacity(pending.len());
+                for p in pending {
+                    p.checkpoint.complete()?;
+                    snapshots.push((p.descriptor, p.snapshot));
+                }
+                self.checkpoint_completed(epoch, snapshots);
+            } else {
+                self.complete_checkpoint_async(epoch, pending);
+            }
 
-            // Send Ack to EpochManager
-            self.epoch_manager.tell(EpochEvent::Ack(
-                self.descriptor.clone(),
-                self.node_state.current_epoch,
-            ));
+            if self.managed.is_some() && self.complete_managed_epoch() {
+                return Ok(());
+            }
 
             // Update current epoch
             self.node_state.current_epoch.epoch += 1;
@@ -452,58 +642,344 @@ where
         Ok(())
     }
 
+    fn handle_unaligned_epoch(&mut self, e: Epoch, sender: NodeID) -> ArconResult<()> {
+        match &mut self.unaligned {
+            Some(unaligned) if unaligned.aligned.contains(&sender) => {
+                // A channel that is an epoch ahead waits for the ongoing checkpoint to complete
+                self.node_state.blocked_channels().insert(sender);
+                self.node_state.message_buffer().append(RawArconMessage {
+                    events: vec![ArconEvent::Epoch(e).into()],
+                    sender,
+                })?;
+                return Ok(());
+            }
+            Some(unaligned) => {
+                unaligned.aligned.insert(sender);
+            }
+            None => {
+                // Overtake the messages still in flight on the other channels
+                self.add_outgoing_marker(ArconEvent::Epoch(self.node_state.current_epoch))?;
+                self.node_state.persist()?;
+                self.persist_operator_state()?;
+                let pending = self.checkpoint()?;
+                self.unaligned = Some(UnalignedCheckpoint::new(
+                    self.node_state.current_epoch,
+                    sender,
+                    pending,
+                ));
+            }
+        }
+
+        let aligned = self.unaligned.as_ref().map(|u| u.aligned.len());
+        if aligned == Some(self.node_state.in_channels.len()) {
+            self.complete_unaligned_checkpoint()?;
+        }
+        Ok(())
+    }
+
+    /// Complete the ongoing unaligned checkpoint once every channel has delivered its marker
+    fn complete_unaligned_checkpoint(&mut self) -> ArconResult<()> {
+        let unaligned = match self.unaligned.take() {
+            Some(unaligned) => unaligned,
+            None => return Ok(()),
+        };
+        let epoch = unaligned.epoch;
+        let mut pending = unaligned.pending;
+
+        let state_id = in_flight_state_id(&self.descriptor);
+        let checkpoint_dir = self.checkpoint_dir(&state_id, epoch)?;
+        let in_flight = unaligned.in_flight;
+        let path = checkpoint_dir.clone();
+        pending.push(PendingCheckpoint {
+            descriptor: state_id,
+            snapshot: Snapshot::new("InFlight".to_string(), epoch.epoch, checkpoint_dir),
+            checkpoint: AsyncCheckpoint::new(move || {
+                write_in_flight(path.as_ref(), &in_flight)?;
+                Ok(())
+            }),
+        });
+        self.complete_checkpoint_async(epoch, pending);
+
+        self.node_state.current_epoch.epoch += 1;
+
+        #[cfg(feature = "metrics")]
+        increment_counter!("epoch_counter", "node" => self.descriptor.clone());
+
+        // release channels that ran ahead and replay what they delivered meanwhile
+        self.node_state.blocked_channels().clear();
+        for message in self.node_state.message_buffer().consume()? {
+            self.handle_events(message.sender, message.events)?;
+        }
+        Ok(())
+    }
+
     #[inline]
     fn add_outgoing_event(&self, event: ArconEvent<OP::OUT>) -> ArconResult<()> {
+        if let Some(marker) = Marker::from_event(&event) {
+            self.operator_context
+                .borrow_mut()
+                .side_outputs
+                .send_marker(marker)?;
+        }
         let strategy = unsafe { &mut *self.channel_strategy.get() };
         common::add_outgoing_event(event, strategy, self)
     }
 
-    fn checkpoint(&mut self) -> ArconResult<()> {
-        if let Some(base_dir) = &self.ctx.config()["checkpoint_dir"].as_string() {
-            let checkpoint_dir = format!(
+    /// Forward a marker downstream
+    ///
+    /// Nodes of managed operators send the marker on behalf of every key group they own,
+    /// which keeps the set of senders stable for downstream nodes while the operator is rescaled.
+    fn add_outgoing_marker(&self, event: ArconEvent<OP::OUT>) -> ArconResult<()> {
+        match &self.managed {
+            Some(managed) => {
+                let strategy = unsafe { &mut *self.channel_strategy.get() };
+                let mut context = self.operator_context.borrow_mut();
+                let marker = Marker::from_event(&event);
+                for group in managed.range.iter() {
+                    let sender_id = NodeID::new(group as u32);
+                    strategy.set_sender_id(sender_id);
+                    common::add_outgoing_event(event.clone(), strategy, self)?;
+                    if let Some(marker) = &marker {
+                        context.side_outputs.set_sender_id(sender_id);
+                        context.side_outputs.send_marker(marker.clone())?;
+                    }
+                }
+                strategy.set_sender_id(self.node_state.id);
+                context.side_outputs.set_sender_id(self.node_state.id);
+                Ok(())
+            }
+            None => self.add_outgoing_event(event),
+        }
+    }
+
+    /// Returns true if the Node retires after `epoch` as its operator is being rescaled
+    fn is_retiring(&self, epoch: Epoch) -> bool {
+        match &self.managed {
+            Some(managed) => managed.scope.should_retire(managed.generation, epoch),
+            None => false,
+        }
+    }
+
+    /// Report the load of the finished epoch and retire the Node if the operator is being rescaled
+    ///
+    /// Returns true if the Node retired.
+    fn complete_managed_epoch(&mut self) -> bool {
+        let epoch = self.node_state.current_epoch;
+        let managed = match &mut self.managed {
+            Some(managed) => managed,
+            None => return false,
+        };
+        let load = std::mem::take(&mut managed.epoch_load);
+        self.node_manager_port
+            .trigger(NodeManagerEvent::Load(self.node_state.id, epoch, load));
+
+        if managed.scope.should_retire(managed.generation, epoch) {
+            let groups = managed.drain(&mut self.operator_context.borrow_mut());
+            managed.scope.release(groups);
+            managed.retired = true;
+            debug!(
+                self.logger,
+                "Node {} retired after epoch {:?}", self.descriptor, epoch
+            );
+            self.node_manager_port
+                .trigger(NodeManagerEvent::Retired(self.node_state.id));
+            return true;
+        }
+        false
+    }
+
+    /// Take the synchronous phase of a checkpoint of every backend the Node owns
+    fn checkpoint(&mut self) -> ArconResult<Vec<PendingCheckpoint>> {
+        match &self.managed {
+            Some(managed) => {
+                let groups: Vec<(String, Arc<B>)> = managed
+                    .groups()
+                    .map(|g| (g.descriptor.clone(), g.backend.clone()))
+                    .collect();
+                groups
+                    .into_iter()
+                    .map(|(descriptor, backend)| self.checkpoint_backend(descriptor, &backend))
+                    .collect()
+            }
+            None => {
+                let backend = self.backend.clone();
+                let descriptor = self.descriptor.clone();
+                Ok(vec![self.checkpoint_backend(descriptor, &backend)?])
+            }
+        }
+    }
+
+    /// Directory of the checkpoint of `state_id` for `epoch`
+    fn checkpoint_dir(&self, state_id: &str, epoch: Epoch) -> ArconResult<String> {
+        match self.ctx.config()["checkpoint_dir"].as_string() {
+            Some(base_dir) => Ok(format!(
                 "{}/{}/checkpoint_{id}_{epoch}",
                 base_dir,
-                self.descriptor,
-                id = self.descriptor,
-                epoch = self.node_state.current_epoch.epoch,
-            );
+                state_id,
+                id = state_id,
+                epoch = epoch.epoch,
+            )),
+            None => reportable_error!("Failed to fetch checkpoint_dir from Config"),
+        }
+    }
 
-            #[cfg(feature = "metrics")]
-            let start_time = Instant::now();
+    fn checkpoint_backend(
+        &mut self,
+        descriptor: String,
+        backend: &B,
+    ) -> ArconResult<PendingCheckpoint> {
+        let checkpoint_dir = self.checkpoint_dir(&descriptor, self.node_state.current_epoch)?;
 
-            self.backend.checkpoint(checkpoint_dir.as_ref())?;
+        #[cfg(feature = "metrics")]
+        let start_time = Instant::now();
 
-            #[cfg(feature = "metrics")]
-            {
-                let elapsed = start_time.elapsed();
-                histogram!("checkpoint_execution_time_ms", elapsed.as_millis() as f64, "node" => self.descriptor.clone());
+        let checkpoint = backend.async_checkpoint(checkpoint_dir.as_ref())?;
+
+        #[cfg(feature = "metrics")]
+        {
+            let elapsed = start_time.elapsed();
+            histogram!("checkpoint_execution_time_ms", elapsed.as_millis() as f64, "node" => self.descriptor.clone());
+        }
+
+        let snapshot = Snapshot::new(
+            std::any::type_name::<B>().to_string(),
+            self.node_state.current_epoch.epoch,
+            checkpoint_dir,
+        );
+        Ok(PendingCheckpoint {
+            descriptor,
+            snapshot,
+            checkpoint,
+        })
+    }
+
+    /// Complete the asynchronous phase of the checkpoints of `epoch` on the checkpoint thread
+    fn complete_checkpoint_async(&mut self, epoch: Epoch, pending: Vec<PendingCheckpoint>) {
+        let (snapshots, checkpoints): (Vec<_>, Vec<_>) = pending
+            .into_iter()
+            .map(|p| ((p.descriptor, p.snapshot), p.checkpoint))
+            .unzip();
+        let name = format!("{}-checkpoint", self.descriptor);
+        let future = self
+            .checkpoint_worker
+            .get_or_insert_with(|| Arc::new(CheckpointWorker::new(name)))
+            .submit(checkpoints);
+        self.spawn_local(move |mut async_self| async move {
+            let result = match future.await {
+                Ok(result) => result,
+                Err(_) => {
+                    reportable_error!("Checkpoint of {:?} was dropped before completion", epoch)
+                }
+            };
+            match result {
+                Ok(()) => {
+                    async_self.checkpoint_completed(epoch, snapshots);
+                    Handled::Ok
+                }
+                Err(err) => {
+                    async_self.checkpoint_failed(epoch, snapshots, err);
+                    Handled::DieNow
+                }
             }
+        });
+    }
 
+    /// Abort `epoch` after its checkpoint failed
+    ///
+    /// Failed checkpoints are not retried, so the Node stops once it has reported the failure.
+    fn checkpoint_failed(&mut self, epoch: Epoch, snapshots: Vec<(String, Snapshot)>, err: Error) {
+        error!(
+            self.logger,
+            "Failed to complete checkpoint of {:?}: {}", epoch, err
+        );
+        for (descriptor, _) in snapshots {
+            self.epoch_manager
+                .tell(EpochEvent::Abort(descriptor, epoch));
+        }
+    }
+
+    /// Report the snapshots of a completed checkpoint and acknowledge `epoch`
+    fn checkpoint_completed(&mut self, epoch: Epoch, snapshots: Vec<(String, Snapshot)>) {
+        for (descriptor, snapshot) in snapshots {
             #[cfg(feature = "metrics")]
-            {
-                let metadata = std::fs::metadata(checkpoint_dir.clone())?;
+            if let Ok(metadata) = std::fs::metadata(&snapshot.snapshot_path) {
                 gauge!("last_checkpoint_size", metadata.len() as f64, "node" => self.descriptor.clone());
             }
 
-            let snapshot = Snapshot::new(
-                std::any::type_name::<B>().to_string(),
-                self.node_state.current_epoch.epoch,
-                checkpoint_dir.clone(),
+            debug!(
+                self.logger,
+                "Completed a Checkpoint to path {}", snapshot.snapshot_path
             );
             self.node_manager_port.trigger(NodeManagerEvent::Checkpoint(
                 self.node_state.id,
-                SnapshotEvent::Snapshot(self.descriptor.clone(), snapshot),
+                SnapshotEvent::Snapshot(descriptor.clone(), snapshot),
             ));
+            self.epoch_manager.tell(EpochEvent::Ack(descriptor, epoch));
+        }
+    }
+}
 
-            debug!(
-                self.logger,
-                "Completed a Checkpoint to path {}", checkpoint_dir
-            );
-        } else {
-            return reportable_error!("Failed to fetch checkpoint_dir from Config");
+/// A checkpoint of a backend that has been taken but not yet written
+struct PendingCheckpoint {
+    descriptor: String,
+    snapshot: Snapshot,
+    checkpoint: AsyncCheckpoint,
+}
+
+impl<OP, B> Node<OP, B>
+where
+    OP: Operator + 'static,
+    B: Backend,
+{
+    /// Periodically fire processing-time timers that are due
+    fn handle_processing_time(&mut self, _: ScheduledTimer) -> Handled {
+        if let Err(err) = self.advance_timers(processing_time_now(), TimeDomain::ProcessingTime) {
+            error!(self.logger, "Failed to advance processing time: {}", err);
         }
+        // Timers scheduled by the timeouts are covered by the check below
+        self.operator_context.get_mut().processing_timer_scheduled = false;
+        if !self.has_processing_timers() {
+            if let Some(timer) = self.processing_time_ticks.take() {
+                self.cancel_timer(timer);
+            }
+        }
+        Handled::Ok
+    }
 
-        Ok(())
+    /// Start firing processing-time timers once the operator has scheduled one
+    fn schedule_processing_time_ticks(&mut self) {
+        let scheduled =
+            std::mem::take(&mut self.operator_context.get_mut().processing_timer_scheduled);
+        if !scheduled || self.processing_time_ticks.is_some() {
+            return;
+        }
+        let interval = self.ctx.config()["processing_time_interval"]
+            .as_i64()
+            .map(|i| i as u64)
+            .unwrap_or_else(processing_time_interval_default);
+        let duration = std::time::Duration::from_millis(interval);
+        self.processing_time_ticks =
+            Some(self.schedule_periodic(duration, duration, Self::handle_processing_time));
+    }
+
+    /// Start firing processing-time timers that were restored along with the state
+    fn resume_processing_time_ticks(&mut self) {
+        if self.has_processing_timers() {
+            self.operator_context.get_mut().processing_timer_scheduled = true;
+        }
+        self.schedule_processing_time_ticks();
+    }
+
+    /// Returns true if the timer of any key group has pending processing-time timers
+    fn has_processing_timers(&mut self) -> bool {
+        let mut context = self.operator_context.borrow_mut();
+        match &mut self.managed {
+            Some(managed) => managed.group_ids().into_iter().any(|group| {
+                managed.activate(group, &mut context);
+                context.timer.has_processing_timers()
+            }),
+            None => context.timer.has_processing_timers(),
+        }
     }
 }
 
@@ -530,8 +1006,26 @@ where
             }
         }
 
-        self.epoch_manager
-            .tell(EpochEvent::Register(self.descriptor.clone()));
+        // Key groups of managed operators are registered by their NodeConstructor
+        if self.managed.is_none() {
+            self.epoch_manager
+                .tell(EpochEvent::Register(self.descriptor.clone()));
+
+            self.unaligned_mode = self.ctx.config()["unaligned_checkpoints"]
+                .as_bool()
+                .unwrap_or(false);
+            if self.unaligned_mode {
+                self.epoch_manager
+                    .tell(EpochEvent::Register(in_flight_state_id(&self.descriptor)));
+            }
+        }
+
+        // Messages of a restored unaligned checkpoint precede everything the Node receives
+        for message in std::mem::take(&mut self.restored_in_flight) {
+            if let Err(err) = self.handle_events(message.sender, message.events) {
+                error!(self.logger, "Failed to replay in-flight messages: {}", err);
+            }
+        }
 
         if self
             .operator
@@ -542,6 +1036,8 @@ where
             error!(self.logger, "Failed to run startup code");
         }
 
+        self.resume_processing_time_ticks();
+
         Handled::Ok
     }
 }
@@ -551,7 +1047,14 @@ where
     OP: Operator + 'static,
     B: Backend,
 {
-    fn handle(&mut self, _: ()) -> Handled {
+    fn handle(&mut self, event: NodeEvent) -> Handled {
+        match event {
+            NodeEvent::KeyGroupsReleased => {
+                if let Err(err) = self.adopt_key_groups() {
+                    error!(self.logger, "Failed to adopt key groups: {}", err);
+                }
+            }
+        }
         Handled::Ok
     }
 }
@@ -578,6 +1081,7 @@ where
         if let Err(err) = self.handle_message(MessageContainer::Local(msg)) {
             error!(self.logger, "Failed to handle message: {}", err);
         }
+        self.schedule_processing_time_ticks();
         Handled::Ok
     }
     fn receive_network(&mut self, msg: NetMessage) -> Handled {
@@ -595,6 +1099,7 @@ where
                 if let Err(err) = self.handle_message(MessageContainer::Raw(m)) {
                     error!(self.logger, "Failed to handle node message: {}", err);
                 }
+                self.schedule_processing_time_ticks();
             }
             Err(e) => error!(self.logger, "Error ArconNetworkMessage: {:?}", e),
         }
@@ -611,15 +1116,20 @@ mod tests {
     #[cfg(not(test))]
     use crate::metrics::perf_event::HardwareCounter;
     use crate::{
-        application::Application,
+        application::{
+            conf::{ApplicationConf, CheckpointMode},
+            Application,
+        },
         dataflow::builder::OperatorBuilder,
         index::EmptyState,
+        manager::epoch::SavepointRequest,
         stream::{
             channel::{strategy::forward::Forward, Channel},
             node::debug::DebugNode,
             operator::function::Filter,
         },
     };
+    use arcon_state::InMemory;
     use std::{sync::Arc, thread, time};
 
     fn node_test_setup() -> (ActorRef<ArconMessage<i32>>, Arc<Component<DebugNode<i32>>>) {
@@ -633,92 +1143,110 @@ mod tests {
             conf: Default::default(),
         };
 
-        fn setup<OP: Operator<IN = i32, OUT = i32> + 'static, B: Backend>(
-            builder: OperatorBuilder<OP, B>,
-        ) -> (ActorRef<ArconMessage<i32>>, Arc<Component<DebugNode<i32>>>) {
-            // Returns a filter Node with input channels: sender1..sender3
-            // And a debug sink receiving its results
-            let app = Application::default();
-            let pool_info = app.get_pool_info();
-            let epoch_manager_ref = app.epoch_manager();
-
-            let sink = app.data_system().create(DebugNode::<i32>::new);
-
-            app.data_system()
-                .start_notify(&sink)
-                .wait_timeout(std::time::Duration::from_millis(1000))
-                .expect("started");
-
-            // Construct Channel to the Debug sink
-            let actor_ref: ActorRefStrong<ArconMessage<i32>> =
-                sink.actor_ref().hold().expect("Failed to fetch");
-            let channel = Channel::Local(actor_ref);
-            let channel_strategy: ChannelStrategy<i32> =
-                ChannelStrategy::Forward(Forward::new(channel, NodeID::new(0), pool_info));
-
-            // Set up  NodeManager
-            let backend = Arc::new(crate::test_utils::temp_backend::<B>());
-            let descriptor = String::from("node_");
-            let in_channels = vec![1.into(), 2.into(), 3.into()];
-
-            let operator = builder.operator.clone();
-            let operator_state = builder.state.clone();
+        setup(builder)
+    }
 
-            #[cfg(not(test))]
-            let mut perf_events = PerfEvents::new();
-
-            let nm = NodeManager::<OP, B>::new(
-                descriptor.clone(),
-                app.data_system().clone(),
-                in_channels.clone(),
-                app.arcon_logger.clone(),
-                Arc::new(builder),
-            );
-            let node_manager_comp = app.ctrl_system().create(|| nm);
-
-            app.ctrl_system()
-                .start_notify(&node_manager_comp)
-                .wait_timeout(std::time::Duration::from_millis(1000))
-                .expect("started");
-
-            let node = Node::<OP, _>::new(
-                descriptor,
-                channel_strategy,
-                operator(),
-                operator_state(backend.clone()),
-                NodeState::new(NodeID::new(0), in_channels, backend.clone()),
-                backend,
-                app.arcon_logger.clone(),
-                epoch_manager_ref,
-                #[cfg(not(test))]
-                perf_events,
-                GlobalNodeId::null(),
-                None,
-            );
+    fn setup<OP: Operator<IN = i32, OUT = i32> + 'static, B: Backend>(
+        builder: OperatorBuilder<OP, B>,
+    ) -> (ActorRef<ArconMessage<i32>>, Arc<Component<DebugNode<i32>>>) {
+        setup_with_conf(builder, Default::default())
+    }
 
-            let filter_comp = app.data_system().create(|| node);
-            let required_ref = filter_comp.on_definition(|cd| cd.node_manager_port.share());
+    fn setup_with_conf<OP: Operator<IN = i32, OUT = i32> + 'static, B: Backend>(
+        builder: OperatorBuilder<OP, B>,
+        conf: ApplicationConf,
+    ) -> (ActorRef<ArconMessage<i32>>, Arc<Component<DebugNode<i32>>>) {
+        let (_, node, sink) = setup_node(builder, conf);
+        (node.actor_ref(), sink)
+    }
 
-            biconnect_components::<NodeManagerPort, _, _>(&node_manager_comp, &filter_comp)
-                .expect("connection");
+    fn setup_node<OP: Operator<IN = i32, OUT = i32> + 'static, B: Backend>(
+        builder: OperatorBuilder<OP, B>,
+        conf: ApplicationConf,
+    ) -> (
+        Application,
+        Arc<Component<Node<OP, B>>>,
+        Arc<Component<DebugNode<i32>>>,
+    ) {
+        // Returns a filter Node with input channels: sender1..sender3
+        // And a debug sink receiving its results
+        let app = Application::new(conf);
+        let pool_info = app.get_pool_info();
+        let epoch_manager_ref = app.epoch_manager();
+
+        let sink = app.data_system().create(DebugNode::<i32>::new);
+
+        app.data_system()
+            .start_notify(&sink)
+            .wait_timeout(std::time::Duration::from_millis(1000))
+            .expect("started");
+
+        // Construct Channel to the Debug sink
+        let actor_ref: ActorRefStrong<ArconMessage<i32>> =
+            sink.actor_ref().hold().expect("Failed to fetch");
+        let channel = Channel::Local(actor_ref);
+        let channel_strategy: ChannelStrategy<i32> =
+            ChannelStrategy::Forward(Forward::new(channel, NodeID::new(0), pool_info));
+
+        // Set up  NodeManager
+        let backend = Arc::new(crate::test_utils::temp_backend::<B>());
+        let descriptor = String::from("node_");
+        let in_channels = vec![1.into(), 2.into(), 3.into()];
+
+        let operator = builder.operator.clone();
+        let operator_state = builder.state.clone();
 
-            app.data_system()
-                .start_notify(&filter_comp)
-                .wait_timeout(std::time::Duration::from_millis(1000))
-                .expect("started");
+        #[cfg(not(test))]
+        let mut perf_events = PerfEvents::new();
 
-            let filter_ref = filter_comp.actor_ref();
+        let nm = NodeManager::<OP, B>::new(
+            descriptor.clone(),
+            app.data_system().clone(),
+            in_channels.clone(),
+            app.arcon_logger.clone(),
+            Arc::new(builder),
+        );
+        let node_manager_comp = app.ctrl_system().create(|| nm);
 
-            node_manager_comp.on_definition(|cd| {
-                // Insert the created Node into the NodeManager
-                cd.nodes
-                    .insert(GlobalNodeId::null(), (filter_comp, required_ref));
-            });
+        app.ctrl_system()
+            .start_notify(&node_manager_comp)
+            .wait_timeout(std::time::Duration::from_millis(1000))
+            .expect("started");
 
-            (filter_ref, sink)
-        }
+        let node = Node::<OP, _>::new(
+            descriptor,
+            channel_strategy,
+            operator(),
+            operator_state(backend.clone()),
+            NodeState::new(NodeID::new(0), in_channels, backend.clone()),
+            backend,
+            app.arcon_logger.clone(),
+            epoch_manager_ref,
+            #[cfg(not(test))]
+            perf_events,
+            GlobalNodeId::null(),
+            None,
+        )
+        .with_checkpoint_worker(app.checkpoint_pool().assign());
+
+        let filter_comp = app.data_system().create(|| node);
+        let required_ref = filter_comp.on_definition(|cd| cd.node_manager_port.share());
+
+        biconnect_components::<NodeManagerPort, _, _>(&node_manager_comp, &filter_comp)
+            .expect("connection");
+
+        app.data_system()
+            .start_notify(&filter_comp)
+            .wait_timeout(std::time::Duration::from_millis(1000))
+            .expect("started");
+
+        node_manager_comp.on_definition(|cd| {
+            // Insert the created Node into the NodeManager
+            cd.nodes
+                .insert(GlobalNodeId::null(), (filter_comp.clone(), required_ref));
+        });
 
-        setup(builder)
+        (app, filter_comp, sink)
     }
 
     fn watermark(time: u64, sender: u32) -> ArconMessage<i32> {
@@ -740,6 +1268,35 @@ mod tests {
         thread::sleep(time::Duration::from_secs(time));
     }
 
+    // Emits every element once a processing-time timer scheduled for it fires
+    struct ProcessingTimeDelay;
+
+    impl Operator for ProcessingTimeDelay {
+        type IN = i32;
+        type OUT = i32;
+        type TimerState = i32;
+        type OperatorState = EmptyState;
+        type ElementIterator = Option<ArconElement<i32>>;
+
+        fn handle_element(
+            &mut self,
+            element: ArconElement<i32>,
+            ctx: &mut OperatorContext<Self::TimerState, Self::OperatorState>,
+        ) -> ArconResult<Self::ElementIterator> {
+            let delay = std::time::Duration::from_millis(100 * element.data as u64);
+            ctx.schedule_processing_time(delay, element.data)?;
+            Ok(None)
+        }
+
+        fn handle_timeout(
+            &mut self,
+            timeout: Self::TimerState,
+            _: &mut OperatorContext<Self::TimerState, Self::OperatorState>,
+        ) -> ArconResult<Option<Self::ElementIterator>> {
+            Ok(Some(Some(ArconElement::new(timeout))))
+        }
+    }
+
     #[test]
     fn node_no_watermark() {
         let (node_ref, sink) = node_test_setup();
@@ -871,4 +1428,123 @@ mod tests {
             assert_eq!(data_len, 6);
         });
     }
+
+    #[test]
+    fn node_unaligned_epoch() {
+        fn filter_fn(x: &i32) -> bool {
+            *x >= 0
+        }
+        let builder = OperatorBuilder::<_> {
+            operator: Arc::new(|| Filter::new(&filter_fn)),
+            state: Arc::new(|_backend| EmptyState),
+            conf: Default::default(),
+        };
+        let base_dir = tempfile::tempdir().unwrap();
+        let conf = ApplicationConf {
+            base_dir: base_dir.path().to_path_buf(),
+            checkpoint_mode: CheckpointMode::Unaligned,
+            ..Default::default()
+        };
+        let checkpoints_dir = conf.checkpoints_dir();
+        let (node_ref, sink) = setup_with_conf(builder, conf);
+
+        node_ref.tell(element(11, 1, 1));
+        node_ref.tell(epoch(1, 1)); // epoch is forwarded right away
+        node_ref.tell(element(12, 1, 1)); // not blocked
+        node_ref.tell(element(21, 1, 2)); // in flight
+        node_ref.tell(epoch(2, 1)); // sender1 is an epoch ahead and blocked
+        node_ref.tell(element(13, 1, 1)); // blocked
+        node_ref.tell(epoch(1, 2));
+        node_ref.tell(epoch(1, 3)); // completes the checkpoint and releases sender1
+
+        wait(1);
+        node_ref.tell(death(3)); // send death marker on unblocked channel to flush
+        wait(1);
+
+        sink.on_definition(|cd| {
+            let data: Vec<i32> = cd.data.iter().map(|e| e.data).collect();
+            assert_eq!(data, vec![11, 12, 21, 13]);
+            assert_eq!(cd.epochs.len(), 2);
+        });
+
+        // Only the message delivered before the marker of its channel is part of the checkpoint
+        let in_flight_id = in_flight_state_id("node_");
+        let path = checkpoints_dir
+            .join(&in_flight_id)
+            .join(format!("checkpoint_{}_0", in_flight_id));
+        let in_flight = unaligned::read_in_flight::<i32>(&path).unwrap();
+        assert_eq!(in_flight.len(), 1);
+        assert_eq!(in_flight[0].sender, NodeID::new(2));
+        match in_flight[0].events[0].unwrap_ref() {
+            ArconEvent::Element(e) => assert_eq!(e.data, 21),
+            _ => panic!("expected an element"),
+        }
+    }
+
+    #[test]
+    fn node_checkpoint_failure() {
+        fn filter_fn(x: &i32) -> bool {
+            *x >= 0
+        }
+        let builder = OperatorBuilder::<_, InMemory> {
+            operator: Arc::new(|| Filter::new(&filter_fn)),
+            state: Arc::new(|_backend| EmptyState),
+            conf: Default::default(),
+        };
+        let base_dir = tempfile::tempdir().unwrap();
+        let conf = ApplicationConf {
+            base_dir: base_dir.path().to_path_buf(),
+            ..Default::default()
+        };
+        // a file in place of the checkpoint directory of the node fails its checkpoints
+        let checkpoints_dir = conf.checkpoints_dir();
+        std::fs::create_dir_all(&checkpoints_dir).unwrap();
+        std::fs::write(checkpoints_dir.join("node_"), b"").unwrap();
+        let (app, node, _sink) = setup_node(builder, conf);
+
+        let epoch_manager = app.epoch_manager.clone().unwrap();
+        let (promise, savepoint) = promise();
+        epoch_manager.on_definition(|cd| {
+            let path = base_dir.path().join("savepoint");
+            cd.savepoints
+                .insert(Epoch::new(0), SavepointRequest { path, promise });
+        });
+
+        let node_ref = node.actor_ref();
+        node_ref.tell(epoch(1, 1));
+        node_ref.tell(epoch(1, 2));
+        node_ref.tell(epoch(1, 3));
+
+        // the epoch of the failed checkpoint is aborted
+        let result = savepoint
+            .wait_timeout(std::time::Duration::from_secs(5))
+            .expect("savepoint was never completed");
+        assert!(result.is_err());
+        wait(1);
+        assert!(node.is_destroyed());
+        epoch_manager.on_definition(|cd| assert_eq!(cd.ongoing_epoch_commit, 1));
+    }
+
+    #[test]
+    fn node_processing_time_timer() {
+        let builder = OperatorBuilder::<_> {
+            operator: Arc::new(|| ProcessingTimeDelay),
+            state: Arc::new(|_backend| EmptyState),
+            conf: Default::default(),
+        };
+        let (node_ref, sink) = setup(builder);
+        node_ref.tell(element(1, 1, 1));
+        node_ref.tell(element(2, 1, 2));
+        // Timers do not depend on watermarks
+        wait(1);
+        node_ref.tell(watermark(1, 1));
+        node_ref.tell(watermark(1, 2));
+        node_ref.tell(watermark(1, 3));
+        wait(1);
+        sink.on_definition(|cd| {
+            let mut data: Vec<i32> = cd.data.iter().map(|e| e.data).collect();
+            data.sort_unstable();
+            assert_eq!(data, vec![1, 2]);
+        });
+    }
 }
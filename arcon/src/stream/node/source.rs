@@ -8,18 +8,25 @@ use crate::{
     application::conf::logger::ArconLogger,
     data::{ArconElement, ArconEvent, Epoch, Watermark},
     error::{source::SourceError, ArconResult, Error},
-    manager::source::{SourceManagerEvent, SourceManagerPort},
+    index::{EagerValue, ValueIndex},
+    manager::{
+        epoch::EpochEvent,
+        snapshot::{Snapshot, SnapshotEvent},
+        source::{SourceManagerEvent, SourceManagerPort},
+    },
     prelude::SourceConf,
+    reportable_error,
     stream::{
         channel::strategy::ChannelStrategy,
         source::{Poll, Source},
         time::ArconTime,
     },
 };
+use arcon_state::Backend;
 use kompact::prelude::*;
 #[cfg(feature = "kafka")]
 use rdkafka::error::KafkaError;
-use std::cell::RefCell;
+use std::{cell::RefCell, sync::Arc};
 
 /// A message type that Source components in Arcon must implement
 #[derive(Debug, PartialEq, Clone)]
@@ -38,10 +45,14 @@ impl Port for LoopbackPort {
 }
 
 /// A [kompact] component to drive the execution of Arcon sources
+///
+/// The number of records polled from the source is checkpointed at every epoch,
+/// so that replayable sources can be rewound when the application is restored.
 #[derive(ComponentDefinition)]
-pub struct SourceNode<S>
+pub struct SourceNode<S, B>
 where
     S: Source,
+    B: Backend,
 {
     ctx: ComponentContext<Self>,
     manager_port: RequiredPort<SourceManagerPort>,
@@ -53,6 +64,16 @@ where
     conf: SourceConf<S::Item>,
     source_index: usize,
     source: S,
+    /// Number of records, including faulty ones, polled from the source
+    offset: usize,
+    /// Offset of the source at the last epoch
+    offset_state: EagerValue<u64, B>,
+    /// Identifier used for checkpoints and epoch acknowledgements
+    state_id: String,
+    /// Backend that is checkpointed at every epoch
+    backend: Arc<B>,
+    /// Actor Reference to the EpochManager
+    epoch_manager: ActorRefStrong<EpochEvent>,
     logger: ArconLogger,
 
     #[cfg(feature = "metrics")]
@@ -60,15 +81,20 @@ where
     descriptor: String,
 }
 
-impl<S> SourceNode<S>
+impl<S, B> SourceNode<S, B>
 where
     S: Source,
+    B: Backend,
 {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         source_index: usize,
-        source: S,
+        mut source: S,
         conf: SourceConf<S::Item>,
         channel_strategy: ChannelStrategy<S::Item>,
+        state_id: String,
+        backend: Arc<B>,
+        epoch_manager: ActorRefStrong<EpochEvent>,
         logger: ArconLogger,
     ) -> Self {
         let borrowed_source_name: &str = &conf.name.clone();
@@ -79,6 +105,17 @@ where
             register_counter!("error_counter", "source" => conf.name.clone());
         }
 
+        // Rewind the source to where it was at the restored epoch
+        let offset_state = EagerValue::new("_source_offset", backend.clone());
+        let offset = offset_state
+            .get()
+            .expect("failed to read source offset")
+            .map(|offset| *offset as usize)
+            .unwrap_or(0);
+        if offset > 0 {
+            source.set_offset(offset);
+        }
+
         Self {
             ctx: ComponentContext::uninitialised(),
             manager_port: RequiredPort::uninitialised(),
@@ -90,6 +127,11 @@ where
             conf,
             source_index,
             source,
+            offset,
+            offset_state,
+            state_id,
+            backend,
+            epoch_manager,
             logger,
 
             #[cfg(feature = "metrics")]
@@ -124,6 +166,7 @@ where
                             self.output(record, crate::util::get_system_time())?
                         }
                     }
+                    self.offset += 1;
                     counter += 1;
                 }
                 Ok(Poll::Pending) => {
@@ -141,6 +184,8 @@ where
 
                     match self.handle_source_error(error) {
                         Ok(_) => {
+                            // The faulty record was still consumed from the source
+                            self.offset += 1;
                             counter += 1;
                         }
                         Err(err) => {
@@ -194,6 +239,7 @@ where
     pub fn handle_source_event(&mut self, event: SourceEvent) -> ArconResult<()> {
         match event {
             SourceEvent::Epoch(epoch) => {
+                self.checkpoint(epoch)?;
                 self.send_event(ArconEvent::Epoch(epoch))?;
             }
             SourceEvent::Watermark(time) => {
@@ -218,6 +264,42 @@ where
         Ok(())
     }
 
+    fn checkpoint(&mut self, epoch: Epoch) -> ArconResult<()> {
+        self.offset_state.put(self.offset as u64)?;
+
+        if let Some(base_dir) = &self.ctx.config()["checkpoint_dir"].as_string() {
+            let checkpoint_dir = format!(
+                "{}/{}/checkpoint_{id}_{epoch}",
+                base_dir,
+                self.state_id,
+                id = self.state_id,
+                epoch = epoch.epoch,
+            );
+            self.backend.checkpoint(checkpoint_dir.as_ref())?;
+
+            let snapshot = Snapshot::new(
+                std::any::type_name::<B>().to_string(),
+                epoch.epoch,
+                checkpoint_dir.clone(),
+            );
+            self.manager_port
+                .trigger(SourceManagerEvent::Checkpoint(SnapshotEvent::Snapshot(
+                    self.state_id.clone(),
+                    snapshot,
+                )));
+            self.epoch_manager
+                .tell(EpochEvent::Ack(self.state_id.clone(), epoch));
+
+            debug!(
+                self.logger,
+                "Completed a Checkpoint to path {}", checkpoint_dir
+            );
+            Ok(())
+        } else {
+            reportable_error!("Failed to fetch checkpoint_dir from Config")
+        }
+    }
+
     fn source_shutdown(&mut self, error: Error) {
         // fatal error, must shutdown..
         // TODO: coordinate shutdown of the application..
@@ -225,24 +307,40 @@ where
     }
 }
 
-impl<S> ComponentLifecycle for SourceNode<S>
+impl<S, B> ComponentLifecycle for SourceNode<S, B>
 where
     S: Source,
+    B: Backend,
 {
     fn on_start(&mut self) -> Handled {
         info!(
             self.logger,
             "Starting up Source {} with Index {}", self.descriptor, self.source_index
         );
+
+        // create directory that keeps checkpoints
+        match self.ctx.config()["checkpoint_dir"].as_string() {
+            Some(base_dir) => {
+                let checkpoint_dir = format!("{}/{}", base_dir, self.state_id);
+                std::fs::create_dir_all(checkpoint_dir).unwrap();
+            }
+            None => {
+                error!(self.logger, "Failed to locate checkpoint_dir config");
+            }
+        }
+        self.epoch_manager
+            .tell(EpochEvent::Register(self.state_id.clone()));
+
         let shared = self.loopback_receive.share();
         self.loopback_send.connect(shared);
         Handled::Ok
     }
 }
 
-impl<S> Provide<LoopbackPort> for SourceNode<S>
+impl<S, B> Provide<LoopbackPort> for SourceNode<S, B>
 where
     S: Source,
+    B: Backend,
 {
     fn handle(&mut self, _event: ProcessSource) -> Handled {
         match self.process() {
@@ -269,27 +367,30 @@ where
     }
 }
 
-impl<S> Require<LoopbackPort> for SourceNode<S>
+impl<S, B> Require<LoopbackPort> for SourceNode<S, B>
 where
     S: Source,
+    B: Backend,
 {
     fn handle(&mut self, _event: Never) -> Handled {
         unreachable!("Never type has no instance");
     }
 }
 
-impl<S> Require<SourceManagerPort> for SourceNode<S>
+impl<S, B> Require<SourceManagerPort> for SourceNode<S, B>
 where
     S: Source,
+    B: Backend,
 {
     fn handle(&mut self, _event: Never) -> Handled {
         unreachable!("Never type has no instance");
     }
 }
 
-impl<S> Actor for SourceNode<S>
+impl<S, B> Actor for SourceNode<S, B>
 where
     S: Source,
+    B: Backend,
 {
     type Message = SourceEvent;
 
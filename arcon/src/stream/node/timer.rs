@@ -1,8 +1,11 @@
-use crate::error::timer::{TimerExpiredError, TimerResult};
+use crate::error::{
+    timer::{TimerExpiredError, TimerResult},
+    ArconResult,
+};
 use crate::index::hash_table::eager::EagerHashTable;
 use arcon_state::{
     backend::{
-        handles::{ActiveHandle, Handle},
+        handles::{ActiveHandle, BoxedIteratorOfResult, Handle},
         Backend, ValueState,
     },
     data::Value,
@@ -13,7 +16,10 @@ use hierarchical_hash_wheel_timer::{
     wheels::{quad_wheel::*, *},
     *,
 };
-use std::sync::Arc;
+use std::{
+    sync::Arc,
+    time::{SystemTime, UNIX_EPOCH},
+};
 
 #[derive(prost::Message, PartialEq, Clone)]
 pub struct TimerEvent<E: Value> {
@@ -43,6 +49,89 @@ pub struct TimeoutId {
     timestamp: u64,
 }
 
+/// Sorted timestamps of the timeouts registered by a key
+#[derive(prost::Message, PartialEq, Clone)]
+struct KeyTimeouts {
+    #[prost(uint64, repeated, tag = "1")]
+    timestamps: Vec<u64>,
+}
+
+/// Stored timeouts along with an index of the timeouts of each key
+///
+/// The index lets the timers of a key be listed without scanning all timeouts.
+/// Checkpoints taken before the index existed only hold the timeouts,
+/// which is why [Timer] rebuilds the index while replaying them.
+struct Timeouts<V, B>
+where
+    V: Value,
+    B: Backend,
+{
+    entries: EagerHashTable<TimeoutId, TimerEvent<V>, B>,
+    by_key: EagerHashTable<u64, KeyTimeouts, B>,
+}
+
+impl<V, B> Timeouts<V, B>
+where
+    V: Value,
+    B: Backend,
+{
+    fn new(id: String, backend: Arc<B>) -> Self {
+        let by_key_id = format!("{}_by_key", id);
+        Timeouts {
+            entries: EagerHashTable::new(id, backend.clone()),
+            by_key: EagerHashTable::new(by_key_id, backend),
+        }
+    }
+
+    fn iter(&self) -> Result<BoxedIteratorOfResult<(TimeoutId, TimerEvent<V>)>> {
+        self.entries.iter()
+    }
+
+    fn put(&mut self, id: TimeoutId, event: TimerEvent<V>) -> Result<()> {
+        self.entries.put(id, event)?;
+        self.index(id)
+    }
+
+    /// Add `id` to the index of its key unless it is already there
+    fn index(&mut self, id: TimeoutId) -> Result<()> {
+        let mut key_timeouts = self.by_key.get(&id.key)?.unwrap_or_default();
+        if let Err(pos) = key_timeouts.timestamps.binary_search(&id.timestamp) {
+            key_timeouts.timestamps.insert(pos, id.timestamp);
+            self.by_key.put(id.key, key_timeouts)?;
+        }
+        Ok(())
+    }
+
+    fn remove(&mut self, id: TimeoutId) -> Result<Option<TimerEvent<V>>> {
+        let event = self.entries.remove(&id)?;
+        if event.is_some() {
+            if let Some(mut key_timeouts) = self.by_key.get(&id.key)? {
+                if let Ok(pos) = key_timeouts.timestamps.binary_search(&id.timestamp) {
+                    key_timeouts.timestamps.remove(pos);
+                }
+                if key_timeouts.timestamps.is_empty() {
+                    self.by_key.remove(&id.key)?;
+                } else {
+                    self.by_key.put(id.key, key_timeouts)?;
+                }
+            }
+        }
+        Ok(event)
+    }
+
+    /// Returns the timeouts registered by `key` ordered by timestamp
+    fn for_key(&self, key: u64) -> Result<Vec<(u64, V)>> {
+        let key_timeouts = self.by_key.get(&key)?.unwrap_or_default();
+        let mut entries = Vec::with_capacity(key_timeouts.timestamps.len());
+        for timestamp in key_timeouts.timestamps {
+            if let Some(event) = self.entries.get(&TimeoutId { key, timestamp })? {
+                entries.push((timestamp, event.payload));
+            }
+        }
+        Ok(entries)
+    }
+}
+
 pub struct TimerEntry<V> {
     id: TimeoutId,
     value: V,
@@ -63,15 +152,44 @@ pub trait ArconTimer: Send {
 
     fn schedule_at(&mut self, time: u64, entry: Self::Value) -> TimerResult<Self::Value>;
     fn advance_to(&mut self, ts: u64) -> Result<Vec<TimerEntry<Self::Value>>>;
+    /// Schedule `entry` to fire once `delay` of wall-clock time has passed
+    ///
+    /// Returns the deadline of the timer in milliseconds since the unix epoch.
+    fn schedule_processing_time(&mut self, delay: Duration, entry: Self::Value)
+        -> ArconResult<u64>;
+    /// Advance processing time to `now`, given in milliseconds since the unix epoch
+    fn advance_processing_time(&mut self, now: u64) -> Result<Vec<TimerEntry<Self::Value>>>;
+    /// Remove the event-time timer of `key` at `time` and return its entry if it existed
+    fn cancel(&mut self, time: u64, key: u64) -> Result<Option<Self::Value>>;
+    /// Returns the pending event-time timers of `key` ordered by time
+    fn timers_for_key(&self, key: u64) -> Result<Vec<(u64, Self::Value)>>;
+    /// Remove the processing-time timer of `key` with the given `deadline`
+    fn cancel_processing_time(&mut self, deadline: u64, key: u64) -> Result<Option<Self::Value>>;
+    /// Returns the pending processing-time timers of `key` ordered by deadline
+    fn processing_timers_for_key(&self, key: u64) -> Result<Vec<(u64, Self::Value)>>;
+    /// Returns true if processing-time timers are waiting to fire
+    fn has_processing_timers(&self) -> bool;
     fn get_time(&self) -> Result<u64>;
     fn active_key(&mut self, key: u64);
 }
 
+/// Returns the wall-clock time in milliseconds
+#[inline]
+pub(crate) fn processing_time_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("system time before unix epoch")
+        .as_millis() as u64
+}
+
 /// An Index for Stream Timers
 ///
 /// The Index utilises the [QuadWheelWithOverflow] data structure
 /// in order to manage the timers. The remaining state is kept in
 /// other indexes such as Map/Value.
+///
+/// Event-time and processing-time timers are kept in separate wheels.
+/// Processing-time timers are identified by their wall-clock deadline.
 pub struct Timer<V, B>
 where
     V: Value,
@@ -79,8 +197,16 @@ where
 {
     timer: QuadWheelWithOverflow<TimeoutId>,
     current_key: u64,
-    timeouts: EagerHashTable<TimeoutId, TimerEvent<V>, B>,
+    timeouts: Timeouts<V, B>,
     time_handle: ActiveHandle<B, ValueState<u64>>,
+    processing_timer: QuadWheelWithOverflow<TimeoutId>,
+    processing_timeouts: Timeouts<V, B>,
+    /// Wall-clock time in milliseconds the processing wheel has been advanced to
+    processing_time: u64,
+    /// Replayed processing timeouts whose deadline passed while the timer was down
+    overdue: Vec<TimeoutId>,
+    /// Amount of processing timeouts in the wheel, including cancelled ones
+    processing_pending: usize,
 }
 
 impl<V, B> Timer<V, B>
@@ -91,6 +217,7 @@ where
     pub fn new(id: impl Into<String>, backend: Arc<B>) -> Self {
         let id = id.into();
         let timeouts_id = format!("_{}_timeouts", id);
+        let processing_timeouts_id = format!("_{}_processing_timeouts", id);
         let time_id = format!("_{}_time", id);
 
         let mut handle = Handle::value(time_id);
@@ -101,18 +228,25 @@ where
         let mut timer = Self {
             timer: QuadWheelWithOverflow::default(),
             current_key: 0,
-            timeouts: EagerHashTable::new(timeouts_id, backend),
+            timeouts: Timeouts::new(timeouts_id, backend.clone()),
             time_handle,
+            processing_timer: QuadWheelWithOverflow::default(),
+            processing_timeouts: Timeouts::new(processing_timeouts_id, backend),
+            processing_time: processing_time_now(),
+            overdue: Vec::new(),
+            processing_pending: 0,
         };
 
         // replay and insert back if any exists
         timer.replay_events();
+        timer.replay_processing_events();
 
         timer
     }
 
     fn replay_events(&mut self) {
         let time = self.current_time().unwrap();
+        let mut replayed = Vec::new();
 
         for res in self.timeouts.iter().expect("could not get timeouts") {
             let (id, entry) = res.expect("could not get timeout entry");
@@ -123,6 +257,41 @@ where
             {
                 panic!("A timeout has expired during replay: {:?}", f);
             }
+            replayed.push(id);
+        }
+
+        for id in replayed {
+            self.timeouts.index(id).expect("could not index timeout");
+        }
+    }
+
+    fn replay_processing_events(&mut self) {
+        let now = self.processing_time;
+        let mut replayed = Vec::new();
+        for res in self
+            .processing_timeouts
+            .iter()
+            .expect("could not get processing timeouts")
+        {
+            let (id, entry) = res.expect("could not get processing timeout entry");
+            replayed.push(id);
+            let deadline = entry.time_when_scheduled + entry.timeout_millis;
+            if deadline <= now {
+                self.overdue.push(id);
+            } else if let Err(f) = self
+                .processing_timer
+                .insert_with_delay(id, Duration::from_millis(deadline - now))
+            {
+                panic!("A processing timeout failed during replay: {:?}", f);
+            } else {
+                self.processing_pending += 1;
+            }
+        }
+
+        for id in replayed {
+            self.processing_timeouts
+                .index(id)
+                .expect("could not index processing timeout");
         }
     }
 
@@ -145,58 +314,18 @@ where
     }
 
     #[inline(always)]
-    pub fn tick_and_collect(
-        &mut self,
-        mut time_left: u32,
-        res: &mut Vec<TimerEntry<V>>,
-    ) -> Result<()> {
-        while time_left > 0 {
-            match self.timer.can_skip() {
-                Skip::Empty => {
-                    // Timer is empty, no point in ticking it
-                    self.add_time(time_left as u64)?;
-                    return Ok(());
-                }
-                Skip::Millis(skip_ms) => {
-                    // Skip forward
-                    if skip_ms >= time_left {
-                        // No more ops to gather, skip the remaining time_left and return
-                        self.timer.skip(time_left);
-                        self.add_time(time_left as u64)?;
-                        return Ok(());
-                    } else {
-                        // Skip lower than time-left:
-                        self.timer.skip(skip_ms);
-                        self.add_time(skip_ms as u64)?;
-                        time_left -= skip_ms;
-                    }
-                }
-                Skip::None => {
-                    for e in self.timer.tick() {
-                        if let Some(entry) = self.take_entry(e) {
-                            res.push(entry);
-                        }
-                    }
-                    self.add_time(1u64)?;
-                    time_left -= 1u32;
-                }
+    pub fn tick_and_collect(&mut self, time_left: u32, res: &mut Vec<TimerEntry<V>>) -> Result<()> {
+        let mut expired = Vec::new();
+        tick_wheel(&mut self.timer, time_left, &mut expired);
+        self.add_time(time_left as u64)?;
+        for id in expired {
+            if let Some(entry) = take_entry(&mut self.timeouts, id) {
+                res.push(entry);
             }
         }
         Ok(())
     }
 
-    // Lookup id, remove from storage, and return Executable action
-    #[inline(always)]
-    fn take_entry(&mut self, id: TimeoutId) -> Option<TimerEntry<V>> {
-        self.timeouts
-            .remove(&id)
-            .expect("no timeout found for id") // this wouldn't necessarily be an error anymore if we add a cancellation API at some point
-            .map(|e| TimerEntry {
-                id,
-                value: e.payload,
-            })
-    }
-
     #[inline(always)]
     pub fn schedule_after(&mut self, id: TimeoutId, delay: u64, entry: V) -> TimerResult<V> {
         match self
@@ -218,6 +347,65 @@ where
     }
 }
 
+/// Tick `wheel` forward by `time_left` milliseconds and collect the ids of expired timeouts
+#[inline(always)]
+fn tick_wheel(
+    wheel: &mut QuadWheelWithOverflow<TimeoutId>,
+    mut time_left: u32,
+    expired: &mut Vec<TimeoutId>,
+) {
+    while time_left > 0 {
+        match wheel.can_skip() {
+            Skip::Empty => {
+                // Timer is empty, no point in ticking it
+                return;
+            }
+            Skip::Millis(skip_ms) => {
+                // Skip forward
+                if skip_ms >= time_left {
+                    // No more ops to gather, skip the remaining time_left and return
+                    wheel.skip(time_left);
+                    return;
+                } else {
+                    // Skip lower than time-left:
+                    wheel.skip(skip_ms);
+                    time_left -= skip_ms;
+                }
+            }
+            Skip::None => {
+                expired.extend(wheel.tick());
+                time_left -= 1u32;
+            }
+        }
+    }
+}
+
+// Lookup id, remove from storage, and return Executable action
+#[inline(always)]
+fn take_entry<V: Value, B: Backend>(
+    timeouts: &mut Timeouts<V, B>,
+    id: TimeoutId,
+) -> Option<TimerEntry<V>> {
+    timeouts
+        .remove(id)
+        .expect("failed to remove timeout")
+        .map(|e| TimerEntry {
+            id,
+            value: e.payload,
+        })
+}
+
+// Cancelled timeouts are only removed from storage, their ids expire without an entry
+#[inline]
+fn cancel_entry<V: Value, B: Backend>(
+    timeouts: &mut Timeouts<V, B>,
+    timestamp: u64,
+    key: u64,
+) -> Result<Option<V>> {
+    let id = TimeoutId { key, timestamp };
+    Ok(timeouts.remove(id)?.map(|e| e.payload))
+}
+
 impl<V, B> ArconTimer for Timer<V, B>
 where
     V: Value,
@@ -271,6 +459,70 @@ where
         let time = self.time_handle.get()?;
         Ok(time.unwrap_or(0))
     }
+
+    fn schedule_processing_time(
+        &mut self,
+        delay: Duration,
+        entry: Self::Value,
+    ) -> ArconResult<u64> {
+        // Timers fire on the next tick at the earliest
+        let delay = std::cmp::max(delay.as_millis() as u64, 1);
+        let now = processing_time_now();
+        let deadline = std::cmp::max(now, self.processing_time) + delay;
+        let timeout_id = TimeoutId {
+            key: self.current_key,
+            timestamp: deadline,
+        };
+        if let Err(f) = self.processing_timer.insert_with_delay(
+            timeout_id,
+            Duration::from_millis(deadline - self.processing_time),
+        ) {
+            return crate::reportable_error!("Could not insert processing timer entry! {:?}", f);
+        }
+        self.processing_pending += 1;
+        let event = TimerEvent::new(now, deadline - now, entry);
+        self.processing_timeouts.put(timeout_id, event)?;
+        Ok(deadline)
+    }
+
+    fn advance_processing_time(&mut self, now: u64) -> Result<Vec<TimerEntry<Self::Value>>> {
+        let mut expired = std::mem::take(&mut self.overdue);
+        if now > self.processing_time {
+            let overdue = expired.len();
+            let mut time_left = now - self.processing_time;
+            while time_left > u32::MAX as u64 {
+                tick_wheel(&mut self.processing_timer, u32::MAX, &mut expired);
+                time_left -= u32::MAX as u64;
+            }
+            tick_wheel(&mut self.processing_timer, time_left as u32, &mut expired);
+            self.processing_pending -= expired.len() - overdue;
+            self.processing_time = now;
+        }
+        Ok(expired
+            .into_iter()
+            .filter_map(|id| take_entry(&mut self.processing_timeouts, id))
+            .collect())
+    }
+
+    fn cancel(&mut self, time: u64, key: u64) -> Result<Option<Self::Value>> {
+        cancel_entry(&mut self.timeouts, time, key)
+    }
+
+    fn timers_for_key(&self, key: u64) -> Result<Vec<(u64, Self::Value)>> {
+        self.timeouts.for_key(key)
+    }
+
+    fn cancel_processing_time(&mut self, deadline: u64, key: u64) -> Result<Option<Self::Value>> {
+        cancel_entry(&mut self.processing_timeouts, deadline, key)
+    }
+
+    fn processing_timers_for_key(&self, key: u64) -> Result<Vec<(u64, Self::Value)>> {
+        self.processing_timeouts.for_key(key)
+    }
+
+    fn has_processing_timers(&self) -> bool {
+        self.processing_pending > 0 || !self.overdue.is_empty()
+    }
 }
 
 #[cfg(test)]
@@ -295,5 +547,92 @@ mod tests {
         let evs = timer.advance_to(2000).unwrap();
         assert_eq!(evs.len(), 1);
     }
+
+    #[test]
+    fn processing_timer_test() {
+        let backend = Arc::new(temp_backend::<Sled>());
+        let mut timer: Timer<u64, Sled> = Timer::new("mytimer", backend.clone());
+        let start = timer.processing_time;
+
+        timer.active_key(1);
+        timer
+            .schedule_processing_time(Duration::from_millis(100), 10)
+            .unwrap();
+        timer.active_key(2);
+        timer
+            .schedule_processing_time(Duration::from_secs(60), 20)
+            .unwrap();
+
+        // Event time does not affect processing timers
+        assert!(timer.advance_to(u32::MAX as u64).unwrap().is_empty());
+
+        let evs = timer.advance_processing_time(start + 1000).unwrap();
+        assert_eq!(evs.len(), 1);
+        assert_eq!(evs[0].key(), 1);
+        assert!(timer.has_processing_timers());
+
+        // Pending processing timers are replayed from the backend
+        let mut restored: Timer<u64, Sled> = Timer::new("mytimer", backend);
+        assert!(restored.has_processing_timers());
+        let evs = restored.advance_processing_time(start + 61_000).unwrap();
+        assert_eq!(evs.len(), 1);
+        assert_eq!(evs[0].key(), 2);
+        assert!(!restored.has_processing_timers());
+    }
+
+    #[test]
+    fn timer_cancel_test() {
+        let backend = Arc::new(temp_backend::<Sled>());
+        let mut timer: Timer<u64, Sled> = Timer::new("mytimer", backend);
+
+        timer.active_key(1);
+        let _ = timer.schedule_at(1000, 10).unwrap();
+        let _ = timer.schedule_at(500, 5).unwrap();
+        timer.active_key(2);
+        let _ = timer.schedule_at(1000, 20).unwrap();
+
+        assert_eq!(timer.timers_for_key(1).unwrap(), vec![(500, 5), (1000, 10)]);
+        assert_eq!(timer.cancel(1000, 1).unwrap(), Some(10));
+        assert_eq!(timer.cancel(1000, 1).unwrap(), None);
+        assert_eq!(timer.timers_for_key(1).unwrap(), vec![(500, 5)]);
+
+        let evs = timer.advance_to(2000).unwrap();
+        let keys: Vec<u64> = evs.iter().map(|e| e.key()).collect();
+        assert_eq!(keys, vec![1, 2]);
+        assert!(timer.timers_for_key(2).unwrap().is_empty());
+
+        timer.active_key(3);
+        let deadline = timer
+            .schedule_processing_time(Duration::from_millis(10), 30)
+            .unwrap();
+        assert_eq!(
+            timer.processing_timers_for_key(3).unwrap(),
+            vec![(deadline, 30)]
+        );
+        assert_eq!(timer.cancel_processing_time(deadline, 3).unwrap(), Some(30));
+        assert!(timer
+            .advance_processing_time(deadline + 1000)
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn timer_key_index_rebuild_test() {
+        let backend = Arc::new(temp_backend::<Sled>());
+
+        // Timeouts stored without the per-key index
+        let mut entries: EagerHashTable<TimeoutId, TimerEvent<u64>, Sled> =
+            EagerHashTable::new("_mytimer_timeouts", backend.clone());
+        let id = TimeoutId {
+            key: 1,
+            timestamp: 1000,
+        };
+        entries.put(id, TimerEvent::new(0, 1000, 10)).unwrap();
+
+        let mut timer: Timer<u64, Sled> = Timer::new("mytimer", backend);
+        assert_eq!(timer.timers_for_key(1).unwrap(), vec![(1000, 10)]);
+        assert_eq!(timer.cancel(1000, 1).unwrap(), Some(10));
+        assert!(timer.timers_for_key(1).unwrap().is_empty());
+    }
     // TODO: more elaborate tests
 }
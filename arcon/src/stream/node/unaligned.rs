@@ -0,0 +1,88 @@
+use super::PendingCheckpoint;
+use crate::data::{ArconEvent, ArconType, Epoch, NodeID, RawArconMessage};
+use fxhash::FxHashSet;
+use prost::Message;
+use std::{fs, io, path::Path};
+
+/// File in which an unaligned checkpoint stores its in-flight messages
+const IN_FLIGHT_FILE: &str = "IN_FLIGHT";
+
+/// State id under which the in-flight messages of the Node `descriptor` are checkpointed
+pub(crate) fn in_flight_state_id(descriptor: &str) -> String {
+    format!("{}_in_flight", descriptor)
+}
+
+/// A checkpoint that waits for the epoch markers of the remaining channels
+///
+/// State is checkpointed when the first marker arrives. Messages that the other
+/// channels deliver until their marker are processed as usual, but they are also
+/// part of the checkpoint, as their senders will not replay them after a restore.
+pub(super) struct UnalignedCheckpoint<A: ArconType> {
+    pub epoch: Epoch,
+    /// Channels that have delivered the epoch marker
+    pub aligned: FxHashSet<NodeID>,
+    /// Backend checkpoints taken when the first marker arrived
+    pub pending: Vec<PendingCheckpoint>,
+    /// Messages delivered by the remaining channels before their marker
+    pub in_flight: Vec<RawArconMessage<A>>,
+}
+
+impl<A: ArconType> UnalignedCheckpoint<A> {
+    pub fn new(epoch: Epoch, sender: NodeID, pending: Vec<PendingCheckpoint>) -> Self {
+        let mut aligned = FxHashSet::default();
+        aligned.insert(sender);
+        UnalignedCheckpoint {
+            epoch,
+            aligned,
+            pending,
+            in_flight: Vec::new(),
+        }
+    }
+
+    /// Record the events of `message` that precede the epoch marker of its channel
+    pub fn record(&mut self, message: &RawArconMessage<A>) {
+        if self.aligned.contains(&message.sender) {
+            return;
+        }
+        let events: Vec<_> = message
+            .events
+            .iter()
+            .take_while(|e| !matches!(e.unwrap_ref(), ArconEvent::Epoch(_) | ArconEvent::Death(_)))
+            .cloned()
+            .collect();
+        if !events.is_empty() {
+            self.in_flight.push(RawArconMessage {
+                events,
+                sender: message.sender,
+            });
+        }
+    }
+}
+
+/// Write the in-flight messages of an unaligned checkpoint to `checkpoint_path`
+pub(super) fn write_in_flight<A: ArconType>(
+    checkpoint_path: &Path,
+    messages: &[RawArconMessage<A>],
+) -> io::Result<()> {
+    fs::create_dir_all(checkpoint_path)?;
+    let mut buf = Vec::new();
+    for message in messages {
+        message
+            .encode_length_delimited(&mut buf)
+            .expect("Vec has no capacity limit");
+    }
+    fs::write(checkpoint_path.join(IN_FLIGHT_FILE), buf)
+}
+
+/// Read the in-flight messages of an unaligned checkpoint from `checkpoint_path`
+pub(crate) fn read_in_flight<A: ArconType>(
+    checkpoint_path: &Path,
+) -> crate::error::ArconResult<Vec<RawArconMessage<A>>> {
+    let bytes = fs::read(checkpoint_path.join(IN_FLIGHT_FILE))?;
+    let mut buf = bytes.as_slice();
+    let mut messages = Vec::new();
+    while !buf.is_empty() {
+        messages.push(RawArconMessage::decode_length_delimited(&mut buf)?);
+    }
+    Ok(messages)
+}
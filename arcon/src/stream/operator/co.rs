@@ -0,0 +1,105 @@
+use crate::{
+    data::{ArconElement, ArconType, CoElement, CoEvent},
+    error::*,
+    index::ArconState,
+    stream::operator::{Operator, OperatorContext},
+};
+use prost::Message;
+
+/// Defines the methods an operator with two inputs must implement
+///
+/// Watermarks and epochs are aligned across both inputs by the runtime,
+/// so a `CoOperator` only has to deal with the records of each side.
+pub trait CoOperator: Send + Sized {
+    /// The type of elements on the left input
+    type LEFT: ArconType;
+    /// The type of elements on the right input
+    type RIGHT: ArconType;
+    /// The type of output elements this operator produces
+    type OUT: ArconType;
+    /// Storage state type for timer facilities
+    type TimerState: Message + Clone + Default;
+    /// State type for the Operator
+    type OperatorState: ArconState;
+    /// Iterator that produces outgoing elements
+    type ElementIterator: IntoIterator<Item = ArconElement<Self::OUT>> + 'static;
+
+    /// Determines what the `CoOperator` runs before beginning to process Elements
+    fn on_start(
+        &mut self,
+        _ctx: &mut OperatorContext<Self::TimerState, Self::OperatorState>,
+    ) -> ArconResult<()> {
+        Ok(())
+    }
+
+    /// Determines how the `CoOperator` processes Elements of the left input
+    fn handle_left(
+        &mut self,
+        element: ArconElement<Self::LEFT>,
+        ctx: &mut OperatorContext<Self::TimerState, Self::OperatorState>,
+    ) -> ArconResult<Self::ElementIterator>;
+
+    /// Determines how the `CoOperator` processes Elements of the right input
+    fn handle_right(
+        &mut self,
+        element: ArconElement<Self::RIGHT>,
+        ctx: &mut OperatorContext<Self::TimerState, Self::OperatorState>,
+    ) -> ArconResult<Self::ElementIterator>;
+
+    /// Determines how the `CoOperator` handles timeouts it registered earlier when they are triggered
+    fn handle_timeout(
+        &mut self,
+        timeout: Self::TimerState,
+        ctx: &mut OperatorContext<Self::TimerState, Self::OperatorState>,
+    ) -> ArconResult<Option<Self::ElementIterator>>;
+}
+
+/// Executes a [CoOperator] as a regular [Operator] over the records of both inputs
+pub struct CoOperatorAdapter<OP: CoOperator> {
+    operator: OP,
+}
+
+impl<OP: CoOperator> CoOperatorAdapter<OP> {
+    pub fn new(operator: OP) -> Self {
+        Self { operator }
+    }
+}
+
+impl<OP: CoOperator> Operator for CoOperatorAdapter<OP> {
+    type IN = CoElement<OP::LEFT, OP::RIGHT>;
+    type OUT = OP::OUT;
+    type TimerState = OP::TimerState;
+    type OperatorState = OP::OperatorState;
+    type ElementIterator = OP::ElementIterator;
+
+    fn on_start(
+        &mut self,
+        ctx: &mut OperatorContext<Self::TimerState, Self::OperatorState>,
+    ) -> ArconResult<()> {
+        self.operator.on_start(ctx)
+    }
+
+    fn handle_element(
+        &mut self,
+        element: ArconElement<Self::IN>,
+        ctx: &mut OperatorContext<Self::TimerState, Self::OperatorState>,
+    ) -> ArconResult<Self::ElementIterator> {
+        let timestamp = element.timestamp;
+        match element.data.unwrap() {
+            CoEvent::Left(data) => self
+                .operator
+                .handle_left(ArconElement::with_timestamp(data, timestamp), ctx),
+            CoEvent::Right(data) => self
+                .operator
+                .handle_right(ArconElement::with_timestamp(data, timestamp), ctx),
+        }
+    }
+
+    fn handle_timeout(
+        &mut self,
+        timeout: Self::TimerState,
+        ctx: &mut OperatorContext<Self::TimerState, Self::OperatorState>,
+    ) -> ArconResult<Option<Self::ElementIterator>> {
+        self.operator.handle_timeout(timeout, ctx)
+    }
+}
@@ -0,0 +1,293 @@
+use crate::{
+    data::{ArconElement, ArconType, Joined},
+    error::*,
+    index::{EagerHashTable, IndexOps},
+    stream::{
+        operator::{co::CoOperator, OperatorContext},
+        time::Time,
+    },
+};
+use arcon_macros::ArconState;
+use arcon_state::Backend;
+use prost::Message;
+use std::{marker::PhantomData, sync::Arc};
+
+#[derive(Message, PartialEq, Clone, Copy)]
+pub struct CleanupEvent {
+    #[prost(uint64, tag = "1")]
+    key: u64,
+    #[prost(uint64, tag = "2")]
+    timestamp: u64,
+}
+
+/// Identifies the records of one input that a key holds at a timestamp
+#[derive(Message, PartialEq, Clone, Copy)]
+pub struct RecordId {
+    #[prost(uint64, tag = "1")]
+    key: u64,
+    #[prost(uint64, tag = "2")]
+    timestamp: u64,
+}
+
+/// Records of one input that share a key and timestamp
+#[derive(Message, Clone)]
+pub struct IntervalRecords<A: ArconType> {
+    #[prost(message, repeated, tag = "1")]
+    records: Vec<A>,
+}
+
+/// Timestamps a key holds records of one input at, in ascending order
+#[derive(Message, PartialEq, Clone)]
+pub struct RecordTimestamps {
+    #[prost(uint64, repeated, tag = "1")]
+    timestamps: Vec<u64>,
+}
+
+#[derive(ArconState)]
+pub struct IntervalJoinState<L: ArconType, R: ArconType, B: Backend> {
+    left: EagerHashTable<RecordId, IntervalRecords<L>, B>,
+    left_timestamps: EagerHashTable<u64, RecordTimestamps, B>,
+    right: EagerHashTable<RecordId, IntervalRecords<R>, B>,
+    right_timestamps: EagerHashTable<u64, RecordTimestamps, B>,
+}
+
+impl<L: ArconType, R: ArconType, B: Backend> IntervalJoinState<L, R, B> {
+    pub fn new(backend: Arc<B>) -> Self {
+        Self {
+            left: EagerHashTable::new("_interval_left_records", backend.clone()),
+            left_timestamps: EagerHashTable::new("_interval_left_timestamps", backend.clone()),
+            right: EagerHashTable::new("_interval_right_records", backend.clone()),
+            right_timestamps: EagerHashTable::new("_interval_right_timestamps", backend),
+        }
+    }
+}
+
+/// Adds `data` to the records of `id` and indexes its timestamp if it is new
+#[inline]
+fn insert_record<A: ArconType, B: Backend>(
+    records: &mut EagerHashTable<RecordId, IntervalRecords<A>, B>,
+    timestamps: &mut EagerHashTable<u64, RecordTimestamps, B>,
+    id: RecordId,
+    data: A,
+) -> ArconResult<()> {
+    let mut entry = records.get(&id)?.unwrap_or_default();
+    if entry.records.is_empty() {
+        let mut key_timestamps = timestamps.get(&id.key)?.unwrap_or_default();
+        if let Err(pos) = key_timestamps.timestamps.binary_search(&id.timestamp) {
+            key_timestamps.timestamps.insert(pos, id.timestamp);
+            timestamps.put(id.key, key_timestamps)?;
+        }
+    }
+    entry.records.push(data);
+    records.put(id, entry)?;
+    Ok(())
+}
+
+/// Returns the records of `key` with a timestamp within `[lower, upper]`
+#[inline]
+fn records_between<A: ArconType, B: Backend>(
+    records: &EagerHashTable<RecordId, IntervalRecords<A>, B>,
+    timestamps: &EagerHashTable<u64, RecordTimestamps, B>,
+    key: u64,
+    lower: u64,
+    upper: u64,
+) -> ArconResult<Vec<(u64, IntervalRecords<A>)>> {
+    let key_timestamps = timestamps.get(&key)?.unwrap_or_default();
+    let start = key_timestamps.timestamps.partition_point(|t| *t < lower);
+    let mut output = Vec::new();
+    for &timestamp in key_timestamps.timestamps[start..]
+        .iter()
+        .take_while(|t| **t <= upper)
+    {
+        if let Some(entry) = records.get(&RecordId { key, timestamp })? {
+            output.push((timestamp, entry));
+        }
+    }
+    Ok(output)
+}
+
+/// Removes the records of `key` with a timestamp below `bound`
+#[inline]
+fn drop_records_before<A: ArconType, B: Backend>(
+    records: &mut EagerHashTable<RecordId, IntervalRecords<A>, B>,
+    timestamps: &mut EagerHashTable<u64, RecordTimestamps, B>,
+    key: u64,
+    bound: u64,
+) -> ArconResult<()> {
+    if let Some(mut key_timestamps) = timestamps.get(&key)? {
+        let end = key_timestamps.timestamps.partition_point(|t| *t < bound);
+        if end == 0 {
+            return Ok(());
+        }
+        for timestamp in key_timestamps.timestamps.drain(..end) {
+            records.remove(&RecordId { key, timestamp })?;
+        }
+        if key_timestamps.timestamps.is_empty() {
+            timestamps.remove(&key)?;
+        } else {
+            timestamps.put(key, key_timestamps)?;
+        }
+    }
+    Ok(())
+}
+
+/// Joins the records of two keyed streams whose timestamps are close to each other
+///
+/// A left record with timestamp `ts` is joined with every right record of the same key
+/// whose timestamp lies within `[ts - lower, ts + upper]`. Records are kept in state
+/// until the event time has passed the interval they can be joined in.
+pub struct IntervalJoin<L, R, B>
+where
+    L: ArconType,
+    R: ArconType,
+    B: Backend,
+{
+    lower: u64,
+    upper: u64,
+    _marker: PhantomData<(L, R, B)>,
+}
+
+impl<L, R, B> IntervalJoin<L, R, B>
+where
+    L: ArconType,
+    R: ArconType,
+    B: Backend,
+{
+    pub fn new(lower: Time, upper: Time) -> Self {
+        IntervalJoin {
+            lower: lower.0,
+            upper: upper.0,
+            _marker: PhantomData,
+        }
+    }
+
+    #[inline]
+    fn schedule_cleanup(
+        &self,
+        time: u64,
+        ctx: &mut OperatorContext<CleanupEvent, IntervalJoinState<L, R, B>>,
+    ) -> ArconResult<()> {
+        let event = CleanupEvent {
+            key: ctx.current_key,
+            timestamp: time,
+        };
+        // An expired request is fine as the record is dropped by a later cleanup
+        let _ = ctx.schedule_at(time, event)?;
+        Ok(())
+    }
+}
+
+impl<L, R, B> CoOperator for IntervalJoin<L, R, B>
+where
+    L: ArconType,
+    R: ArconType,
+    B: Backend,
+{
+    type LEFT = L;
+    type RIGHT = R;
+    type OUT = Joined<L, R>;
+    type TimerState = CleanupEvent;
+    type OperatorState = IntervalJoinState<L, R, B>;
+    type ElementIterator = Vec<ArconElement<Self::OUT>>;
+
+    fn handle_left(
+        &mut self,
+        element: ArconElement<L>,
+        ctx: &mut OperatorContext<Self::TimerState, Self::OperatorState>,
+    ) -> ArconResult<Self::ElementIterator> {
+        let ts = element.timestamp;
+        if ts < ctx.current_time()? {
+            // Late arrival
+            return Ok(Vec::new());
+        }
+        let lower = ts.saturating_sub(self.lower);
+        let upper = ts.saturating_add(self.upper);
+
+        let key = ctx.current_key;
+        let state = ctx.state();
+        let mut output = Vec::new();
+        for (r_ts, entry) in
+            records_between(&state.right, &state.right_timestamps, key, lower, upper)?
+        {
+            for r in entry.records {
+                output.push(ArconElement::with_timestamp(
+                    Joined::new(element.data.clone(), r),
+                    ts.max(r_ts),
+                ));
+            }
+        }
+        let id = RecordId { key, timestamp: ts };
+        insert_record(
+            &mut state.left,
+            &mut state.left_timestamps,
+            id,
+            element.data,
+        )?;
+
+        self.schedule_cleanup(upper.saturating_add(1), ctx)?;
+        Ok(output)
+    }
+
+    fn handle_right(
+        &mut self,
+        element: ArconElement<R>,
+        ctx: &mut OperatorContext<Self::TimerState, Self::OperatorState>,
+    ) -> ArconResult<Self::ElementIterator> {
+        let ts = element.timestamp;
+        if ts < ctx.current_time()? {
+            // Late arrival
+            return Ok(Vec::new());
+        }
+        let lower = ts.saturating_sub(self.upper);
+        let upper = ts.saturating_add(self.lower);
+
+        let key = ctx.current_key;
+        let state = ctx.state();
+        let mut output = Vec::new();
+        for (l_ts, entry) in
+            records_between(&state.left, &state.left_timestamps, key, lower, upper)?
+        {
+            for l in entry.records {
+                output.push(ArconElement::with_timestamp(
+                    Joined::new(l, element.data.clone()),
+                    ts.max(l_ts),
+                ));
+            }
+        }
+        let id = RecordId { key, timestamp: ts };
+        insert_record(
+            &mut state.right,
+            &mut state.right_timestamps,
+            id,
+            element.data,
+        )?;
+
+        self.schedule_cleanup(upper.saturating_add(1), ctx)?;
+        Ok(output)
+    }
+
+    fn handle_timeout(
+        &mut self,
+        timeout: Self::TimerState,
+        ctx: &mut OperatorContext<Self::TimerState, Self::OperatorState>,
+    ) -> ArconResult<Option<Self::ElementIterator>> {
+        let CleanupEvent { key, timestamp } = timeout;
+        ctx.current_key = key;
+
+        // Drop the records that can no longer be joined
+        let state = ctx.state();
+        drop_records_before(
+            &mut state.left,
+            &mut state.left_timestamps,
+            key,
+            timestamp.saturating_sub(self.upper),
+        )?;
+        drop_records_before(
+            &mut state.right,
+            &mut state.right_timestamps,
+            key,
+            timestamp.saturating_sub(self.lower),
+        )?;
+        Ok(None)
+    }
+}
@@ -0,0 +1,7 @@
+/// Interval join
+pub mod interval;
+/// Window join
+pub mod window;
+
+pub use interval::{IntervalJoin, IntervalJoinState};
+pub use window::{WindowJoin, WindowJoinState};
@@ -0,0 +1,209 @@
+use crate::{
+    data::{ArconElement, ArconType, Joined},
+    dataflow::builder::Assigner,
+    error::*,
+    index::{AppenderIndex, EagerAppender, EagerHashTable, IndexOps},
+    stream::operator::{
+        co::CoOperator,
+        window::{assigner::WindowEvent, WindowContext},
+        OperatorContext,
+    },
+};
+use arcon_macros::ArconState;
+use arcon_state::Backend;
+use kompact::prelude::error;
+use std::{marker::PhantomData, sync::Arc};
+
+#[derive(ArconState)]
+pub struct WindowJoinState<L: ArconType, R: ArconType, B: Backend> {
+    left: EagerAppender<L, B>,
+    right: EagerAppender<R, B>,
+    active_windows: EagerHashTable<WindowContext, (), B>,
+}
+
+impl<L: ArconType, R: ArconType, B: Backend> WindowJoinState<L, R, B> {
+    pub fn new(backend: Arc<B>) -> Self {
+        Self {
+            left: EagerAppender::new("_join_left", backend.clone()),
+            right: EagerAppender::new("_join_right", backend.clone()),
+            active_windows: EagerHashTable::new("_join_active_windows", backend),
+        }
+    }
+
+    // Point the buffers of both sides at the given window
+    #[inline]
+    fn set_window(&mut self, window_ctx: WindowContext) {
+        let id = u64::from(window_ctx);
+        self.left.set_key(id);
+        self.right.set_key(id);
+    }
+}
+
+/// Joins the records of two keyed streams that fall into the same window
+///
+/// Records of both sides are buffered per key and window. Once a window
+/// is triggered, every pair of left and right records is emitted.
+pub struct WindowJoin<L, R, B>
+where
+    L: ArconType,
+    R: ArconType,
+    B: Backend,
+{
+    // effectively immutable, so no reason to persist
+    window_length: u64,
+    window_slide: u64,
+    late_arrival_time: u64,
+    _marker: PhantomData<(L, R, B)>,
+}
+
+impl<L, R, B> WindowJoin<L, R, B>
+where
+    L: ArconType,
+    R: ArconType,
+    B: Backend,
+{
+    pub fn new(assigner: Assigner) -> Self {
+        let (length, slide, late) = match assigner {
+            Assigner::Sliding {
+                length,
+                slide,
+                late_arrival,
+            } => (length.0, slide.0, late_arrival.0),
+            Assigner::Tumbling {
+                length,
+                late_arrival,
+            } => (length.0, length.0, late_arrival.0),
+            Assigner::Session { .. } | Assigner::Count { .. } | Assigner::Global => {
+                panic!("Window joins only support sliding and tumbling windows")
+            }
+        };
+        if length < slide {
+            panic!("Window Length lower than slide!");
+        }
+        if length % slide != 0 {
+            panic!("Window Length not divisible by slide!");
+        }
+        WindowJoin {
+            window_length: length,
+            window_slide: slide,
+            late_arrival_time: late,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Returns the windows that `element` should be buffered in
+    fn assign<T: ArconType>(
+        &self,
+        element: &ArconElement<T>,
+        ctx: &mut OperatorContext<WindowEvent, WindowJoinState<L, R, B>>,
+    ) -> ArconResult<Vec<WindowContext>> {
+        let ts = element.timestamp;
+        let time = ctx.current_time()?;
+
+        if ts < time.saturating_sub(self.late_arrival_time) {
+            // Late arrival
+            return Ok(Vec::new());
+        }
+
+        // Windows are aligned to timestamp 0 so that both sides agree on them
+        let ceil = ts / self.window_slide;
+        let floor = if ts >= self.window_length {
+            (ts - self.window_length) / self.window_slide + 1
+        } else {
+            0
+        };
+
+        let mut windows = Vec::new();
+        for index in floor..=ceil {
+            let window_ctx = WindowContext::new(ctx.current_key, index);
+            let active_exist = ctx.state().active_windows().contains(&window_ctx)?;
+            if !active_exist {
+                let end = index * self.window_slide + self.window_length;
+                let request = ctx.schedule_at(
+                    end + self.late_arrival_time,
+                    WindowEvent::new(window_ctx.key, index, end),
+                )?;
+                if let Err(expired) = request {
+                    // The window has already been triggered
+                    error!(ctx.log(), "{}", expired);
+                    continue;
+                }
+                ctx.state().active_windows().put(window_ctx, ())?;
+            }
+            windows.push(window_ctx);
+        }
+        Ok(windows)
+    }
+}
+
+impl<L, R, B> CoOperator for WindowJoin<L, R, B>
+where
+    L: ArconType,
+    R: ArconType,
+    B: Backend,
+{
+    type LEFT = L;
+    type RIGHT = R;
+    type OUT = Joined<L, R>;
+    type TimerState = WindowEvent;
+    type OperatorState = WindowJoinState<L, R, B>;
+    type ElementIterator = Vec<ArconElement<Self::OUT>>;
+
+    fn handle_left(
+        &mut self,
+        element: ArconElement<L>,
+        ctx: &mut OperatorContext<Self::TimerState, Self::OperatorState>,
+    ) -> ArconResult<Self::ElementIterator> {
+        for window_ctx in self.assign(&element, ctx)? {
+            let state = ctx.state();
+            state.set_window(window_ctx);
+            state.left().append(element.data.clone())?;
+        }
+        Ok(Vec::new())
+    }
+
+    fn handle_right(
+        &mut self,
+        element: ArconElement<R>,
+        ctx: &mut OperatorContext<Self::TimerState, Self::OperatorState>,
+    ) -> ArconResult<Self::ElementIterator> {
+        for window_ctx in self.assign(&element, ctx)? {
+            let state = ctx.state();
+            state.set_window(window_ctx);
+            state.right().append(element.data.clone())?;
+        }
+        Ok(Vec::new())
+    }
+
+    fn handle_timeout(
+        &mut self,
+        timeout: Self::TimerState,
+        ctx: &mut OperatorContext<Self::TimerState, Self::OperatorState>,
+    ) -> ArconResult<Option<Self::ElementIterator>> {
+        let WindowEvent {
+            key,
+            index,
+            timestamp,
+            ..
+        } = timeout;
+        ctx.current_key = key;
+        let window_ctx = WindowContext::new(key, index);
+
+        let state = ctx.state();
+        state.set_window(window_ctx);
+        let left = state.left().consume()?;
+        let right = state.right().consume()?;
+        state.active_windows().remove(&window_ctx)?;
+
+        let mut output = Vec::with_capacity(left.len() * right.len());
+        for l in left.iter() {
+            for r in right.iter() {
+                output.push(ArconElement::with_timestamp(
+                    Joined::new(l.clone(), r.clone()),
+                    timestamp,
+                ));
+            }
+        }
+        Ok(Some(output))
+    }
+}
@@ -1,5 +1,11 @@
+/// Operators with two inputs
+pub mod co;
 /// Available function operators
 pub mod function;
+/// Available join operators
+pub mod join;
+/// Side outputs of operators
+pub mod side;
 /// Available sink operators
 pub mod sink;
 /// Available window operators
@@ -16,6 +22,8 @@ use crate::{
     stream::node::timer::ArconTimer,
 };
 use prost::Message;
+use side::{OutputTag, SideOutputs};
+use std::time::Duration;
 
 /// Defines the methods an `Operator` must implement
 pub trait Operator: Send + Sized {
@@ -51,6 +59,14 @@ pub trait Operator: Send + Sized {
         timeout: Self::TimerState,
         ctx: &mut OperatorContext<Self::TimerState, Self::OperatorState>,
     ) -> ArconResult<Option<Self::ElementIterator>>;
+
+    /// Determines whether the `Operator` receives elements that arrive behind the watermark
+    ///
+    /// Late elements are dropped by default. Operators that, for example,
+    /// redirect late arrivals to a side output may opt in to receive them.
+    fn handles_late_elements(&self) -> bool {
+        false
+    }
 }
 
 /// Helper macro to implement an empty ´handle_timeout` function while creating a custom [Operator]
@@ -81,6 +97,12 @@ where
     pub(crate) logger: ArconLogger,
     /// Active key that is set by the runtime
     pub(crate) current_key: u64,
+    /// Current watermark that is set by the runtime
+    pub(crate) watermark: u64,
+    /// Side output channels that are set by the runtime
+    pub(crate) side_outputs: SideOutputs,
+    /// Set when a processing-time timer is scheduled, cleared by the runtime
+    pub(crate) processing_timer_scheduled: bool,
     #[cfg(feature = "metrics")]
     name: String,
 }
@@ -102,6 +124,9 @@ where
             state,
             logger,
             current_key: 0,
+            watermark: 0,
+            side_outputs: SideOutputs::default(),
+            processing_timer_scheduled: false,
             #[cfg(feature = "metrics")]
             name,
         }
@@ -109,6 +134,7 @@ where
     #[inline]
     pub fn state(&mut self) -> &mut OperatorState {
         self.state.set_key(self.current_key);
+        self.state.set_time(self.watermark);
         &mut self.state
     }
 
@@ -136,6 +162,67 @@ where
         self.timer.schedule_at(time, entry)
     }
 
+    /// Schedule a timer for the current key that fires once `duration` of wall-clock time has passed
+    ///
+    /// Unlike [OperatorContext::schedule_at], the timer does not depend on watermarks.
+    /// The timeout is passed to [Operator::handle_timeout] like event-time timeouts.
+    /// As with [OperatorContext::schedule_at], a timer with the same key and deadline
+    /// replaces the one scheduled before it.
+    ///
+    /// Returns the deadline of the timer in milliseconds since the unix epoch.
+    #[inline]
+    pub fn schedule_processing_time(
+        &mut self,
+        duration: Duration,
+        entry: TimerState,
+    ) -> ArconResult<u64> {
+        self.timer.active_key(self.current_key);
+        self.processing_timer_scheduled = true;
+        self.timer.schedule_processing_time(duration, entry)
+    }
+
+    /// Cancel the event-time timer of the current key at `time`
+    ///
+    /// Returns the entry of the timer if it was pending.
+    #[inline]
+    pub fn cancel_timer(&mut self, time: u64) -> ArconResult<Option<TimerState>> {
+        Ok(self.timer.cancel(time, self.current_key)?)
+    }
+
+    /// Returns the pending event-time timers of the current key ordered by time
+    #[inline]
+    pub fn timers(&self) -> ArconResult<Vec<(u64, TimerState)>> {
+        Ok(self.timer.timers_for_key(self.current_key)?)
+    }
+
+    /// Cancel the processing-time timer of the current key with the given `deadline`
+    ///
+    /// Returns the entry of the timer if it was pending.
+    #[inline]
+    pub fn cancel_processing_timer(&mut self, deadline: u64) -> ArconResult<Option<TimerState>> {
+        Ok(self
+            .timer
+            .cancel_processing_time(deadline, self.current_key)?)
+    }
+
+    /// Returns the pending processing-time timers of the current key ordered by deadline
+    #[inline]
+    pub fn processing_timers(&self) -> ArconResult<Vec<(u64, TimerState)>> {
+        Ok(self.timer.processing_timers_for_key(self.current_key)?)
+    }
+
+    /// Emit an element to the side output identified by `tag`
+    ///
+    /// Elements are dropped if no stream has been created for the side output.
+    #[inline]
+    pub fn output<T: ArconType>(
+        &mut self,
+        tag: &OutputTag<T>,
+        element: ArconElement<T>,
+    ) -> ArconResult<()> {
+        self.side_outputs.emit(tag, element)
+    }
+
     #[cfg(feature = "metrics")]
     pub fn register_gauge(&mut self, name: &str) {
         register_gauge!(format!("{}_{}", self.name, name));
@@ -0,0 +1,161 @@
+use crate::{
+    data::{ArconElement, ArconEvent, ArconType, Epoch, NodeID, Watermark},
+    error::*,
+    reportable_error,
+    stream::channel::{strategy::ChannelStrategy, Channel},
+};
+use std::{any::Any, marker::PhantomData};
+
+/// Identifies a side output of an [Operator](super::Operator)
+///
+/// Records of type `T` that are emitted with
+/// [OperatorContext::output](super::OperatorContext::output) end up on the stream
+/// returned by [Stream::side_output](crate::dataflow::stream::Stream::side_output).
+pub struct OutputTag<T: ArconType> {
+    id: String,
+    _marker: PhantomData<T>,
+}
+
+impl<T: ArconType> OutputTag<T> {
+    pub fn new(id: impl Into<String>) -> Self {
+        Self {
+            id: id.into(),
+            _marker: PhantomData,
+        }
+    }
+
+    /// Returns the identifier of the side output
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+}
+
+impl<T: ArconType> Clone for OutputTag<T> {
+    fn clone(&self) -> Self {
+        Self::new(self.id.clone())
+    }
+}
+
+/// Events that are forwarded on every side output of a Node
+#[derive(Debug, Clone)]
+pub(crate) enum Marker {
+    Watermark(Watermark),
+    Epoch(Epoch),
+    Death(String),
+}
+
+impl Marker {
+    /// Returns the marker of `event` or `None` if it is an element
+    pub fn from_event<A: ArconType>(event: &ArconEvent<A>) -> Option<Marker> {
+        match event {
+            ArconEvent::Element(_) => None,
+            ArconEvent::Watermark(w) => Some(Marker::Watermark(*w)),
+            ArconEvent::Epoch(e) => Some(Marker::Epoch(*e)),
+            ArconEvent::Death(s) => Some(Marker::Death(s.clone())),
+        }
+    }
+
+    fn into_event<A: ArconType>(self) -> ArconEvent<A> {
+        match self {
+            Marker::Watermark(w) => ArconEvent::Watermark(w),
+            Marker::Epoch(e) => ArconEvent::Epoch(e),
+            Marker::Death(s) => ArconEvent::Death(s),
+        }
+    }
+}
+
+/// Type erased side output channel of a Node
+pub(crate) trait ErasedSideChannel: Send {
+    fn id(&self) -> &str;
+    fn set_sender_id(&mut self, sender_id: NodeID);
+    fn send_marker(&mut self, marker: Marker) -> ArconResult<()>;
+    fn as_any(&mut self) -> &mut dyn Any;
+}
+
+/// Sends the records of a side output downstream
+pub(crate) struct SideChannel<T: ArconType> {
+    id: String,
+    strategy: ChannelStrategy<T>,
+}
+
+impl<T: ArconType> SideChannel<T> {
+    pub fn new(id: String, strategy: ChannelStrategy<T>) -> Self {
+        Self { id, strategy }
+    }
+
+    fn push(&mut self, event: ArconEvent<T>) -> ArconResult<()> {
+        for (channel, msg) in self.strategy.push(event) {
+            match &*channel {
+                Channel::Local(actor_ref) => actor_ref.tell(msg),
+                Channel::Remote(..) => {
+                    return Err(Error::Unsupported {
+                        msg: "Side outputs to remote channels are not supported".to_string(),
+                    });
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<T: ArconType> ErasedSideChannel for SideChannel<T> {
+    fn id(&self) -> &str {
+        &self.id
+    }
+    fn set_sender_id(&mut self, sender_id: NodeID) {
+        self.strategy.set_sender_id(sender_id);
+    }
+    fn send_marker(&mut self, marker: Marker) -> ArconResult<()> {
+        self.push(marker.into_event())
+    }
+    fn as_any(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+/// The side output channels of a Node
+#[derive(Default)]
+pub(crate) struct SideOutputs {
+    channels: Vec<Box<dyn ErasedSideChannel>>,
+}
+
+impl SideOutputs {
+    pub fn new(channels: Vec<Box<dyn ErasedSideChannel>>) -> Self {
+        Self { channels }
+    }
+
+    /// Send `element` on every channel of the side output `tag`
+    ///
+    /// Elements of side outputs that no stream has been created for are dropped.
+    pub fn emit<T: ArconType>(
+        &mut self,
+        tag: &OutputTag<T>,
+        element: ArconElement<T>,
+    ) -> ArconResult<()> {
+        for channel in self.channels.iter_mut().filter(|c| c.id() == tag.id()) {
+            match channel.as_any().downcast_mut::<SideChannel<T>>() {
+                Some(channel) => channel.push(ArconEvent::Element(element.clone()))?,
+                None => {
+                    return reportable_error!(
+                        "Side output {} is used with different types",
+                        tag.id()
+                    );
+                }
+            }
+        }
+        Ok(())
+    }
+
+    pub fn set_sender_id(&mut self, sender_id: NodeID) {
+        for channel in self.channels.iter_mut() {
+            channel.set_sender_id(sender_id);
+        }
+    }
+
+    pub fn send_marker(&mut self, marker: Marker) -> ArconResult<()> {
+        for channel in self.channels.iter_mut() {
+            channel.send_marker(marker.clone())?;
+        }
+        Ok(())
+    }
+}
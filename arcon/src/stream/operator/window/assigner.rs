@@ -1,4 +1,10 @@
-use super::WindowContext;
+use super::{
+    evictor::Evictor,
+    trigger::{
+        CountTrigger, EventTimeTrigger, NeverTrigger, Trigger, TriggerResult, TriggerWindow,
+    },
+    WindowContext,
+};
 use crate::dataflow::{builder::Assigner, conf::WindowConf};
 use crate::index::WindowIndex;
 use crate::prelude::EagerValue;
@@ -6,8 +12,10 @@ use crate::{
     data::ArconElement,
     error::*,
     index::{EagerHashTable, IndexOps, ValueIndex},
-    reportable_error,
-    stream::operator::{Operator, OperatorContext},
+    stream::{
+        node::timer::processing_time_now,
+        operator::{side::OutputTag, Operator, OperatorContext},
+    },
 };
 use arcon_macros::ArconState;
 use arcon_state::Backend;
@@ -23,27 +31,96 @@ type Timestamp = u64;
 #[derive(Message, PartialEq, Clone, Copy)]
 pub struct WindowEvent {
     #[prost(uint64, tag = "1")]
-    key: Key,
+    pub(crate) key: Key,
     #[prost(uint64, tag = "2")]
-    index: Index,
+    pub(crate) index: Index,
     #[prost(uint64, tag = "3")]
-    timestamp: Timestamp,
+    pub(crate) timestamp: Timestamp,
+    /// Set for the processing-time timers of a key, see [ProcessingWindows]
+    #[prost(bool, tag = "4")]
+    pub(crate) processing_time: bool,
 }
 
 impl WindowEvent {
-    fn new(key: Key, index: Index, timestamp: Timestamp) -> WindowEvent {
+    pub(crate) fn new(key: Key, index: Index, timestamp: Timestamp) -> WindowEvent {
         WindowEvent {
             key,
             index,
             timestamp,
+            processing_time: false,
+        }
+    }
+
+    fn processing_timer(key: Key) -> WindowEvent {
+        WindowEvent {
+            key,
+            index: 0,
+            timestamp: 0,
+            processing_time: true,
         }
     }
 }
 
+/// An active session window
+#[derive(Message, PartialEq, Clone, Copy)]
+pub struct SessionWindow {
+    #[prost(uint64, tag = "1")]
+    index: Index,
+    #[prost(uint64, tag = "2")]
+    start: Timestamp,
+    /// Timestamp of the last element plus the session gap
+    #[prost(uint64, tag = "3")]
+    end: Timestamp,
+}
+
+impl SessionWindow {
+    #[inline]
+    fn intersects(&self, start: Timestamp, end: Timestamp) -> bool {
+        self.start <= end && start <= self.end
+    }
+}
+
+/// The active session windows of a key
+#[derive(Message, PartialEq, Clone)]
+pub struct Sessions {
+    #[prost(message, repeated, tag = "1")]
+    windows: Vec<SessionWindow>,
+    #[prost(uint64, tag = "2")]
+    next_index: Index,
+}
+
+/// A window waiting for a processing time callback of its [Trigger]
+#[derive(Message, PartialEq, Clone, Copy)]
+pub struct ProcessingWindow {
+    #[prost(uint64, tag = "1")]
+    index: Index,
+    /// Wall-clock time in milliseconds at which the callback is due
+    #[prost(uint64, tag = "2")]
+    deadline: u64,
+}
+
+/// The windows of a key waiting for processing time callbacks
+///
+/// The processing-time timers of a key all carry the same [WindowEvent], so windows whose
+/// callbacks share a deadline do not replace each other. A timer handles every window that is due.
+#[derive(Message, PartialEq, Clone)]
+pub struct ProcessingWindows {
+    #[prost(message, repeated, tag = "1")]
+    windows: Vec<ProcessingWindow>,
+}
+
 #[derive(ArconState)]
 pub struct WindowState<I: WindowIndex, B: Backend> {
     window_start: EagerValue<Timestamp, B>,
     active_windows: EagerHashTable<WindowContext, (), B>,
+    /// Number of elements added to each window since it was created or purged
+    window_counts: EagerHashTable<WindowContext, u64, B>,
+    /// Active [Sessions] of each key
+    sessions: EagerHashTable<Key, Sessions, B>,
+    /// Number of elements each key has assigned to count windows
+    count_position: EagerValue<u64, B>,
+    /// [ProcessingWindows] of each key
+    processing_windows: EagerHashTable<Key, ProcessingWindows, B>,
     index: I,
 }
 
@@ -51,22 +128,41 @@ impl<I: WindowIndex, B: Backend> WindowState<I, B> {
     pub fn new(index: I, backend: Arc<B>) -> Self {
         Self {
             window_start: EagerValue::new("_window_start", backend.clone()),
-            active_windows: EagerHashTable::new("_active_windows", backend),
+            active_windows: EagerHashTable::new("_active_windows", backend.clone()),
+            window_counts: EagerHashTable::new("_window_counts", backend.clone()),
+            sessions: EagerHashTable::new("_sessions", backend.clone()),
+            count_position: EagerValue::new("_count_position", backend.clone()),
+            processing_windows: EagerHashTable::new("_processing_windows", backend),
             index,
         }
     }
 }
 
+/// The kind of windows a [WindowAssigner] creates
+#[derive(Clone, Copy)]
+enum WindowKind {
+    Time { length: u64, slide: u64 },
+    Session { gap: u64 },
+    Count { size: u64, slide: u64 },
+    Global,
+}
+
 /// Window Assigner Based on Event Time
+///
+/// When a window emits its result is decided by a [Trigger]. Each kind of
+/// window comes with a default trigger that may be replaced using [WindowAssigner::with_trigger].
 pub struct WindowAssigner<I, B>
 where
     I: WindowIndex,
     B: Backend,
 {
     // effectively immutable, so no reason to persist
-    window_length: u64,
-    window_slide: u64,
+    kind: WindowKind,
     late_arrival_time: u64,
+    trigger: Box<dyn Trigger>,
+    evictor: Option<Box<dyn Evictor<I::IN>>>,
+    /// Side output that receives elements arriving after their windows closed
+    late_data: Option<OutputTag<I::IN>>,
     _marker: PhantomData<(I, B)>,
 }
 
@@ -86,6 +182,30 @@ where
                 length,
                 late_arrival,
             } => Self::setup(length.0, length.0, late_arrival.0),
+            Assigner::Session { gap, late_arrival } => {
+                if gap.0 == 0 {
+                    panic!("Session gap must be greater than zero!");
+                }
+                Self::with_kind(
+                    WindowKind::Session { gap: gap.0 },
+                    late_arrival.0,
+                    Box::new(EventTimeTrigger),
+                )
+            }
+            Assigner::Count { size, slide } => {
+                if slide == 0 {
+                    panic!("Count window slide must be greater than zero!");
+                }
+                if size < slide {
+                    panic!("Count window size lower than slide!");
+                }
+                Self::with_kind(
+                    WindowKind::Count { size, slide },
+                    0,
+                    Box::new(CountTrigger::of(size)),
+                )
+            }
+            Assigner::Global => Self::with_kind(WindowKind::Global, 0, Box::new(NeverTrigger)),
         }
     }
 
@@ -99,34 +219,432 @@ where
             panic!("Window Length not divisible by slide!");
         }
 
+        Self::with_kind(
+            WindowKind::Time { length, slide },
+            late,
+            Box::new(EventTimeTrigger),
+        )
+    }
+
+    fn with_kind(kind: WindowKind, late: u64, trigger: Box<dyn Trigger>) -> Self {
         WindowAssigner {
-            window_length: length,
-            window_slide: slide,
+            kind,
             late_arrival_time: late,
+            trigger,
+            evictor: None,
+            late_data: None,
             _marker: Default::default(),
         }
     }
 
-    #[inline]
-    fn new_window_trigger(
+    /// Emit elements that arrive too late for their windows to the side output `tag`
+    ///
+    /// Late elements are dropped if no side output is set.
+    pub fn with_late_data(mut self, tag: OutputTag<I::IN>) -> Self {
+        self.late_data = Some(tag);
+        self
+    }
+
+    /// Replace the default trigger of the assigner with `trigger`
+    pub fn with_trigger(mut self, trigger: impl Trigger + 'static) -> Self {
+        self.trigger = Box::new(trigger);
+        self
+    }
+
+    /// Remove elements of windows with `evictor` every time they fire
+    pub fn with_evictor(mut self, evictor: impl Evictor<I::IN> + 'static) -> Self {
+        self.evictor = Some(Box::new(evictor));
+        self
+    }
+
+    /// Add `data` to the window and return the number of elements added since it was purged
+    fn add_element(
+        &mut self,
+        data: I::IN,
+        window_ctx: WindowContext,
+        ctx: &mut OperatorContext<WindowEvent, WindowState<I, B>>,
+    ) -> ArconResult<u64> {
+        let state = ctx.state();
+        state.index().on_element(data, window_ctx)?;
+        let count = state.window_counts().get(&window_ctx)?.unwrap_or(0) + 1;
+        state.window_counts().put(window_ctx, count)?;
+        Ok(count)
+    }
+
+    /// Let the trigger act on an element added to `window`, emitting the window with `timestamp` if it fires
+    fn element_trigger(
+        &mut self,
+        element_timestamp: Timestamp,
+        window: &TriggerWindow,
+        timestamp: Timestamp,
+        ctx: &mut OperatorContext<WindowEvent, WindowState<I, B>>,
+        output: &mut Vec<ArconElement<I::OUT>>,
+    ) -> ArconResult<()> {
+        // The first element since the window was created or purged
+        if window.count == 1 {
+            self.schedule_processing_time(window, ctx)?;
+        }
+        let result = self.trigger.on_element(element_timestamp, window);
+        self.apply_trigger(result, window, timestamp, ctx, output)
+    }
+
+    /// Register a processing time callback for `window` if its trigger asks for one
+    fn schedule_processing_time(
+        &mut self,
+        window: &TriggerWindow,
+        ctx: &mut OperatorContext<WindowEvent, WindowState<I, B>>,
+    ) -> ArconResult<()> {
+        let delay = match self.trigger.processing_time_delay(window) {
+            Some(delay) => delay,
+            None => return Ok(()),
+        };
+        let key = window.ctx.key;
+        let deadline = ctx.schedule_processing_time(delay, WindowEvent::processing_timer(key))?;
+        let mut pending = ctx
+            .state()
+            .processing_windows()
+            .get(&key)?
+            .unwrap_or_default();
+        // A window restarted after a purge only keeps its latest callback
+        pending.windows.retain(|w| w.index != window.ctx.index);
+        pending.windows.push(ProcessingWindow {
+            index: window.ctx.index,
+            deadline,
+        });
+        ctx.state().processing_windows().put(key, pending)?;
+        Ok(())
+    }
+
+    /// Event time at which `window_ctx` ends, `None` for count and global windows
+    fn window_end(
+        &self,
+        window_ctx: WindowContext,
+        ctx: &mut OperatorContext<WindowEvent, WindowState<I, B>>,
+    ) -> ArconResult<Option<Timestamp>> {
+        match self.kind {
+            WindowKind::Time { length, slide } => {
+                let start = ctx.state().window_start().get()?.map(|s| *s).unwrap_or(0);
+                Ok(Some(start + (window_ctx.index * slide) + length))
+            }
+            WindowKind::Session { .. } => Ok(ctx
+                .state()
+                .sessions()
+                .get(&window_ctx.key)?
+                .and_then(|sessions| {
+                    sessions
+                        .windows
+                        .iter()
+                        .find(|s| s.index == window_ctx.index)
+                        .map(|s| s.end)
+                })),
+            WindowKind::Count { .. } | WindowKind::Global => Ok(None),
+        }
+    }
+
+    /// Let the trigger act on the windows of the current key whose processing time callbacks are due
+    fn handle_processing_timeout(
+        &mut self,
+        ctx: &mut OperatorContext<WindowEvent, WindowState<I, B>>,
+    ) -> ArconResult<Vec<ArconElement<I::OUT>>> {
+        let key = ctx.current_key;
+        let mut output = Vec::new();
+        let pending = match ctx.state().processing_windows().remove(&key)? {
+            Some(pending) => pending,
+            None => return Ok(output),
+        };
+        let now = processing_time_now();
+        let (due, waiting): (Vec<ProcessingWindow>, Vec<ProcessingWindow>) =
+            pending.windows.into_iter().partition(|w| w.deadline <= now);
+        if !waiting.is_empty() {
+            let waiting = ProcessingWindows { windows: waiting };
+            ctx.state().processing_windows().put(key, waiting)?;
+        }
+
+        for ProcessingWindow { index, .. } in due {
+            let window_ctx = WindowContext::new(key, index);
+            // Windows that have been purged or closed have no count
+            let count = match ctx.state().window_counts().get(&window_ctx)? {
+                Some(count) => count,
+                None => continue,
+            };
+            let end = self.window_end(window_ctx, ctx)?;
+            let window = TriggerWindow {
+                ctx: window_ctx,
+                end,
+                count,
+            };
+            let result = self.trigger.on_processing_time(now, &window);
+            let timestamp = match end {
+                Some(end) => end,
+                None => ctx.current_time()?,
+            };
+            self.apply_trigger(result, &window, timestamp, ctx, &mut output)?;
+            if ctx.state().window_counts().contains(&window_ctx)? {
+                self.schedule_processing_time(&window, ctx)?;
+            }
+        }
+
+        Ok(output)
+    }
+
+    /// Act on the `result` of a trigger, emitting the window with `timestamp` if it fires
+    fn apply_trigger(
+        &mut self,
+        result: TriggerResult,
+        window: &TriggerWindow,
+        timestamp: Timestamp,
+        ctx: &mut OperatorContext<WindowEvent, WindowState<I, B>>,
+        output: &mut Vec<ArconElement<I::OUT>>,
+    ) -> ArconResult<()> {
+        // There is nothing to emit for windows that have been purged
+        if result.is_fire() && window.count > 0 {
+            if let Some(evictor) = self.evictor.as_mut() {
+                ctx.state()
+                    .index()
+                    .evict(window.ctx, &mut |elements| evictor.evict(elements, window))?;
+            }
+            let result = ctx.state().index().result(window.ctx)?;
+            output.push(ArconElement::with_timestamp(result, timestamp));
+        }
+        if result.is_purge() {
+            self.purge(window.ctx, ctx)?;
+        }
+        Ok(())
+    }
+
+    fn purge(
         &mut self,
         window_ctx: WindowContext,
         ctx: &mut OperatorContext<WindowEvent, WindowState<I, B>>,
     ) -> ArconResult<()> {
-        let window_start = match ctx.state().window_start().get()? {
+        let state = ctx.state();
+        state.index().clear(window_ctx)?;
+        state.window_counts().remove(&window_ctx)?;
+        Ok(())
+    }
+
+    fn handle_time_element(
+        &mut self,
+        element: ArconElement<I::IN>,
+        length: u64,
+        slide: u64,
+        ctx: &mut OperatorContext<WindowEvent, WindowState<I, B>>,
+        output: &mut Vec<ArconElement<I::OUT>>,
+    ) -> ArconResult<()> {
+        let ts = element.timestamp;
+        let start = match ctx.state().window_start().get()? {
             Some(start) => *start,
             None => {
-                return reportable_error!(
-                    "Unexpected failure, could not find window start for existing key"
-                );
+                if ts < self.late_arrival_time {
+                    0
+                } else {
+                    let start = ts - self.late_arrival_time;
+                    ctx.state().window_start().put(start)?;
+                    start
+                }
+            }
+        };
+        let ceil = (ts - start) / slide;
+        let floor = if ceil >= (length / slide) {
+            ceil - (length / slide) + 1
+        } else {
+            0
+        };
+
+        // For all windows, insert element....
+        for index in floor..=ceil {
+            let window_ctx = WindowContext {
+                key: ctx.current_key,
+                index,
+            };
+            let end = start + (index * slide) + length;
+            let count = self.add_element(element.data.clone(), window_ctx, ctx)?;
+
+            let active_exist = ctx.state().active_windows().contains(&window_ctx)?;
+
+            // if it does not exist, then add active window and create trigger
+            if !active_exist {
+                ctx.state().active_windows().put(window_ctx, ())?;
+
+                self.new_window_trigger(window_ctx, end, ctx)?;
+            }
+
+            let window = TriggerWindow {
+                ctx: window_ctx,
+                end: Some(end),
+                count,
+            };
+            self.element_trigger(ts, &window, end, ctx, output)?;
+        }
+
+        Ok(())
+    }
+
+    fn handle_count_element(
+        &mut self,
+        element: ArconElement<I::IN>,
+        size: u64,
+        slide: u64,
+        ctx: &mut OperatorContext<WindowEvent, WindowState<I, B>>,
+        output: &mut Vec<ArconElement<I::OUT>>,
+    ) -> ArconResult<()> {
+        let ts = element.timestamp;
+        let position = ctx.state().count_position().get()?.map(|p| *p).unwrap_or(0);
+        ctx.state().count_position().put(position + 1)?;
+
+        let last = position / slide;
+        let first = if position >= size {
+            (position - size) / slide + 1
+        } else {
+            0
+        };
+
+        for index in first..=last {
+            let window_ctx = WindowContext::new(ctx.current_key, index);
+            let count = self.add_element(element.data.clone(), window_ctx, ctx)?;
+            let window = TriggerWindow {
+                ctx: window_ctx,
+                end: None,
+                count,
+            };
+            self.element_trigger(ts, &window, ts, ctx, output)?;
+
+            // The window has received all of its elements
+            if position == index * slide + size - 1 {
+                self.purge(window_ctx, ctx)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Assign `element` to a session of the current key, merging sessions that overlap
+    fn handle_session_element(
+        &mut self,
+        element: ArconElement<I::IN>,
+        gap: u64,
+        ctx: &mut OperatorContext<WindowEvent, WindowState<I, B>>,
+        output: &mut Vec<ArconElement<I::OUT>>,
+    ) -> ArconResult<()> {
+        let key = ctx.current_key;
+        let start = element.timestamp;
+        let end = start + gap;
+        let mut sessions = ctx.state().sessions().get(&key)?.unwrap_or_default();
+
+        let (overlapping, mut windows): (Vec<SessionWindow>, Vec<SessionWindow>) = sessions
+            .windows
+            .iter()
+            .partition(|session| session.intersects(start, end));
+
+        let mut session = match overlapping.first() {
+            Some(first) => *first,
+            None => {
+                let index = sessions.next_index;
+                sessions.next_index += 1;
+                SessionWindow { index, start, end }
             }
         };
+        let session_ctx = WindowContext::new(key, session.index);
+
+        for other in overlapping.iter().skip(1) {
+            let other_ctx = WindowContext::new(key, other.index);
+            let state = ctx.state();
+            state.index().merge(other_ctx, session_ctx)?;
+            let merged = state.window_counts().remove(&other_ctx)?.unwrap_or(0);
+            let count = state.window_counts().get(&session_ctx)?.unwrap_or(0);
+            state.window_counts().put(session_ctx, count + merged)?;
+            session.start = session.start.min(other.start);
+            session.end = session.end.max(other.end);
+        }
+        session.start = session.start.min(start);
+        session.end = session.end.max(end);
+
+        let count = self.add_element(element.data, session_ctx, ctx)?;
+
+        // Replace the timers of the sessions that have been merged or extended
+        for other in overlapping.iter() {
+            ctx.cancel_timer(other.end + self.late_arrival_time)?;
+        }
+        let request = ctx.schedule_at(
+            session.end + self.late_arrival_time,
+            WindowEvent::new(key, session.index, session.end),
+        )?;
+        if let Err(expired) = request {
+            error!(ctx.log(), "{}", expired);
+        }
+
+        windows.push(session);
+        sessions.windows = windows;
+        ctx.state().sessions().put(key, sessions)?;
+
+        let window = TriggerWindow {
+            ctx: session_ctx,
+            end: Some(session.end),
+            count,
+        };
+        self.element_trigger(start, &window, session.end, ctx, output)
+    }
+
+    /// Close the session `index` of the current key that ends at `timestamp`
+    fn handle_session_timeout(
+        &mut self,
+        index: Index,
+        timestamp: Timestamp,
+        ctx: &mut OperatorContext<WindowEvent, WindowState<I, B>>,
+    ) -> ArconResult<Vec<ArconElement<I::OUT>>> {
+        let key = ctx.current_key;
+        let mut output = Vec::new();
+        let mut sessions = match ctx.state().sessions().get(&key)? {
+            Some(sessions) => sessions,
+            None => return Ok(output),
+        };
+        let position = match sessions.windows.iter().position(|s| s.index == index) {
+            Some(position) => position,
+            None => return Ok(output),
+        };
+        let session = sessions.windows.remove(position);
+        let window_ctx = WindowContext::new(key, session.index);
+
+        self.close_window(window_ctx, timestamp, ctx, &mut output)?;
 
-        let ts = window_start + (window_ctx.index * self.window_slide) + self.window_length;
+        if sessions.windows.is_empty() {
+            ctx.state().sessions().remove(&key)?;
+        } else {
+            ctx.state().sessions().put(key, sessions)?;
+        }
+
+        Ok(output)
+    }
+
+    /// Let the trigger act on a time window that has ended at `timestamp` and remove it
+    fn close_window(
+        &mut self,
+        window_ctx: WindowContext,
+        timestamp: Timestamp,
+        ctx: &mut OperatorContext<WindowEvent, WindowState<I, B>>,
+        output: &mut Vec<ArconElement<I::OUT>>,
+    ) -> ArconResult<()> {
+        let count = ctx.state().window_counts().get(&window_ctx)?.unwrap_or(0);
+        let window = TriggerWindow {
+            ctx: window_ctx,
+            end: Some(timestamp),
+            count,
+        };
+        let result = self.trigger.on_event_time(timestamp, &window);
+        self.apply_trigger(result, &window, timestamp, ctx, output)?;
+        self.purge(window_ctx, ctx)
+    }
 
+    #[inline]
+    fn new_window_trigger(
+        &mut self,
+        window_ctx: WindowContext,
+        end: Timestamp,
+        ctx: &mut OperatorContext<WindowEvent, WindowState<I, B>>,
+    ) -> ArconResult<()> {
         let request = ctx.schedule_at(
-            ts + self.late_arrival_time,
-            WindowEvent::new(window_ctx.key, window_ctx.index, ts),
+            end + self.late_arrival_time,
+            WindowEvent::new(window_ctx.key, window_ctx.index, end),
         )?;
 
         if let Err(expired) = request {
@@ -146,13 +664,35 @@ where
     type OUT = I::OUT;
     type TimerState = WindowEvent;
     type OperatorState = WindowState<I, B>;
-    type ElementIterator = Option<ArconElement<I::OUT>>;
+    type ElementIterator = Vec<ArconElement<I::OUT>>;
 
     fn handle_element(
         &mut self,
         element: ArconElement<Self::IN>,
         ctx: &mut OperatorContext<Self::TimerState, Self::OperatorState>,
     ) -> ArconResult<Self::ElementIterator> {
+        let mut output = Vec::new();
+
+        match self.kind {
+            WindowKind::Count { size, slide } => {
+                self.handle_count_element(element, size, slide, ctx, &mut output)?;
+                return Ok(output);
+            }
+            WindowKind::Global => {
+                let ts = element.timestamp;
+                let window_ctx = WindowContext::new(ctx.current_key, 0);
+                let count = self.add_element(element.data, window_ctx, ctx)?;
+                let window = TriggerWindow {
+                    ctx: window_ctx,
+                    end: None,
+                    count,
+                };
+                self.element_trigger(ts, &window, ts, ctx, &mut output)?;
+                return Ok(output);
+            }
+            _ => (),
+        }
+
         let ts = element.timestamp;
 
         let time = ctx.current_time()?;
@@ -161,49 +701,23 @@ where
 
         if ts < ts_lower_bound {
             // Late arrival: early return
-            return Ok(None);
+            if let Some(tag) = &self.late_data {
+                ctx.output(tag, element)?;
+            }
+            return Ok(output);
         }
 
-        let start = match ctx.state().window_start().get()? {
-            Some(start) => *start,
-            None => {
-                if ts < self.late_arrival_time {
-                    0
-                } else {
-                    let start = ts - self.late_arrival_time;
-                    ctx.state().window_start().put(start)?;
-                    start
-                }
+        match self.kind {
+            WindowKind::Session { gap } => {
+                self.handle_session_element(element, gap, ctx, &mut output)?
             }
-        };
-        let ceil = (ts - start) / self.window_slide;
-        let floor = if ceil >= (self.window_length / self.window_slide) {
-            ceil - (self.window_length / self.window_slide) + 1
-        } else {
-            0
-        };
-
-        // For all windows, insert element....
-        for index in floor..=ceil {
-            let window_ctx = WindowContext {
-                key: ctx.current_key,
-                index,
-            };
-            ctx.state()
-                .index()
-                .on_element(element.data.clone(), window_ctx)?;
-
-            let active_exist = ctx.state().active_windows().contains(&window_ctx)?;
-
-            // if it does not exist, then add active window and create trigger
-            if !active_exist {
-                ctx.state().active_windows().put(window_ctx, ())?;
-
-                self.new_window_trigger(window_ctx, ctx)?;
+            WindowKind::Time { length, slide } => {
+                self.handle_time_element(element, length, slide, ctx, &mut output)?
             }
+            WindowKind::Count { .. } | WindowKind::Global => (),
         }
 
-        Ok(None)
+        Ok(output)
     }
 
     fn handle_timeout(
@@ -215,16 +729,26 @@ where
             key,
             index,
             timestamp,
+            processing_time,
         } = timeout;
         ctx.current_key = key;
+        if processing_time {
+            return Ok(Some(self.handle_processing_timeout(ctx)?));
+        }
+        if let WindowKind::Session { .. } = self.kind {
+            return Ok(Some(self.handle_session_timeout(index, timestamp, ctx)?));
+        }
         let window_ctx = WindowContext::new(key, index);
 
-        let state = ctx.state();
-        let result = state.index().result(window_ctx)?;
-        state.index().clear(window_ctx)?;
-        state.active_windows().remove(&window_ctx)?;
+        let mut output = Vec::new();
+        self.close_window(window_ctx, timestamp, ctx, &mut output)?;
+        ctx.state().active_windows().remove(&window_ctx)?;
+
+        Ok(Some(output))
+    }
 
-        Ok(Some(Some(ArconElement::with_timestamp(result, timestamp))))
+    fn handles_late_elements(&self) -> bool {
+        self.late_data.is_some()
     }
 }
 
@@ -247,6 +771,10 @@ mod tests {
                 Channel,
             },
             node::{debug::DebugNode, Node, NodeState},
+            operator::{
+                side::{SideChannel, SideOutputs},
+                window::{CountEvictor, CountTrigger},
+            },
             time::Time,
         },
     };
@@ -254,21 +782,25 @@ mod tests {
     use kompact::prelude::{biconnect_components, ActorRefFactory, ActorRefStrong, Component};
     use std::{sync::Arc, thread, time, time::UNIX_EPOCH};
 
+    type DebugSink = Arc<Component<DebugNode<u64>>>;
+    type TestAssigner = WindowAssigner<AppenderWindow<u64, u64, fn(&[u64]) -> u64, Sled>, Sled>;
+
+    fn appender_fn(u: &[u64]) -> u64 {
+        u.len() as u64
+    }
+
     // helper functions
     fn window_assigner_test_setup(
         length: u64,
         slide: u64,
         late: u64,
         keyed: bool,
-    ) -> (
-        ActorRefStrong<ArconMessage<u64>>,
-        Arc<Component<DebugNode<u64>>>,
-    ) {
-        let app = Application::default();
-        let pool_info = app.get_pool_info();
-        let epoch_manager_ref = app.epoch_manager();
+    ) -> (ActorRefStrong<ArconMessage<u64>>, DebugSink) {
+        let (assigner_ref, sink, _) = window_assigner_late_data_setup(length, slide, late, keyed);
+        (assigner_ref, sink)
+    }
 
-        // Create a sink
+    fn debug_sink(app: &Application) -> (DebugSink, ActorRefStrong<ArconMessage<u64>>) {
         let sink = app.data_system().create(DebugNode::<u64>::new);
 
         app.data_system()
@@ -278,34 +810,75 @@ mod tests {
 
         let sink_ref: ActorRefStrong<ArconMessage<u64>> =
             sink.actor_ref().hold().expect("failed to get strong ref");
+        (sink, sink_ref)
+    }
+
+    // Sets up a window assigner whose late arrivals are sent to a separate sink
+    fn window_assigner_late_data_setup(
+        length: u64,
+        slide: u64,
+        late: u64,
+        keyed: bool,
+    ) -> (ActorRefStrong<ArconMessage<u64>>, DebugSink, DebugSink) {
+        let assigner = Assigner::Sliding {
+            length: Time::seconds(length),
+            slide: Time::seconds(slide),
+            late_arrival: Time::seconds(late),
+        };
+        window_assigner_setup(assigner, keyed, |assigner| assigner)
+    }
+
+    fn session_window_test_setup(
+        gap: u64,
+        late: u64,
+        keyed: bool,
+    ) -> (ActorRefStrong<ArconMessage<u64>>, DebugSink) {
+        let assigner = Assigner::Session {
+            gap: Time::seconds(gap),
+            late_arrival: Time::seconds(late),
+        };
+        let (assigner_ref, sink, _) = window_assigner_setup(assigner, keyed, |assigner| assigner);
+        (assigner_ref, sink)
+    }
+
+    fn window_assigner_setup(
+        assigner: Assigner,
+        keyed: bool,
+        configure: fn(TestAssigner) -> TestAssigner,
+    ) -> (ActorRefStrong<ArconMessage<u64>>, DebugSink, DebugSink) {
+        let app = Application::default();
+        let pool_info = app.get_pool_info();
+        let epoch_manager_ref = app.epoch_manager();
+
+        // Create a sink
+        let (sink, sink_ref) = debug_sink(&app);
+        let (late_sink, late_sink_ref) = debug_sink(&app);
 
         let channel_strategy = ChannelStrategy::Forward(Forward::new(
             Channel::Local(sink_ref),
             NodeID::new(1),
-            pool_info,
+            pool_info.clone(),
         ));
+        let side_outputs = SideOutputs::new(vec![Box::new(SideChannel::new(
+            String::from("late_data"),
+            ChannelStrategy::Forward(Forward::new(
+                Channel::Local(late_sink_ref),
+                NodeID::new(1),
+                pool_info,
+            )),
+        ))]);
 
         let backend = Arc::new(crate::test_utils::temp_backend::<Sled>());
         let descriptor = String::from("node_");
         let in_channels = vec![0.into()];
 
-        fn appender_fn(u: &[u64]) -> u64 {
-            u.len() as u64
-        }
-
         let builder = OperatorBuilder {
             operator: Arc::new(move || {
-                let conf = WindowConf {
-                    assigner: Assigner::Sliding {
-                        length: Time::seconds(length),
-                        slide: Time::seconds(slide),
-                        late_arrival: Time::seconds(late),
-                    },
-                };
-                WindowAssigner::new(conf)
+                let conf = WindowConf { assigner };
+                configure(WindowAssigner::new(conf).with_late_data(OutputTag::new("late_data")))
             }),
             state: Arc::new(|backend: Arc<Sled>| {
-                let index = AppenderWindow::new(backend.clone(), &appender_fn);
+                let index = AppenderWindow::new(backend.clone(), appender_fn as fn(&[u64]) -> u64);
                 WindowState::new(index, backend)
             }),
             conf: Default::default(),
@@ -355,7 +928,8 @@ mod tests {
             perf_events,
             GlobalNodeId::null(),
             key_builder,
-        );
+        )
+        .with_side_outputs(side_outputs);
 
         let window_comp = app.data_system().create(|| node);
         let required_ref = window_comp.on_definition(|cd| cd.node_manager_port.share());
@@ -379,7 +953,7 @@ mod tests {
                 .insert(GlobalNodeId::null(), (window_comp, required_ref));
         });
 
-        (win_ref, sink)
+        (win_ref, sink, late_sink)
     }
     fn now() -> u64 {
         time::SystemTime::now()
@@ -473,6 +1047,30 @@ mod tests {
         });
     }
     #[test]
+    fn window_late_arrival_side_output() {
+        // Send 2 messages on time, a watermark and a late arrival which ends up in the side output
+        let (assigner_ref, sink, late_sink) = window_assigner_late_data_setup(10, 10, 0, false);
+        wait(1);
+        // Send messages
+        let moment = now();
+        assigner_ref.tell(timestamped_event(moment));
+        assigner_ref.tell(timestamped_event(moment));
+        assigner_ref.tell(watermark(moment + 10));
+        wait(1);
+        assigner_ref.tell(timestamped_event(moment + 1));
+        assigner_ref.tell(watermark(moment + 20));
+        wait(1);
+        // Inspect and assert
+        sink.on_definition(|cd| {
+            assert_eq!(cd.data.len(), 1);
+            assert_eq!(cd.data[0].data, 2);
+        });
+        late_sink.on_definition(|cd| {
+            assert_eq!(cd.data.len(), 1);
+            assert_eq!(cd.data[0].timestamp, moment + 1);
+        });
+    }
+    #[test]
     fn window_allow_late_arrival() {
         // Send 2 messages on time, and then 1 message which is too late
         let (assigner_ref, sink) = window_assigner_test_setup(10, 10, 10, false);
@@ -582,4 +1180,181 @@ mod tests {
             assert_eq!(r0, &0);
         });
     }
+    #[test]
+    fn session_window_merge() {
+        let (assigner_ref, sink) = session_window_test_setup(5, 0, false);
+        wait(1);
+        let moment = now();
+        // Session [moment, moment + 8]
+        assigner_ref.tell(timestamped_event(moment));
+        assigner_ref.tell(timestamped_event(moment + 3));
+        // Separate sessions [moment + 20, moment + 25] and [moment + 10, moment + 15]
+        assigner_ref.tell(timestamped_event(moment + 20));
+        assigner_ref.tell(timestamped_event(moment + 10));
+        // Bridges the first two sessions into [moment, moment + 15]
+        assigner_ref.tell(timestamped_event(moment + 7));
+        assigner_ref.tell(watermark(moment + 30));
+        wait(2);
+        sink.on_definition(|cd| {
+            assert_eq!(cd.data.len(), 2);
+            assert_eq!(cd.data[0].data, 4);
+            assert_eq!(cd.data[0].timestamp, moment + 15);
+            assert_eq!(cd.data[1].data, 1);
+            assert_eq!(cd.data[1].timestamp, moment + 25);
+        });
+    }
+    #[test]
+    fn session_window_by_key() {
+        let (assigner_ref, sink) = session_window_test_setup(5, 0, true);
+        wait(1);
+        let moment = now();
+        assigner_ref.tell(timestamped_keyed_event(moment, 1));
+        assigner_ref.tell(timestamped_keyed_event(moment + 2, 2));
+        assigner_ref.tell(timestamped_keyed_event(moment + 4, 1));
+        assigner_ref.tell(timestamped_keyed_event(moment + 20, 2));
+        // Only closes the sessions ending at moment + 9 and moment + 7
+        assigner_ref.tell(watermark(moment + 10));
+        wait(2);
+        sink.on_definition(|cd| {
+            assert_eq!(cd.data.len(), 2);
+            assert_eq!(cd.data[0].data, 1);
+            assert_eq!(cd.data[0].timestamp, moment + 7);
+            assert_eq!(cd.data[1].data, 2);
+            assert_eq!(cd.data[1].timestamp, moment + 9);
+        });
+    }
+    #[test]
+    fn count_window_sliding() {
+        let assigner = Assigner::Count { size: 3, slide: 2 };
+        let (assigner_ref, sink, _) = window_assigner_setup(assigner, true, |assigner| assigner);
+        wait(1);
+        let moment = now();
+        for i in 0..7 {
+            assigner_ref.tell(timestamped_keyed_event(moment + i, 1));
+        }
+        assigner_ref.tell(timestamped_keyed_event(moment, 2));
+        assigner_ref.tell(watermark(moment + 10));
+        wait(1);
+        sink.on_definition(|cd| {
+            // Key 1 completes the windows [0, 3), [2, 5) and [4, 7), key 2 none
+            assert_eq!(cd.data.len(), 3);
+            assert!(cd.data.iter().all(|e| e.data == 3));
+            assert_eq!(cd.data[0].timestamp, moment + 2);
+            assert_eq!(cd.data[2].timestamp, moment + 6);
+        });
+    }
+    #[test]
+    fn global_window_with_trigger_and_evictor() {
+        let (assigner_ref, sink, _) = window_assigner_setup(Assigner::Global, false, |assigner| {
+            assigner
+                .with_trigger(CountTrigger::of(3))
+                .with_evictor(CountEvictor::of(2))
+        });
+        wait(1);
+        let moment = now();
+        for i in 0..7 {
+            assigner_ref.tell(timestamped_event(moment + i));
+        }
+        assigner_ref.tell(watermark(moment + 10));
+        wait(1);
+        sink.on_definition(|cd| {
+            // Fires at the 3rd and 6th element, keeping the last 2 elements each time
+            assert_eq!(cd.data.len(), 2);
+            assert_eq!(cd.data[0].data, 2);
+            assert_eq!(cd.data[1].data, 2);
+            assert_eq!(cd.data[1].timestamp, moment + 5);
+        });
+    }
+    #[test]
+    fn window_early_firing() {
+        let assigner = Assigner::Tumbling {
+            length: Time::seconds(10),
+            late_arrival: Time::seconds(0),
+        };
+        let (assigner_ref, sink, _) = window_assigner_setup(assigner, false, |assigner| {
+            assigner.with_trigger(EarlyTrigger)
+        });
+        wait(1);
+        let moment = now();
+        for _ in 0..5 {
+            assigner_ref.tell(timestamped_event(moment));
+        }
+        assigner_ref.tell(watermark(moment + 10));
+        wait(1);
+        sink.on_definition(|cd| {
+            // Early results after 2 and 4 elements, then the final result
+            let results: Vec<u64> = cd.data.iter().map(|e| e.data).collect();
+            assert_eq!(results, vec![2, 4, 5]);
+            assert!(cd.data.iter().all(|e| e.timestamp == moment + 10));
+        });
+    }
+
+    #[test]
+    fn window_processing_time_firing() {
+        let assigner = Assigner::Tumbling {
+            length: Time::seconds(10),
+            late_arrival: Time::seconds(0),
+        };
+        let (assigner_ref, sink, _) = window_assigner_setup(assigner, false, |assigner| {
+            assigner.with_trigger(ProcessingTimeTrigger)
+        });
+        wait(1);
+        let moment = now();
+        assigner_ref.tell(timestamped_event(moment));
+        assigner_ref.tell(timestamped_event(moment + 1));
+        wait(1);
+        // Fired by processing time before the watermark passed the end of the window
+        sink.on_definition(|cd| {
+            let results: Vec<u64> = cd.data.iter().map(|e| e.data).collect();
+            assert_eq!(results, vec![2]);
+            assert_eq!(cd.data[0].timestamp, moment + 10);
+        });
+
+        // The purged window registers a new callback with its next element
+        assigner_ref.tell(timestamped_event(moment + 2));
+        wait(1);
+        assigner_ref.tell(watermark(moment + 10));
+        wait(1);
+        sink.on_definition(|cd| {
+            let results: Vec<u64> = cd.data.iter().map(|e| e.data).collect();
+            assert_eq!(results, vec![2, 1]);
+        });
+    }
+
+    // Fires and purges windows 100ms of processing time after their first element
+    struct ProcessingTimeTrigger;
+
+    impl Trigger for ProcessingTimeTrigger {
+        fn on_element(&mut self, _: u64, _: &TriggerWindow) -> TriggerResult {
+            TriggerResult::Continue
+        }
+        fn on_event_time(&mut self, _: u64, _: &TriggerWindow) -> TriggerResult {
+            TriggerResult::FireAndPurge
+        }
+        fn on_processing_time(&mut self, _: u64, _: &TriggerWindow) -> TriggerResult {
+            TriggerResult::FireAndPurge
+        }
+        fn processing_time_delay(&self, _: &TriggerWindow) -> Option<std::time::Duration> {
+            Some(std::time::Duration::from_millis(100))
+        }
+    }
+
+    // Fires every second element and when the window ends
+    struct EarlyTrigger;
+
+    impl Trigger for EarlyTrigger {
+        fn on_element(&mut self, _: u64, window: &TriggerWindow) -> TriggerResult {
+            if window.count % 2 == 0 {
+                TriggerResult::Fire
+            } else {
+                TriggerResult::Continue
+            }
+        }
+        fn on_event_time(&mut self, _: u64, _: &TriggerWindow) -> TriggerResult {
+            TriggerResult::FireAndPurge
+        }
+        fn on_processing_time(&mut self, _: u64, _: &TriggerWindow) -> TriggerResult {
+            TriggerResult::Continue
+        }
+    }
 }
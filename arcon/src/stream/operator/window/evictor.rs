@@ -0,0 +1,31 @@
+use super::trigger::TriggerWindow;
+use crate::data::ArconType;
+
+/// Removes elements from a window before its result is computed
+///
+/// Evictors require a [WindowIndex](crate::index::WindowIndex) that keeps
+/// the elements of a window around, such as [AppenderWindow](crate::index::AppenderWindow).
+pub trait Evictor<IN: ArconType>: Send {
+    /// Called with the elements of `window`, in arrival order, before the window fires
+    fn evict(&mut self, elements: &mut Vec<IN>, window: &TriggerWindow);
+}
+
+/// Keeps only the last `count` elements of a window
+#[derive(Debug, Clone, Copy)]
+pub struct CountEvictor {
+    count: usize,
+}
+
+impl CountEvictor {
+    pub fn of(count: usize) -> Self {
+        Self { count }
+    }
+}
+
+impl<IN: ArconType> Evictor<IN> for CountEvictor {
+    fn evict(&mut self, elements: &mut Vec<IN>, _: &TriggerWindow) {
+        if elements.len() > self.count {
+            elements.drain(..elements.len() - self.count);
+        }
+    }
+}
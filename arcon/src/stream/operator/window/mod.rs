@@ -1,6 +1,10 @@
 pub mod assigner;
+pub mod evictor;
+pub mod trigger;
 
 pub use assigner::{WindowAssigner, WindowState};
+pub use evictor::{CountEvictor, Evictor};
+pub use trigger::{CountTrigger, EventTimeTrigger, Trigger, TriggerResult, TriggerWindow};
 
 use fxhash::FxHasher;
 use std::hash::{Hash, Hasher};
@@ -0,0 +1,131 @@
+use super::WindowContext;
+use std::time::Duration;
+
+/// Determines what happens to a window after a [Trigger] has been invoked
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TriggerResult {
+    /// Do nothing with the window
+    Continue,
+    /// Emit the current result of the window and keep its contents
+    Fire,
+    /// Discard the contents of the window without emitting a result
+    Purge,
+    /// Emit the current result of the window and then discard its contents
+    FireAndPurge,
+}
+
+impl TriggerResult {
+    #[inline]
+    pub fn is_fire(&self) -> bool {
+        matches!(self, TriggerResult::Fire | TriggerResult::FireAndPurge)
+    }
+
+    #[inline]
+    pub fn is_purge(&self) -> bool {
+        matches!(self, TriggerResult::Purge | TriggerResult::FireAndPurge)
+    }
+}
+
+/// The window a [Trigger] is invoked for
+#[derive(Debug, Clone, Copy)]
+pub struct TriggerWindow {
+    pub ctx: WindowContext,
+    /// Event time at which the window ends
+    ///
+    /// `None` for count and global windows.
+    pub end: Option<u64>,
+    /// Number of elements added to the window since it was created or last purged
+    ///
+    /// Elements removed by an [Evictor](super::Evictor) are still counted.
+    pub count: u64,
+}
+
+/// Decides when a [WindowAssigner](super::WindowAssigner) emits the result of a window
+///
+/// Time windows are removed once their end plus the allowed lateness has passed,
+/// regardless of what the trigger returns. Count windows are removed once they have
+/// received all of their elements.
+pub trait Trigger: Send {
+    /// Called for every element that is added to `window`
+    fn on_element(&mut self, timestamp: u64, window: &TriggerWindow) -> TriggerResult;
+    /// Called when the watermark has passed the end of `window`
+    fn on_event_time(&mut self, time: u64, window: &TriggerWindow) -> TriggerResult;
+    /// Called when a processing time timer of `window` fires
+    ///
+    /// `time` is the wall-clock time in milliseconds since the unix epoch.
+    fn on_processing_time(&mut self, time: u64, window: &TriggerWindow) -> TriggerResult;
+    /// Wall-clock delay after which [Trigger::on_processing_time] is called for `window`
+    ///
+    /// Asked when `window` receives its first element since it was created or purged, and
+    /// again after every call to [Trigger::on_processing_time] while it still holds elements.
+    /// The default of `None` never calls [Trigger::on_processing_time].
+    fn processing_time_delay(&self, _window: &TriggerWindow) -> Option<Duration> {
+        None
+    }
+}
+
+/// Fires a window once the watermark passes its end
+///
+/// The default trigger of sliding, tumbling and session windows.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct EventTimeTrigger;
+
+impl Trigger for EventTimeTrigger {
+    fn on_element(&mut self, _: u64, _: &TriggerWindow) -> TriggerResult {
+        TriggerResult::Continue
+    }
+    fn on_event_time(&mut self, _: u64, _: &TriggerWindow) -> TriggerResult {
+        TriggerResult::FireAndPurge
+    }
+    fn on_processing_time(&mut self, _: u64, _: &TriggerWindow) -> TriggerResult {
+        TriggerResult::Continue
+    }
+}
+
+/// Fires a window every time it has received another `count` elements
+///
+/// The default trigger of count windows. Used on time windows, it produces early
+/// results while the window is still open.
+#[derive(Debug, Clone, Copy)]
+pub struct CountTrigger {
+    count: u64,
+}
+
+impl CountTrigger {
+    pub fn of(count: u64) -> Self {
+        assert!(count > 0, "CountTrigger count must be greater than zero!");
+        Self { count }
+    }
+}
+
+impl Trigger for CountTrigger {
+    fn on_element(&mut self, _: u64, window: &TriggerWindow) -> TriggerResult {
+        if window.count % self.count == 0 {
+            TriggerResult::Fire
+        } else {
+            TriggerResult::Continue
+        }
+    }
+    fn on_event_time(&mut self, _: u64, _: &TriggerWindow) -> TriggerResult {
+        TriggerResult::Continue
+    }
+    fn on_processing_time(&mut self, _: u64, _: &TriggerWindow) -> TriggerResult {
+        TriggerResult::Continue
+    }
+}
+
+/// A trigger that never fires, the default of global windows
+#[derive(Debug, Clone, Copy, Default)]
+pub(crate) struct NeverTrigger;
+
+impl Trigger for NeverTrigger {
+    fn on_element(&mut self, _: u64, _: &TriggerWindow) -> TriggerResult {
+        TriggerResult::Continue
+    }
+    fn on_event_time(&mut self, _: u64, _: &TriggerWindow) -> TriggerResult {
+        TriggerResult::Continue
+    }
+    fn on_processing_time(&mut self, _: u64, _: &TriggerWindow) -> TriggerResult {
+        TriggerResult::Continue
+    }
+}
@@ -58,5 +58,10 @@ where
             None => Ok(Ok(Poll::Done)),
         }
     }
-    fn set_offset(&mut self, _: usize) {}
+    fn set_offset(&mut self, offset: usize) {
+        // Every line is a record, including the ones that failed to parse
+        if offset > 0 {
+            let _ = self.lines.nth(offset - 1);
+        }
+    }
 }
@@ -27,6 +27,8 @@ pub trait Source: Send + 'static {
     /// Set offset for the source
     ///
     /// May be used by replayable sources to set a certain offset..
+    /// When an application is restored, `offset` is the number of records
+    /// the source had produced at the restored epoch.
     fn set_offset(&mut self, offset: usize);
 }
 
@@ -44,7 +46,11 @@ where
             None => Ok(Ok(Poll::Done)),
         }
     }
-    fn set_offset(&mut self, _: usize) {}
+    fn set_offset(&mut self, offset: usize) {
+        if offset > 0 {
+            let _ = self.nth(offset - 1);
+        }
+    }
 }
 
 #[cfg(test)]
@@ -64,4 +70,14 @@ mod tests {
         let sum = sum(v.into_iter());
         assert_eq!(sum, 10);
     }
+
+    #[test]
+    fn iterator_source_offset_test() {
+        let mut source = vec![1u32, 2, 3, 4].into_iter();
+        source.set_offset(2);
+        assert!(matches!(
+            source.poll_next().unwrap().unwrap(),
+            Poll::Ready(3)
+        ));
+    }
 }
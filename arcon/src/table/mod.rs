@@ -1,4 +1,8 @@
-use crate::data::arrow::ToArrow;
+use crate::{data::arrow::ToArrow, error::ArconResult};
+use arcon_state::{
+    data::{Key, Value},
+    Backend, StateInspector,
+};
 use arrow::{
     array::{ArrayBuilder, StructBuilder},
     datatypes::Schema,
@@ -340,6 +344,26 @@ pub fn parquet_arrow_reader(
     Ok(ParquetFileArrowReader::new(Arc::new(file_reader)))
 }
 
+/// Creates a table of the values in the map handle `handle_id` of an inspected checkpoint
+pub fn state_table<K, V, B>(
+    inspector: &StateInspector<B>,
+    handle_id: &str,
+) -> ArconResult<ImmutableTable>
+where
+    K: Key,
+    V: Value + ToArrow,
+    B: Backend,
+{
+    let mut table = V::table();
+    table.load(
+        inspector
+            .map::<K, V>(handle_id)?
+            .into_iter()
+            .map(|(_, v)| v),
+    )?;
+    Ok(table.immutable()?)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -406,6 +430,37 @@ mod tests {
         assert_eq!(batch.num_rows(), total_rows);
     }
 
+    #[derive(Arrow, prost::Message, Clone)]
+    pub struct Count {
+        #[prost(uint64, tag = "1")]
+        pub id: u64,
+        #[prost(uint64, tag = "2")]
+        pub count: u64,
+    }
+
+    #[test]
+    fn state_table_test() {
+        let dir = tempdir().unwrap();
+        let checkpoint = dir.path().join("checkpoint");
+        {
+            let backend =
+                arcon_state::Sled::create(&dir.path().join("live"), "counts".into()).unwrap();
+            let mut handle =
+                arcon_state::Handle::<arcon_state::MapState<u64, Count>>::map("counts");
+            backend.register_map_handle(&mut handle);
+            for id in 0..10 {
+                backend
+                    .map_fast_insert(&handle, id, Count { id, count: id * 2 })
+                    .unwrap();
+            }
+            backend.checkpoint(&checkpoint).unwrap();
+        }
+
+        let inspector = StateInspector::<arcon_state::Sled>::open(&checkpoint).unwrap();
+        let table = state_table::<u64, Count, _>(&inspector, "counts").unwrap();
+        assert_eq!(table.total_rows(), 10);
+    }
+
     #[test]
     fn table_serde_test() {
         let table = test_table();
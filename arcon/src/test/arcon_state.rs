@@ -8,6 +8,7 @@ pub struct StreamingState<B: Backend> {
     watermark: LazyValue<u64, B>,
     epoch: LazyValue<u64, B>,
     counters: HashTable<u64, u64, B>,
+    item_counts: LazyMap<u64, u64, B>,
     #[ephemeral]
     emph: u64,
 }
@@ -19,7 +20,8 @@ fn streaming_state_test() -> ArconResult<()> {
     let mut state = StreamingState {
         watermark: LazyValue::new("_watermark", backend.clone()),
         epoch: LazyValue::new("_epoch", backend.clone()),
-        counters: HashTable::new("_counters", backend),
+        counters: HashTable::new("_counters", backend.clone()),
+        item_counts: LazyMap::new("_item_counts", backend),
         emph: 0,
     };
 
@@ -35,6 +37,49 @@ fn streaming_state_test() -> ArconResult<()> {
     assert_eq!(state.counters().get(&10).unwrap(), Some(&1));
     assert_eq!(state.counters().get(&12).unwrap(), Some(&2));
     assert_eq!(state.emph(), &0);
+
+    // user -> item -> count
+    state.set_key(1);
+    state.item_counts().rmw(&7, |count| *count += 1)?;
+    state.item_counts().rmw(&7, |count| *count += 1)?;
+    state.set_key(2);
+    state.item_counts().rmw(&7, |count| *count += 1)?;
+    assert!(state.persist().is_ok());
+
+    state.set_key(1);
+    assert_eq!(state.item_counts().get(&7)?, Some(2));
+    state.set_key(2);
+    assert_eq!(state.item_counts().get(&7)?, Some(1));
+    assert_eq!(state.item_counts().len(), 1);
+    Ok(())
+}
+
+#[derive(ArconState)]
+pub struct SessionState<B: Backend> {
+    #[ttl(event_time = "10ms")]
+    clicks: LazyValue<u64, B>,
+    #[ttl = "1h"]
+    visits: EagerHashTable<u64, u64, B>,
+}
+
+#[test]
+fn ttl_state_test() -> ArconResult<()> {
+    let backend = Arc::new(crate::test_utils::temp_backend::<Sled>());
+
+    let mut state = SessionState {
+        clicks: LazyValue::new("_clicks", backend.clone()),
+        visits: EagerHashTable::new("_visits", backend),
+    };
+
+    state.set_key(1);
+    state.clicks().put(3)?;
+    state.visits().put(1, 1)?;
+
+    state.set_time(9);
+    assert_eq!(state.clicks().get()?.unwrap().as_ref(), &3);
+    state.set_time(10);
+    assert_eq!(state.clicks().get()?, None);
+    assert_eq!(state.visits().get(&1)?, Some(1));
     assert!(state.persist().is_ok());
     Ok(())
 }
@@ -0,0 +1,146 @@
+use crate::prelude::*;
+use std::thread::sleep;
+use std::time::Duration;
+
+const PARALLELISM: usize = 2;
+const EVENT_COUNT: u64 = 50000;
+const RIGHT_OFFSET: u64 = 1_000_000;
+const KEYS: u64 = 100;
+
+fn operator_conf() -> OperatorConf {
+    OperatorConf {
+        parallelism_strategy: ParallelismStrategy::Static(PARALLELISM),
+        ..Default::default()
+    }
+}
+
+fn event_stream(offset: u64) -> Stream<u64> {
+    (offset..offset + EVENT_COUNT).to_stream(|conf| {
+        conf.set_arcon_time(ArconTime::Process);
+    })
+}
+
+fn keyed_stream() -> Stream<u64> {
+    (0..EVENT_COUNT).map(|x| x % KEYS).to_stream(|conf| {
+        conf.set_arcon_time(ArconTime::Process);
+    })
+}
+
+#[derive(ArconState)]
+pub struct SideCount<B: Backend> {
+    #[table = "SideCount"]
+    left: EagerValue<u64, B>,
+    right: EagerValue<u64, B>,
+}
+
+// Counts records per key and side, emitting the updated count.
+// Counts from the right input are tagged with RIGHT_OFFSET.
+pub struct Tag;
+
+impl CoOperator for Tag {
+    type LEFT = u64;
+    type RIGHT = u64;
+    type OUT = u64;
+    type TimerState = ArconNever;
+    type OperatorState = SideCount<Sled>;
+    type ElementIterator = std::iter::Once<ArconElement<u64>>;
+
+    fn handle_left(
+        &mut self,
+        element: ArconElement<u64>,
+        ctx: &mut OperatorContext<Self::TimerState, Self::OperatorState>,
+    ) -> ArconResult<Self::ElementIterator> {
+        ctx.state().left().rmw(|v| *v += 1)?;
+        let count = ctx.state().left().get()?.map(|v| *v).unwrap_or_default();
+        Ok(std::iter::once(ArconElement::with_timestamp(
+            count,
+            element.timestamp,
+        )))
+    }
+
+    fn handle_right(
+        &mut self,
+        element: ArconElement<u64>,
+        ctx: &mut OperatorContext<Self::TimerState, Self::OperatorState>,
+    ) -> ArconResult<Self::ElementIterator> {
+        ctx.state().right().rmw(|v| *v += 1)?;
+        let count = ctx.state().right().get()?.map(|v| *v).unwrap_or_default();
+        Ok(std::iter::once(ArconElement::with_timestamp(
+            RIGHT_OFFSET + count,
+            element.timestamp,
+        )))
+    }
+
+    crate::ignore_timeout!();
+}
+
+#[test]
+fn union_integration() {
+    let mut app = event_stream(0)
+        .union(event_stream(RIGHT_OFFSET))
+        .operator(OperatorBuilder {
+            operator: Arc::new(|| Map::new(|x: u64| x)),
+            state: Arc::new(|_| EmptyState),
+            conf: operator_conf(),
+        })
+        .debug()
+        .builder()
+        .build();
+    app.run();
+    sleep(Duration::from_secs(4));
+
+    if let Some(debug_node) = app.get_debug_node::<u64>() {
+        debug_node.on_definition(|c| {
+            assert!(c.data.iter().any(|e| e.data < RIGHT_OFFSET));
+            assert!(c.data.iter().any(|e| e.data >= RIGHT_OFFSET));
+        })
+    } else {
+        panic!("Failed to get DebugNode!")
+    }
+}
+
+#[test]
+fn connect_integration() {
+    let left = keyed_stream().key_by(|x: &u64| x);
+    let right = keyed_stream().key_by(|x: &u64| x);
+    let mut app = left
+        .connect(right)
+        .operator(CoOperatorBuilder {
+            operator: Arc::new(|| Tag),
+            state: Arc::new(|backend| SideCount {
+                left: EagerValue::new("_left", backend),
+                right: EagerValue::new("_right", backend),
+            }),
+            conf: operator_conf(),
+        })
+        .debug()
+        .builder()
+        .build();
+    app.run();
+    sleep(Duration::from_secs(4));
+
+    if let Some(debug_node) = app.get_debug_node::<u64>() {
+        debug_node.on_definition(|c| {
+            let (left, right): (Vec<u64>, Vec<u64>) = c
+                .data
+                .iter()
+                .map(|e| e.data)
+                .partition(|x| *x < RIGHT_OFFSET);
+            assert_eq!(left.len() as u64, EVENT_COUNT);
+            assert_eq!(right.len() as u64, EVENT_COUNT);
+            // Each key counts up to its own share of the records on both sides
+            let per_key = EVENT_COUNT / KEYS;
+            let right: Vec<u64> = right.into_iter().map(|x| x - RIGHT_OFFSET).collect();
+            for counts in [left, right].iter() {
+                for count in 1..=per_key {
+                    let seen = counts.iter().filter(|x| **x == count).count() as u64;
+                    assert_eq!(seen, KEYS);
+                }
+            }
+            // Both CoOperator nodes received records
+            assert_eq!(c.senders.len(), PARALLELISM);
+        })
+    } else {
+        panic!("Failed to get DebugNode!")
+    }
+}
@@ -0,0 +1,79 @@
+use crate::prelude::*;
+use std::thread::sleep;
+use std::time::Duration;
+
+#[derive(Arcon, prost::Message, Copy, Clone)]
+#[arcon(reliable_ser_id = 13, version = 1)]
+pub struct Event {
+    #[prost(uint64)]
+    pub data: u64,
+    #[prost(uint64)]
+    pub key: u64,
+}
+
+const EVENT_COUNT: u64 = 20000;
+const NUM_KEYS: u64 = 4;
+const WINDOW_LENGTH: u64 = 10;
+
+fn keyed_stream() -> KeyedStream<Event> {
+    (0u64..EVENT_COUNT)
+        .to_stream(|conf| {
+            conf.set_arcon_time(ArconTime::Event);
+            conf.set_timestamp_extractor(|x: &u64| *x);
+        })
+        .map(|x| Event {
+            data: x,
+            key: x % NUM_KEYS,
+        })
+        .key_by(|event: &Event| &event.key)
+}
+
+fn operator_conf() -> OperatorConf {
+    OperatorConf {
+        parallelism_strategy: ParallelismStrategy::Static(2),
+        ..Default::default()
+    }
+}
+
+fn joined_pairs(stream: Stream<Joined<Event, Event>>) -> Vec<(Event, Event)> {
+    let mut app = stream.debug().builder().build();
+    app.run();
+    sleep(Duration::from_secs(4));
+
+    let debug_node = app
+        .get_debug_node::<Joined<Event, Event>>()
+        .expect("Failed to get DebugNode!");
+    debug_node.on_definition(|c| c.data.iter().map(|e| (e.data.left, e.data.right)).collect())
+}
+
+#[test]
+fn window_join_integration() {
+    let stream = keyed_stream()
+        .join(keyed_stream())
+        .conf(operator_conf())
+        .window(Assigner::Tumbling {
+            length: Time(WINDOW_LENGTH),
+            late_arrival: Time(0),
+        });
+    let pairs = joined_pairs(stream);
+    assert!(!pairs.is_empty());
+    for (left, right) in pairs {
+        // Only records of the same key and window are joined
+        assert_eq!(left.key, right.key);
+        assert_eq!(left.data / WINDOW_LENGTH, right.data / WINDOW_LENGTH);
+    }
+}
+
+#[test]
+fn interval_join_integration() {
+    let stream = keyed_stream()
+        .join(keyed_stream())
+        .conf(operator_conf())
+        .between(Time(NUM_KEYS), Time(NUM_KEYS));
+    let pairs = joined_pairs(stream);
+    assert!(pairs.iter().any(|(l, r)| l.data != r.data));
+    for (left, right) in pairs {
+        assert_eq!(left.key, right.key);
+        assert!(left.data.max(right.data) - left.data.min(right.data) <= NUM_KEYS);
+    }
+}
@@ -0,0 +1,116 @@
+use crate::prelude::*;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread::sleep;
+use std::time::Duration;
+
+#[derive(Arcon, Arrow, prost::Message, Copy, Clone)]
+#[arcon(reliable_ser_id = 13, version = 1)]
+pub struct Event {
+    #[prost(uint64)]
+    pub data: u64,
+    #[prost(uint32)]
+    pub key: u32,
+}
+
+#[derive(Arcon, Arrow, prost::Message, Copy, Clone)]
+#[arcon(reliable_ser_id = 13, version = 1)]
+pub struct EnrichedEvent {
+    #[prost(uint64)]
+    pub data: u64,
+    #[prost(uint32)]
+    pub key: u32,
+    #[prost(uint64)]
+    pub first_val: u64,
+}
+
+#[derive(ArconState)]
+pub struct FirstVal<B: Backend> {
+    #[table = "Count"]
+    events: EagerValue<u64, B>,
+}
+
+const NUM_KEYS: u64 = 64;
+const EVENT_COUNT: u64 = 200000;
+
+// Amount of operator instances that have been created, i.e., nodes spawned
+static OPERATORS_CREATED: AtomicUsize = AtomicUsize::new(0);
+
+// Any load above 1 element per node and epoch makes the operator scale up to max_parallelism
+fn managed_conf() -> OperatorConf {
+    OperatorConf {
+        parallelism_strategy: ParallelismStrategy::Managed,
+        scaling: ScalingConf {
+            initial_parallelism: 1,
+            min_parallelism: 1,
+            max_parallelism: 4,
+            scale_up_threshold: 1,
+            scale_down_threshold: 0,
+        },
+        ..Default::default()
+    }
+}
+
+#[test]
+fn managed_rescale_integration() {
+    let conf = ApplicationConf {
+        epoch_interval: 20,
+        ..Default::default()
+    };
+    let mut app = (0u64..EVENT_COUNT)
+        .map(|i| Event {
+            data: i,
+            key: (i % NUM_KEYS) as u32,
+        })
+        .to_stream(|conf| {
+            conf.set_arcon_time(ArconTime::Process);
+        })
+        .key_by(|event| &event.key)
+        .operator(OperatorBuilder {
+            operator: Arc::new(|| {
+                OPERATORS_CREATED.fetch_add(1, Ordering::SeqCst);
+                Map::stateful(|event: Event, state: &mut FirstVal<_>| {
+                    let first_val: u64 = if let Some(value) = state.events().get()? {
+                        *value
+                    } else {
+                        state.events().put(event.data)?;
+                        event.data
+                    };
+                    Ok(EnrichedEvent {
+                        data: event.data,
+                        key: event.key,
+                        first_val,
+                    })
+                })
+            }),
+            state: Arc::new(|backend| FirstVal {
+                events: EagerValue::new("_events", backend),
+            }),
+            conf: managed_conf(),
+        })
+        .debug()
+        .builder()
+        .config(conf)
+        .build();
+
+    app.run();
+    sleep(Duration::from_secs(15));
+
+    // The initial node is replaced by a generation of max_parallelism nodes
+    let scaling = managed_conf().scaling;
+    assert_eq!(
+        OPERATORS_CREATED.load(Ordering::SeqCst),
+        scaling.initial_parallelism + scaling.max_parallelism
+    );
+
+    if let Some(debug_node) = app.get_debug_node::<EnrichedEvent>() {
+        debug_node.on_definition(|c| {
+            assert!(!c.data.is_empty());
+            // The first value of each key must survive the handover of its key group
+            for element in c.data.iter() {
+                assert_eq!(element.data.first_val, element.data.key as u64);
+            }
+        })
+    } else {
+        panic!("Failed to get DebugNode!")
+    }
+}
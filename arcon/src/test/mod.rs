@@ -1,2 +1,8 @@
 mod arcon_state;
+mod connect_integration;
+mod join_integration;
 mod keyby_integration;
+mod managed_integration;
+mod restore_integration;
+mod side_output_integration;
+mod split_integration;
@@ -0,0 +1,301 @@
+use crate::{dataflow::conf::DefaultBackend, prelude::*};
+use arcon_state::MemoryObjectStore;
+use std::{path::Path, thread::sleep, time::Duration};
+
+#[derive(ArconState)]
+pub struct RunningSum<B: Backend> {
+    sum: EagerValue<u64, B>,
+}
+
+fn running_sum_conf(base_dir: &Path, restore: RestoreMode) -> ApplicationConf {
+    ApplicationConf {
+        base_dir: base_dir.to_path_buf(),
+        epoch_interval: 50,
+        restore,
+        ..Default::default()
+    }
+}
+
+// Emits the sum of all numbers that the operator has seen so far
+fn running_sum_app(base_dir: &Path, events: u64, restore: RestoreMode) -> Application {
+    running_sum_app_with_conf(events, running_sum_conf(base_dir, restore))
+}
+
+fn running_sum_app_with_conf(events: u64, conf: ApplicationConf) -> Application {
+    (0u64..events)
+        .to_stream(|conf| {
+            conf.set_arcon_time(ArconTime::Process);
+        })
+        .operator(OperatorBuilder {
+            operator: Arc::new(|| {
+                Map::stateful(|x: u64, state: &mut RunningSum<_>| {
+                    let sum = state.sum().get()?.map(|sum| *sum).unwrap_or(0) + x;
+                    state.sum().put(sum)?;
+                    Ok(sum)
+                })
+            }),
+            state: Arc::new(|backend| RunningSum {
+                sum: EagerValue::new("_sum", backend),
+            }),
+            conf: Default::default(),
+        })
+        .debug()
+        .builder()
+        .config(conf)
+        .build()
+}
+
+#[test]
+fn restore_integration() {
+    let base_dir = tempfile::tempdir().unwrap();
+
+    // The first run processes 0..100 and commits its final epoch
+    let mut app = running_sum_app(base_dir.path(), 100, RestoreMode::Disabled);
+    app.run();
+    sleep(Duration::from_secs(2));
+    app.shutdown();
+
+    // Both the source and the operator are part of the persisted catalog
+    let checkpoints_dir = base_dir.path().join("checkpoints");
+    let catalog = SnapshotCatalog::load(&checkpoints_dir).unwrap();
+    let latest = catalog.latest().expect("no committed epoch");
+    assert_eq!(catalog.get(latest).unwrap().snapshots.len(), 2);
+
+    // The source of the second run replays 0..150 but is rewound to 100,
+    // while the operator continues from the restored sum
+    let mut app = running_sum_app(base_dir.path(), 150, RestoreMode::Latest);
+    app.run();
+    sleep(Duration::from_secs(2));
+
+    let expected: Vec<u64> = (100u64..150).map(|x| (0..=x).sum()).collect();
+    if let Some(debug_node) = app.get_debug_node::<u64>() {
+        debug_node.on_definition(|c| {
+            let sums: Vec<u64> = c.data.iter().map(|e| e.data).collect();
+            assert_eq!(sums, expected);
+        })
+    } else {
+        panic!("Failed to get DebugNode!")
+    }
+    app.shutdown();
+}
+
+#[test]
+fn savepoint_integration() {
+    let base_dir = tempfile::tempdir().unwrap();
+
+    let mut app = running_sum_app(base_dir.path(), 100, RestoreMode::Disabled);
+    app.run();
+    sleep(Duration::from_secs(2));
+    let savepoint = app.savepoint("sp").unwrap();
+    assert!(app.savepoint("sp").is_err());
+    app.shutdown();
+
+    // The savepoint stays usable after being moved out of the application's directory
+    let target_dir = tempfile::tempdir().unwrap();
+    let moved = target_dir.path().join("sp");
+    std::fs::rename(&savepoint, &moved).unwrap();
+    let catalog = SnapshotCatalog::load(&moved).unwrap();
+    assert_eq!(catalog.epochs().len(), 1);
+
+    // A fresh application started from the savepoint continues where it was taken
+    let restore_dir = tempfile::tempdir().unwrap();
+    let mut app = running_sum_app(restore_dir.path(), 150, RestoreMode::Savepoint(moved));
+    app.run();
+    sleep(Duration::from_secs(2));
+
+    let expected: Vec<u64> = (100u64..150).map(|x| (0..=x).sum()).collect();
+    if let Some(debug_node) = app.get_debug_node::<u64>() {
+        debug_node.on_definition(|c| {
+            let sums: Vec<u64> = c.data.iter().map(|e| e.data).collect();
+            assert_eq!(sums, expected);
+        })
+    } else {
+        panic!("Failed to get DebugNode!")
+    }
+    app.shutdown();
+}
+
+#[test]
+fn unaligned_restore_integration() {
+    let base_dir = tempfile::tempdir().unwrap();
+    let conf = |restore| ApplicationConf {
+        checkpoint_mode: CheckpointMode::Unaligned,
+        ..running_sum_conf(base_dir.path(), restore)
+    };
+
+    let mut app = running_sum_app_with_conf(100, conf(RestoreMode::Disabled));
+    app.run();
+    sleep(Duration::from_secs(2));
+    app.shutdown();
+
+    // The in-flight messages of the operator are checkpointed next to its state
+    let catalog = SnapshotCatalog::load(base_dir.path().join("checkpoints")).unwrap();
+    let latest = catalog.latest().expect("no committed epoch");
+    assert_eq!(catalog.get(latest).unwrap().snapshots.len(), 3);
+
+    let mut app = running_sum_app_with_conf(150, conf(RestoreMode::Latest));
+    app.run();
+    sleep(Duration::from_secs(2));
+
+    let expected: Vec<u64> = (100u64..150).map(|x| (0..=x).sum()).collect();
+    if let Some(debug_node) = app.get_debug_node::<u64>() {
+        debug_node.on_definition(|c| {
+            let sums: Vec<u64> = c.data.iter().map(|e| e.data).collect();
+            assert_eq!(sums, expected);
+        })
+    } else {
+        panic!("Failed to get DebugNode!")
+    }
+    app.shutdown();
+}
+
+#[test]
+fn storage_restore_integration() {
+    let store = MemoryObjectStore::default();
+    let conf = |base_dir: &Path, restore| ApplicationConf {
+        checkpoint_storage: Some(Arc::new(ObjectStorage::new(
+            store.clone(),
+            "arcon",
+            "running_sum",
+        ))),
+        ..running_sum_conf(base_dir, restore)
+    };
+
+    let base_dir = tempfile::tempdir().unwrap();
+    let mut app = running_sum_app_with_conf(100, conf(base_dir.path(), RestoreMode::Disabled));
+    app.run();
+    sleep(Duration::from_secs(2));
+    app.shutdown();
+    let checkpoints_dir = base_dir.path().join("checkpoints");
+    let latest = SnapshotCatalog::load(&checkpoints_dir).unwrap().latest();
+    assert!(latest.is_some());
+
+    // The local checkpoints are lost together with the machine
+    drop(base_dir);
+
+    let base_dir = tempfile::tempdir().unwrap();
+    let mut app = running_sum_app_with_conf(150, conf(base_dir.path(), RestoreMode::Latest));
+    assert_eq!(app.restored_epoch, latest);
+    app.run();
+    sleep(Duration::from_secs(2));
+
+    let expected: Vec<u64> = (100u64..150).map(|x| (0..=x).sum()).collect();
+    if let Some(debug_node) = app.get_debug_node::<u64>() {
+        debug_node.on_definition(|c| {
+            let sums: Vec<u64> = c.data.iter().map(|e| e.data).collect();
+            assert_eq!(sums, expected);
+        })
+    } else {
+        panic!("Failed to get DebugNode!")
+    }
+    app.shutdown();
+}
+
+#[test]
+fn bootstrap_restore_integration() {
+    let savepoint_dir = tempfile::tempdir().unwrap();
+
+    // Seed the running sum of the operator, which comes right after the source
+    let mut bootstrap = StateBootstrap::new(
+        savepoint_dir.path().join("bootstrap"),
+        1,
+        &OperatorConf::default(),
+        |backend: Arc<DefaultBackend>| RunningSum {
+            sum: EagerValue::new("_sum", backend),
+        },
+    )
+    .unwrap();
+    bootstrap
+        .update_hashed(0, |state| Ok(state.sum().put(1000)?))
+        .unwrap();
+    let savepoint = bootstrap.finish().unwrap();
+    assert_eq!(SnapshotCatalog::load(&savepoint).unwrap().epochs().len(), 1);
+
+    let base_dir = tempfile::tempdir().unwrap();
+    let mut app = running_sum_app(base_dir.path(), 10, RestoreMode::Savepoint(savepoint));
+    app.run();
+    sleep(Duration::from_secs(2));
+
+    let expected: Vec<u64> = (0u64..10).map(|x| 1000 + (0..=x).sum::<u64>()).collect();
+    if let Some(debug_node) = app.get_debug_node::<u64>() {
+        debug_node.on_definition(|c| {
+            let sums: Vec<u64> = c.data.iter().map(|e| e.data).collect();
+            assert_eq!(sums, expected);
+        })
+    } else {
+        panic!("Failed to get DebugNode!")
+    }
+    app.shutdown();
+}
+
+// Emits the sum of every key, which is the number itself, with a parallelism of 2
+fn keyed_sum_app(base_dir: &Path, conf: OperatorConf, restore: RestoreMode) -> Application {
+    (0u64..10)
+        .to_stream(|conf| {
+            conf.set_arcon_time(ArconTime::Process);
+        })
+        .key_by(|x: &u64| x)
+        .operator(OperatorBuilder {
+            operator: Arc::new(|| {
+                Map::stateful(|x: u64, state: &mut RunningSum<_>| {
+                    let sum = state.sum().get()?.map(|sum| *sum).unwrap_or(0) + x;
+                    state.sum().put(sum)?;
+                    Ok(sum)
+                })
+            }),
+            state: Arc::new(|backend| RunningSum {
+                sum: EagerValue::new("_sum", backend),
+            }),
+            conf,
+        })
+        .debug()
+        .builder()
+        .config(running_sum_conf(base_dir, restore))
+        .build()
+}
+
+#[test]
+fn bootstrap_parallel_restore_integration() {
+    let savepoint_dir = tempfile::tempdir().unwrap();
+    let conf = OperatorConf {
+        parallelism_strategy: ParallelismStrategy::Static(2),
+        ..Default::default()
+    };
+
+    // Every key is seeded on the node that the keyed stream routes it to
+    let mut bootstrap = StateBootstrap::new(
+        savepoint_dir.path().join("bootstrap"),
+        1,
+        &conf,
+        |backend: Arc<DefaultBackend>| RunningSum {
+            sum: EagerValue::new("_sum", backend),
+        },
+    )
+    .unwrap();
+    for key in 0u64..10 {
+        bootstrap
+            .update(&key, |state| Ok(state.sum().put(1000)?))
+            .unwrap();
+    }
+    let savepoint = bootstrap.finish().unwrap();
+    let catalog = SnapshotCatalog::load(&savepoint).unwrap();
+    let epoch = catalog.latest().unwrap();
+    assert_eq!(catalog.get(epoch).unwrap().snapshots.len(), 2);
+
+    let base_dir = tempfile::tempdir().unwrap();
+    let mut app = keyed_sum_app(base_dir.path(), conf, RestoreMode::Savepoint(savepoint));
+    app.run();
+    sleep(Duration::from_secs(2));
+
+    let expected: Vec<u64> = (0u64..10).map(|x| 1000 + x).collect();
+    if let Some(debug_node) = app.get_debug_node::<u64>() {
+        debug_node.on_definition(|c| {
+            let mut sums: Vec<u64> = c.data.iter().map(|e| e.data).collect();
+            sums.sort_unstable();
+            assert_eq!(sums, expected);
+        })
+    } else {
+        panic!("Failed to get DebugNode!")
+    }
+    app.shutdown();
+}
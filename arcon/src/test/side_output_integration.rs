@@ -0,0 +1,72 @@
+use crate::prelude::*;
+use std::thread::sleep;
+use std::time::Duration;
+
+const EVENT_COUNT: u64 = 1000;
+
+// Emits odd numbers to a side output of another type
+pub struct SplitOdd {
+    odd: OutputTag<u32>,
+}
+
+impl Operator for SplitOdd {
+    type IN = u64;
+    type OUT = u64;
+    type TimerState = ArconNever;
+    type OperatorState = EmptyState;
+    type ElementIterator = Option<ArconElement<u64>>;
+
+    fn handle_element(
+        &mut self,
+        element: ArconElement<u64>,
+        ctx: &mut OperatorContext<Self::TimerState, Self::OperatorState>,
+    ) -> ArconResult<Self::ElementIterator> {
+        if element.data % 2 == 1 {
+            let odd = ArconElement::with_timestamp(element.data as u32, element.timestamp);
+            ctx.output(&self.odd, odd)?;
+            Ok(None)
+        } else {
+            Ok(Some(element))
+        }
+    }
+
+    crate::ignore_timeout!();
+}
+
+#[test]
+fn side_output_integration() {
+    let odd = OutputTag::<u32>::new("odd");
+    let tag = odd.clone();
+    let evens = (0..EVENT_COUNT)
+        .to_stream(|conf| {
+            conf.set_arcon_time(ArconTime::Event);
+            conf.set_timestamp_extractor(|x: &u64| *x);
+        })
+        .operator(OperatorBuilder {
+            operator: Arc::new(move || SplitOdd { odd: tag.clone() }),
+            state: Arc::new(|_| EmptyState),
+            conf: OperatorConf {
+                parallelism_strategy: ParallelismStrategy::Static(2),
+                ..Default::default()
+            },
+        });
+
+    let _odds = evens.side_output(&odd).map(|x| x * 10).debug();
+    let mut app = evens.ignore().builder().build();
+    app.run();
+    sleep(Duration::from_secs(4));
+
+    if let Some(debug_node) = app.get_debug_node::<u32>() {
+        debug_node.on_definition(|c| {
+            let mut odds: Vec<(u32, u64)> = c.data.iter().map(|e| (e.data, e.timestamp)).collect();
+            odds.sort_unstable();
+            let expected: Vec<(u32, u64)> = (0..EVENT_COUNT)
+                .filter(|x| x % 2 == 1)
+                .map(|x| (x as u32 * 10, x))
+                .collect();
+            assert_eq!(odds, expected);
+        })
+    } else {
+        panic!("Failed to get DebugNode!")
+    }
+}
@@ -0,0 +1,92 @@
+use crate::prelude::*;
+use std::thread::sleep;
+use std::time::Duration;
+
+const EVENT_COUNT: u64 = 1000;
+
+fn event_stream() -> Stream<u64> {
+    (0..EVENT_COUNT).to_stream(|conf| {
+        conf.set_arcon_time(ArconTime::Process);
+    })
+}
+
+#[test]
+fn split_integration() {
+    let (evens, odds) = event_stream().map(|x| x + 1).split();
+    let _debug = evens.filter(|x| x % 2 == 0).debug();
+
+    // The debug branch is part of the application even though
+    // it is built from the other branch
+    let mut app = odds.filter(|x| x % 2 == 1).ignore().builder().build();
+    app.run();
+    sleep(Duration::from_secs(4));
+
+    if let Some(debug_node) = app.get_debug_node::<u64>() {
+        debug_node.on_definition(|c| {
+            assert_eq!(c.data.len() as u64, EVENT_COUNT / 2);
+            assert!(c.data.iter().all(|e| e.data % 2 == 0));
+        })
+    } else {
+        panic!("Failed to get DebugNode!")
+    }
+}
+
+#[test]
+fn split_union_integration() {
+    let (left, right) = event_stream().split();
+    let mut app = left
+        .map(|x| x * 2)
+        .union(right.map(|x| x * 2 + 1))
+        .debug()
+        .builder()
+        .build();
+    app.run();
+    sleep(Duration::from_secs(4));
+
+    if let Some(debug_node) = app.get_debug_node::<u64>() {
+        debug_node.on_definition(|c| {
+            let mut data: Vec<u64> = c.data.iter().map(|e| e.data).collect();
+            data.sort_unstable();
+            assert_eq!(data, (0..EVENT_COUNT * 2).collect::<Vec<u64>>());
+        })
+    } else {
+        panic!("Failed to get DebugNode!")
+    }
+}
+
+#[test]
+fn split_typed_debug_integration() {
+    let (evens, odds) = event_stream().split();
+    let _debug = evens.filter(|x| x % 2 == 0).debug();
+
+    // Debug branches of different types get a DebugNode each
+    let mut app = odds
+        .filter(|x| x % 2 == 1)
+        .map(|x| x.to_string())
+        .debug()
+        .builder()
+        .build();
+    app.run();
+    sleep(Duration::from_secs(4));
+
+    if let Some(debug_node) = app.get_debug_node::<u64>() {
+        debug_node.on_definition(|c| {
+            assert_eq!(c.data.len() as u64, EVENT_COUNT / 2);
+            assert!(c.data.iter().all(|e| e.data % 2 == 0));
+        })
+    } else {
+        panic!("Failed to get DebugNode!")
+    }
+
+    if let Some(debug_node) = app.get_debug_node::<String>() {
+        debug_node.on_definition(|c| {
+            assert_eq!(c.data.len() as u64, EVENT_COUNT / 2);
+            assert!(c
+                .data
+                .iter()
+                .all(|e| e.data.parse::<u64>().unwrap() % 2 == 1));
+        })
+    } else {
+        panic!("Failed to get DebugNode!")
+    }
+}
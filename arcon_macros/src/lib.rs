@@ -85,9 +85,11 @@ pub fn app(delimiter: TokenStream, input: TokenStream) -> TokenStream {
 /// #[derive(ArconState)]
 /// pub struct StreamingState<B: Backend> {
 ///   values: LazyValue<u64, B>,
+///   #[ttl = "30m"]
+///   sessions: HashTable<u64, u64, B>,
 /// }
 /// ```
-#[proc_macro_derive(ArconState, attributes(ephemeral, table))]
+#[proc_macro_derive(ArconState, attributes(ephemeral, table, ttl))]
 pub fn state(input: TokenStream) -> TokenStream {
     state::derive_state(input)
 }
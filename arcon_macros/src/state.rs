@@ -13,14 +13,21 @@ pub fn derive_state(input: TokenStream) -> TokenStream {
         let mut field_getters = Vec::new();
         let mut persist_quotes = Vec::new();
         let mut key_quotes = Vec::new();
+        let mut time_quotes = Vec::new();
 
         if let syn::Fields::Named(ref fields_named) = s.fields {
             for field in fields_named.named.iter() {
                 let mut ephemeral = false;
+                let mut ttl = None;
                 let ident = &field.ident;
                 let ty = &field.ty;
                 for attr in field.attrs.iter() {
-                    ephemeral = is_ephemeral(attr);
+                    ephemeral |= is_ephemeral(attr);
+                    match get_ttl(attr) {
+                        Ok(Some(quote)) => ttl = Some(quote),
+                        Ok(None) => (),
+                        Err(err) => panic!("{}", err),
+                    }
                     {
                         match get_table(attr, ident) {
                             Ok(Some(quote)) => {
@@ -39,6 +46,19 @@ pub fn derive_state(input: TokenStream) -> TokenStream {
                     }
                 }
 
+                if let Some(ttl) = ttl {
+                    assert!(
+                        !ephemeral,
+                        "Cannot use ephemeral attribute with ttl attribute"
+                    );
+                    // The ttl is (re)applied whenever the runtime activates or persists the state
+                    let field_gen = quote! { ::arcon::TtlIndex::set_ttl(&mut self.#ident, #ttl); };
+                    persist_quotes.push(field_gen.clone());
+                    key_quotes.push(field_gen);
+                    let field_gen = quote! { ::arcon::TtlIndex::set_time(&mut self.#ident, time); };
+                    time_quotes.push(field_gen);
+                }
+
                 if !ephemeral {
                     let field_gen = quote! { self.#ident.persist()?; };
                     persist_quotes.push(field_gen);
@@ -61,6 +81,17 @@ pub fn derive_state(input: TokenStream) -> TokenStream {
             }
         };
 
+        let set_time = if time_quotes.is_empty() {
+            quote! {}
+        } else {
+            quote! {
+                #[inline]
+                fn set_time(&mut self, time: u64) {
+                    #(#time_quotes)*
+                }
+            }
+        };
+
         let tables = quote! {
             #[inline]
             fn tables(&mut self) -> Vec<::arcon::ImmutableTable> {
@@ -97,6 +128,7 @@ pub fn derive_state(input: TokenStream) -> TokenStream {
                     fn set_key(&mut self, key: u64) {
                         #(#key_quotes)*
                     }
+                    #set_time
 
                     #tables
                 }
@@ -158,3 +190,58 @@ fn get_table(
         }
     }
 }
+
+/// Parses `#[ttl = "..."]`, `#[ttl(processing_time = "...")]` or `#[ttl(event_time = "...")]`
+fn get_ttl(attr: &syn::Attribute) -> syn::Result<Option<proc_macro2::TokenStream>> {
+    if !attr.path.is_ident("ttl") {
+        return Ok(None);
+    }
+
+    let message = "expected #[ttl = \"...\"], #[ttl(processing_time = \"...\")] or #[ttl(event_time = \"...\")]";
+    let (constructor, duration) = match attr.parse_meta()? {
+        syn::Meta::NameValue(syn::MetaNameValue {
+            lit: syn::Lit::Str(duration),
+            ..
+        }) => (quote! { processing_time }, duration),
+        syn::Meta::List(list) if list.nested.len() == 1 => match &list.nested[0] {
+            syn::NestedMeta::Meta(syn::Meta::NameValue(syn::MetaNameValue {
+                path,
+                lit: syn::Lit::Str(duration),
+                ..
+            })) if path.is_ident("processing_time") => {
+                (quote! { processing_time }, duration.clone())
+            }
+            syn::NestedMeta::Meta(syn::Meta::NameValue(syn::MetaNameValue {
+                path,
+                lit: syn::Lit::Str(duration),
+                ..
+            })) if path.is_ident("event_time") => (quote! { event_time }, duration.clone()),
+            _ => return Err(syn::Error::new_spanned(attr, message)),
+        },
+        _ => return Err(syn::Error::new_spanned(attr, message)),
+    };
+
+    let millis = parse_duration_millis(&duration.value())
+        .ok_or_else(|| syn::Error::new_spanned(&duration, "expected a duration such as \"30m\""))?;
+
+    Ok(Some(quote! {
+        ::arcon::Ttl::#constructor(::std::time::Duration::from_millis(#millis))
+    }))
+}
+
+/// Parses durations such as `500ms`, `10s`, `30m`, `12h` or `7d` into milliseconds
+fn parse_duration_millis(duration: &str) -> Option<u64> {
+    let duration = duration.trim();
+    let split = duration.find(|c: char| !c.is_ascii_digit())?;
+    let (amount, unit) = duration.split_at(split);
+    let amount: u64 = amount.parse().ok()?;
+    let millis_per_unit = match unit.trim() {
+        "ms" => 1,
+        "s" => 1_000,
+        "m" => 60 * 1_000,
+        "h" => 60 * 60 * 1_000,
+        "d" => 24 * 60 * 60 * 1_000,
+        _ => return None,
+    };
+    amount.checked_mul(millis_per_unit)
+}